@@ -0,0 +1,183 @@
+//! An accordion: a stack of headed sections that open and close.
+//!
+//! ```ignore
+//! Accordion::new(Mode::Single)
+//!     .section("Shipping", &shipping, true)
+//!     .section("Returns", &returns, false)
+//!     .mount(&mut app, &parent);
+//! ```
+//!
+//! Each header is a `<button aria-expanded aria-controls>` inside a heading, and each
+//! panel a `role="region"` labelled by its button, following the WAI-ARIA accordion
+//! pattern. Panels slide open and shut unless motion is reduced.
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
+
+use wasm_bindgen::JsCast;
+use web_sys::{Animation, Element, HtmlElement, MouseEvent};
+
+use crate::{
+    animate,
+    app::App,
+    dom::{create_element, spawn_local},
+};
+
+const SLIDE: Duration = Duration::from_millis(200);
+
+thread_local! {
+    static NEXT_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Opening a section closes the others.
+    Single,
+    /// Any number can be open at once.
+    Multiple,
+}
+
+pub struct Accordion {
+    mode: Mode,
+    sections: Vec<(String, Element, bool)>,
+}
+
+impl Accordion {
+    pub fn new(mode: Mode) -> Self {
+        Accordion {
+            mode,
+            sections: Vec::new(),
+        }
+    }
+
+    /// Adds a section titled `title` holding `content`, initially `open` or not.
+    pub fn section(mut self, title: &str, content: &Element, open: bool) -> Self {
+        self.sections
+            .push((title.to_string(), content.clone(), open));
+        self
+    }
+
+    pub fn mount(self, app: &mut App, parent: &Element) {
+        let root = create_element("div");
+        let mut sections = Vec::new();
+        let mut any_open = false;
+        for (title, content, open) in self.sections {
+            // only the first of several open sections stays open in single mode
+            let open = open && !(self.mode == Mode::Single && any_open);
+            any_open |= open;
+            let section = Section::new(&title, &content, open);
+            root.append_child(&section.heading).unwrap();
+            root.append_child(&section.panel).unwrap();
+            sections.push(Rc::new(section));
+        }
+        app.append(parent, &root);
+
+        let sections = Rc::new(sections);
+        for (i, section) in sections.iter().enumerate() {
+            let sections = sections.clone();
+            let mode = self.mode;
+            app.listen(&section.button, "click", move |_: MouseEvent| {
+                let section = &sections[i];
+                let open = !section.open.get();
+                if open && mode == Mode::Single {
+                    for other in sections.iter().filter(|other| other.open.get()) {
+                        other.set_open(false);
+                    }
+                }
+                section.set_open(open);
+            });
+        }
+    }
+}
+
+struct Section {
+    heading: Element,
+    button: HtmlElement,
+    panel: HtmlElement,
+    open: Cell<bool>,
+    /// The slide in progress, if any, so a quick second click can cut it short.
+    sliding: RefCell<Option<Animation>>,
+}
+
+impl Section {
+    fn new(title: &str, content: &Element, open: bool) -> Self {
+        let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+        let (button_id, panel_id) = (format!("accordion-{id}"), format!("accordion-{id}-panel"));
+
+        let heading = create_element("h3");
+        _ = heading.set_attribute("style", "margin: 0");
+        let button = create_element("button").unchecked_into::<HtmlElement>();
+        button.set_id(&button_id);
+        _ = button.set_attribute("aria-controls", &panel_id);
+        _ = button.set_attribute(
+            "style",
+            "display: block; width: 100%; padding: 0.5em 0; border: none; \
+             background: none; font: inherit; text-align: left; cursor: pointer",
+        );
+        button.set_text_content(Some(title));
+        heading.append_child(&button).unwrap();
+
+        let panel = create_element("div").unchecked_into::<HtmlElement>();
+        panel.set_id(&panel_id);
+        _ = panel.set_attribute("role", "region");
+        _ = panel.set_attribute("aria-labelledby", &button_id);
+        _ = panel.set_attribute("style", "overflow: hidden");
+        panel.append_child(content).unwrap();
+
+        let section = Section {
+            heading,
+            button,
+            panel,
+            open: Cell::new(open),
+            sliding: RefCell::new(None),
+        };
+        section.render();
+        section
+    }
+
+    fn render(&self) {
+        let open = self.open.get();
+        _ = self
+            .button
+            .set_attribute("aria-expanded", &open.to_string());
+        self.panel.set_hidden(!open);
+    }
+
+    fn set_open(self: &Rc<Self>, open: bool) {
+        if let Some(sliding) = self.sliding.borrow_mut().take() {
+            sliding.cancel();
+        }
+        // measured while visible, whichever way it's going
+        self.panel.set_hidden(false);
+        let height = format!("{}px", self.panel.scroll_height());
+        self.open.set(open);
+        _ = self
+            .button
+            .set_attribute("aria-expanded", &open.to_string());
+
+        let (from, to) = if open {
+            ("0px", height.as_str())
+        } else {
+            (height.as_str(), "0px")
+        };
+        let Some(slide) = animate::animate(
+            &self.panel,
+            &[&[("height", from)], &[("height", to)]],
+            SLIDE,
+        ) else {
+            return self.render();
+        };
+        *self.sliding.borrow_mut() = Some(slide.clone());
+        let section = self.clone();
+        spawn_local(async move {
+            // a cancelled slide was replaced by another, which will tidy up itself
+            if animate::finished(&slide).await {
+                section.sliding.borrow_mut().take();
+                section.render();
+            }
+        });
+    }
+}
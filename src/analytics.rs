@@ -0,0 +1,195 @@
+//! Pluggable analytics.
+//!
+//! Code all over the app calls [`message`], [`route`] and [`error`]; those events are
+//! queued and handed in batches to every registered [`Analytics`] backend when the
+//! browser is idle, when the queue fills up, or when the page is being hidden.
+
+use std::{cell::RefCell, fmt::Debug, rc::Rc, time::Duration};
+
+use serde::Serialize;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::Event;
+
+use crate::{
+    app::App,
+    dom::{document, log, window},
+    errors,
+};
+
+/// Flush as soon as this many events are waiting, idle or not.
+const MAX_BATCH: usize = 20;
+/// Browsers without `requestIdleCallback` (Safari) flush after this delay instead.
+const IDLE_FALLBACK: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnalyticsEvent {
+    Message {
+        name: String,
+    },
+    RouteChange {
+        from: String,
+        to: String,
+    },
+    Error {
+        message: String,
+    },
+    /// An exposure to `variant` when there's no `metric`, otherwise an interaction.
+    Experiment {
+        experiment: String,
+        variant: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        metric: Option<String>,
+    },
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Record {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: f64,
+    #[serde(flatten)]
+    pub event: AnalyticsEvent,
+}
+
+pub trait Analytics {
+    /// Receives a batch of events, oldest first.
+    fn report(&self, batch: &[Record]);
+}
+
+/// Logs each batch to the console; handy while developing.
+pub struct ConsoleAnalytics;
+
+impl Analytics for ConsoleAnalytics {
+    fn report(&self, batch: &[Record]) {
+        for record in batch {
+            log!("[analytics] {:?}", record.event);
+        }
+    }
+}
+
+/// POSTs each batch as JSON to `endpoint`.
+///
+/// Uses `navigator.sendBeacon`, which (unlike a plain `fetch`) is allowed to finish
+/// after the page has been closed, so the final flush on unload isn't lost.
+pub struct BeaconAnalytics {
+    pub endpoint: String,
+}
+
+impl Analytics for BeaconAnalytics {
+    fn report(&self, batch: &[Record]) {
+        let Ok(body) = serde_json::to_string(batch) else {
+            return;
+        };
+        let queued = window()
+            .navigator()
+            .send_beacon_with_opt_str(&self.endpoint, Some(&body));
+        if queued != Ok(true) {
+            log!(
+                "[analytics] couldn't send {} events to {}",
+                batch.len(),
+                self.endpoint
+            );
+        }
+    }
+}
+
+#[derive(Default)]
+struct Tracker {
+    backends: Vec<Rc<dyn Analytics>>,
+    queue: Vec<Record>,
+    /// An idle flush is pending. Only that callback clears it, since flushing for
+    /// any other reason leaves the callback still on its way.
+    flush_scheduled: bool,
+}
+
+thread_local! {
+    static TRACKER: RefCell<Tracker> = RefCell::new(Tracker::default());
+}
+
+pub fn register(backend: impl Analytics + 'static) {
+    TRACKER.with(|tracker| tracker.borrow_mut().backends.push(Rc::new(backend)));
+}
+
+/// Flushes whatever is queued when the page is hidden or unloaded.
+pub fn install(app: &mut App) {
+    app.listen(&window(), "pagehide", |_: Event| flush());
+    // mobile browsers often kill hidden tabs without ever firing `pagehide`
+    app.listen(&document(), "visibilitychange", |_: Event| {
+        if document().hidden() {
+            flush();
+        }
+    });
+}
+
+pub fn message(msg: &impl Debug) {
+    errors::breadcrumb(msg);
+    track(AnalyticsEvent::Message {
+        name: format!("{msg:?}"),
+    });
+}
+
+pub fn route(from: &str, to: &str) {
+    track(AnalyticsEvent::RouteChange {
+        from: from.to_string(),
+        to: to.to_string(),
+    });
+}
+
+pub fn error(message: &str) {
+    track(AnalyticsEvent::Error {
+        message: message.to_string(),
+    });
+}
+
+pub fn track(event: AnalyticsEvent) {
+    let record = Record {
+        timestamp: js_sys::Date::now(),
+        event,
+    };
+    let (full, schedule) = TRACKER.with(|tracker| {
+        let mut tracker = tracker.borrow_mut();
+        if tracker.backends.is_empty() {
+            return (false, false);
+        }
+        tracker.queue.push(record);
+        let full = tracker.queue.len() >= MAX_BATCH;
+        let schedule = !full && !std::mem::replace(&mut tracker.flush_scheduled, true);
+        (full, schedule)
+    });
+    if full {
+        flush();
+    } else if schedule {
+        schedule_flush();
+    }
+}
+
+/// Sends everything that's queued to all backends right now.
+pub fn flush() {
+    // take everything out first, so backends are free to track events of their own
+    let (batch, backends) = TRACKER.with(|tracker| {
+        let mut tracker = tracker.borrow_mut();
+        (std::mem::take(&mut tracker.queue), tracker.backends.clone())
+    });
+    if batch.is_empty() {
+        return;
+    }
+    for backend in backends {
+        backend.report(&batch);
+    }
+}
+
+fn schedule_flush() {
+    let callback = Closure::once_into_js(|| {
+        TRACKER.with(|tracker| tracker.borrow_mut().flush_scheduled = false);
+        flush();
+    });
+    if window()
+        .request_idle_callback(callback.unchecked_ref())
+        .is_err()
+    {
+        _ = window().set_timeout_with_callback_and_timeout_and_arguments_0(
+            callback.unchecked_ref(),
+            IDLE_FALLBACK.as_millis() as i32,
+        );
+    }
+}
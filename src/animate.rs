@@ -0,0 +1,97 @@
+//! A thin wrapper over the Web Animations API (`element.animate(...)`).
+//!
+//! `Element::animate` isn't in our `web_sys` yet, so it's bound by hand; what comes
+//! back is a plain `web_sys::Animation` that can be cancelled or reversed.
+//!
+//! ```ignore
+//! let fade = animate(&el, &[&[("opacity", "0")], &[("opacity", "1")]], FADE);
+//! if let Some(fade) = fade {
+//!     finished(&fade).await;
+//! }
+//! ```
+
+use std::time::Duration;
+
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Animation, Element};
+
+use crate::{battery, dom::window, flags};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = Element)]
+    type Animatable;
+
+    #[wasm_bindgen(method, catch)]
+    fn animate(
+        this: &Animatable,
+        keyframes: &js_sys::Array,
+        options: &js_sys::Object,
+    ) -> Result<Animation, JsValue>;
+}
+
+/// One keyframe: CSS properties in camelCase (`"maxHeight"`) and their values.
+pub type Keyframe<'a> = &'a [(&'a str, &'a str)];
+
+/// Whether to skip animating: the user asked for less motion, the battery's low, or
+/// the animations flag is off.
+pub fn reduced_motion() -> bool {
+    if !flags::enabled("animations") {
+        return true;
+    }
+    let prefers = window()
+        .match_media("(prefers-reduced-motion: reduce)")
+        .ok()
+        .flatten()
+        .is_some_and(|query| query.matches());
+    prefers || battery::low_power()
+}
+
+/// Starts animating `el` through `keyframes` over `duration`, easing in and out.
+/// `None` if motion is reduced or the browser can't animate: the caller should
+/// then jump straight to the end state.
+pub fn animate(el: &Element, keyframes: &[Keyframe], duration: Duration) -> Option<Animation> {
+    if reduced_motion() {
+        return None;
+    }
+    let options = js_sys::Object::new();
+    _ = js_sys::Reflect::set(
+        &options,
+        &"duration".into(),
+        &(duration.as_millis() as f64).into(),
+    );
+    _ = js_sys::Reflect::set(&options, &"easing".into(), &"ease-in-out".into());
+    animate_with_options(el, keyframes, &options)
+}
+
+/// Like [`animate`], with the options object (`timeline`, `fill`, ...) as it's given,
+/// and whether or not motion is reduced.
+pub fn animate_with_options(
+    el: &Element,
+    keyframes: &[Keyframe],
+    options: &js_sys::Object,
+) -> Option<Animation> {
+    let frames = keyframes
+        .iter()
+        .map(|frame| {
+            let object = js_sys::Object::new();
+            for (property, value) in frame.iter() {
+                _ = js_sys::Reflect::set(&object, &(*property).into(), &(*value).into());
+            }
+            object
+        })
+        .collect::<js_sys::Array>();
+    wasm_bindgen::JsCast::unchecked_ref::<Animatable>(el)
+        .animate(&frames, options)
+        .ok()
+}
+
+/// Resolves once `animation` is over: `true` if it ran to the end, `false` if it was
+/// cancelled first.
+pub async fn finished(animation: &Animation) -> bool {
+    match animation.finished() {
+        Ok(promise) => JsFuture::from(promise).await.is_ok(),
+        Err(_) => false,
+    }
+}
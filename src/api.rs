@@ -0,0 +1,67 @@
+//! JSON over `fetch`, typed at both ends: a request body is anything `Serialize`, and a
+//! response is parsed into whatever the caller asks for.
+//!
+//! Unlike [`fetch::mutate`](crate::fetch::mutate), nothing here is queued for later:
+//! these are for requests whose answer is wanted now, so going offline is an error
+//! like any other, for the caller to show.
+
+use std::fmt;
+
+use serde::{de::DeserializeOwned, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{RequestInit, Response};
+
+use crate::dom::window;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// There was no response at all.
+    Network,
+    /// A response, but not a 2xx one.
+    Status(u16),
+    /// A 2xx response that isn't the JSON that was expected.
+    Body(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Network => write!(f, "couldn't reach the server"),
+            Error::Status(status) => write!(f, "the server answered {status}"),
+            Error::Body(error) => write!(f, "unexpected response: {error}"),
+        }
+    }
+}
+
+pub async fn get_json<T: DeserializeOwned>(url: &str) -> Result<T, Error> {
+    request(url, &RequestInit::new()).await
+}
+
+/// POSTs `body` as JSON, and parses the JSON that comes back.
+pub async fn post_json<B: Serialize, T: DeserializeOwned>(url: &str, body: &B) -> Result<T, Error> {
+    let body = serde_json::to_string(body).map_err(|error| Error::Body(error.to_string()))?;
+    let headers = js_sys::Object::new();
+    _ = js_sys::Reflect::set(&headers, &"Content-Type".into(), &"application/json".into());
+    let mut init = RequestInit::new();
+    init.method("POST")
+        .headers(&headers)
+        .body(Some(&JsValue::from_str(&body)));
+    request(url, &init).await
+}
+
+async fn request<T: DeserializeOwned>(url: &str, init: &RequestInit) -> Result<T, Error> {
+    let response = JsFuture::from(window().fetch_with_str_and_init(url, init))
+        .await
+        .map_err(|_| Error::Network)?
+        .unchecked_into::<Response>();
+    if !response.ok() {
+        return Err(Error::Status(response.status()));
+    }
+    let text = JsFuture::from(response.text().map_err(|_| Error::Network)?)
+        .await
+        .map_err(|_| Error::Network)?
+        .as_string()
+        .unwrap_or_default();
+    serde_json::from_str(&text).map_err(|error| Error::Body(error.to_string()))
+}
@@ -0,0 +1,134 @@
+//! The search example: find-as-you-type over a bundled list of web APIs.
+//!
+//! The index is built once at mount and every keystroke searches it again, with the
+//! time each search took shown next to the results: a few dozen microseconds, which
+//! is why there's no debounce.
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event, HtmlInputElement};
+
+use crate::{
+    app::App,
+    dom::{create_element, window},
+    search::{self, Document, Index},
+};
+
+/// Results past this are counted but not shown.
+const LIMIT: usize = 20;
+
+const APIS: &[(&str, &str)] = &[
+    ("Fetch API", "Make network requests with fetch() and handle the Response as text, JSON or a stream."),
+    ("Clipboard API", "Read from and write to the system clipboard asynchronously with navigator.clipboard."),
+    ("Web Animations API", "Animate elements from script with element.animate(), keyframes and timing options."),
+    ("View Transitions API", "Animate between two states of the document with document.startViewTransition()."),
+    ("Intersection Observer", "Get notified when an element enters or leaves the viewport, for lazy loading and infinite scroll."),
+    ("Resize Observer", "Get notified when an element's size changes, without polling layout."),
+    ("Mutation Observer", "Watch the DOM tree for added and removed nodes and attribute changes."),
+    ("Web Storage", "Store key/value strings per origin with localStorage and sessionStorage."),
+    ("IndexedDB", "A transactional database in the browser for large amounts of structured data and blobs."),
+    ("Service Worker API", "Intercept network requests, cache responses and work offline with a worker script."),
+    ("Web Workers", "Run scripts on background threads and talk to them with postMessage."),
+    ("Broadcast Channel", "Send messages between tabs, windows and workers of the same origin."),
+    ("WebSocket", "Open a two-way, message-based connection to a server."),
+    ("Server-Sent Events", "Receive a stream of events from a server over HTTP with EventSource."),
+    ("Notifications API", "Show system notifications to the user, once they've given permission."),
+    ("Permissions API", "Query the state of permissions like geolocation, notifications and camera."),
+    ("Geolocation API", "Get the user's location, with their permission, once or as it changes."),
+    ("Battery Status API", "Read the battery level and charging state with navigator.getBattery()."),
+    ("Network Information API", "Read the connection's effective type, downlink and data saver setting."),
+    ("Idle Detection API", "Find out when the user is idle, including outside the page, with permission."),
+    ("Page Visibility API", "Find out whether the page is visible or hidden in a background tab."),
+    ("Web Share API", "Share text, links and files through the platform's share sheet with navigator.share()."),
+    ("Media Session API", "Show metadata and handle media keys and lock screen controls while audio plays."),
+    ("MediaDevices", "Capture the camera, microphone or screen with getUserMedia and getDisplayMedia."),
+    ("Web Audio API", "Process and synthesize audio with a graph of audio nodes, including analysers."),
+    ("Canvas API", "Draw 2D graphics, images and text on a canvas element, and read back the pixels."),
+    ("WebGL", "Render hardware-accelerated 3D graphics into a canvas."),
+    ("File API", "Read files the user picked or dropped as text, data URLs or array buffers."),
+    ("Drag and Drop", "Drag elements and files around the page and drop them on targets."),
+    ("Pointer Events", "Handle mouse, pen and touch input with one set of events, including pointer capture."),
+    ("Popover API", "Show content in the top layer with the popover attribute and showPopover()."),
+    ("Dialog element", "A native modal or non-modal dialog with showModal(), close() and a return value."),
+    ("History API", "Change the URL and handle back and forward navigation with pushState and popstate."),
+    ("URL API", "Parse and build URLs, and create object URLs for blobs and files."),
+    ("Encoding API", "Convert between strings and bytes with TextEncoder and TextDecoder, streaming included."),
+    ("Intl", "Format dates, numbers and lists, and compare strings, for any locale."),
+    ("WebAuthn", "Sign in with passkeys and security keys through navigator.credentials."),
+    ("Payment Request API", "Collect payment and shipping details through the browser's own checkout UI."),
+    ("Performance API", "Measure timing precisely with performance.now(), marks and measures."),
+    ("requestAnimationFrame", "Run a callback right before the next repaint, for smooth animation loops."),
+];
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let index = Index::new(
+        APIS.iter()
+            .map(|(title, body)| Document {
+                title: title.to_string(),
+                body: body.to_string(),
+            })
+            .collect(),
+    );
+
+    let section = create_element("section");
+    let input = create_element("input").unchecked_into::<HtmlInputElement>();
+    input.set_type("search");
+    input.set_placeholder(&format!("Search {} web APIs…", index.len()));
+    _ = input.set_attribute("aria-label", "Search web APIs");
+    _ = input.set_attribute("aria-controls", "search-results");
+    _ = input.set_attribute("style", "width: 100%; font-size: 1.2em");
+    let stats = create_element("p");
+    _ = stats.set_attribute("aria-live", "polite");
+    let results = create_element("ol");
+    results.set_id("search-results");
+    section.append_child(&input).unwrap();
+    section.append_child(&stats).unwrap();
+    section.append_child(&results).unwrap();
+    app.append(parent, &section);
+
+    app.listen(&input.clone(), "input", move |_: Event| {
+        let query = input.value();
+        let started = now();
+        let hits = index.search(&query);
+        let took = now() - started;
+
+        results.set_inner_html("");
+        if query.trim().is_empty() {
+            stats.set_text_content(None);
+            return;
+        }
+        stats.set_text_content(Some(&format!("{} result(s) in {:.2} ms", hits.len(), took)));
+        for hit in hits.iter().take(LIMIT) {
+            let item = create_element("li");
+            let title = create_element("strong");
+            append_highlighted(&title, &hit.document.title, &query);
+            let body = create_element("div");
+            append_highlighted(&body, &hit.document.body, &query);
+            item.append_child(&title).unwrap();
+            item.append_child(&body).unwrap();
+            results.append_child(&item).unwrap();
+        }
+    });
+
+    app
+}
+
+fn append_highlighted(parent: &Element, text: &str, query: &str) {
+    for (piece, matched) in search::highlight(text, query) {
+        if matched {
+            let mark = create_element("mark");
+            mark.set_text_content(Some(piece));
+            parent.append_child(&mark).unwrap();
+        } else {
+            _ = parent.append_with_str_1(piece);
+        }
+    }
+}
+
+/// Milliseconds, to a fraction of one.
+fn now() -> f64 {
+    window()
+        .performance()
+        .map_or(0.0, |performance| performance.now())
+}
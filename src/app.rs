@@ -0,0 +1,185 @@
+//! A handle that owns everything an app creates, so it can all be torn down again.
+//!
+//! Instead of appending nodes, attaching listeners and spawning tasks directly, go
+//! through the [`App`]: it remembers each of them and undoes them on `unmount()`.
+
+use std::future::Future;
+
+use futures::future::{abortable, AbortHandle};
+use wasm_bindgen::JsCast;
+use web_sys::{AddEventListenerOptions, EventTarget, Node};
+
+use crate::{
+    dom::{add_event_listener_with_options, spawn_local, EventListenerHandle},
+    leak::Tracked,
+};
+
+struct MountedNode {
+    node: Node,
+    _tracked: Tracked,
+}
+
+struct Listener {
+    _handle: EventListenerHandle,
+    _tracked: Tracked,
+}
+
+#[derive(Default)]
+pub struct App {
+    nodes: Vec<MountedNode>,
+    listeners: Vec<Listener>,
+    tasks: Vec<AbortHandle>,
+    cleanups: Vec<(Box<dyn FnOnce()>, Tracked)>,
+}
+
+impl App {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `child` to `parent`; the child is removed again on unmount.
+    pub fn append(&mut self, parent: &Node, child: &Node) {
+        parent.append_child(child).expect("to append the node");
+        self.nodes.push(MountedNode {
+            node: child.clone(),
+            _tracked: Tracked::new("node"),
+        });
+    }
+
+    /// Takes `node`, already on the page, to remove on unmount as if it'd been appended.
+    pub fn adopt(&mut self, node: &Node) {
+        self.nodes.push(MountedNode {
+            node: node.clone(),
+            _tracked: Tracked::new("node"),
+        });
+    }
+
+    /// Like [`add_event_listener`](crate::dom::add_event_listener), but the app holds
+    /// the handle, so the listener is removed (and its closure freed) on unmount.
+    pub fn listen<E>(
+        &mut self,
+        target: &EventTarget,
+        event_name: &'static str,
+        cb: impl FnMut(E) + 'static,
+    ) where
+        E: JsCast + 'static,
+    {
+        self.listen_with_options(target, event_name, &AddEventListenerOptions::new(), cb)
+    }
+
+    /// Like [`App::listen`], with `passive`/`capture`/`once` options.
+    pub fn listen_with_options<E>(
+        &mut self,
+        target: &EventTarget,
+        event_name: &'static str,
+        options: &AddEventListenerOptions,
+        mut cb: impl FnMut(E) + 'static,
+    ) where
+        E: JsCast + 'static,
+    {
+        // lives inside the closure, so it only goes away when the closure itself is dropped
+        let tracked = Tracked::new("closure");
+        let handle = add_event_listener_with_options(target, event_name, options, move |ev: E| {
+            let _ = &tracked;
+            cb(ev)
+        });
+        self.listeners.push(Listener {
+            _handle: handle,
+            _tracked: Tracked::new("listener"),
+        });
+    }
+
+    /// Spawns a future that is cancelled on unmount.
+    pub fn spawn(&mut self, fut: impl Future<Output = ()> + 'static) {
+        let tracked = Tracked::new("task");
+        let (fut, abort) = abortable(fut);
+        spawn_local(async move {
+            _ = fut.await;
+            drop(tracked);
+        });
+        self.tasks.push(abort);
+    }
+
+    /// Runs `f` on unmount, e.g. to disconnect an observer.
+    pub fn on_cleanup(&mut self, f: impl FnOnce() + 'static) {
+        self.cleanups.push((Box::new(f), Tracked::new("cleanup")));
+    }
+
+    /// Removes all nodes, listeners, tasks and observers created through this handle.
+    pub fn unmount(self) {
+        drop(self)
+    }
+
+    /// Keeps the app alive for the rest of the page's lifetime.
+    pub fn forget(self) {
+        std::mem::forget(self)
+    }
+}
+
+impl Drop for App {
+    fn drop(&mut self) {
+        for (cleanup, _tracked) in self.cleanups.drain(..).rev() {
+            cleanup();
+        }
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+        // each handle detaches its listener as it's dropped
+        self.listeners.clear();
+        for mounted in self.nodes.drain(..).rev() {
+            if let Some(parent) = mounted.node.parent_node() {
+                _ = parent.remove_child(&mounted.node);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use std::time::Duration;
+
+    use wasm_bindgen_test::*;
+
+    use web_sys::Element;
+
+    use super::App;
+    use crate::{counter, dom::body, kanban, leak, pair, time::sleep, totals};
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn mount_unmount_does_not_leak() {
+        assert_no_leaks(counter::mount_to).await;
+    }
+
+    /// The examples whose components are kept in an [`arena`](crate::arena), which
+    /// counts them as "component"s.
+    #[wasm_bindgen_test]
+    async fn arena_components_do_not_leak() {
+        assert_no_leaks(pair::mount_to).await;
+        assert_no_leaks(totals::mount_to).await;
+        assert_no_leaks(kanban::mount_to).await;
+    }
+
+    async fn assert_no_leaks(mount_to: fn(&Element) -> App) {
+        let body = body().unwrap();
+        let before = leak::snapshot();
+        let children_before = body.child_element_count();
+
+        for _ in 0..100 {
+            mount_to(&body).unmount();
+        }
+        // aborted tasks are only dropped the next time the executor polls them
+        sleep(Duration::from_millis(0)).await;
+
+        let after = leak::snapshot();
+        for (kind, count) in after {
+            assert_eq!(
+                count,
+                before.get(kind).copied().unwrap_or(0),
+                "{kind} leaked"
+            );
+        }
+        assert_eq!(body.child_element_count(), children_before);
+    }
+}
@@ -0,0 +1,251 @@
+//! Components kept in one place and referred to by typed [`Key`]s, slotmap-style.
+//!
+//! When closures hold `Rc<RefCell<_>>`s of the components they work on, and the
+//! components hold the apps that hold those closures, the graph can loop back on
+//! itself, and a loop of `Rc`s is never freed. Here the [`Registry`] is the only owner
+//! of its components: closures hold a [`Key`], which is `Copy` and owns nothing, and a
+//! [`Handle`], which is weak. Dropping the registry drops every component, whatever
+//! closures are still around, and a closure that runs after that finds nothing.
+//!
+//! A key to a removed component doesn't find whatever was put in its slot next: each
+//! slot counts how many times it's been emptied, and a key remembers the count.
+
+use std::{
+    cell::RefCell,
+    fmt,
+    marker::PhantomData,
+    rc::{Rc, Weak},
+};
+
+use crate::leak::Tracked;
+
+pub struct Key<T> {
+    index: u32,
+    generation: u32,
+    _component: PhantomData<fn() -> T>,
+}
+
+// not derived, which would ask for `T: Clone` and so on
+impl<T> Clone for Key<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Key<T> {}
+
+impl<T> PartialEq for Key<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.index, self.generation) == (other.index, other.generation)
+    }
+}
+
+impl<T> Eq for Key<T> {}
+
+impl<T> fmt::Debug for Key<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Key({}v{})", self.index, self.generation)
+    }
+}
+
+struct Slot<T> {
+    generation: u32,
+    value: Option<(T, Tracked)>,
+}
+
+pub struct Arena<T> {
+    slots: Vec<Slot<T>>,
+    /// Empty slots, to fill before adding new ones.
+    free: Vec<u32>,
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Arena {
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+}
+
+impl<T> Arena<T> {
+    pub fn insert(&mut self, value: T) -> Key<T> {
+        let value = Some((value, Tracked::new("component")));
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index as usize].value = value;
+                index
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    value,
+                });
+                self.slots.len() as u32 - 1
+            }
+        };
+        Key {
+            index,
+            generation: self.slots[index as usize].generation,
+            _component: PhantomData,
+        }
+    }
+
+    /// Takes the component out, if `key` is still its key.
+    pub fn remove(&mut self, key: Key<T>) -> Option<T> {
+        let slot = self.slots.get_mut(key.index as usize)?;
+        if slot.generation != key.generation {
+            return None;
+        }
+        let (value, _) = slot.value.take()?;
+        slot.generation += 1;
+        self.free.push(key.index);
+        Some(value)
+    }
+
+    pub fn get_mut(&mut self, key: Key<T>) -> Option<&mut T> {
+        let slot = self.slots.get_mut(key.index as usize)?;
+        let (value, _) = slot.value.as_mut()?;
+        (slot.generation == key.generation).then_some(value)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.slots
+            .iter_mut()
+            .filter_map(|slot| Some(&mut slot.value.as_mut()?.0))
+    }
+
+    /// Takes every component out, leaving the arena empty: its slots stay, for the
+    /// next components, but no key from before finds anything in them.
+    pub fn drain(&mut self) -> Vec<T> {
+        let mut values = Vec::new();
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if let Some((value, _)) = slot.value.take() {
+                slot.generation += 1;
+                self.free.push(index as u32);
+                values.push(value);
+            }
+        }
+        values
+    }
+}
+
+/// Owns an [`Arena`], which goes (with everything in it) when this does.
+pub struct Registry<T>(Rc<RefCell<Arena<T>>>);
+
+impl<T> Default for Registry<T> {
+    fn default() -> Self {
+        Registry(Rc::default())
+    }
+}
+
+impl<T> Registry<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Something for closures to reach the components through, without owning them.
+    pub fn handle(&self) -> Handle<T> {
+        Handle(Rc::downgrade(&self.0))
+    }
+
+    pub fn insert(&self, value: T) -> Key<T> {
+        self.0.borrow_mut().insert(value)
+    }
+
+    /// Takes every component out; they're the caller's to dispose of.
+    pub fn drain(&self) -> Vec<T> {
+        self.0.borrow_mut().drain()
+    }
+}
+
+/// A weak reference to a [`Registry`]'s components.
+pub struct Handle<T>(Weak<RefCell<Arena<T>>>);
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Handle(self.0.clone())
+    }
+}
+
+impl<T> Handle<T> {
+    /// Calls `f` with the component `key` is for, if it (and its registry) is still
+    /// around.
+    ///
+    /// The arena is borrowed meanwhile, so `f` mustn't reach back into it.
+    pub fn with<R>(&self, key: Key<T>, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let arena = self.0.upgrade()?;
+        let mut arena = arena.borrow_mut();
+        arena.get_mut(key).map(f)
+    }
+
+    /// Calls `f` with each component, in the order of their slots.
+    ///
+    /// As with [`Handle::with`], `f` mustn't reach back into the arena.
+    pub fn for_each(&self, mut f: impl FnMut(&mut T)) {
+        if let Some(arena) = self.0.upgrade() {
+            arena.borrow_mut().values_mut().for_each(&mut f);
+        }
+    }
+
+    /// Puts `value` in the registry, unless it's gone, in which case `value` is too.
+    pub fn insert(&self, value: T) -> Option<Key<T>> {
+        Some(self.0.upgrade()?.borrow_mut().insert(value))
+    }
+
+    pub fn remove(&self, key: Key<T>) -> Option<T> {
+        self.0.upgrade()?.borrow_mut().remove(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_keys_find_nothing() {
+        let mut arena = Arena::default();
+        let a = arena.insert("a");
+        let b = arena.insert("b");
+        assert_eq!(arena.remove(a), Some("a"));
+        assert_eq!(arena.remove(a), None);
+        // the same slot, a new generation
+        let c = arena.insert("c");
+        assert_ne!(a, c);
+        assert_eq!(arena.get_mut(a), None);
+        assert_eq!(arena.get_mut(c), Some(&mut "c"));
+        assert_eq!(arena.get_mut(b), Some(&mut "b"));
+        assert_eq!(arena.drain(), ["c", "b"]);
+        assert_eq!(arena.get_mut(b), None);
+    }
+
+    #[test]
+    fn keys_from_before_a_drain_find_nothing() {
+        let mut arena = Arena::default();
+        let a = arena.insert("a");
+        let b = arena.insert("b");
+        assert_eq!(arena.drain(), ["a", "b"]);
+        assert!(arena.drain().is_empty());
+        // the slots are reused, under new generations
+        let c = arena.insert("c");
+        let d = arena.insert("d");
+        assert_eq!(arena.slots.len(), 2);
+        assert_eq!(arena.get_mut(a), None);
+        assert_eq!(arena.get_mut(b), None);
+        assert_eq!(arena.remove(b), None);
+        assert_eq!(arena.get_mut(c), Some(&mut "c"));
+        assert_eq!(arena.get_mut(d), Some(&mut "d"));
+    }
+
+    #[test]
+    fn handles_dont_keep_components_alive() {
+        let component = Rc::new(());
+        let registry = Registry::new();
+        let key = registry.insert(component.clone());
+        let handle = registry.handle();
+        assert_eq!(handle.with(key, |c| Rc::strong_count(c)), Some(2));
+        drop(registry);
+        assert_eq!(Rc::strong_count(&component), 1);
+        assert_eq!(handle.with(key, |_| ()), None);
+    }
+}
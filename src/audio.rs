@@ -0,0 +1,403 @@
+//! The audio example: a spectrum of the microphone, or of a tune, drawn as bars.
+//!
+//! The sound goes through an `AnalyserNode`, and before every repaint its frequency
+//! data is copied into a buffer on the Rust side and drawn on a canvas. The
+//! microphone isn't connected to the speakers, so there's no feedback; the tune is
+//! synthesized in Rust ([`synth`]) and played by an `<audio>` element, which the OS's
+//! media controls can play, pause and seek through the [`media_session`]. Stopping, or
+//! leaving the example, stops the sound and closes the audio graph.
+
+use std::{cell::RefCell, rc::Rc};
+
+use futures::StreamExt;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    AnalyserNode, AudioContext, Blob, BlobPropertyBag, CanvasRenderingContext2d, Element, Event,
+    HtmlAudioElement, HtmlButtonElement, HtmlCanvasElement, MediaStreamConstraints, Url,
+};
+
+use crate::{
+    app::App,
+    dom::{create_element, spawn_local},
+    media,
+    media_session::{self, Action, Metadata},
+    synth::{self, Note},
+    time,
+};
+
+/// Twice the number of bars.
+const FFT_SIZE: u32 = 256;
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 240;
+/// How far the seek buttons go when the OS doesn't say.
+const SEEK_STEP: f64 = 5.0;
+const TEMPO: f32 = 120.0;
+const TUNE: &[Note] = &[
+    (Some(64), 1.0),
+    (Some(64), 1.0),
+    (Some(65), 1.0),
+    (Some(67), 1.0),
+    (Some(67), 1.0),
+    (Some(65), 1.0),
+    (Some(64), 1.0),
+    (Some(62), 1.0),
+    (Some(60), 1.0),
+    (Some(60), 1.0),
+    (Some(62), 1.0),
+    (Some(64), 1.0),
+    (Some(64), 1.5),
+    (Some(62), 0.5),
+    (Some(62), 2.0),
+    (Some(64), 1.0),
+    (Some(64), 1.0),
+    (Some(65), 1.0),
+    (Some(67), 1.0),
+    (Some(67), 1.0),
+    (Some(65), 1.0),
+    (Some(64), 1.0),
+    (Some(62), 1.0),
+    (Some(60), 1.0),
+    (Some(60), 1.0),
+    (Some(62), 1.0),
+    (Some(64), 1.0),
+    (Some(62), 1.5),
+    (Some(60), 0.5),
+    (Some(60), 2.0),
+];
+
+/// Where the sound comes from.
+enum Source {
+    /// Held only so the stream stops when it's dropped.
+    Microphone {
+        _stream: media::Running,
+    },
+    Tune(Tune),
+}
+
+/// The tune's `<audio>` element and the object URL it plays.
+struct Tune {
+    audio: HtmlAudioElement,
+    url: String,
+}
+
+impl Drop for Tune {
+    fn drop(&mut self) {
+        _ = self.audio.pause();
+        _ = Url::revoke_object_url(&self.url);
+    }
+}
+
+/// A source wired to an analyser.
+struct Graph {
+    context: AudioContext,
+    analyser: AnalyserNode,
+    source: Source,
+}
+
+impl Graph {
+    async fn microphone() -> Result<Graph, String> {
+        let mut constraints = MediaStreamConstraints::new();
+        constraints.audio(&true.into());
+        let stream = media::Running(media::user_media(&constraints, "microphone").await?);
+        let (context, analyser) = analyser()?;
+        context
+            .create_media_stream_source(&stream.0)
+            .and_then(|source| source.connect_with_audio_node(&analyser))
+            .map_err(|e| describe(&e))?;
+        Ok(Graph {
+            context,
+            analyser,
+            source: Source::Microphone { _stream: stream },
+        })
+    }
+
+    fn tune() -> Result<Graph, String> {
+        let wav = synth::wav(&synth::render(TUNE, TEMPO));
+        let mut options = BlobPropertyBag::new();
+        options.type_("audio/wav");
+        let blob = Blob::new_with_u8_array_sequence_and_options(
+            &js_sys::Array::of1(&js_sys::Uint8Array::from(&wav[..])),
+            &options,
+        )
+        .map_err(|e| describe(&e))?;
+        let url = Url::create_object_url_with_blob(&blob).map_err(|e| describe(&e))?;
+        let audio = HtmlAudioElement::new_with_src(&url).map_err(|e| describe(&e))?;
+        let tune = Tune { audio, url };
+
+        let (context, analyser) = analyser()?;
+        context
+            .create_media_element_source(&tune.audio)
+            .and_then(|source| source.connect_with_audio_node(&analyser))
+            // unlike the microphone, this one's meant to be heard
+            .and_then(|_| analyser.connect_with_audio_node(&context.destination()))
+            .map_err(|e| describe(&e))?;
+        Ok(Graph {
+            context,
+            analyser,
+            source: Source::Tune(tune),
+        })
+    }
+}
+
+impl Drop for Graph {
+    fn drop(&mut self) {
+        if let Ok(closed) = self.context.close() {
+            spawn_local(async move { _ = JsFuture::from(closed).await });
+        }
+    }
+}
+
+fn analyser() -> Result<(AudioContext, AnalyserNode), String> {
+    let context = AudioContext::new().map_err(|e| describe(&e))?;
+    let analyser = context.create_analyser().map_err(|e| describe(&e))?;
+    analyser.set_fft_size(FFT_SIZE);
+    analyser.set_smoothing_time_constant(0.8);
+    Ok((context, analyser))
+}
+
+#[derive(Clone)]
+struct View {
+    microphone: HtmlButtonElement,
+    tune: HtmlButtonElement,
+    status: Element,
+    ctx: CanvasRenderingContext2d,
+}
+
+impl View {
+    /// Back to nothing playing.
+    fn reset(&self, status: &str) {
+        self.microphone
+            .set_text_content(Some("Start the microphone"));
+        self.tune.set_text_content(Some("Play a tune"));
+        self.status.set_text_content(Some(status));
+        self.ctx.clear_rect(0.0, 0.0, WIDTH as f64, HEIGHT as f64);
+    }
+}
+
+/// The graph while there's one, with the app drawing it (and, for the tune, handling
+/// its controls).
+type Running = Rc<RefCell<Option<(Graph, App)>>>;
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let section = create_element("section");
+    let microphone = create_element("button").unchecked_into::<HtmlButtonElement>();
+    let tune = create_element("button").unchecked_into::<HtmlButtonElement>();
+    let status = create_element("p");
+    _ = status.set_attribute("role", "status");
+    let canvas = create_element("canvas").unchecked_into::<HtmlCanvasElement>();
+    canvas.set_width(WIDTH);
+    canvas.set_height(HEIGHT);
+    _ = canvas.set_attribute("style", "width: 100%; max-width: 640px; background: #111");
+    _ = canvas.set_attribute("role", "img");
+    _ = canvas.set_attribute("aria-label", "Frequency spectrum");
+    let ctx = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .expect("a 2d canvas context")
+        .unchecked_into::<CanvasRenderingContext2d>();
+    let controls = create_element("p");
+    controls.append_child(&microphone).unwrap();
+    _ = controls.append_with_str_1(" ");
+    controls.append_child(&tune).unwrap();
+    section.append_child(&controls).unwrap();
+    section.append_child(&status).unwrap();
+    section.append_child(&canvas).unwrap();
+    app.append(parent, &section);
+    let view = View {
+        microphone,
+        tune,
+        status,
+        ctx,
+    };
+    view.reset("");
+
+    let running = Running::default();
+    app.listen(&view.microphone.clone(), "click", {
+        let running = Rc::downgrade(&running);
+        let view = view.clone();
+        move |_: Event| {
+            let Some(current) = running.upgrade() else {
+                return;
+            };
+            let was_microphone = matches!(
+                current.borrow_mut().take(),
+                Some((
+                    Graph {
+                        source: Source::Microphone { .. },
+                        ..
+                    },
+                    _
+                ))
+            );
+            if was_microphone {
+                view.reset("Stopped.");
+                return;
+            }
+            view.reset("Asking for the microphone…");
+            view.microphone.set_disabled(true);
+            let (running, view) = (running.clone(), view.clone());
+            spawn_local(async move {
+                let graph = Graph::microphone().await;
+                // left the example while the browser was asking: the graph just drops
+                let Some(running) = running.upgrade() else {
+                    return;
+                };
+                view.microphone.set_disabled(false);
+                match graph {
+                    Ok(graph) => {
+                        let drawing = draw(&graph.analyser, view.ctx.clone());
+                        *running.borrow_mut() = Some((graph, drawing));
+                        view.microphone
+                            .set_text_content(Some("Stop the microphone"));
+                        view.status
+                            .set_text_content(Some("Listening. Make some noise!"));
+                    }
+                    Err(e) => view.status.set_text_content(Some(&e)),
+                }
+            });
+        }
+    });
+    app.listen(&view.tune.clone(), "click", {
+        let running = Rc::downgrade(&running);
+        let view = view.clone();
+        move |_: Event| {
+            let Some(running) = running.upgrade() else {
+                return;
+            };
+            // play or pause the tune if it's loaded, or start it in place of the mic
+            if let Some((
+                Graph {
+                    source: Source::Tune(tune),
+                    ..
+                },
+                _,
+            )) = &*running.borrow()
+            {
+                if tune.audio.paused() {
+                    _ = tune.audio.play();
+                } else {
+                    _ = tune.audio.pause();
+                }
+                return;
+            }
+            running.borrow_mut().take();
+            view.reset("");
+            match Graph::tune() {
+                Ok(graph) => {
+                    let mut controls = draw(&graph.analyser, view.ctx.clone());
+                    if let Source::Tune(tune) = &graph.source {
+                        control(&mut controls, &tune.audio, &view);
+                        _ = tune.audio.play();
+                    }
+                    *running.borrow_mut() = Some((graph, controls));
+                }
+                Err(e) => view.status.set_text_content(Some(&e)),
+            }
+        }
+    });
+    app.on_cleanup(move || drop(running.take()));
+
+    app
+}
+
+/// Keeps the button, the status line and the OS's media controls in step with the
+/// tune, and lets the OS controls drive it.
+fn control(app: &mut App, audio: &HtmlAudioElement, view: &View) {
+    media_session::claim(
+        app,
+        &Metadata {
+            title: "Ode to Joy",
+            artist: "Beethoven, played by Rust",
+            album: "Rust + Wasm vanilla examples",
+            artwork: "icon.svg",
+        },
+        {
+            let audio = audio.clone();
+            move |action| {
+                let at = audio.current_time();
+                match action {
+                    Action::Play => _ = audio.play(),
+                    Action::Pause => _ = audio.pause(),
+                    Action::Stop => {
+                        _ = audio.pause();
+                        audio.set_current_time(0.0);
+                    }
+                    Action::SeekBackward(by) => {
+                        audio.set_current_time((at - by.unwrap_or(SEEK_STEP)).max(0.0))
+                    }
+                    Action::SeekForward(by) => {
+                        audio.set_current_time((at + by.unwrap_or(SEEK_STEP)).min(audio.duration()))
+                    }
+                    Action::SeekTo(to) => audio.set_current_time(to),
+                }
+            }
+        },
+    );
+
+    let update = {
+        let audio = audio.clone();
+        let view = view.clone();
+        move |_: Event| {
+            let playing = !audio.paused();
+            let (at, duration) = (audio.current_time(), audio.duration());
+            view.tune
+                .set_text_content(Some(if playing { "Pause" } else { "Play" }));
+            if duration.is_finite() {
+                view.status.set_text_content(Some(&format!(
+                    "Ode to Joy · {} / {}",
+                    minutes(at),
+                    minutes(duration)
+                )));
+            }
+            media_session::set_playing(playing);
+            media_session::set_position(at, duration);
+        }
+    };
+    for event in [
+        "play",
+        "pause",
+        "ended",
+        "seeked",
+        "timeupdate",
+        "loadedmetadata",
+    ] {
+        app.listen(audio, event, update.clone());
+    }
+}
+
+fn minutes(seconds: f64) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Draws the analyser's spectrum every frame until the returned app unmounts.
+fn draw(analyser: &AnalyserNode, ctx: CanvasRenderingContext2d) -> App {
+    let mut app = App::new();
+    let analyser = analyser.clone();
+    app.spawn(async move {
+        let mut bins = vec![0; analyser.frequency_bin_count() as usize];
+        let bar = WIDTH as f64 / bins.len() as f64;
+        let mut frames = time::frames();
+        while frames.next().await.is_some() {
+            analyser.get_byte_frequency_data(&mut bins);
+            ctx.clear_rect(0.0, 0.0, WIDTH as f64, HEIGHT as f64);
+            for (i, level) in bins.iter().enumerate() {
+                let height = *level as f64 / 255.0 * HEIGHT as f64;
+                let hue = i * 270 / bins.len();
+                ctx.set_fill_style(&format!("hsl({hue}, 80%, 55%)").into());
+                ctx.fill_rect(i as f64 * bar, HEIGHT as f64 - height, bar - 1.0, height);
+            }
+        }
+    });
+    app
+}
+
+fn describe(e: &JsValue) -> String {
+    e.dyn_ref::<js_sys::Error>()
+        .map(|e| String::from(e.message()))
+        .unwrap_or_else(|| format!("{e:?}"))
+}
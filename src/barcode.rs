@@ -0,0 +1,205 @@
+//! Reading QR codes and barcodes from the camera.
+//!
+//! Where the browser has a `BarcodeDetector` (the Shape Detection API), it does the
+//! work, in whatever formats it supports. Elsewhere each frame is copied to a canvas,
+//! turned to grayscale and searched for QR codes in Rust, with `rqrr`. Either way
+//! [`scan`] shows the camera in a dialog until a code turns up or it's cancelled.
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    CanvasRenderingContext2d, HtmlCanvasElement, HtmlVideoElement, MediaStreamConstraints,
+};
+
+use crate::{
+    app::App,
+    dialog::Dialog,
+    dom::{create_element, window},
+    filters, media, time,
+};
+
+/// How often to look at the video for a code.
+const SCAN_PERIOD: Duration = Duration::from_millis(250);
+/// Frames are scaled down to this width before the Rust fallback searches them.
+const FALLBACK_WIDTH: u32 = 640;
+
+#[wasm_bindgen]
+extern "C" {
+    type BarcodeDetector;
+
+    #[wasm_bindgen(constructor, catch)]
+    fn new() -> Result<BarcodeDetector, JsValue>;
+
+    /// Resolves to the `DetectedBarcode`s in an image, video frame or canvas.
+    #[wasm_bindgen(method)]
+    fn detect(this: &BarcodeDetector, source: &JsValue) -> js_sys::Promise;
+
+    type DetectedBarcode;
+
+    #[wasm_bindgen(method, getter, js_name = rawValue)]
+    fn raw_value(this: &DetectedBarcode) -> String;
+
+    #[wasm_bindgen(method, getter)]
+    fn format(this: &DetectedBarcode) -> String;
+}
+
+/// A code that was read: what it says, and what kind of code it was.
+pub struct Scanned {
+    pub value: String,
+    /// As `BarcodeDetector` names them: `"qr_code"`, `"ean_13"`, …
+    pub format: String,
+}
+
+enum Detector {
+    Native(BarcodeDetector),
+    Rust {
+        canvas: HtmlCanvasElement,
+        ctx: CanvasRenderingContext2d,
+    },
+}
+
+impl Detector {
+    fn new() -> Detector {
+        let native = js_sys::Reflect::has(&window(), &"BarcodeDetector".into())
+            .unwrap_or(false)
+            .then(BarcodeDetector::new)
+            .and_then(Result::ok);
+        match native {
+            Some(detector) => Detector::Native(detector),
+            None => {
+                let canvas = create_element("canvas").unchecked_into::<HtmlCanvasElement>();
+                let ctx = canvas
+                    .get_context("2d")
+                    .ok()
+                    .flatten()
+                    .expect("a 2d canvas context")
+                    .unchecked_into::<CanvasRenderingContext2d>();
+                Detector::Rust { canvas, ctx }
+            }
+        }
+    }
+
+    /// The first code in the video's current frame, if there is one.
+    async fn detect(&self, video: &HtmlVideoElement) -> Option<Scanned> {
+        if video.video_width() == 0 {
+            // no frame yet
+            return None;
+        }
+        match self {
+            Detector::Native(detector) => {
+                let found = JsFuture::from(detector.detect(video)).await.ok()?;
+                let code = js_sys::Array::from(&found)
+                    .get(0)
+                    .dyn_into::<DetectedBarcode>()
+                    .ok()?;
+                Some(Scanned {
+                    value: code.raw_value(),
+                    format: code.format(),
+                })
+            }
+            Detector::Rust { canvas, ctx } => {
+                let scale = (FALLBACK_WIDTH as f64 / video.video_width() as f64).min(1.0);
+                let width = (video.video_width() as f64 * scale) as u32;
+                let height = (video.video_height() as f64 * scale) as u32;
+                canvas.set_width(width);
+                canvas.set_height(height);
+                ctx.draw_image_with_html_video_element_and_dw_and_dh(
+                    video,
+                    0.0,
+                    0.0,
+                    width as f64,
+                    height as f64,
+                )
+                .ok()?;
+                let pixels = ctx
+                    .get_image_data(0.0, 0.0, width as f64, height as f64)
+                    .ok()?
+                    .data();
+                decode_qr(&pixels, width as usize, height as usize).map(|value| Scanned {
+                    value,
+                    format: "qr_code".to_string(),
+                })
+            }
+        }
+    }
+}
+
+/// Finds and reads a QR code in RGBA pixels.
+fn decode_qr(pixels: &[u8], width: usize, height: usize) -> Option<String> {
+    let mut image = rqrr::PreparedImage::prepare_from_greyscale(width, height, |x, y| {
+        let i = (y * width + x) * 4;
+        filters::luma(&pixels[i..i + 4])
+    });
+    image
+        .detect_grids()
+        .into_iter()
+        .find_map(|grid| grid.decode().ok())
+        .map(|(_, content)| content)
+}
+
+/// Shows the camera until it sees a code, and returns it; `None` if the user gave up
+/// first.
+pub async fn scan() -> Option<Scanned> {
+    let dialog = Dialog::new("Scan a code").button("cancel", "Cancel");
+    let video = create_element("video").unchecked_into::<HtmlVideoElement>();
+    video.set_autoplay(true);
+    video.set_muted(true);
+    _ = video.set_attribute("playsinline", "");
+    _ = video.set_attribute(
+        "style",
+        "display: block; width: 320px; max-width: 100%; background: #111",
+    );
+    let status = create_element("p");
+    _ = status.set_attribute("role", "status");
+    status.set_text_content(Some("Asking for the camera…"));
+    dialog.body().append_child(&video).unwrap();
+    dialog.body().append_child(&status).unwrap();
+
+    let scanned = Rc::new(RefCell::new(None));
+    // dropped when the dialog closes, which stops the scanning and the camera with it
+    let mut scanning = App::new();
+    scanning.spawn({
+        let scanned = scanned.clone();
+        let close = dialog.closer();
+        async move {
+            // the back camera, on a phone
+            let video_constraints = js_sys::Object::new();
+            _ = js_sys::Reflect::set(
+                &video_constraints,
+                &"facingMode".into(),
+                &"environment".into(),
+            );
+            let mut constraints = MediaStreamConstraints::new();
+            constraints.video(&video_constraints);
+            let stream = match media::user_media(&constraints, "camera").await {
+                Ok(stream) => media::Running(stream),
+                Err(e) => {
+                    status.set_text_content(Some(&e));
+                    return;
+                }
+            };
+            video.set_src_object(Some(&stream.0));
+            let detector = Detector::new();
+            status.set_text_content(Some(match detector {
+                Detector::Native(_) => "Point the camera at a QR code or barcode.",
+                Detector::Rust { .. } => "Point the camera at a QR code.",
+            }));
+            loop {
+                time::sleep(SCAN_PERIOD).await;
+                if let Some(found) = detector.detect(&video).await {
+                    *scanned.borrow_mut() = Some(found);
+                    close("scanned");
+                    return;
+                }
+            }
+        }
+    });
+    let closed_with = dialog.show_modal().await;
+    drop(scanning);
+
+    closed_with
+        .filter(|value| value == "scanned")
+        .and_then(|_| scanned.take())
+}
@@ -0,0 +1,138 @@
+//! A battery indicator, and a low-power mode that turns animations off.
+//!
+//! `navigator.getBattery()` isn't in `web_sys`, so it's bound by hand here. Only
+//! Chromium browsers have it; elsewhere the indicator just stays hidden.
+
+use std::cell::Cell;
+
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Element, Event};
+
+use crate::{
+    app::App,
+    dom::{create_element, document},
+};
+
+/// Below this charge (and not charging), animations are switched off.
+const LOW_LEVEL: f64 = 0.2;
+const LOW_POWER_ATTRIBUTE: &str = "data-low-power";
+const LOW_POWER_STYLE: &str = "html[data-low-power] *, html[data-low-power] *::before, \
+     html[data-low-power] *::after { animation: none !important; transition: none !important; }";
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = navigator, js_name = getBattery, catch)]
+    fn get_battery() -> Result<js_sys::Promise, JsValue>;
+
+    #[wasm_bindgen(extends = web_sys::EventTarget)]
+    #[derive(Clone)]
+    pub type BatteryManager;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn charging(this: &BatteryManager) -> bool;
+
+    /// From 0.0 to 1.0.
+    #[wasm_bindgen(method, getter)]
+    pub fn level(this: &BatteryManager) -> f64;
+
+    /// Seconds until full, or `Infinity` when not charging.
+    #[wasm_bindgen(method, getter, js_name = chargingTime)]
+    pub fn charging_time(this: &BatteryManager) -> f64;
+
+    /// Seconds until empty, or `Infinity` when charging.
+    #[wasm_bindgen(method, getter, js_name = dischargingTime)]
+    pub fn discharging_time(this: &BatteryManager) -> f64;
+}
+
+thread_local! {
+    static LOW_POWER: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether the battery is low; code that animates from Rust should skip it if so.
+pub fn low_power() -> bool {
+    LOW_POWER.with(Cell::get)
+}
+
+pub async fn battery() -> Option<BatteryManager> {
+    let promise = get_battery().ok()?;
+    JsFuture::from(promise)
+        .await
+        .ok()
+        .map(JsCast::unchecked_into)
+}
+
+/// Adds the (initially hidden) indicator to `parent` and keeps it up to date.
+pub fn mount_indicator(app: &mut App, parent: &Element) {
+    let indicator = create_element("span");
+    _ = indicator.set_attribute("role", "status");
+    _ = indicator.set_attribute("hidden", "");
+    _ = indicator.set_attribute("style", "margin-left: 1em");
+    app.append(parent, &indicator);
+
+    let style = create_element("style");
+    style.set_text_content(Some(LOW_POWER_STYLE));
+    if let Some(head) = document().head() {
+        app.append(&head, &style);
+    }
+    app.on_cleanup(|| set_low_power(false));
+
+    app.spawn(async move {
+        let Some(battery) = battery().await else {
+            return;
+        };
+        let render = {
+            let battery = battery.clone();
+            move || render(&indicator, &battery)
+        };
+        render();
+
+        // the listeners belong to this task: they go when it's aborted on unmount
+        let mut listeners = App::new();
+        for event in [
+            "levelchange",
+            "chargingchange",
+            "chargingtimechange",
+            "dischargingtimechange",
+        ] {
+            let render = render.clone();
+            listeners.listen(&battery, event, move |_: Event| render());
+        }
+        futures::future::pending::<()>().await;
+        drop(listeners);
+    });
+}
+
+fn render(indicator: &Element, battery: &BatteryManager) {
+    let level = battery.level();
+    let charging = battery.charging();
+    let percent = (level * 100.0).round();
+    let (icon, remaining) = if charging {
+        ("⚡", battery.charging_time())
+    } else {
+        ("🔋", battery.discharging_time())
+    };
+    indicator.set_text_content(Some(&format!("{icon} {percent}%")));
+    let mut title = if charging {
+        format!("Charging, {percent}%")
+    } else {
+        format!("On battery, {percent}%")
+    };
+    if remaining.is_finite() && remaining > 0.0 {
+        let minutes = (remaining / 60.0).round();
+        let until = if charging { "full" } else { "empty" };
+        title.push_str(&format!(", about {minutes} min until {until}"));
+    }
+    _ = indicator.set_attribute("title", &title);
+    _ = indicator.set_attribute("aria-label", &title);
+    _ = indicator.remove_attribute("hidden");
+
+    set_low_power(!charging && level < LOW_LEVEL);
+}
+
+fn set_low_power(low: bool) {
+    LOW_POWER.with(|cell| cell.set(low));
+    if let Some(root) = document().document_element() {
+        _ = root.toggle_attribute_with_force(LOW_POWER_ATTRIBUTE, low);
+    }
+}
@@ -0,0 +1,274 @@
+//! The camera example: a live preview, snapshots of it, and a way to save them.
+//!
+//! Once the camera is allowed, the page can see every camera's name, so a picker
+//! lists them and switching restarts the stream on the chosen one; plugging one in or
+//! out updates the list. A snapshot copies the current video frame to a canvas, which
+//! can then be saved as a PNG through the [`downloads`] panel.
+
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use wasm_bindgen::JsCast;
+use web_sys::{
+    CanvasRenderingContext2d, Element, Event, HtmlButtonElement, HtmlCanvasElement,
+    HtmlSelectElement, HtmlVideoElement, MediaDeviceKind, MediaStream, MediaStreamConstraints,
+    MediaStreamTrack, MediaTrackConstraints,
+};
+
+use crate::{
+    app::App,
+    dom::{create_element, spawn_local},
+    downloads, export, media, toast,
+};
+
+/// Everything the example touches.
+#[derive(Clone)]
+struct View {
+    start: HtmlButtonElement,
+    snap: HtmlButtonElement,
+    cameras: HtmlSelectElement,
+    status: Element,
+    video: HtmlVideoElement,
+    canvas: HtmlCanvasElement,
+    save: HtmlButtonElement,
+}
+
+/// The camera stream while it runs.
+#[derive(Default)]
+struct State {
+    stream: Option<MediaStream>,
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        if let Some(stream) = &self.stream {
+            media::stop(stream);
+        }
+    }
+}
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let view = View {
+        start: button("Start the camera"),
+        snap: button("Take a snapshot"),
+        cameras: create_element("select").unchecked_into(),
+        status: create_element("p"),
+        video: create_element("video").unchecked_into(),
+        canvas: create_element("canvas").unchecked_into(),
+        save: button("Save the snapshot"),
+    };
+    view.snap.set_disabled(true);
+    _ = view.cameras.set_attribute("aria-label", "Camera");
+    _ = view.cameras.set_attribute("hidden", "");
+    _ = view.status.set_attribute("role", "status");
+    // muted and inline, or mobile browsers won't autoplay it
+    view.video.set_autoplay(true);
+    view.video.set_muted(true);
+    _ = view.video.set_attribute("playsinline", "");
+    _ = view
+        .video
+        .set_attribute("style", "width: 100%; max-width: 480px; background: #111");
+    _ = view
+        .canvas
+        .set_attribute("style", "width: 100%; max-width: 480px");
+    _ = view.canvas.set_attribute("hidden", "");
+    _ = view.save.set_attribute("hidden", "");
+
+    let section = create_element("section");
+    let controls = create_element("p");
+    for el in [
+        view.start.unchecked_ref::<Element>(),
+        view.snap.unchecked_ref(),
+        view.cameras.unchecked_ref(),
+    ] {
+        controls.append_child(el).unwrap();
+        _ = controls.append_with_str_1(" ");
+    }
+    let shots = create_element("div");
+    shots.append_child(&view.canvas).unwrap();
+    shots.append_child(&create_element("br")).unwrap();
+    shots.append_child(&view.save).unwrap();
+    for el in [&controls, &view.status, view.video.unchecked_ref(), &shots] {
+        section.append_child(el).unwrap();
+    }
+    app.append(parent, &section);
+
+    let state = Rc::new(RefCell::new(State::default()));
+
+    app.listen(&view.start.clone(), "click", {
+        let view = view.clone();
+        let state = Rc::downgrade(&state);
+        move |_: Event| {
+            let Some(current) = state.upgrade() else {
+                return;
+            };
+            let stream = current.borrow_mut().stream.take();
+            match stream {
+                Some(stream) => {
+                    media::stop(&stream);
+                    view.video.set_src_object(None);
+                    view.start.set_text_content(Some("Start the camera"));
+                    view.snap.set_disabled(true);
+                    view.status.set_text_content(Some("Stopped."));
+                }
+                None => spawn_local(start(view.clone(), state.clone(), None)),
+            }
+        }
+    });
+    app.listen(&view.cameras.clone(), "change", {
+        let view = view.clone();
+        let state = Rc::downgrade(&state);
+        move |_: Event| {
+            let Some(current) = state.upgrade() else {
+                return;
+            };
+            if let Some(stream) = current.borrow_mut().stream.take() {
+                media::stop(&stream);
+            }
+            let device = view.cameras.value();
+            spawn_local(start(view.clone(), state.clone(), Some(device)));
+        }
+    });
+    app.listen(&view.snap.clone(), "click", {
+        let view = view.clone();
+        move |_: Event| snapshot(&view)
+    });
+    app.listen(&view.save.clone(), "click", {
+        let canvas = view.canvas.clone();
+        move |_: Event| spawn_local(save(canvas.clone()))
+    });
+    // cameras plugged in or out
+    if let Ok(devices) = media::media_devices("camera") {
+        let view = view.clone();
+        let state = Rc::downgrade(&state);
+        app.listen(&devices, "devicechange", move |_: Event| {
+            let has_stream = state
+                .upgrade()
+                .is_some_and(|state| state.borrow().stream.is_some());
+            if has_stream {
+                spawn_local(list_cameras(view.clone(), current_device(&state)));
+            }
+        });
+    }
+    app.on_cleanup(move || drop(state));
+
+    app
+}
+
+fn button(label: &str) -> HtmlButtonElement {
+    let button = create_element("button").unchecked_into::<HtmlButtonElement>();
+    button.set_text_content(Some(label));
+    button
+}
+
+/// Starts the camera (a particular one, or whichever the browser picks), then lists
+/// the cameras there are now that their names can be read.
+async fn start(view: View, state: Weak<RefCell<State>>, device: Option<String>) {
+    view.start.set_disabled(true);
+    view.status.set_text_content(Some("Asking for the camera…"));
+    let mut constraints = MediaStreamConstraints::new();
+    match &device {
+        Some(device) => {
+            let exact = js_sys::Object::new();
+            _ = js_sys::Reflect::set(&exact, &"exact".into(), &device.into());
+            let mut video = MediaTrackConstraints::new();
+            video.device_id(&exact);
+            constraints.video(&video)
+        }
+        None => constraints.video(&true.into()),
+    };
+    let result = media::user_media(&constraints, "camera").await;
+    view.start.set_disabled(false);
+    // left the example while the browser was asking
+    let Some(current) = state.upgrade() else {
+        if let Ok(stream) = result {
+            media::stop(&stream);
+        }
+        return;
+    };
+    match result {
+        Ok(stream) => {
+            view.video.set_src_object(Some(&stream));
+            current.borrow_mut().stream = Some(stream);
+            view.start.set_text_content(Some("Stop the camera"));
+            view.snap.set_disabled(false);
+            view.status.set_text_content(None);
+            list_cameras(view, current_device(&state)).await;
+        }
+        Err(e) => {
+            view.start.set_text_content(Some("Start the camera"));
+            view.snap.set_disabled(true);
+            view.status.set_text_content(Some(&e));
+        }
+    }
+}
+
+/// Fills the picker with the cameras, with `current` selected. One camera needs no
+/// picker.
+async fn list_cameras(view: View, current: Option<String>) {
+    let cameras = media::devices(MediaDeviceKind::Videoinput).await;
+    view.cameras.set_inner_html("");
+    for (i, camera) in cameras.iter().enumerate() {
+        let option = create_element("option");
+        _ = option.set_attribute("value", &camera.device_id());
+        let mut label = camera.label();
+        if label.is_empty() {
+            label = format!("Camera {}", i + 1);
+        }
+        option.set_text_content(Some(&label));
+        view.cameras.append_child(&option).unwrap();
+    }
+    if let Some(current) = current {
+        view.cameras.set_value(&current);
+    }
+    if cameras.len() > 1 {
+        _ = view.cameras.remove_attribute("hidden");
+    } else {
+        _ = view.cameras.set_attribute("hidden", "");
+    }
+}
+
+/// The id of the camera the stream is coming from.
+fn current_device(state: &Weak<RefCell<State>>) -> Option<String> {
+    let state = state.upgrade()?;
+    let state = state.borrow();
+    let track = state.stream.as_ref()?.get_video_tracks().get(0);
+    let settings = track.dyn_into::<MediaStreamTrack>().ok()?.get_settings();
+    js_sys::Reflect::get(&settings, &"deviceId".into())
+        .ok()?
+        .as_string()
+}
+
+/// Copies the current frame to the canvas.
+fn snapshot(view: &View) {
+    let (width, height) = (view.video.video_width(), view.video.video_height());
+    if width == 0 || height == 0 {
+        return;
+    }
+    view.canvas.set_width(width);
+    view.canvas.set_height(height);
+    let Some(ctx) = view
+        .canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .and_then(|ctx| ctx.dyn_into::<CanvasRenderingContext2d>().ok())
+    else {
+        return;
+    };
+    _ = ctx.draw_image_with_html_video_element(&view.video, 0.0, 0.0);
+    _ = view.canvas.remove_attribute("hidden");
+    _ = view.save.remove_attribute("hidden");
+}
+
+/// Downloads the snapshot as a PNG.
+async fn save(canvas: HtmlCanvasElement) {
+    match export::canvas_to_blob(&canvas, "image/png").await {
+        Ok(blob) => downloads::download_blob("snapshot.png", &blob),
+        Err(error) => toast::error(&format!("Couldn't save the snapshot: {error}")),
+    }
+}
@@ -0,0 +1,106 @@
+//! Ten thousand cards, rendered [`progressive`]ly: grey placeholders for all of them
+//! first, and then each card's colour and text, a frame's worth at a time. The line
+//! above them says when the first ones were painted, and how far it's got.
+
+use std::time::Duration;
+
+use web_sys::Element;
+
+use crate::{
+    app::App,
+    dom::create_element,
+    html::el,
+    progressive::{self, Progress},
+};
+
+const CARDS: usize = 10_000;
+/// Of each frame's 16ms, leaving the rest for the browser.
+const BUDGET: Duration = Duration::from_millis(8);
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+    let stats = el!(p).text("Rendering…").mount(&mut app, parent);
+    let grid = el!(
+        ul,
+        style = "display: grid; grid-template-columns: repeat(auto-fill, minmax(120px, 1fr)); \
+                 gap: 8px; padding: 0; list-style: none",
+    )
+    .mount(&mut app, parent);
+
+    app.spawn(async move {
+        let cards: Vec<usize> = (0..CARDS).collect();
+        progressive::render(&grid, &cards, BUDGET, placeholder, fill, |progress| {
+            stats.set_text_content(Some(&describe(progress)));
+        })
+        .await;
+    });
+    app
+}
+
+fn placeholder(i: usize, _: &usize) -> Element {
+    let card = create_element("li");
+    _ = card.set_attribute(
+        "style",
+        "height: 80px; padding: 8px; border-radius: 6px; background: #eee",
+    );
+    card.set_text_content(Some(&format!("#{}", i + 1)));
+    card
+}
+
+fn fill(i: &usize, card: &Element) {
+    let hue = i * 37 % 360;
+    _ = card.set_attribute(
+        "style",
+        &format!(
+            "height: 80px; padding: 8px; border-radius: 6px; \
+             background: hsl({hue} 70% 85%)"
+        ),
+    );
+    card.set_inner_html("");
+    let title = create_element("strong");
+    title.set_text_content(Some(&format!("Card {}", i + 1)));
+    let detail = create_element("p");
+    detail.set_text_content(Some(&format!("Hue {hue}°")));
+    card.append_child(&title).unwrap();
+    card.append_child(&detail).unwrap();
+}
+
+fn describe(progress: &Progress) -> String {
+    let first_paint = progress.first_paint.map_or(String::new(), |ms| {
+        format!("First cards painted after {ms:.0}ms. ")
+    });
+    if progress.done() {
+        format!(
+            "{first_paint}All {} rendered in {:.0}ms.",
+            progress.total, progress.elapsed
+        )
+    } else {
+        format!(
+            "{first_paint}{} of {} placed, {} filled in…",
+            progress.placed, progress.total, progress.filled
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_progress() {
+        let mut progress = Progress {
+            total: 10,
+            placed: 4,
+            ..Progress::default()
+        };
+        assert_eq!(describe(&progress), "4 of 10 placed, 0 filled in…");
+        progress.first_paint = Some(12.4);
+        progress.placed = 10;
+        progress.filled = 10;
+        progress.elapsed = 250.0;
+        assert_eq!(
+            describe(&progress),
+            "First cards painted after 12ms. All 10 rendered in 250ms."
+        );
+    }
+}
@@ -0,0 +1,451 @@
+//! A shopping cart with a mock checkout through the Payment Request API.
+//!
+//! `PaymentRequest` isn't in our version of `web_sys`, so it's bound by hand here, and
+//! its flow turned into plain async Rust: [`checkout`] starts the browser's payment
+//! sheet, keeps the total up to date when the shipping option changes (the sheet asks
+//! through an event, and waits for `updateWith`), and ends with a [`Paid`] or an error.
+//!
+//! The method is Google's demo payment app, so nothing is ever charged. Browsers
+//! without the API, or without a way to pay with that method, get a plain form instead.
+
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    rc::Rc,
+    time::Duration,
+};
+
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Element, HtmlInputElement, HtmlSelectElement, MouseEvent};
+
+use crate::{
+    app::App,
+    dom::{add_event_listener, create_element, spawn_local, window},
+    time::sleep,
+};
+
+/// Google's demo payment handler, which "pays" with a fake balance.
+const PAYMENT_METHOD: &str = "https://bobbucks.dev/pay";
+const CURRENCY: &str = "USD";
+
+/// Name and price in cents.
+const PRODUCTS: &[(&str, u32)] = &[
+    ("Rust sticker", 300),
+    ("Ferris plushie", 2400),
+    ("WebAssembly mug", 1250),
+];
+
+struct ShippingOption {
+    id: &'static str,
+    label: &'static str,
+    cents: u32,
+}
+
+const SHIPPING: &[ShippingOption] = &[
+    ShippingOption {
+        id: "standard",
+        label: "Standard (5–7 days)",
+        cents: 0,
+    },
+    ShippingOption {
+        id: "express",
+        label: "Express (1–2 days)",
+        cents: 1500,
+    },
+];
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends = web_sys::EventTarget)]
+    #[derive(Clone)]
+    type PaymentRequest;
+
+    #[wasm_bindgen(constructor, catch)]
+    fn new(
+        methods: &JsValue,
+        details: &JsValue,
+        options: &JsValue,
+    ) -> Result<PaymentRequest, JsValue>;
+
+    #[wasm_bindgen(method, js_name = canMakePayment, catch)]
+    fn can_make_payment(this: &PaymentRequest) -> Result<js_sys::Promise, JsValue>;
+
+    /// Opens the payment sheet; has to be called during a user gesture.
+    #[wasm_bindgen(method, catch)]
+    fn show(this: &PaymentRequest) -> Result<js_sys::Promise, JsValue>;
+
+    #[wasm_bindgen(method, getter, js_name = shippingOption)]
+    fn shipping_option(this: &PaymentRequest) -> Option<String>;
+
+    #[wasm_bindgen(extends = web_sys::Event)]
+    type PaymentRequestUpdateEvent;
+
+    /// Tells the sheet what the details are now; it waits until this is called.
+    #[wasm_bindgen(method, js_name = updateWith)]
+    fn update_with(this: &PaymentRequestUpdateEvent, details: &JsValue);
+
+    type PaymentResponse;
+
+    #[wasm_bindgen(method, getter, js_name = methodName)]
+    fn method_name(this: &PaymentResponse) -> String;
+
+    #[wasm_bindgen(method, getter, js_name = payerName)]
+    fn payer_name(this: &PaymentResponse) -> Option<String>;
+
+    /// Closes the sheet with "success", "fail" or "unknown".
+    #[wasm_bindgen(method)]
+    fn complete(this: &PaymentResponse, result: &str) -> js_sys::Promise;
+}
+
+/// A checkout that went through.
+pub struct Paid {
+    pub method: String,
+    pub payer: Option<String>,
+    pub cents: u32,
+}
+
+/// What's in the cart: a quantity for each of the [`PRODUCTS`].
+type Quantities = Rc<RefCell<Vec<u32>>>;
+
+fn subtotal(quantities: &[u32]) -> u32 {
+    quantities
+        .iter()
+        .zip(PRODUCTS)
+        .map(|(quantity, (_, cents))| quantity * cents)
+        .sum()
+}
+
+fn shipping(id: &str) -> &'static ShippingOption {
+    SHIPPING
+        .iter()
+        .find(|option| option.id == id)
+        .unwrap_or(&SHIPPING[0])
+}
+
+/// "12.50", for cents = 1250.
+fn price(cents: u32) -> String {
+    format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+/// Whether this browser can pay with our method through a payment sheet.
+async fn can_pay() -> bool {
+    let supported = js_sys::Reflect::has(&window(), &"PaymentRequest".into()).unwrap_or(false);
+    if !supported {
+        return false;
+    }
+    let Ok(request) = PaymentRequest::new(&methods(), &details(&[], "standard"), &options()) else {
+        return false;
+    };
+    let Ok(promise) = request.can_make_payment() else {
+        return false;
+    };
+    JsFuture::from(promise)
+        .await
+        .is_ok_and(|can| can.as_bool() == Some(true))
+}
+
+/// Opens the payment sheet for `quantities`, returning a future for how it went. Call
+/// it straight from a click handler: the sheet opens before it returns.
+fn checkout(quantities: Vec<u32>) -> impl Future<Output = Result<Paid, String>> {
+    let started = PaymentRequest::new(&methods(), &details(&quantities, "standard"), &options())
+        .map(|request| {
+            let shown = request.show();
+            (request, shown)
+        });
+    // the sheet waits for new details every time the shipping option changes
+    let listener = started.as_ref().ok().map(|(request, _)| {
+        let request = request.clone();
+        let quantities = quantities.clone();
+        add_event_listener(
+            &request.clone(),
+            "shippingoptionchange",
+            move |ev: PaymentRequestUpdateEvent| {
+                let option = request.shipping_option().unwrap_or_default();
+                ev.update_with(&details(&quantities, &option));
+            },
+        )
+    });
+    async move {
+        let _listener = listener;
+        let (request, shown) = started.map_err(|e| describe(&e))?;
+        let response: PaymentResponse = JsFuture::from(shown.map_err(|e| describe(&e))?)
+            .await
+            .map_err(|e| describe(&e))?
+            .unchecked_into();
+        let option = request.shipping_option().unwrap_or_default();
+        finish(response, &quantities, &option).await
+    }
+}
+
+async fn finish(
+    response: PaymentResponse,
+    quantities: &[u32],
+    option: &str,
+) -> Result<Paid, String> {
+    // where a real shop would send the response to its server
+    sleep(Duration::from_millis(500)).await;
+    _ = JsFuture::from(response.complete("success")).await;
+    Ok(Paid {
+        method: response.method_name(),
+        payer: response.payer_name(),
+        cents: subtotal(quantities) + shipping(option).cents,
+    })
+}
+
+fn methods() -> JsValue {
+    let method = object(&[("supportedMethods", PAYMENT_METHOD.into())]);
+    js_sys::Array::of1(&method).into()
+}
+
+fn options() -> JsValue {
+    object(&[
+        ("requestPayerName", true.into()),
+        ("requestShipping", true.into()),
+    ])
+}
+
+/// The line items, the shipping options with `selected` picked, and the total.
+fn details(quantities: &[u32], selected: &str) -> JsValue {
+    let items = js_sys::Array::new();
+    for (quantity, (name, cents)) in quantities.iter().zip(PRODUCTS) {
+        if *quantity > 0 {
+            items.push(&item(&format!("{name} × {quantity}"), quantity * cents));
+        }
+    }
+    let chosen = shipping(selected);
+    items.push(&item("Shipping", chosen.cents));
+    let options = js_sys::Array::new();
+    for option in SHIPPING {
+        options.push(&object(&[
+            ("id", option.id.into()),
+            ("label", option.label.into()),
+            ("amount", amount(option.cents)),
+            ("selected", (option.id == chosen.id).into()),
+        ]));
+    }
+    object(&[
+        ("total", item("Total", subtotal(quantities) + chosen.cents)),
+        ("displayItems", items.into()),
+        ("shippingOptions", options.into()),
+    ])
+}
+
+fn item(label: &str, cents: u32) -> JsValue {
+    object(&[("label", label.into()), ("amount", amount(cents))])
+}
+
+fn amount(cents: u32) -> JsValue {
+    object(&[
+        ("currency", CURRENCY.into()),
+        ("value", price(cents).into()),
+    ])
+}
+
+fn object(entries: &[(&str, JsValue)]) -> JsValue {
+    let object = js_sys::Object::new();
+    for (key, value) in entries {
+        _ = js_sys::Reflect::set(&object, &(*key).into(), value);
+    }
+    object.into()
+}
+
+fn describe(e: &JsValue) -> String {
+    let error = e.dyn_ref::<js_sys::Error>();
+    match error.map(|e| String::from(e.name())).as_deref() {
+        Some("AbortError") => "Checkout cancelled.".to_string(),
+        Some("SecurityError") => "Payments need a secure origin (https or localhost).".to_string(),
+        _ => error
+            .map(|e| String::from(e.message()))
+            .unwrap_or_else(|| format!("{e:?}")),
+    }
+}
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+    let quantities: Quantities = Rc::new(RefCell::new(vec![0; PRODUCTS.len()]));
+
+    let section = create_element("section");
+    let list = create_element("ul");
+    let total = create_element("p");
+    let pay = create_element("button");
+    pay.set_text_content(Some("Checkout"));
+    let status = create_element("p");
+    _ = status.set_attribute("role", "status");
+    let form = create_element("div");
+    _ = form.set_attribute("hidden", "");
+    for child in [&list, &total, &pay, &form, &status] {
+        section.append_child(child).unwrap();
+    }
+    app.append(parent, &section);
+
+    for (index, (name, cents)) in PRODUCTS.iter().enumerate() {
+        let item = create_element("li");
+        item.set_text_content(Some(&format!("{name}, ${} ", price(*cents))));
+        for (label, delta) in [("−", -1), ("+", 1)] {
+            let button = create_element("button");
+            button.set_text_content(Some(label));
+            _ = button.set_attribute("aria-label", &format!("{label} {name}"));
+            _ = button.set_attribute("data-product", &index.to_string());
+            _ = button.set_attribute("data-delta", &delta.to_string());
+            item.append_child(&button).unwrap();
+        }
+        let quantity = create_element("span");
+        _ = quantity.set_attribute("data-quantity", "");
+        item.append_child(&quantity).unwrap();
+        list.append_child(&item).unwrap();
+    }
+    let render = {
+        let quantities = quantities.clone();
+        let list = list.clone();
+        let pay = pay.clone();
+        move || {
+            let quantities = quantities.borrow();
+            let shown = list.query_selector_all("[data-quantity]").unwrap();
+            for (index, quantity) in quantities.iter().enumerate() {
+                if let Some(node) = shown.get(index as u32) {
+                    node.set_text_content(Some(&format!(" × {quantity}")));
+                }
+            }
+            total.set_text_content(Some(&format!(
+                "Subtotal: ${}",
+                price(subtotal(&quantities))
+            )));
+            _ = pay.toggle_attribute_with_force("disabled", subtotal(&quantities) == 0);
+        }
+    };
+    render();
+
+    app.listen(&list, "click", {
+        let quantities = quantities.clone();
+        move |ev: MouseEvent| {
+            let Some(button) = ev
+                .target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+            else {
+                return;
+            };
+            let product = button
+                .get_attribute("data-product")
+                .and_then(|i| i.parse::<usize>().ok());
+            let delta = button
+                .get_attribute("data-delta")
+                .and_then(|d| d.parse().ok());
+            if let (Some(product), Some(delta)) = (product, delta) {
+                if let Some(quantity) = quantities.borrow_mut().get_mut(product) {
+                    *quantity = quantity.saturating_add_signed(delta);
+                }
+                render();
+            }
+        }
+    });
+
+    // found out up front, since the sheet has to open in the click itself
+    let sheet = Rc::new(Cell::new(false));
+    app.spawn({
+        let sheet = sheet.clone();
+        async move { sheet.set(can_pay().await) }
+    });
+    let fallback = mount_fallback_form(&mut app, &form, &quantities, &status);
+
+    app.listen(&pay, "click", move |_: MouseEvent| {
+        if !sheet.get() {
+            fallback();
+            return;
+        }
+        let done = checkout(quantities.borrow().clone());
+        let status = status.clone();
+        spawn_local(async move {
+            let message = match done.await {
+                Ok(paid) => paid_message(&paid),
+                Err(error) => error,
+            };
+            status.set_text_content(Some(&message));
+        });
+    });
+
+    app
+}
+
+fn paid_message(paid: &Paid) -> String {
+    let payer = paid.payer.as_deref().map(|payer| format!(" by {payer}"));
+    format!(
+        "Paid ${}{} with {} (a demo: nothing was charged).",
+        price(paid.cents),
+        payer.unwrap_or_default(),
+        paid.method
+    )
+}
+
+/// The form shown where there's no payment sheet; returns what opens it.
+fn mount_fallback_form(
+    app: &mut App,
+    form: &Element,
+    quantities: &Quantities,
+    status: &Element,
+) -> impl Fn() {
+    let name = create_element("input").unchecked_into::<HtmlInputElement>();
+    name.set_placeholder("Name on card");
+    _ = name.set_attribute("aria-label", "Name on card");
+    let card = create_element("input").unchecked_into::<HtmlInputElement>();
+    card.set_placeholder("4242 4242 4242 4242");
+    _ = card.set_attribute("aria-label", "Card number");
+    _ = card.set_attribute("inputmode", "numeric");
+    let option = create_element("select").unchecked_into::<HtmlSelectElement>();
+    _ = option.set_attribute("aria-label", "Shipping");
+    for shipping in SHIPPING {
+        let choice = create_element("option");
+        _ = choice.set_attribute("value", shipping.id);
+        choice.set_text_content(Some(&format!(
+            "{}, ${}",
+            shipping.label,
+            price(shipping.cents)
+        )));
+        option.append_child(&choice).unwrap();
+    }
+    let submit = create_element("button");
+    submit.set_text_content(Some("Pay"));
+    for child in [
+        name.unchecked_ref(),
+        card.unchecked_ref(),
+        option.unchecked_ref(),
+        &submit,
+    ] {
+        form.append_child(child).unwrap();
+    }
+
+    app.listen(&submit, "click", {
+        let form = form.clone();
+        let quantities = quantities.clone();
+        let status = status.clone();
+        move |_: MouseEvent| {
+            let digits = card.value().chars().filter(char::is_ascii_digit).count();
+            if name.value().trim().is_empty() || !(12..=19).contains(&digits) {
+                status.set_text_content(Some("Enter a name and a card number."));
+                return;
+            }
+            _ = form.set_attribute("hidden", "");
+            status.set_text_content(Some(&paid_message(&Paid {
+                method: "the card form".to_string(),
+                payer: Some(name.value()),
+                cents: subtotal(&quantities.borrow()) + shipping(&option.value()).cents,
+            })));
+        }
+    });
+
+    let form = form.clone();
+    move || _ = form.remove_attribute("hidden")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn totals_in_cents() {
+        assert_eq!(subtotal(&[2, 0, 1]), 1850);
+        assert_eq!(price(1850), "18.50");
+        assert_eq!(price(5), "0.05");
+        assert_eq!(shipping("nonsense").id, "standard");
+    }
+}
@@ -0,0 +1,55 @@
+//! Clipboard access: the async Clipboard API and paste events (text and images).
+//!
+//! `web_sys` only exposes `navigator.clipboard` behind `--cfg=web_sys_unstable_apis`,
+//! so the parts we need are bound by hand.
+
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+#[wasm_bindgen]
+extern "C" {
+    /// The promise-returning call behind [`write_text`], for callers that have to start
+    /// the write synchronously (inside a user gesture) and await it later.
+    #[wasm_bindgen(js_namespace = ["navigator", "clipboard"], js_name = writeText, catch)]
+    pub fn write_text_promise(text: &str) -> Result<js_sys::Promise, JsValue>;
+}
+
+/// Copies `text` to the clipboard. Fails if the page isn't focused, the user
+/// denied permission, or the API doesn't exist (e.g. on plain `http://`).
+pub async fn write_text(text: &str) -> Result<(), JsValue> {
+    JsFuture::from(write_text_promise(text)?).await?;
+    Ok(())
+}
+
+#[wasm_bindgen]
+extern "C" {
+    /// `ClipboardEvent` is also behind the unstable flag; this is the subset we use.
+    #[wasm_bindgen(extends = web_sys::Event)]
+    pub type ClipboardEvent;
+
+    #[wasm_bindgen(method, getter, js_name = clipboardData)]
+    pub fn clipboard_data(this: &ClipboardEvent) -> Option<web_sys::DataTransfer>;
+}
+
+impl ClipboardEvent {
+    /// The plain-text contents being pasted, if there are any.
+    pub fn text(&self) -> Option<String> {
+        self.clipboard_data()?
+            .get_data("text/plain")
+            .ok()
+            .filter(|text| !text.is_empty())
+    }
+
+    /// The images being pasted, e.g. a screenshot or an image copied from a web page.
+    /// These come through as files even when they never were one.
+    pub fn images(&self) -> Vec<web_sys::File> {
+        let Some(items) = self.clipboard_data().map(|data| data.items()) else {
+            return Vec::new();
+        };
+        (0..items.length())
+            .filter_map(|i| items.get(i))
+            .filter(|item| item.kind() == "file" && item.type_().starts_with("image/"))
+            .filter_map(|item| item.get_as_file().ok().flatten())
+            .collect()
+    }
+}
@@ -0,0 +1,64 @@
+//! The current time, updated every second.
+//!
+//! The ticking is a stream (`time::every`) consumed by a task owned by the `App`, so
+//! unmounting the example aborts the task, which drops the stream and clears the
+//! interval: no timers are left running once you navigate away. Nobody's looking
+//! while the user is idle, so it stops rendering then.
+
+use std::{rc::Rc, time::Duration};
+
+use futures::StreamExt;
+use js_sys::Date;
+use web_sys::Element;
+
+use crate::{app::App, dom::create_element, idle, intl::DateTimeFormat, time};
+
+const TICK: Duration = Duration::from_secs(1);
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let section = create_element("section");
+    let clock = create_element("time");
+    _ = clock.set_attribute(
+        "style",
+        "display: block; font-size: 3em; font-variant-numeric: tabular-nums",
+    );
+    // screen readers would otherwise announce every single second
+    _ = clock.set_attribute("aria-live", "off");
+    let date = create_element("p");
+    section.append_child(&clock).unwrap();
+    section.append_child(&date).unwrap();
+    app.append(parent, &section);
+
+    let time_format = DateTimeFormat::time();
+    let date_format = DateTimeFormat::long_date();
+    let render = move || {
+        let now = Date::new_0();
+        clock.set_text_content(Some(&time_format.format(&now)));
+        _ = clock.set_attribute("datetime", &String::from(now.to_iso_string()));
+        date.set_text_content(Some(&date_format.format(&now)));
+    };
+
+    render();
+    let render = Rc::new(render);
+    // catch up straight away instead of up to a second later
+    idle::subscribe(&mut app, {
+        let render = render.clone();
+        move |idle| {
+            if !idle {
+                render();
+            }
+        }
+    });
+    app.spawn(async move {
+        let mut ticks = time::every(TICK);
+        while ticks.next().await.is_some() {
+            if !idle::is_idle() {
+                render();
+            }
+        }
+    });
+
+    app
+}
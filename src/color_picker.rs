@@ -0,0 +1,293 @@
+//! A color picker: a saturation/value square above a hue strip, both drawn on canvases.
+//!
+//! The picker keeps its own HSV state (hue has to survive dragging to grey, where RGB
+//! forgets it) and reports every change as an RGB [`Color`]. Drag with the mouse, pen
+//! or a finger; with a canvas focused the arrow keys nudge it (Shift for bigger steps).
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    rc::Rc,
+};
+
+use wasm_bindgen::{Clamped, JsCast};
+use web_sys::{
+    CanvasRenderingContext2d, Element, HtmlCanvasElement, ImageData, KeyboardEvent, PointerEvent,
+};
+
+use crate::{app::App, dom::create_element};
+
+const SIZE: u32 = 200;
+const HUE_HEIGHT: u32 = 16;
+const MARKER_RADIUS: f64 = 5.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    /// `h` in degrees `[0, 360)`, `s` and `v` in `[0, 1]`.
+    pub fn from_hsv(h: f64, s: f64, v: f64) -> Color {
+        let c = v * s;
+        let h = (h.rem_euclid(360.0)) / 60.0;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = v - c;
+        let channel = |value: f64| ((value + m) * 255.0).round() as u8;
+        Color {
+            r: channel(r),
+            g: channel(g),
+            b: channel(b),
+        }
+    }
+
+    /// The inverse of [`Color::from_hsv`]; greys come back with a hue of 0.
+    pub fn to_hsv(self) -> (f64, f64, f64) {
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| c as f64 / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let h = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        let s = if max == 0.0 { 0.0 } else { delta / max };
+        (h, s, max)
+    }
+}
+
+/// `#rrggbb`
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+pub struct ColorPicker {
+    square: HtmlCanvasElement,
+    square_ctx: CanvasRenderingContext2d,
+    hue: HtmlCanvasElement,
+    hue_ctx: CanvasRenderingContext2d,
+    hsv: Cell<(f64, f64, f64)>,
+    /// The square's pixels for the current hue, so moving the marker doesn't redraw them.
+    pixels: RefCell<Option<(f64, ImageData)>>,
+    on_change: Box<dyn Fn(Color)>,
+}
+
+impl ColorPicker {
+    pub fn mount(
+        app: &mut App,
+        parent: &Element,
+        initial: Color,
+        on_change: impl Fn(Color) + 'static,
+    ) -> Rc<Self> {
+        let root = create_element("div");
+        _ = root.set_attribute(
+            "style",
+            "display: inline-flex; flex-direction: column; gap: 8px",
+        );
+        let (square, square_ctx) = canvas(SIZE, SIZE);
+        _ = square.set_attribute("aria-label", "Saturation and brightness");
+        let (hue, hue_ctx) = canvas(SIZE, HUE_HEIGHT);
+        _ = hue.set_attribute("aria-label", "Hue");
+        for canvas in [&square, &hue] {
+            _ = canvas.set_attribute("role", "slider");
+            _ = canvas.set_attribute("tabindex", "0");
+            // otherwise touch-dragging scrolls the page instead
+            _ = canvas.set_attribute("style", "touch-action: none; cursor: crosshair");
+            root.append_child(canvas).unwrap();
+        }
+        app.append(parent, &root);
+
+        let picker = Rc::new(ColorPicker {
+            square,
+            square_ctx,
+            hue,
+            hue_ctx,
+            hsv: Cell::new(initial.to_hsv()),
+            pixels: RefCell::new(None),
+            on_change: Box::new(on_change),
+        });
+        picker.draw();
+
+        on_drag(app, &picker.square, {
+            let picker = picker.clone();
+            move |x, y| {
+                let (h, _, _) = picker.hsv.get();
+                picker.set_hsv((h, x, 1.0 - y));
+            }
+        });
+        on_drag(app, &picker.hue, {
+            let picker = picker.clone();
+            move |x, _| {
+                let (_, s, v) = picker.hsv.get();
+                // 360° is the same as 0°, so stop just short of it
+                picker.set_hsv((x * 359.0, s, v));
+            }
+        });
+
+        app.listen(&picker.square.clone(), "keydown", {
+            let picker = picker.clone();
+            move |ev: KeyboardEvent| {
+                let step = if ev.shift_key() { 0.1 } else { 0.01 };
+                let (h, s, v) = picker.hsv.get();
+                let hsv = match ev.key().as_str() {
+                    "ArrowLeft" => (h, s - step, v),
+                    "ArrowRight" => (h, s + step, v),
+                    "ArrowUp" => (h, s, v + step),
+                    "ArrowDown" => (h, s, v - step),
+                    _ => return,
+                };
+                ev.prevent_default();
+                picker.set_hsv(hsv);
+            }
+        });
+        app.listen(&picker.hue.clone(), "keydown", {
+            let picker = picker.clone();
+            move |ev: KeyboardEvent| {
+                let step = if ev.shift_key() { 10.0 } else { 1.0 };
+                let (h, s, v) = picker.hsv.get();
+                let h = match ev.key().as_str() {
+                    "ArrowLeft" | "ArrowDown" => h - step,
+                    "ArrowRight" | "ArrowUp" => h + step,
+                    _ => return,
+                };
+                ev.prevent_default();
+                picker.set_hsv((h.clamp(0.0, 359.0), s, v));
+            }
+        });
+
+        picker
+    }
+
+    pub fn color(&self) -> Color {
+        let (h, s, v) = self.hsv.get();
+        Color::from_hsv(h, s, v)
+    }
+
+    fn set_hsv(&self, (h, s, v): (f64, f64, f64)) {
+        let hsv = (h, s.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
+        if hsv == self.hsv.get() {
+            return;
+        }
+        let before = self.color();
+        self.hsv.set(hsv);
+        self.draw();
+        // many HSV positions round to the same RGB color
+        if self.color() != before {
+            (self.on_change)(self.color());
+        }
+    }
+
+    fn draw(&self) {
+        let (h, s, v) = self.hsv.get();
+
+        let mut pixels = self.pixels.borrow_mut();
+        if pixels.as_ref().map(|(hue, _)| *hue) != Some(h) {
+            *pixels = Some((h, square_pixels(h)));
+        }
+        if let Some((_, data)) = pixels.as_ref() {
+            _ = self.square_ctx.put_image_data(data, 0.0, 0.0);
+        }
+        let size = SIZE as f64 - 1.0;
+        marker(&self.square_ctx, s * size, (1.0 - v) * size);
+        _ = self.square.set_attribute(
+            "aria-valuetext",
+            &format!("saturation {:.0}%, brightness {:.0}%", s * 100.0, v * 100.0),
+        );
+
+        for x in 0..SIZE {
+            let color = Color::from_hsv(x as f64 / (SIZE - 1) as f64 * 359.0, 1.0, 1.0);
+            self.hue_ctx.set_fill_style(&color.to_string().into());
+            self.hue_ctx
+                .fill_rect(x as f64, 0.0, 1.0, HUE_HEIGHT as f64);
+        }
+        marker(&self.hue_ctx, h / 359.0 * size, HUE_HEIGHT as f64 / 2.0);
+        _ = self.hue.set_attribute("aria-valuenow", &format!("{h:.0}"));
+    }
+}
+
+fn canvas(width: u32, height: u32) -> (HtmlCanvasElement, CanvasRenderingContext2d) {
+    let canvas = create_element("canvas").unchecked_into::<HtmlCanvasElement>();
+    canvas.set_width(width);
+    canvas.set_height(height);
+    let ctx = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .expect("a 2d canvas context")
+        .unchecked_into::<CanvasRenderingContext2d>();
+    (canvas, ctx)
+}
+
+/// Saturation grows to the right, value grows upwards.
+fn square_pixels(hue: f64) -> ImageData {
+    let mut data = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    let max = (SIZE - 1) as f64;
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let color = Color::from_hsv(hue, x as f64 / max, 1.0 - y as f64 / max);
+            data.extend([color.r, color.g, color.b, 255]);
+        }
+    }
+    ImageData::new_with_u8_clamped_array_and_sh(Clamped(&data), SIZE, SIZE)
+        .expect("the buffer to match the size")
+}
+
+/// A ring that stays visible on both light and dark colors.
+fn marker(ctx: &CanvasRenderingContext2d, x: f64, y: f64) {
+    for (color, radius) in [("black", MARKER_RADIUS + 1.0), ("white", MARKER_RADIUS)] {
+        ctx.begin_path();
+        _ = ctx.arc(x, y, radius, 0.0, std::f64::consts::TAU);
+        ctx.set_stroke_style(&color.into());
+        ctx.stroke();
+    }
+}
+
+/// Calls `f` with the pointer position as fractions of `el`'s size (0–1) while the
+/// pointer is pressed on it, even if it strays outside.
+fn on_drag(app: &mut App, el: &HtmlCanvasElement, f: impl Fn(f64, f64) + 'static) {
+    let f = Rc::new(f);
+    let report = {
+        let el = el.clone();
+        move |ev: &PointerEvent| {
+            let rect = el.get_bounding_client_rect();
+            let x = (ev.client_x() as f64 - rect.left()) / rect.width();
+            let y = (ev.client_y() as f64 - rect.top()) / rect.height();
+            f(x.clamp(0.0, 1.0), y.clamp(0.0, 1.0));
+        }
+    };
+
+    app.listen(el, "pointerdown", {
+        let el = el.clone();
+        let report = report.clone();
+        move |ev: PointerEvent| {
+            // keeps the moves coming (and the drag going) when the pointer leaves the canvas
+            _ = el.set_pointer_capture(ev.pointer_id());
+            report(&ev);
+        }
+    });
+    app.listen(el, "pointermove", {
+        let el = el.clone();
+        move |ev: PointerEvent| {
+            if el.has_pointer_capture(ev.pointer_id()) {
+                report(&ev);
+            }
+        }
+    });
+}
@@ -0,0 +1,62 @@
+//! The color picker example: the picked color is kept in state and previewed.
+
+use std::{cell::Cell, rc::Rc};
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement};
+
+use crate::{
+    app::App,
+    color_picker::{Color, ColorPicker},
+    dom::create_element,
+};
+
+const INITIAL: Color = Color {
+    r: 0x33,
+    g: 0x66,
+    b: 0xcc,
+};
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let section = create_element("section");
+    _ = section.set_attribute("style", "display: flex; gap: 16px; align-items: flex-start");
+    app.append(parent, &section);
+
+    let state = Rc::new(Cell::new(INITIAL));
+
+    let preview = create_element("output").unchecked_into::<HtmlElement>();
+    _ = preview.set_attribute(
+        "style",
+        "display: grid; place-items: end center; width: 120px; height: 120px; \
+         border-radius: 8px; font-family: monospace",
+    );
+    let render = {
+        let preview = preview.clone();
+        move |color: Color| {
+            _ = preview
+                .style()
+                .set_property("background", &color.to_string());
+            let (h, s, v) = color.to_hsv();
+            preview.set_text_content(Some(&format!(
+                "{color} hsv({h:.0}, {:.0}%, {:.0}%)",
+                s * 100.0,
+                v * 100.0
+            )));
+        }
+    };
+
+    ColorPicker::mount(&mut app, &section, state.get(), {
+        let state = state.clone();
+        let render = render.clone();
+        move |color| {
+            state.set(color);
+            render(color);
+        }
+    });
+    render(state.get());
+    app.append(&section, &preview);
+
+    app
+}
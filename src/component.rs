@@ -0,0 +1,22 @@
+//! Components: UI that can be put on the page, told things while it's there, and
+//! taken off again, as many times over and in as many places as needed.
+//!
+//! An example's `mount_to` hands back an [`App`](crate::app::App), and all that can
+//! be done with that is unmount it; whatever it shows only changes from the inside. A
+//! component keeps its own `App` (and whatever it takes messages through), so the code
+//! that made it can send it messages too, and each instance is a value of its own.
+
+use web_sys::Element;
+
+pub trait Component {
+    type Msg;
+
+    /// Puts the component in `parent`. Mounting a mounted component starts it over.
+    fn mount(&mut self, parent: &Element);
+
+    /// Tells the component something; nothing happens while it isn't mounted.
+    fn update(&mut self, msg: Self::Msg);
+
+    /// Takes the component off the page, if it's on it.
+    fn unmount(&mut self);
+}
@@ -0,0 +1,203 @@
+//! A custom right-click menu.
+//!
+//! ```ignore
+//! ContextMenu::new()
+//!     .item("Reset", move || reset())
+//!     .item("Copy value", move || copy())
+//!     .attach(&mut app, &el);
+//! ```
+//!
+//! Where popovers are supported the menu is shown as one, in the top layer, so it
+//! can't end up underneath anything; elsewhere it relies on its `z-index`.
+
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, KeyboardEvent, MouseEvent, Node, PointerEvent};
+
+use crate::{
+    app::App,
+    dom::{body, create_element, document, window},
+    geometry::{self, Align, Placement, Rect, Side},
+    popover,
+};
+
+type Action = Box<dyn FnMut()>;
+
+#[derive(Default)]
+pub struct ContextMenu {
+    items: Vec<(String, Action)>,
+}
+
+impl ContextMenu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn item(mut self, label: impl Into<String>, action: impl FnMut() + 'static) -> Self {
+        self.items.push((label.into(), Box::new(action)));
+        self
+    }
+
+    /// Shows the menu instead of the browser's when `target` is right-clicked.
+    pub fn attach(self, app: &mut App, target: &Element) {
+        let menu = create_element("ul");
+        menu.set_class_name("context-menu");
+        _ = menu.set_attribute("role", "menu");
+        _ = menu.set_attribute(
+            "style",
+            "position: fixed; margin: 0; padding: 4px 0; list-style: none; \
+             background: white; border: 1px solid #ccc; box-shadow: 2px 2px 6px #0003; z-index: 1000; \
+             inset: auto",
+        );
+        // manual: dismissing it is already handled below
+        if popover::is_supported() {
+            popover::make(&menu);
+        }
+
+        let menu = Rc::new(Menu { el: menu });
+
+        for (label, action) in self.items {
+            let action = RefCell::new(action);
+            let item = create_element("li");
+            _ = item.set_attribute("role", "none");
+            let button = create_element("button");
+            button.set_text_content(Some(&label));
+            _ = button.set_attribute("role", "menuitem");
+            _ = button.set_attribute(
+                "style",
+                "display: block; width: 100%; padding: 4px 16px; border: none; \
+                 background: none; text-align: left",
+            );
+            item.append_child(&button).unwrap();
+            menu.el.append_child(&item).unwrap();
+
+            app.listen(&button, "click", {
+                let menu = menu.clone();
+                move |_: MouseEvent| {
+                    // close first, so an action that opens a prompt doesn't leave the menu behind it
+                    menu.close();
+                    (action.borrow_mut())();
+                }
+            });
+        }
+
+        app.listen(target, "contextmenu", {
+            let menu = menu.clone();
+            move |ev: MouseEvent| {
+                ev.prevent_default();
+                menu.open(ev.client_x(), ev.client_y());
+            }
+        });
+
+        // the document-level listeners stay attached the whole time and check
+        // whether the menu is open, rather than being added and removed on every open
+        app.listen(&document(), "pointerdown", {
+            let menu = menu.clone();
+            move |ev: PointerEvent| {
+                let inside = ev
+                    .target()
+                    .and_then(|target| target.dyn_into::<Node>().ok())
+                    .is_some_and(|target| menu.el.contains(Some(&target)));
+                if !inside {
+                    menu.close();
+                }
+            }
+        });
+
+        app.listen(&document(), "keydown", {
+            let menu = menu.clone();
+            move |ev: KeyboardEvent| {
+                if !menu.is_open() {
+                    return;
+                }
+                match ev.key().as_str() {
+                    "Escape" => {
+                        ev.prevent_default();
+                        menu.close();
+                    }
+                    "ArrowDown" => {
+                        ev.prevent_default();
+                        menu.move_focus(1);
+                    }
+                    "ArrowUp" => {
+                        ev.prevent_default();
+                        menu.move_focus(-1);
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        // the position is stale once the window changes size or loses focus
+        for event_name in ["resize", "blur"] {
+            app.listen(&window(), event_name, {
+                let menu = menu.clone();
+                move |_: web_sys::Event| menu.close()
+            });
+        }
+
+        app.on_cleanup(move || menu.close());
+    }
+}
+
+struct Menu {
+    el: Element,
+}
+
+impl Menu {
+    fn is_open(&self) -> bool {
+        self.el.is_connected()
+    }
+
+    fn open(&self, x: i32, y: i32) {
+        body().unwrap().append_child(&self.el).unwrap();
+        popover::show(&self.el);
+
+        // measure once it's in the document, then keep it inside the viewport
+        geometry::place_next_to(
+            &self.el,
+            Rect::point(x as f64, y as f64),
+            Placement {
+                side: Side::Bottom,
+                align: Align::Start,
+                gap: 0.0,
+            },
+        );
+
+        self.move_focus(1);
+    }
+
+    fn close(&self) {
+        if self.is_open() {
+            popover::hide(&self.el);
+            self.el.remove();
+        }
+    }
+
+    /// Moves focus to the next (`1`) or previous (`-1`) item, wrapping around.
+    fn move_focus(&self, direction: i32) {
+        let Ok(items) = self.el.query_selector_all("[role=menuitem]") else {
+            return;
+        };
+        let len = items.length() as i32;
+        if len == 0 {
+            return;
+        }
+        let active = document().active_element();
+        let current = (0..len).find(|i| {
+            items
+                .item(*i as u32)
+                .zip(active.as_ref())
+                .is_some_and(|(item, active)| item.is_same_node(Some(active)))
+        });
+        let next = match current {
+            Some(i) => (i + direction).rem_euclid(len),
+            None if direction < 0 => len - 1,
+            None => 0,
+        };
+        if let Some(item) = items.item(next as u32) {
+            _ = item.unchecked_ref::<HtmlElement>().focus();
+        }
+    }
+}
@@ -0,0 +1,931 @@
+//! The counter from version 4 (channel + reducer), packaged as a mountable app.
+//!
+//! The channel and the loop around the reducer are a [`Store`].
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event, HtmlCanvasElement, HtmlInputElement, MouseEvent, UrlSearchParams};
+
+use crate::{
+    analytics,
+    app::App,
+    barcode,
+    clipboard::{self, ClipboardEvent},
+    component::Component,
+    context_menu::ContextMenu,
+    custom_event, dialog,
+    dom::{create_element, document, log, spawn_local, window},
+    downloads, flags,
+    gestures::{on_double_click, on_press_repeat, on_wheel_notches},
+    history_log::HistoryLog,
+    hot,
+    hotkeys::is_editable,
+    idle,
+    json_viewer::JsonViewer,
+    print,
+    qr::{self, QrCode},
+    share::{self, Outcome, ShareData},
+    storage,
+    store::{Filter, Logger, Pipeline, Reducer, Store, Throttle, Timing},
+    time, toast,
+    tooltip::tooltip,
+};
+
+const REPEAT_DELAY: Duration = Duration::from_millis(400);
+const REPEAT_PERIOD: Duration = Duration::from_millis(80);
+/// Wheel (and press-repeat) nudges closer together than this are dropped; shorter than
+/// [`REPEAT_PERIOD`], so holding a button isn't slowed down.
+const NUDGE_THROTTLE: Duration = Duration::from_millis(50);
+const HISTORY_CAPACITY: usize = 50;
+const AUTO_PERIOD: Duration = Duration::from_secs(1);
+/// Pixels per QR module.
+const QR_SCALE: u32 = 3;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct State {
+    pub count: i32,
+    /// What `Msg::Reset` goes back to.
+    pub start: i32,
+    pub step: i32,
+    pub min: Option<i32>,
+    pub max: Option<i32>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            count: 0,
+            start: 0,
+            step: 1,
+            min: None,
+            max: None,
+        }
+    }
+}
+
+/// Dispatched on a counter's parent, bubbling, whenever its count changes (and once
+/// when it's mounted), carrying a [`CountChanged`].
+pub const COUNT_CHANGED: &str = "count-changed";
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct CountChanged {
+    pub count: i32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Msg {
+    Increment,
+    Decrement,
+    IncrementBy(i32),
+    MultiplyBy(i32),
+    Set(i32),
+    Reset,
+    SetStep(i32),
+}
+// = JS type Msg = "inc" | "dec" | "reset" | { incBy: number } | { mulBy: number }
+//                 | { set: number } | { setStep: number };
+
+impl State {
+    pub fn update(&mut self, msg: Msg) {
+        // No `_ =>` arm on purpose: when a new message is added, this (and every other
+        // match on `Msg`) stops compiling until it has decided what the message means.
+        let count = match msg {
+            Msg::Increment => self.count.saturating_add(self.step),
+            Msg::Decrement => self.count.saturating_sub(self.step),
+            Msg::IncrementBy(delta) => self.count.saturating_add(delta),
+            Msg::MultiplyBy(factor) => self.count.saturating_mul(factor),
+            Msg::Set(count) => count,
+            Msg::Reset => self.start,
+            Msg::SetStep(step) => {
+                self.step = step;
+                return;
+            }
+        };
+        self.count = self.clamp(count);
+    }
+
+    fn clamp(&self, count: i32) -> i32 {
+        let count = self.min.map_or(count, |min| count.max(min));
+        self.max.map_or(count, |max| count.min(max))
+    }
+
+    pub fn at_min(&self) -> bool {
+        self.min.is_some_and(|min| self.count <= min)
+    }
+
+    pub fn at_max(&self) -> bool {
+        self.max.is_some_and(|max| self.count >= max)
+    }
+}
+
+impl Reducer for State {
+    type Msg = Msg;
+
+    /// Like [`State::update`], but turns down a step that isn't positive instead of
+    /// taking it.
+    fn reduce(&mut self, msg: Msg) -> Result<(), String> {
+        if let Msg::SetStep(step) = msg {
+            if step <= 0 {
+                return Err(format!("a step has to be positive, not {step}"));
+            }
+        }
+        self.update(msg);
+        Ok(())
+    }
+}
+
+/// Per-instance settings read from the host element, so a counter can be
+/// customized from plain HTML:
+///
+/// ```html
+/// <div id="apples" data-start="10" data-step="5" data-min="0" data-label="Apples:"></div>
+/// ```
+pub struct Config {
+    pub start: i32,
+    pub step: i32,
+    pub min: Option<i32>,
+    pub max: Option<i32>,
+    pub label: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            start: 0,
+            step: 1,
+            min: None,
+            max: None,
+            label: "count is".to_string(),
+        }
+    }
+}
+
+impl Config {
+    pub fn from_element(el: &Element) -> Self {
+        Self::from_attributes(|name| el.get_attribute(name))
+    }
+
+    /// The config in the `data-` attributes that `attribute` looks up by name. A step
+    /// that isn't positive is ignored, as the reducer would refuse it.
+    fn from_attributes(attribute: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Config::default();
+        let parse = |name| parse_attr(name, attribute(name));
+        Config {
+            start: parse("data-start").unwrap_or(defaults.start),
+            step: parse("data-step")
+                .filter(|step| *step > 0)
+                .unwrap_or(defaults.step),
+            min: parse("data-min"),
+            max: parse("data-max"),
+            label: attribute("data-label").unwrap_or(defaults.label),
+        }
+    }
+}
+
+fn parse_attr(name: &str, value: Option<String>) -> Option<i32> {
+    let value = value?;
+    match value.trim().parse() {
+        Ok(n) => Some(n),
+        Err(_) => {
+            log!("ignoring {name}={value:?}: not an integer");
+            None
+        }
+    }
+}
+
+/// How far `notches` of the wheel move the count: a step each, or ten when `fast`.
+/// A big enough step would overflow, so it saturates, as the count itself does.
+fn wheel_delta(notches: i32, fast: bool, step: i32) -> i32 {
+    let multiplier = if fast { 10 } else { 1 };
+    notches.saturating_mul(multiplier).saturating_mul(step)
+}
+
+/// A link that opens the page with this count and step, e.g. `/?count=7&step=2#counter`.
+pub fn deep_link(state: &State) -> String {
+    let location = window().location();
+    format!(
+        "{}{}?count={}&step={}{}",
+        location.origin().unwrap_or_default(),
+        location.pathname().unwrap_or_default(),
+        state.count,
+        state.step,
+        location.hash().unwrap_or_default()
+    )
+}
+
+/// The `count` and `step` from a [`deep_link`] the page was opened with.
+fn linked_state() -> (Option<i32>, Option<i32>) {
+    let search = window().location().search().unwrap_or_default();
+    let Ok(params) = UrlSearchParams::new_with_str(&search) else {
+        return (None, None);
+    };
+    let param = |name: &str| params.get(name).and_then(|value| value.parse().ok());
+    (param("count"), param("step"))
+}
+
+/// The messages a scanned code stands for: a [`deep_link`] sets the step and count,
+/// a plain number just the count.
+fn scanned_messages(text: &str) -> Option<Vec<Msg>> {
+    let text = text.trim();
+    if let Ok(count) = text.parse() {
+        return Some(vec![Msg::Set(count)]);
+    }
+    let query = text.split_once('?')?.1;
+    let query = query.split('#').next().unwrap_or_default();
+    let (mut count, mut step) = (None, None);
+    for pair in query.split('&') {
+        match pair.split_once('=') {
+            Some(("count", value)) => count = value.parse().ok(),
+            Some(("step", value)) => step = value.parse().ok(),
+            _ => {}
+        }
+    }
+    let mut messages = step.map(Msg::SetStep).into_iter().collect::<Vec<_>>();
+    messages.push(Msg::Set(count?));
+    Some(messages)
+}
+
+/// Mounts a counter into the element matching `selector`, if there is one.
+/// Every call creates a separate instance with its own state and channel.
+pub fn mount(selector: &str) -> Option<App> {
+    let parent = document().query_selector(selector).ok()??;
+    Some(mount_to(&parent))
+}
+
+pub fn mount_to(parent: &Element) -> App {
+    mount_with_store(parent).0
+}
+
+/// The counter as a [`Component`], for code that wants to send it messages, or to
+/// mount several: each instance has its own state, kept under its parent's id.
+#[derive(Default)]
+pub struct Counter {
+    mounted: Option<(App, Store<State>)>,
+}
+
+impl Component for Counter {
+    type Msg = Msg;
+
+    fn mount(&mut self, parent: &Element) {
+        self.unmount();
+        self.mounted = Some(mount_with_store(parent));
+    }
+
+    fn update(&mut self, msg: Msg) {
+        if let Some((_, store)) = &self.mounted {
+            store.dispatch(msg);
+        }
+    }
+
+    fn unmount(&mut self) {
+        if let Some((app, _)) = self.mounted.take() {
+            app.unmount();
+        }
+    }
+}
+
+/// Mounts a counter, handing back its store along with its `App`.
+fn mount_with_store(parent: &Element) -> (App, Store<State>) {
+    let mut app = App::new();
+
+    // instances in different hosts shouldn't restore each other's state
+    let key = match parent.id() {
+        id if id.is_empty() => "counter".to_string(),
+        id => format!("counter#{id}"),
+    };
+    let config = Config::from_element(parent);
+    let mut state = hot::restore::<State>(&key).unwrap_or_else(|| {
+        // the last visit's count and step, within the bounds the markup sets now
+        let saved = storage::load::<State>(&key);
+        let mut state = State {
+            count: 0,
+            start: config.start,
+            step: saved.as_ref().map_or(config.step, |saved| saved.step),
+            min: config.min,
+            max: config.max,
+        };
+        state.update(saved.map_or(Msg::Reset, |saved| Msg::Set(saved.count)));
+        state
+    });
+    // a shared link beats whatever was there before
+    let (count, step) = linked_state();
+    if let Some(step) = step.filter(|step| *step > 0) {
+        state.update(Msg::SetStep(step));
+    }
+    if let Some(count) = count {
+        state.update(Msg::Set(count));
+    }
+    let state = Rc::new(RefCell::new(state));
+    hot::preserve_on_unload(&mut app, &key, &state);
+
+    let view = View::new(config.label);
+    app.append(parent, &view.decrement);
+    app.append(parent, &view.p);
+    app.append(parent, &view.increment);
+    app.append(parent, &view.step);
+    app.append(parent, &view.actions);
+    app.append(parent, &view.printout);
+    app.append(parent, &view.qr_panel);
+    view.render(&state.borrow());
+    for (el, text) in [
+        (
+            &view.reset,
+            "Go back to the starting count (or double-click the count)",
+        ),
+        (&view.double, "Double the count"),
+        (&view.negate, "Flip the sign of the count"),
+        (view.step.as_ref(), "How much +/− change the count by"),
+        (view.auto.as_ref(), "Add a step every second"),
+    ] {
+        tooltip(&mut app, el, text);
+    }
+    let history = HistoryLog::mount(&mut app, parent, HISTORY_CAPACITY);
+
+    // buttons and inputs mean nothing on paper, so print a summary table instead
+    let view = Rc::new(view);
+    let inspector =
+        cfg!(debug_assertions).then(|| mount_inspector(&mut app, parent, &state, &view));
+    let mut middleware: Pipeline<State> = vec![
+        // a trackpad can send notches faster than anyone can read the count
+        Box::new(Throttle::new(NUDGE_THROTTLE, |msg| {
+            matches!(msg, Msg::IncrementBy(_))
+        })),
+        // counting past a bound would only be clamped back, and fill the history
+        Box::new(Filter(|msg: &Msg, state: &State| match msg {
+            Msg::Increment => !state.at_max(),
+            Msg::Decrement => !state.at_min(),
+            _ => true,
+        })),
+    ];
+    if cfg!(debug_assertions) {
+        middleware.push(Box::new(Logger));
+        middleware.push(Box::new(Timing::new(Duration::from_millis(1))));
+    }
+    let store = Store::new(&mut app, state.clone(), middleware, {
+        let view = view.clone();
+        let key = key.clone();
+        let parent = parent.clone();
+        let mut last_count = state.borrow().count;
+        move |state: &State, messages: &[Msg]| {
+            storage::persist(&key, state);
+            if state.count != last_count {
+                last_count = state.count;
+                announce(&parent, state.count);
+            }
+            for msg in messages {
+                analytics::message(msg);
+            }
+            // a transaction is one entry: nothing in between was ever shown
+            let entry = messages
+                .iter()
+                .map(|msg| format!("{msg:?}"))
+                .collect::<Vec<_>>()
+                .join(" + ");
+            history.push(&entry, state.count);
+            view.render(state);
+            if let Some(inspector) = &inspector {
+                inspector.set_value(to_json(state));
+            }
+        }
+    });
+    print::on_print(
+        &mut app,
+        {
+            let view = view.clone();
+            let state = state.clone();
+            move || view.show_printout(&state.borrow())
+        },
+        {
+            let view = view.clone();
+            move || view.hide_printout()
+        },
+    );
+
+    // holding a button down keeps counting; these have to be registered before the
+    // click listeners so the click that ends a held press isn't counted twice
+    if flags::enabled("press-repeat") {
+        for (button, msg) in [(&view.increment, 1), (&view.decrement, -1)] {
+            let store = store.clone();
+            let state = state.clone();
+            on_press_repeat(&mut app, button, REPEAT_DELAY, REPEAT_PERIOD, move || {
+                let step = state.borrow().step;
+                store.dispatch(Msg::IncrementBy(msg * step));
+            });
+        }
+    }
+
+    // double-clicking the count resets it to where it started
+    on_double_click(&mut app, &view.p, {
+        let store = store.clone();
+        move |_| {
+            store.dispatch(Msg::Reset);
+        }
+    });
+
+    if flags::enabled("context-menu") {
+        ContextMenu::new()
+            .item("Reset", {
+                let store = store.clone();
+                move || {
+                    store.dispatch(Msg::Reset);
+                }
+            })
+            .item("Copy value", {
+                let state = state.clone();
+                move || {
+                    let count = state.borrow().count.to_string();
+                    spawn_local(async move {
+                        if let Err(e) = clipboard::write_text(&count).await {
+                            log!("couldn't copy to the clipboard: {e:?}");
+                        }
+                    });
+                }
+            })
+            .item("Set value…", {
+                let store = store.clone();
+                let state = state.clone();
+                move || {
+                    let current = state.borrow().count.to_string();
+                    let store = store.clone();
+                    spawn_local(async move {
+                        let Some(input) = dialog::prompt("Set the count to:", &current).await
+                        else {
+                            return;
+                        };
+                        match input.trim().parse() {
+                            Ok(n) => store.dispatch(Msg::Set(n)),
+                            Err(_) => toast::error(&format!("{input:?} is not an integer")),
+                        }
+                    });
+                }
+            })
+            .item("Download state", {
+                let state = state.clone();
+                move || match serde_json::to_vec_pretty(&*state.borrow()) {
+                    Ok(json) => downloads::download("counter.json", &json, "application/json"),
+                    Err(e) => toast::error(&format!("Couldn't save the state: {e}")),
+                }
+            })
+            .item("Scan a code…", {
+                let store = store.clone();
+                move || {
+                    let store = store.clone();
+                    spawn_local(async move {
+                        let Some(scanned) = barcode::scan().await else {
+                            return;
+                        };
+                        match scanned_messages(&scanned.value) {
+                            // a link's step and count go in together, or not at all
+                            Some(messages) => {
+                                let applied = store.transaction(|tx| {
+                                    for msg in messages {
+                                        tx.dispatch(msg);
+                                    }
+                                });
+                                if let Err(error) = applied.await {
+                                    toast::error(&format!("Couldn't use that code: {error}"));
+                                }
+                            }
+                            None => toast::error(&format!(
+                                "That {} says {:?}, which isn't a count or a link to one",
+                                scanned.format.replace('_', " "),
+                                scanned.value
+                            )),
+                        }
+                    });
+                }
+            })
+            .attach(&mut app, parent);
+    }
+
+    // scrolling over the count nudges it; holding shift or ctrl makes it 10 steps a notch
+    if flags::enabled("wheel-adjust") {
+        on_wheel_notches(&mut app, &view.p, {
+            let store = store.clone();
+            let state = state.clone();
+            move |notches, ev| {
+                let fast = ev.shift_key() || ev.ctrl_key();
+                let step = state.borrow().step;
+                store.dispatch(Msg::IncrementBy(wheel_delta(notches, fast, step)));
+            }
+        });
+    }
+
+    // pasting a number anywhere in the counter (outside of text fields) sets the count
+    app.listen(&document(), "paste", {
+        let store = store.clone();
+        let parent = parent.clone();
+        move |ev: ClipboardEvent| {
+            let target = ev
+                .target()
+                .and_then(|target| target.dyn_into::<Element>().ok());
+            let Some(target) = target else { return };
+            if !parent.contains(Some(&target)) || is_editable(&target) {
+                return;
+            }
+            ev.prevent_default();
+
+            let text = ev.text().unwrap_or_default();
+            match text.trim().parse::<i32>() {
+                Ok(n) => {
+                    store.dispatch(Msg::Set(n));
+                    toast::info(&format!("Count set to {n} from the clipboard"));
+                }
+                Err(_) => toast::error(&format!("Can't set the count to {text:?}: not an integer")),
+            }
+        }
+    });
+
+    // has to call into the browser during the click itself, see `share::share`
+    app.listen(&view.share, "click", {
+        let state = state.clone();
+        move |_: MouseEvent| {
+            let state = state.borrow();
+            let url = deep_link(&state);
+            let text = format!("The count is {}", state.count);
+            let shared = share::share(&ShareData {
+                title: "Counter",
+                text: &text,
+                url: &url,
+            });
+            spawn_local(async move {
+                match shared.await {
+                    Ok(Outcome::Copied) => toast::info("Link copied to the clipboard"),
+                    Ok(Outcome::Shared | Outcome::Cancelled) => {}
+                    Err(e) => toast::error(&format!("Couldn't share: {e}")),
+                }
+            });
+        }
+    });
+
+    for (button, msg) in [
+        (&view.reset, Msg::Reset),
+        (&view.double, Msg::MultiplyBy(2)),
+        (&view.negate, Msg::MultiplyBy(-1)),
+    ] {
+        let store = store.clone();
+        app.listen(button, "click", move |_: MouseEvent| {
+            store.dispatch(msg.clone());
+        });
+    }
+
+    app.listen(&view.increment, "click", {
+        let store = store.clone();
+        move |_: MouseEvent| {
+            store.dispatch(Msg::Increment);
+        }
+    });
+
+    app.listen(&view.decrement, "click", {
+        let store = store.clone();
+        move |_: MouseEvent| {
+            store.dispatch(Msg::Decrement);
+        }
+    });
+
+    // auto-counting holds off while nobody's there to watch it
+    app.spawn({
+        let store = store.clone();
+        let auto = view.auto.clone();
+        async move {
+            let mut ticks = time::every(AUTO_PERIOD);
+            while ticks.next().await.is_some() {
+                if auto.checked() && !idle::is_idle() {
+                    store.dispatch(Msg::Increment);
+                }
+            }
+        }
+    });
+
+    app.listen(&view.step, "input", {
+        let step = view.step.clone();
+        let store = store.clone();
+        move |_: Event| {
+            // half-typed values like "" or "-" just don't update the step yet
+            if let Ok(n) = step.value().parse::<i32>() {
+                if n > 0 {
+                    store.dispatch(Msg::SetStep(n));
+                }
+            }
+        }
+    });
+
+    announce(parent, state.borrow().count);
+    (app, store)
+}
+
+fn announce(parent: &Element, count: i32) {
+    if let Err(error) = custom_event::dispatch(parent, COUNT_CHANGED, &CountChanged { count }) {
+        log!("[counter] couldn't announce the count: {error}");
+    }
+}
+
+/// Debug builds show the live state, which can also be edited in place.
+fn mount_inspector(
+    app: &mut App,
+    parent: &Element,
+    state: &Rc<RefCell<State>>,
+    view: &Rc<View>,
+) -> Rc<JsonViewer> {
+    let details = create_element("details");
+    let summary = create_element("summary");
+    summary.set_text_content(Some("State"));
+    details.append_child(&summary).unwrap();
+    app.append(parent, &details);
+
+    // edits skip the reducer on purpose: this is for poking at states it would never produce
+    JsonViewer::mount(app, &details, to_json(&state.borrow()), {
+        let state = state.clone();
+        let view = view.clone();
+        move |viewer, edited| match serde_json::from_value::<State>(edited) {
+            Ok(edited) => {
+                view.render(&edited);
+                viewer.set_value(to_json(&edited));
+                *state.borrow_mut() = edited;
+            }
+            Err(e) => toast::error(&format!("Not a valid state: {e}")),
+        }
+    })
+}
+
+fn to_json(state: &State) -> serde_json::Value {
+    serde_json::to_value(state).unwrap_or_default()
+}
+
+struct View {
+    label: String,
+    p: Element,
+    increment: Element,
+    decrement: Element,
+    step: HtmlInputElement,
+    actions: Element,
+    reset: Element,
+    double: Element,
+    negate: Element,
+    share: Element,
+    /// Counts up on its own while checked.
+    auto: HtmlInputElement,
+    printout: Element,
+    qr_panel: Element,
+    qr: HtmlCanvasElement,
+    link: Element,
+}
+
+impl View {
+    fn new(label: String) -> Self {
+        let step = create_element("input").unchecked_into::<HtmlInputElement>();
+        step.set_type("number");
+        step.set_min("1");
+        _ = step.set_attribute("aria-label", "Step");
+
+        let actions = create_element("div");
+        let button = |text: &str| {
+            let button = create_element("button");
+            button.set_text_content(Some(text));
+            actions.append_child(&button).unwrap();
+            button
+        };
+        let reset = button("Reset");
+        let double = button("×2");
+        let negate = button("×−1");
+        let share = button(if share::is_supported() {
+            "Share…"
+        } else {
+            "Copy link"
+        });
+        let auto = create_element("input").unchecked_into::<HtmlInputElement>();
+        auto.set_type("checkbox");
+        let auto_label = create_element("label");
+        auto_label.append_child(&auto).unwrap();
+        _ = auto_label.append_with_str_1(" Auto");
+        actions.append_child(&auto_label).unwrap();
+
+        let printout = create_element("table");
+        _ = printout.set_attribute("hidden", "");
+
+        let figure = create_element("figure");
+        let qr = create_element("canvas").unchecked_into::<HtmlCanvasElement>();
+        _ = qr.set_attribute("role", "img");
+        _ = qr.set_attribute("aria-label", "QR code of the link to this count");
+        let link = create_element("a");
+        let caption = create_element("figcaption");
+        caption.append_child(&link).unwrap();
+        figure.append_child(&qr).unwrap();
+        figure.append_child(&caption).unwrap();
+
+        View {
+            label,
+            p: create_element("p"),
+            increment: create_element("button"),
+            decrement: create_element("button"),
+            step,
+            actions,
+            reset,
+            double,
+            negate,
+            share,
+            auto,
+            printout,
+            qr_panel: figure,
+            qr,
+            link,
+        }
+    }
+
+    fn interactive(&self) -> [&Element; 5] {
+        [
+            &self.decrement,
+            &self.p,
+            &self.increment,
+            &self.step,
+            &self.actions,
+        ]
+    }
+
+    fn show_printout(&self, state: &State) {
+        let bound = |bound: Option<i32>| bound.map_or("none".to_string(), |n| n.to_string());
+        let rows = [
+            (self.label.as_str(), state.count.to_string()),
+            ("step", state.step.to_string()),
+            ("minimum", bound(state.min)),
+            ("maximum", bound(state.max)),
+        ];
+        self.printout.set_inner_html("");
+        for (name, value) in rows {
+            let tr = create_element("tr");
+            let th = create_element("th");
+            th.set_text_content(Some(name));
+            let td = create_element("td");
+            td.set_text_content(Some(&value));
+            tr.append_child(&th).unwrap();
+            tr.append_child(&td).unwrap();
+            self.printout.append_child(&tr).unwrap();
+        }
+
+        for el in self.interactive() {
+            _ = el.set_attribute("hidden", "");
+        }
+        _ = self.printout.remove_attribute("hidden");
+    }
+
+    fn hide_printout(&self) {
+        for el in self.interactive() {
+            _ = el.remove_attribute("hidden");
+        }
+        _ = self.printout.set_attribute("hidden", "");
+    }
+
+    fn render(&self, state: &State) {
+        self.p
+            .set_text_content(Some(&format!("{} {}", self.label, state.count)));
+        self.increment
+            .set_text_content(Some(&format!("+{}", state.step)));
+        self.decrement
+            .set_text_content(Some(&format!("-{}", state.step)));
+        _ = self
+            .increment
+            .toggle_attribute_with_force("disabled", state.at_max());
+        _ = self
+            .decrement
+            .toggle_attribute_with_force("disabled", state.at_min());
+        // don't fight the user while they're typing into the input
+        if self.step.value().parse() != Ok(state.step) {
+            self.step.set_value(&state.step.to_string());
+        }
+
+        let link = deep_link(state);
+        if self.link.get_attribute("href").as_deref() != Some(link.as_str()) {
+            _ = self.link.set_attribute("href", &link);
+            self.link.set_text_content(Some(&link));
+            match QrCode::encode(link.as_bytes()) {
+                Some(code) => {
+                    qr::draw(&self.qr, &code, QR_SCALE);
+                    _ = self.qr.remove_attribute("hidden");
+                }
+                // too long for the encoder; the link itself still works
+                None => _ = self.qr.set_attribute("hidden", ""),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(count: i32) -> State {
+        State {
+            count,
+            ..State::default()
+        }
+    }
+
+    #[test]
+    fn increment_and_decrement_use_the_step() {
+        let mut state = state(0);
+        state.update(Msg::SetStep(5));
+        state.update(Msg::Increment);
+        state.update(Msg::Increment);
+        state.update(Msg::Decrement);
+        assert_eq!(state.count, 5);
+    }
+
+    #[test]
+    fn multiply_by() {
+        let mut state = state(3);
+        state.update(Msg::MultiplyBy(2));
+        assert_eq!(state.count, 6);
+        state.update(Msg::MultiplyBy(-1));
+        assert_eq!(state.count, -6);
+    }
+
+    #[test]
+    fn arithmetic_saturates_instead_of_overflowing() {
+        let mut state = state(i32::MAX - 1);
+        state.update(Msg::MultiplyBy(2));
+        assert_eq!(state.count, i32::MAX);
+        state.update(Msg::Increment);
+        assert_eq!(state.count, i32::MAX);
+    }
+
+    #[test]
+    fn wheel_deltas_saturate_with_large_steps() {
+        assert_eq!(wheel_delta(-3, false, 2), -6);
+        assert_eq!(wheel_delta(2, true, 5), 100);
+        assert_eq!(wheel_delta(3, true, i32::MAX / 2), i32::MAX);
+        assert_eq!(wheel_delta(-3, true, i32::MAX / 2), i32::MIN);
+        let mut state = state(0);
+        state.update(Msg::IncrementBy(wheel_delta(1, true, i32::MAX)));
+        assert_eq!(state.count, i32::MAX);
+    }
+
+    #[test]
+    fn config_ignores_steps_that_arent_positive() {
+        let config = |step: &str| {
+            let step = step.to_string();
+            Config::from_attributes(|name| (name == "data-step").then(|| step.clone()))
+        };
+        assert_eq!(config("5").step, 5);
+        assert_eq!(config("0").step, 1);
+        assert_eq!(config("-3").step, 1);
+    }
+
+    #[test]
+    fn reset_goes_back_to_start() {
+        let mut state = State {
+            start: 7,
+            ..state(0)
+        };
+        state.update(Msg::Set(100));
+        state.update(Msg::Reset);
+        assert_eq!(state.count, 7);
+    }
+
+    #[test]
+    fn every_change_is_clamped_to_the_bounds() {
+        let mut state = State {
+            min: Some(-10),
+            max: Some(10),
+            ..state(0)
+        };
+        state.update(Msg::Set(50));
+        assert_eq!(state.count, 10);
+        assert!(state.at_max());
+        state.update(Msg::MultiplyBy(-3));
+        assert_eq!(state.count, -10);
+        assert!(state.at_min());
+        state.update(Msg::IncrementBy(5));
+        assert_eq!(state.count, -5);
+        assert!(!state.at_min() && !state.at_max());
+    }
+
+    #[test]
+    fn scanned_links_and_numbers_become_messages() {
+        let mut state = state(0);
+        let link = "https://example.com/?count=42&step=3#counter";
+        for msg in scanned_messages(link).unwrap() {
+            state.update(msg);
+        }
+        assert_eq!((state.count, state.step), (42, 3));
+        for msg in scanned_messages(" -7 ").unwrap() {
+            state.update(msg);
+        }
+        assert_eq!((state.count, state.step), (-7, 3));
+        assert!(scanned_messages("https://example.com/?step=3").is_none());
+        assert!(scanned_messages("hello").is_none());
+    }
+
+    #[test]
+    fn a_step_that_isnt_positive_is_rejected() {
+        let mut state = state(1);
+        assert!(state.reduce(Msg::SetStep(0)).is_err());
+        assert!(state.reduce(Msg::SetStep(-2)).is_err());
+        assert_eq!(state.step, 1);
+        assert!(state.reduce(Msg::SetStep(2)).is_ok());
+        assert!(state.reduce(Msg::Increment).is_ok());
+        assert_eq!((state.count, state.step), (3, 2));
+    }
+}
@@ -0,0 +1,160 @@
+//! CSV reading and writing (RFC 4180: comma-separated, `"` quotes fields, `""` is a
+//! literal quote, quoted fields may contain commas and newlines).
+//!
+//! The parser is incremental: [`CsvParser::feed`] takes text in arbitrary chunks, e.g.
+//! straight from a file being read piece by piece, and returns whichever records were
+//! completed by it, even if a chunk ends halfway through a quoted field.
+
+#[derive(Default)]
+pub struct CsvParser {
+    record: Vec<String>,
+    field: String,
+    in_quotes: bool,
+    /// The current field was quoted, so even empty it makes a line a record.
+    quoted: bool,
+    /// Just saw a `"` inside a quoted field: either the closing quote or the first
+    /// half of `""`, depending on what comes next.
+    quote_pending: bool,
+    /// Just saw a `\r`, so a following `\n` belongs to the same line break.
+    after_cr: bool,
+}
+
+impl CsvParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feed(&mut self, chunk: &str) -> Vec<Vec<String>> {
+        let mut records = Vec::new();
+        for c in chunk.chars() {
+            if std::mem::take(&mut self.after_cr) && c == '\n' {
+                continue;
+            }
+            if self.quote_pending {
+                self.quote_pending = false;
+                if c == '"' {
+                    self.field.push('"');
+                    continue;
+                }
+                self.in_quotes = false;
+            }
+            match c {
+                '"' if self.in_quotes => self.quote_pending = true,
+                // a quote only opens a quoted field at its very start
+                '"' if self.field.is_empty() => {
+                    self.in_quotes = true;
+                    self.quoted = true;
+                }
+                _ if self.in_quotes => self.field.push(c),
+                ',' => {
+                    self.quoted = false;
+                    self.record.push(std::mem::take(&mut self.field));
+                }
+                '\r' | '\n' => {
+                    self.after_cr = c == '\r';
+                    if let Some(record) = self.end_record() {
+                        records.push(record);
+                    }
+                }
+                _ => self.field.push(c),
+            }
+        }
+        records
+    }
+
+    /// Ends the input, returning the last record if the text didn't end in a newline.
+    pub fn finish(mut self) -> Option<Vec<String>> {
+        self.end_record()
+    }
+
+    fn end_record(&mut self) -> Option<Vec<String>> {
+        self.record.push(std::mem::take(&mut self.field));
+        let record = std::mem::take(&mut self.record);
+        // blank lines aren't records, but a line with just `""` is one empty field
+        let blank = record.len() == 1 && record[0].is_empty() && !self.quoted;
+        self.quoted = false;
+        (!blank).then_some(record)
+    }
+}
+
+/// One line of CSV, quoting only the fields that need it.
+pub fn write_record<S: AsRef<str>>(fields: &[S]) -> String {
+    let mut line = fields
+        .iter()
+        .map(|field| {
+            let field = field.as_ref();
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Everything `text` parses to when it's fed in pieces split at `splits`.
+    fn parse(text: &str, splits: &[usize]) -> Vec<Vec<String>> {
+        let mut parser = CsvParser::new();
+        let mut records = Vec::new();
+        let mut start = 0;
+        for &end in splits.iter().chain([&text.len()]) {
+            records.extend(parser.feed(&text[start..end]));
+            start = end;
+        }
+        records.extend(parser.finish());
+        records
+    }
+
+    fn records(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(|field| field.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn reads_quotes_newlines_and_crlf() {
+        let text = "name,note\r\n\"Smith, J\",\"said \"\"hi\"\"\"\r\nx,\"two\nlines\"\r\n\r\nlast,";
+        let expected = records(&[
+            &["name", "note"],
+            &["Smith, J", "said \"hi\""],
+            &["x", "two\nlines"],
+            &["last", ""],
+        ]);
+        assert_eq!(parse(text, &[]), expected);
+        // split everywhere: inside quotes, between "" and between \r and \n
+        for split in 1..text.len() {
+            assert_eq!(parse(text, &[split]), expected, "split at {split}");
+        }
+        let every_char = (1..text.len()).collect::<Vec<_>>();
+        assert_eq!(parse(text, &every_char), expected);
+    }
+
+    #[test]
+    fn a_quoted_empty_field_is_a_record() {
+        assert_eq!(
+            parse("a\n\"\"\n\nb\n", &[]),
+            records(&[&["a"], &[""], &["b"]])
+        );
+        assert_eq!(parse("\"\"", &[1]), records(&[&[""]]));
+        // and the flag doesn't outlive its field
+        assert_eq!(parse("\"\",x\n\n", &[]), records(&[&["", "x"]]));
+    }
+
+    #[test]
+    fn writes_what_it_reads() {
+        let fields = ["plain", "with, comma", "with \"quotes\"", "two\nlines", ""];
+        let line = write_record(&fields);
+        assert_eq!(
+            line,
+            "plain,\"with, comma\",\"with \"\"quotes\"\"\",\"two\nlines\",\r\n"
+        );
+        assert_eq!(parse(&line, &[]), records(&[&fields]));
+    }
+}
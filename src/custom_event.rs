@@ -0,0 +1,47 @@
+//! Events of our own, for a component to tell whatever it's inside what happened.
+//!
+//! A `CustomEvent` bubbles up from the element it's dispatched on like a click does,
+//! so a parent hears its children without either holding the other. Its `detail` is
+//! a JS value; here that's a Rust value gone through JSON, and [`detail`] reads it back
+//! as the same type, or as nothing if it isn't one.
+
+use serde::{de::DeserializeOwned, Serialize};
+use wasm_bindgen::JsCast;
+use web_sys::{CustomEvent, CustomEventInit, Event, EventTarget};
+
+use crate::{app::App, dom::log};
+
+/// Dispatches a bubbling `name` event on `target`, carrying `detail`.
+pub fn dispatch<T: Serialize>(target: &EventTarget, name: &str, detail: &T) -> Result<(), String> {
+    let json = serde_json::to_string(detail).map_err(|error| error.to_string())?;
+    let detail = js_sys::JSON::parse(&json).map_err(|error| format!("{error:?}"))?;
+    let mut init = CustomEventInit::new();
+    init.bubbles(true).detail(&detail);
+    let event =
+        CustomEvent::new_with_event_init_dict(name, &init).map_err(|error| format!("{error:?}"))?;
+    target
+        .dispatch_event(&event)
+        .map_err(|error| format!("{error:?}"))?;
+    Ok(())
+}
+
+/// The `detail` of `ev`, if it's a `CustomEvent` carrying a `T`.
+pub fn detail<T: DeserializeOwned>(ev: &Event) -> Option<T> {
+    let detail = ev.dyn_ref::<CustomEvent>()?.detail();
+    let json = js_sys::JSON::stringify(&detail).ok()?.as_string()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Calls `f` with each `name` event that reaches `target` carrying a `T`, and the event
+/// itself (for its `target`), until `app` unmounts.
+pub fn listen<T: DeserializeOwned + 'static>(
+    app: &mut App,
+    target: &EventTarget,
+    name: &'static str,
+    mut f: impl FnMut(T, &Event) + 'static,
+) {
+    app.listen(target, name, move |ev: Event| match detail::<T>(&ev) {
+        Some(detail) => f(detail, &ev),
+        None => log!("[{name}] ignoring an event without the right detail"),
+    });
+}
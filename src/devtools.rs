@@ -0,0 +1,183 @@
+//! A state inspector for reducer loops: the state as JSON, every message as it's
+//! dispatched, an input to dispatch one by hand, and how the loop's supervised
+//! background tasks are doing. Under those, how often the DOM
+//! helpers found the strings they pass to JS already [`intern`](crate::intern)ed.
+//!
+//! [`tap`] sits between a loop and its channel, so the loop reads the same messages in
+//! the same order and doesn't know it's being watched. The state is read again each
+//! time the loop comes back for its next message, which is when it's done with the
+//! last one.
+
+use std::{cell::RefCell, collections::BTreeMap, fmt::Debug, task::Poll};
+
+use futures::{
+    channel::mpsc::{Receiver, Sender},
+    stream::{self, LocalBoxStream},
+    StreamExt,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlInputElement, KeyboardEvent, MouseEvent};
+
+use crate::{
+    app::App,
+    dom::{body, create_element},
+    intern,
+    supervisor::{Supervisor, TaskStatus},
+};
+
+/// How many messages the log keeps.
+const LOG_LIMIT: u32 = 100;
+
+/// Shows `state` and each message from `receiver` in a panel on the page until `app`
+/// unmounts, passing the messages on unchanged. Messages typed into the panel (as
+/// JSON) go into `sender`, like any other. Under them, the status of each of
+/// `supervisor`'s tasks, as it changes.
+pub fn tap<M, S>(
+    app: &mut App,
+    title: &str,
+    mut receiver: Receiver<M>,
+    sender: Sender<M>,
+    supervisor: &Supervisor,
+    state: impl Fn() -> S + 'static,
+) -> LocalBoxStream<'static, M>
+where
+    M: Debug + DeserializeOwned + 'static,
+    S: Serialize,
+{
+    let Some(body) = body() else {
+        return receiver.boxed_local();
+    };
+    let panel = create_element("div");
+    _ = panel.set_attribute(
+        "style",
+        "position: fixed; right: 1em; bottom: 1em; max-width: 28em; max-height: 60vh; \
+         overflow: auto; padding: 0.5em; background: white; border: 1px solid #ccc; \
+         font: 12px monospace",
+    );
+    let details = create_element("details");
+    let summary = create_element("summary");
+    summary.set_text_content(Some(&format!("Devtools: {title}")));
+    let current = create_element("pre");
+    let log = create_element("ol");
+    let input = create_element("input").unchecked_into::<HtmlInputElement>();
+    input.set_placeholder(r#"{"Counter": "Increment"}"#);
+    _ = input.set_attribute("aria-label", "Raw message");
+    let dispatch = create_element("button");
+    dispatch.set_text_content(Some("Dispatch"));
+    let error = create_element("p");
+    _ = error.set_attribute("role", "alert");
+    let tasks = create_element("ul");
+    let interning = create_element("p");
+    for child in [
+        &summary, &current, &log, &input, &dispatch, &error, &tasks, &interning,
+    ] {
+        details.append_child(child).unwrap();
+    }
+    panel.append_child(&details).unwrap();
+    app.append(&body, &panel);
+
+    // the supervisor only says what changed, so it's kept here in full
+    let statuses = supervisor
+        .statuses()
+        .into_iter()
+        .map(|(name, status)| (name.to_string(), status))
+        .collect::<BTreeMap<_, _>>();
+    show_tasks(&tasks, &statuses);
+    supervisor.on_status({
+        let statuses = RefCell::new(statuses);
+        move |name, status| {
+            let mut statuses = statuses.borrow_mut();
+            statuses.insert(name.to_string(), status.clone());
+            show_tasks(&tasks, &statuses);
+        }
+    });
+
+    let send = {
+        let input = input.clone();
+        move || match serde_json::from_str::<M>(&input.value()) {
+            Ok(msg) => {
+                error.set_text_content(None);
+                _ = sender.clone().try_send(msg);
+            }
+            Err(parse_error) => error.set_text_content(Some(&parse_error.to_string())),
+        }
+    };
+    app.listen(&dispatch, "click", {
+        let send = send.clone();
+        move |_: MouseEvent| send()
+    });
+    app.listen(&input, "keydown", move |ev: KeyboardEvent| {
+        if ev.key() == "Enter" {
+            send();
+        }
+    });
+
+    stream::poll_fn(move |cx| {
+        let json = serde_json::to_string_pretty(&state())
+            .unwrap_or_else(|error| format!("couldn't serialize: {error}"));
+        current.set_text_content(Some(&json));
+        interning.set_text_content(Some(&describe(intern::stats())));
+        let next = receiver.poll_next_unpin(cx);
+        if let Poll::Ready(Some(msg)) = &next {
+            append_entry(&log, msg);
+        }
+        next
+    })
+    .boxed_local()
+}
+
+fn show_tasks(list: &Element, statuses: &BTreeMap<String, TaskStatus>) {
+    list.set_inner_html("");
+    for (name, status) in statuses {
+        let entry = create_element("li");
+        entry.set_text_content(Some(&format!("{name}: {}", describe_task(status))));
+        list.append_child(&entry).unwrap();
+    }
+}
+
+fn describe_task(status: &TaskStatus) -> String {
+    match status {
+        TaskStatus::Running { restarts: 0 } => "running".to_string(),
+        TaskStatus::Running { restarts } => format!("running, restarted {restarts} times"),
+        TaskStatus::Restarting {
+            delay,
+            error: Some(error),
+            ..
+        } => format!("failed ({error}), restarting in {}ms", delay.as_millis()),
+        TaskStatus::Restarting { delay, .. } => {
+            format!("finished, restarting in {}ms", delay.as_millis())
+        }
+        TaskStatus::Finished => "finished".to_string(),
+        TaskStatus::Failed(error) => format!("gave up: {error}"),
+        TaskStatus::Stopped => "stopped".to_string(),
+    }
+}
+
+fn describe(stats: intern::Stats) -> String {
+    format!(
+        "Interned strings: {}, found {:.0}% of the time ({} hits, {} misses)",
+        stats.interned,
+        stats.hit_rate() * 100.0,
+        stats.hits,
+        stats.misses
+    )
+}
+
+fn append_entry(log: &Element, msg: &impl Debug) {
+    let now = js_sys::Date::new_0();
+    let entry = create_element("li");
+    entry.set_text_content(Some(&format!(
+        "{:02}:{:02}:{:02}.{:03} {msg:?}",
+        now.get_hours(),
+        now.get_minutes(),
+        now.get_seconds(),
+        now.get_milliseconds()
+    )));
+    log.append_child(&entry).unwrap();
+    if log.child_element_count() > LOG_LIMIT {
+        if let Some(oldest) = log.first_element_child() {
+            oldest.remove();
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Modal dialogs on the native `<dialog>` element.
+//!
+//! `<dialog>` opened with `showModal()` already does what a hand-rolled modal has to
+//! work for: it sits in the top layer, makes the rest of the page inert, traps focus,
+//! closes on Escape and hands focus back when it closes. Buttons in its
+//! `<form method="dialog">` close it with their `value` as the return value.
+//!
+//! ```ignore
+//! let choice = Dialog::new("Discard changes?")
+//!     .button("discard", "Discard")
+//!     .button("keep", "Keep editing")
+//!     .show_modal()
+//!     .await; // Some("discard"), Some("keep"), or None for Escape
+//! ```
+
+use futures::channel::oneshot;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event, HtmlDialogElement, HtmlInputElement};
+
+use crate::{
+    app::App,
+    dom::{body, create_element},
+    hotkeys::{self, Scope},
+};
+
+pub struct Dialog {
+    el: HtmlDialogElement,
+    body: Element,
+    buttons: Element,
+}
+
+impl Dialog {
+    pub fn new(title: &str) -> Self {
+        let el = create_element("dialog").unchecked_into::<HtmlDialogElement>();
+        let heading = create_element("h2");
+        _ = heading.set_attribute("style", "margin-top: 0; font-size: 1.2em");
+        heading.set_text_content(Some(title));
+        let form = create_element("form");
+        _ = form.set_attribute("method", "dialog");
+        let body = create_element("div");
+        let buttons = create_element("p");
+        _ = buttons.set_attribute("style", "display: flex; gap: 0.5em; justify-content: end");
+        form.append_child(&body).unwrap();
+        form.append_child(&buttons).unwrap();
+        el.append_child(&heading).unwrap();
+        el.append_child(&form).unwrap();
+        Dialog { el, body, buttons }
+    }
+
+    /// Where the dialog's content goes, between the title and the buttons.
+    pub fn body(&self) -> &Element {
+        &self.body
+    }
+
+    /// A button that closes the dialog with `value`. The first one is the default:
+    /// Enter in a text field submits with it.
+    pub fn button(self, value: &str, label: &str) -> Self {
+        let button = create_element("button");
+        _ = button.set_attribute("value", value);
+        button.set_text_content(Some(label));
+        self.buttons.append_child(&button).unwrap();
+        self
+    }
+
+    /// Something that closes the dialog with `value`, for content that's done without
+    /// a button being pressed.
+    pub fn closer(&self) -> impl Fn(&str) + 'static {
+        let el = self.el.clone();
+        move |value| el.close_with_return_value(value)
+    }
+
+    /// Shows the dialog modally until it closes, then removes it. Resolves to the
+    /// value of the button that closed it, or `None` if it was cancelled (Escape).
+    pub async fn show_modal(self) -> Option<String> {
+        body().unwrap().append_child(&self.el).unwrap();
+        // taken down again however this ends, even if the future is dropped halfway
+        let _shown = Shown(self.el.clone());
+        self.el.show_modal().ok()?;
+
+        let (closed, on_close) = oneshot::channel();
+        let mut listeners = App::new();
+        // the page's shortcuts are off while it's covered
+        hotkeys::push_scope(&mut listeners, Scope::Modal);
+        // Escape fires `cancel` and then `close`; forget any value from a previous use
+        listeners.listen(&self.el, "cancel", {
+            let el = self.el.clone();
+            move |_: Event| el.set_return_value("")
+        });
+        let mut closed = Some(closed);
+        listeners.listen(&self.el, "close", move |_: Event| {
+            if let Some(closed) = closed.take() {
+                _ = closed.send(());
+            }
+        });
+        _ = on_close.await;
+        drop(listeners);
+
+        Some(self.el.return_value()).filter(|value| !value.is_empty())
+    }
+}
+
+struct Shown(HtmlDialogElement);
+
+impl Drop for Shown {
+    fn drop(&mut self) {
+        self.0.close();
+        self.0.remove();
+    }
+}
+
+/// A `window.prompt` replacement: the text entered, or `None` if cancelled.
+pub async fn prompt(message: &str, default: &str) -> Option<String> {
+    let dialog = Dialog::new(message)
+        .button("ok", "OK")
+        .button("cancel", "Cancel");
+    let input = create_element("input").unchecked_into::<HtmlInputElement>();
+    input.set_value(default);
+    _ = input.set_attribute("aria-label", message);
+    _ = input.set_attribute("autofocus", "");
+    dialog.body().append_child(&input).unwrap();
+    match dialog.show_modal().await.as_deref() {
+        Some("ok") => Some(input.value()),
+        _ => None,
+    }
+}
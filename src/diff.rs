@@ -0,0 +1,200 @@
+//! Myers' diff: the shortest edit script between two sequences.
+//!
+//! Works on any `PartialEq` items (lines, words, characters). It's O((N + M) · D) for
+//! D differences, so near-identical texts are fast however long they are. The trace
+//! it keeps for backtracking holds only the diagonals each round reached, 2d + 3 of
+//! them in round d, so it's O(D²): a few megabytes for a thousand differences, but
+//! two unrelated texts of N items each have 2N, so callers should cap what they pass.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change<'a, T> {
+    Same(&'a T),
+    Removed(&'a T),
+    Added(&'a T),
+}
+
+/// Turns `old` into `new` with as few removals and additions as possible, keeping
+/// everything else in order. Removals come before additions where they're adjacent.
+pub fn diff<'a, T: PartialEq>(old: &'a [T], new: &'a [T]) -> Vec<Change<'a, T>> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = (n + m) as usize;
+    // v[k] is the furthest x reached on diagonal k = x - y, offset so k can be negative
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    // before each round d, the diagonals -d - 1..=d + 1 as the round before left them,
+    // which is all backtracking through round d looks at
+    let mut trace = Vec::new();
+
+    'search: for d in 0..=max as isize {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let i = (k + offset) as usize;
+            // step down (an addition) or right (a removal), whichever got further
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                v[i + 1]
+            } else {
+                v[i - 1] + 1
+            };
+            let mut y = x - k;
+            // then follow the diagonal of matching items as far as it goes
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    // walk back from the end through the saved rounds, emitting changes in reverse
+    let mut changes = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, reached) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let v = |k: isize| reached[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v(k - 1) < v(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            changes.push(Change::Same(&old[x as usize]));
+        }
+        if d > 0 {
+            if x == prev_x {
+                changes.push(Change::Added(&new[prev_y as usize]));
+            } else {
+                changes.push(Change::Removed(&old[prev_x as usize]));
+            }
+        }
+        (x, y) = (prev_x, prev_y);
+    }
+    changes.reverse();
+    changes
+}
+
+/// Splits `text` into runs of whitespace and runs of everything else, so a diff of
+/// the pieces is a word diff that still puts the text back together exactly.
+pub fn words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut in_space = None;
+    for (i, c) in text.char_indices() {
+        let space = c.is_whitespace();
+        if in_space.is_some_and(|in_space| in_space != space) {
+            words.push(&text[start..i]);
+            start = i;
+        }
+        in_space = Some(space);
+    }
+    if start < text.len() {
+        words.push(&text[start..]);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The length of the longest common subsequence, the slow and obvious way.
+    fn lcs(old: &[u8], new: &[u8]) -> usize {
+        let mut table = vec![vec![0; new.len() + 1]; old.len() + 1];
+        for (i, a) in old.iter().enumerate() {
+            for (j, b) in new.iter().enumerate() {
+                table[i + 1][j + 1] = if a == b {
+                    table[i][j] + 1
+                } else {
+                    table[i][j + 1].max(table[i + 1][j])
+                };
+            }
+        }
+        table[old.len()][new.len()]
+    }
+
+    /// Checks that `changes` turns `old` into `new`, in as few changes as there can be,
+    /// with removals before additions.
+    fn check(old: &[u8], new: &[u8]) {
+        let changes = diff(old, new);
+        let before = changes.iter().filter_map(|change| match change {
+            Change::Same(item) | Change::Removed(item) => Some(**item),
+            Change::Added(_) => None,
+        });
+        let after = changes.iter().filter_map(|change| match change {
+            Change::Same(item) | Change::Added(item) => Some(**item),
+            Change::Removed(_) => None,
+        });
+        assert_eq!(before.collect::<Vec<_>>(), old, "{old:?} -> {new:?}");
+        assert_eq!(after.collect::<Vec<_>>(), new, "{old:?} -> {new:?}");
+        let edits = changes
+            .iter()
+            .filter(|change| !matches!(change, Change::Same(_)))
+            .count();
+        assert_eq!(edits, old.len() + new.len() - 2 * lcs(old, new));
+        assert!(!changes
+            .windows(2)
+            .any(|pair| matches!(pair, [Change::Added(_), Change::Removed(_)])));
+    }
+
+    #[test]
+    fn edge_cases() {
+        for (old, new) in [
+            ("", ""),
+            ("", "abc"),
+            ("abc", ""),
+            ("abc", "abc"),
+            ("abc", "xyz"),
+        ] {
+            check(old.as_bytes(), new.as_bytes());
+        }
+        assert_eq!(
+            diff(b"ab", b"b"),
+            [Change::Removed(&b'a'), Change::Same(&b'b')]
+        );
+    }
+
+    #[test]
+    fn shortest_scripts_for_small_inputs() {
+        // a fixed pseudo-random sequence, so a failure can be reproduced
+        let mut seed = 0x2545_f491_u32;
+        let mut next = move |below: u32| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed % below
+        };
+        for _ in 0..2_000 {
+            let (old_len, new_len) = (next(10), next(10));
+            let old = (0..old_len)
+                .map(|_| b"abc"[next(3) as usize])
+                .collect::<Vec<_>>();
+            let new = (0..new_len)
+                .map(|_| b"abc"[next(3) as usize])
+                .collect::<Vec<_>>();
+            check(&old, &new);
+        }
+    }
+
+    #[test]
+    fn diffs_unrelated_inputs_as_long_as_the_example_allows() {
+        // text_diff::MAX_PIECES a side, with nothing in common: 2,000 rounds, whose trace
+        // is about 4M entries
+        let old = vec![0u8; 1_000];
+        let new = vec![1u8; 1_000];
+        assert_eq!(diff(&old, &new).len(), 2_000);
+    }
+
+    #[test]
+    fn splits_words_and_spaces() {
+        assert_eq!(words("a  bc\nd"), ["a", "  ", "bc", "\n", "d"]);
+        assert_eq!(words(" x").concat(), " x");
+        assert!(words("").is_empty());
+    }
+}
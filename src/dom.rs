@@ -0,0 +1,171 @@
+//! The handful of DOM helpers everything here is written with, directly over
+//! `web_sys` and `wasm_bindgen`.
+//!
+//! The one with an ownership question is [`add_event_listener`]: a Rust closure handed
+//! to JS has to live somewhere for as long as JS might call it. Here that's the
+//! returned [`EventListenerHandle`], which removes the listener and frees the closure
+//! when it's dropped; [`EventListenerHandle::forget`] is the explicit way to keep one
+//! for the rest of the page's life. (An [`App`](crate::app::App) holds the handles of
+//! all the listeners of one example at once.)
+
+use wasm_bindgen::{closure::Closure, JsCast, UnwrapThrowExt};
+use web_sys::{
+    AddEventListenerOptions, Document, Element, Event, EventTarget, HtmlElement, HtmlInputElement,
+    HtmlSelectElement, HtmlTextAreaElement, Window,
+};
+
+pub use wasm_bindgen_futures::spawn_local;
+
+use crate::intern::intern;
+
+pub fn window() -> Window {
+    web_sys::window().expect_throw("there to be a window")
+}
+
+pub fn document() -> Document {
+    window().document().expect_throw("there to be a document")
+}
+
+pub fn body() -> Option<HtmlElement> {
+    document().body()
+}
+
+pub fn create_element(tag_name: &str) -> Element {
+    document()
+        .create_element(intern(tag_name))
+        .expect_throw("to create the element")
+}
+
+/// Sets the attribute `name` on `element`, with the name (and a class list, which
+/// tends to repeat too) [`intern`]ed.
+pub fn set_attribute(element: &Element, name: &str, value: &str) {
+    let value = if name == "class" {
+        intern(value)
+    } else {
+        value
+    };
+    _ = element.set_attribute(intern(name), value);
+}
+
+/// Creates a `tag_name` element as the `web_sys` type it is, so that its own methods
+/// (`HtmlInputElement::value`, say) are there without a cast:
+///
+/// ```ignore
+/// let input = create::<HtmlInputElement>("input");
+/// ```
+///
+/// Throws if `tag_name` doesn't make a `T`.
+pub fn create<T: JsCast>(tag_name: &str) -> T {
+    create_element(tag_name)
+        .dyn_into()
+        .expect_throw("the element to be the type asked for")
+}
+
+/// The value of the `<input>`, `<select>` or `<textarea>` an `input` or `change` event
+/// came from, or `""` for anything else.
+///
+/// `value` isn't on `Element`, or even `HtmlElement`: it's on each of those three
+/// separately, so the target has to be cast to the one it really is.
+pub fn value_of(event: &Event) -> String {
+    let Some(target) = event.target() else {
+        return String::new();
+    };
+    if let Some(input) = target.dyn_ref::<HtmlInputElement>() {
+        input.value()
+    } else if let Some(select) = target.dyn_ref::<HtmlSelectElement>() {
+        select.value()
+    } else if let Some(textarea) = target.dyn_ref::<HtmlTextAreaElement>() {
+        textarea.value()
+    } else {
+        String::new()
+    }
+}
+
+/// Logs to the console, `format!`-style.
+macro_rules! log {
+    ($($arg:tt)*) => {
+        web_sys::console::log_1(&format!($($arg)*).into())
+    };
+}
+pub(crate) use log;
+
+/// An attached listener, which owns its closure: dropping the handle (or calling
+/// [`remove`](EventListenerHandle::remove)) detaches the listener and frees the closure.
+#[must_use = "the listener is removed as soon as this is dropped"]
+pub struct EventListenerHandle {
+    target: EventTarget,
+    event_name: &'static str,
+    /// Removal only matches if `capture` is the same as when the listener was added.
+    capture: bool,
+    closure: Option<Closure<dyn FnMut(Event)>>,
+}
+
+impl EventListenerHandle {
+    /// Detaches the listener now.
+    pub fn remove(self) {
+        drop(self)
+    }
+
+    /// Keeps the listener (and its closure) for the rest of the page's life.
+    pub fn forget(mut self) {
+        if let Some(closure) = self.closure.take() {
+            closure.forget();
+        }
+    }
+}
+
+impl Drop for EventListenerHandle {
+    fn drop(&mut self) {
+        if let Some(closure) = self.closure.take() {
+            _ = self.target.remove_event_listener_with_callback_and_bool(
+                self.event_name,
+                closure.as_ref().unchecked_ref(),
+                self.capture,
+            );
+        }
+    }
+}
+
+/// Calls `cb` with every `event_name` event on `target`, for as long as the returned
+/// handle is kept.
+pub fn add_event_listener<E>(
+    target: &EventTarget,
+    event_name: &'static str,
+    cb: impl FnMut(E) + 'static,
+) -> EventListenerHandle
+where
+    E: JsCast + 'static,
+{
+    add_event_listener_with_options(target, event_name, &AddEventListenerOptions::new(), cb)
+}
+
+/// Like [`add_event_listener`], with `passive`/`capture`/`once` options.
+pub fn add_event_listener_with_options<E>(
+    target: &EventTarget,
+    event_name: &'static str,
+    options: &AddEventListenerOptions,
+    mut cb: impl FnMut(E) + 'static,
+) -> EventListenerHandle
+where
+    E: JsCast + 'static,
+{
+    let closure =
+        Closure::wrap(Box::new(move |ev: Event| cb(ev.unchecked_into())) as Box<dyn FnMut(Event)>);
+    target
+        .add_event_listener_with_callback_and_add_event_listener_options(
+            intern(event_name),
+            closure.as_ref().unchecked_ref(),
+            options,
+        )
+        .expect_throw("to add the event listener");
+    let capture = js_sys::Reflect::get(options, &"capture".into())
+        .ok()
+        .and_then(|capture| capture.as_bool())
+        .unwrap_or(false);
+    EventListenerHandle {
+        target: target.clone(),
+        event_name,
+        capture,
+        closure: Some(closure),
+    }
+}
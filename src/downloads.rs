@@ -0,0 +1,157 @@
+//! Files the app makes for the user to save: CSV exports, snapshots, state dumps.
+//!
+//! [`download`] wraps the bytes in a blob, gives the blob an object URL and clicks a
+//! link to it. Each URL is kept for a while afterwards, listed in a small downloads
+//! panel so the file can be saved again, and then revoked; only the newest few are
+//! kept at all, so generated files don't pile up in memory.
+
+use std::{cell::RefCell, time::Duration};
+
+use wasm_bindgen::JsCast;
+use web_sys::{Blob, BlobPropertyBag, Element, HtmlAnchorElement, MouseEvent, Url};
+
+use crate::{
+    dom::{add_event_listener, body, create_element, document, spawn_local},
+    time::sleep,
+    toast,
+};
+
+const PANEL_ID: &str = "downloads";
+/// How long a download can be saved again from the panel.
+const KEEP_FOR: Duration = Duration::from_secs(60);
+/// Older downloads are revoked as soon as there are more than this.
+const MAX_KEPT: usize = 5;
+
+struct Entry {
+    id: usize,
+    url: String,
+    /// Its row in the panel.
+    item: Element,
+}
+
+#[derive(Default)]
+struct Downloads {
+    next_id: usize,
+    entries: Vec<Entry>,
+}
+
+thread_local! {
+    static DOWNLOADS: RefCell<Downloads> = RefCell::new(Downloads::default());
+}
+
+/// Saves `bytes` as a file called `filename`.
+pub fn download(filename: &str, bytes: &[u8], mime: &str) {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let mut options = BlobPropertyBag::new();
+    options.type_(mime);
+    match Blob::new_with_u8_array_sequence_and_options(&parts, &options) {
+        Ok(blob) => download_blob(filename, &blob),
+        Err(_) => toast::error(&format!("Couldn't make {filename}")),
+    }
+}
+
+/// Saves a blob the browser already has, like a canvas's, as a file called `filename`.
+pub fn download_blob(filename: &str, blob: &Blob) {
+    let Ok(url) = Url::create_object_url_with_blob(blob) else {
+        toast::error(&format!("Couldn't make {filename}"));
+        return;
+    };
+    let link = create_element("a").unchecked_into::<HtmlAnchorElement>();
+    link.set_href(&url);
+    link.set_download(filename);
+    link.click();
+
+    let item = create_element("li");
+    let again = link.clone_node_with_deep(false).unwrap();
+    again.set_text_content(Some(filename));
+    item.append_child(&again).unwrap();
+    _ = item.append_with_str_1(&format!(" ({}) ", size(blob.size())));
+    let dismiss = create_element("button");
+    dismiss.set_text_content(Some("×"));
+    _ = dismiss.set_attribute("aria-label", &format!("Forget {filename}"));
+    item.append_child(&dismiss).unwrap();
+
+    let panel = panel();
+    let list = panel.query_selector("ul").unwrap().unwrap();
+    _ = list.prepend_with_node_1(&item);
+    _ = panel.remove_attribute("hidden");
+
+    let id = DOWNLOADS.with(|downloads| {
+        let mut downloads = downloads.borrow_mut();
+        let id = downloads.next_id;
+        downloads.next_id += 1;
+        downloads.entries.push(Entry { id, url, item });
+        id
+    });
+    _ = dismiss.set_attribute("data-download", &id.to_string());
+    while DOWNLOADS.with(|downloads| downloads.borrow().entries.len()) > MAX_KEPT {
+        let oldest = DOWNLOADS.with(|downloads| downloads.borrow().entries[0].id);
+        revoke(oldest);
+    }
+    spawn_local(async move {
+        sleep(KEEP_FOR).await;
+        revoke(id);
+    });
+}
+
+/// Revokes a download's URL and takes it off the panel, if that hasn't happened yet.
+fn revoke(id: usize) {
+    let entry = DOWNLOADS.with(|downloads| {
+        let mut downloads = downloads.borrow_mut();
+        let index = downloads.entries.iter().position(|entry| entry.id == id)?;
+        Some(downloads.entries.remove(index))
+    });
+    let Some(entry) = entry else {
+        return;
+    };
+    _ = Url::revoke_object_url(&entry.url);
+    entry.item.remove();
+    if DOWNLOADS.with(|downloads| downloads.borrow().entries.is_empty()) {
+        _ = panel().set_attribute("hidden", "");
+    }
+}
+
+fn size(bytes: f64) -> String {
+    if bytes < 1024.0 {
+        format!("{bytes} B")
+    } else if bytes < 1024.0 * 1024.0 {
+        format!("{:.1} KB", bytes / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes / 1024.0 / 1024.0)
+    }
+}
+
+/// The panel listing recent downloads, created on first use.
+fn panel() -> Element {
+    document().get_element_by_id(PANEL_ID).unwrap_or_else(|| {
+        let panel = create_element("aside");
+        panel.set_id(PANEL_ID);
+        _ = panel.set_attribute("aria-label", "Downloads");
+        _ = panel.set_attribute(
+            "style",
+            "position: fixed; right: 16px; top: 16px; padding: 0 12px; background: white; \
+             border: 1px solid #ccc; border-radius: 4px; z-index: 1000",
+        );
+        let heading = create_element("p");
+        heading.set_text_content(Some("Downloads"));
+        _ = heading.set_attribute("style", "font-weight: bold");
+        panel.append_child(&heading).unwrap();
+        let list = create_element("ul");
+        _ = list.set_attribute("style", "padding-left: 16px");
+        panel.append_child(&list).unwrap();
+        // the panel stays for good, so one listener handles every row's button
+        add_event_listener(&panel, "click", |ev: MouseEvent| {
+            let id = ev
+                .target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+                .and_then(|target| target.get_attribute("data-download"))
+                .and_then(|id| id.parse().ok());
+            if let Some(id) = id {
+                revoke(id);
+            }
+        })
+        .forget();
+        body().unwrap().append_child(&panel).unwrap();
+        panel
+    })
+}
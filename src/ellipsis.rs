@@ -0,0 +1,202 @@
+//! Measuring text, and cutting it to fit from the middle: `IMG_2024…0412.jpg` keeps
+//! both ends of a name, where CSS's `text-overflow` only ever keeps the start.
+//!
+//! Widths come from a canvas's `measureText` in the element's own font. That measures
+//! strings that aren't on the page, so trying a dozen lengths to find the longest that
+//! fits doesn't lay the page out a dozen times. Each element is fitted again whenever
+//! its width changes, which a `ResizeObserver` reports; our web-sys only has that
+//! behind its unstable APIs, so the little of it used here is bound by hand.
+
+use std::cell::RefCell;
+
+use wasm_bindgen::{closure::Closure, prelude::wasm_bindgen, JsCast};
+use web_sys::{CanvasRenderingContext2d, Element};
+
+use crate::dom::{create_element, window};
+
+const ELLIPSIS: char = '…';
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends = js_sys::Object)]
+    type ResizeObserver;
+
+    #[wasm_bindgen(constructor)]
+    fn new(callback: &js_sys::Function) -> ResizeObserver;
+
+    #[wasm_bindgen(method)]
+    fn observe(this: &ResizeObserver, target: &Element);
+
+    #[wasm_bindgen(method)]
+    fn unobserve(this: &ResizeObserver, target: &Element);
+
+    #[wasm_bindgen(method)]
+    fn disconnect(this: &ResizeObserver);
+
+    type ResizeObserverEntry;
+
+    #[wasm_bindgen(method, getter)]
+    fn target(this: &ResizeObserverEntry) -> Element;
+}
+
+thread_local! {
+    /// A canvas that's never shown, only measured with.
+    static CONTEXT: RefCell<Option<CanvasRenderingContext2d>> = const { RefCell::new(None) };
+}
+
+/// How wide `text` is in `font` (CSS `font` shorthand), in CSS pixels.
+pub fn text_width(text: &str, font: &str) -> f64 {
+    CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+        if context.is_none() {
+            *context = create_element("canvas")
+                .unchecked_into::<web_sys::HtmlCanvasElement>()
+                .get_context("2d")
+                .ok()
+                .flatten()
+                .map(JsCast::unchecked_into);
+        }
+        let Some(context) = context.as_ref() else {
+            return 0.0;
+        };
+        context.set_font(font);
+        context
+            .measure_text(text)
+            .map_or(0.0, |metrics| metrics.width())
+    })
+}
+
+/// `text`, or as much of its start and end as fits in `max_width` with an ellipsis
+/// between them, by `measure`.
+pub fn truncate_middle(text: &str, max_width: f64, measure: impl Fn(&str) -> f64) -> String {
+    if text.is_empty() || measure(text) <= max_width {
+        return text.to_string();
+    }
+    let chars: Vec<char> = text.chars().collect();
+    // the start gets the odd one out: it's usually what tells names apart
+    let kept = |n: usize| -> String {
+        let start = n.div_ceil(2);
+        let end = n - start;
+        chars[..start]
+            .iter()
+            .chain([&ELLIPSIS])
+            .chain(&chars[chars.len() - end..])
+            .collect()
+    };
+    // the most characters that still fit; even none might not, but that's all there is
+    let (mut low, mut high) = (0, chars.len() - 1);
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if measure(&kept(mid)) <= max_width {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    kept(low)
+}
+
+/// Fits elements' text to their width, from the middle, and again each time their
+/// width changes, until it's dropped.
+///
+/// The elements have to have a width of their own (not just their text's), and
+/// `white-space: nowrap; overflow: hidden`.
+pub struct MiddleEllipsis {
+    observer: ResizeObserver,
+    _callback: Closure<dyn FnMut(js_sys::Array)>,
+}
+
+impl MiddleEllipsis {
+    pub fn new() -> MiddleEllipsis {
+        let callback = Closure::wrap(Box::new(|entries: js_sys::Array| {
+            for entry in entries.iter() {
+                fit(&entry.unchecked_into::<ResizeObserverEntry>().target());
+            }
+        }) as Box<dyn FnMut(js_sys::Array)>);
+        let observer = ResizeObserver::new(callback.as_ref().unchecked_ref());
+        MiddleEllipsis {
+            observer,
+            _callback: callback,
+        }
+    }
+
+    /// Shows `text` in `el`, cut to fit. The observer reports every element it starts
+    /// observing, before the next paint, so the whole text is never seen overflowing.
+    pub fn show(&self, el: &Element, text: &str) {
+        _ = el.set_attribute("data-text", text);
+        el.set_text_content(Some(text));
+        self.observer.observe(el);
+    }
+
+    /// Stops fitting `el`, which would otherwise be kept (detached or not) for as long
+    /// as this is.
+    pub fn forget(&self, el: &Element) {
+        self.observer.unobserve(el);
+    }
+
+    /// Stops fitting every element.
+    pub fn clear(&self) {
+        self.observer.disconnect();
+    }
+}
+
+impl Drop for MiddleEllipsis {
+    fn drop(&mut self) {
+        self.observer.disconnect();
+    }
+}
+
+fn fit(el: &Element) {
+    let Some(text) = el.get_attribute("data-text") else {
+        return;
+    };
+    let Ok(Some(style)) = window().get_computed_style(el) else {
+        return;
+    };
+    let property = |name: &str| style.get_property_value(name).unwrap_or_default();
+    let pixels = |name: &str| {
+        property(name)
+            .trim_end_matches("px")
+            .parse::<f64>()
+            .unwrap_or(0.0)
+    };
+    // the computed `font` shorthand is empty in some browsers, so it's put back together
+    let font = format!(
+        "{} {} {} {}",
+        property("font-style"),
+        property("font-weight"),
+        property("font-size"),
+        property("font-family")
+    );
+    let width = el.client_width() as f64 - pixels("padding-left") - pixels("padding-right");
+    let shown = truncate_middle(&text, width, |text| text_width(text, &font));
+    if shown == text {
+        _ = el.remove_attribute("title");
+    } else {
+        _ = el.set_attribute("title", &text);
+    }
+    el.set_text_content(Some(&shown));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every character is 1 wide.
+    fn chars(text: &str) -> f64 {
+        text.chars().count() as f64
+    }
+
+    #[test]
+    fn keeps_both_ends() {
+        assert_eq!(truncate_middle("report.pdf", 10.0, chars), "report.pdf");
+        assert_eq!(
+            truncate_middle("holiday-photo.jpg", 10.0, chars),
+            "holid….jpg"
+        );
+        assert_eq!(truncate_middle("holiday-photo.jpg", 4.0, chars), "ho…g");
+        assert_eq!(truncate_middle("naïve café", 5.0, chars), "na…fé");
+        assert_eq!(truncate_middle("anything", 0.0, chars), "…");
+        assert_eq!(truncate_middle("", -8.0, chars), "");
+    }
+}
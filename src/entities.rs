@@ -0,0 +1,131 @@
+//! A normalized collection: entities by id, in the order they were first added.
+//!
+//! Listeners hear about each entity that changes, one call per entity, so a view can
+//! update just the rows (or cards) involved. An upsert that leaves an entity as it was
+//! isn't a change, which makes re-importing the same data free.
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, PartialEq)]
+pub enum Change<'a, V> {
+    Added(&'a V),
+    Updated(&'a V),
+    Removed,
+}
+
+type Listener<K, V> = Box<dyn FnMut(&K, Change<'_, V>)>;
+
+pub struct EntityStore<K, V> {
+    entities: BTreeMap<K, V>,
+    ids: Vec<K>,
+    listeners: Vec<Listener<K, V>>,
+}
+
+impl<K, V> Default for EntityStore<K, V> {
+    fn default() -> Self {
+        EntityStore {
+            entities: BTreeMap::new(),
+            ids: Vec::new(),
+            listeners: Vec::new(),
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: PartialEq> EntityStore<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calls `listener` with every entity that changes from now on. It's called while
+    /// the store is being changed, so it gets the entity rather than reading the store.
+    pub fn subscribe(&mut self, listener: impl FnMut(&K, Change<'_, V>) + 'static) {
+        self.listeners.push(Box::new(listener));
+    }
+
+    /// Every id, in the order the entities were first added.
+    pub fn ids(&self) -> &[K] {
+        &self.ids
+    }
+
+    pub fn get(&self, id: &K) -> Option<&V> {
+        self.entities.get(id)
+    }
+
+    /// Adds the entities that are new and replaces the ones that differ.
+    pub fn upsert_many(&mut self, entities: impl IntoIterator<Item = (K, V)>) {
+        for (id, entity) in entities {
+            let added = match self.entities.get(&id) {
+                Some(existing) if *existing == entity => continue,
+                Some(_) => false,
+                None => {
+                    self.ids.push(id.clone());
+                    true
+                }
+            };
+            self.entities.insert(id.clone(), entity);
+            let entity = &self.entities[&id];
+            let change = if added {
+                Change::Added
+            } else {
+                Change::Updated
+            };
+            for listener in &mut self.listeners {
+                listener(&id, change(entity));
+            }
+        }
+    }
+
+    pub fn remove(&mut self, id: &K) -> Option<V> {
+        let entity = self.entities.remove(id)?;
+        self.ids.retain(|other| other != id);
+        for listener in &mut self.listeners {
+            listener(id, Change::Removed);
+        }
+        Some(entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    fn store() -> (EntityStore<u32, &'static str>, Rc<RefCell<Vec<String>>>) {
+        let mut store = EntityStore::new();
+        let heard = Rc::new(RefCell::new(Vec::new()));
+        store.subscribe({
+            let heard = heard.clone();
+            move |id, change| heard.borrow_mut().push(format!("{id} {change:?}"))
+        });
+        (store, heard)
+    }
+
+    #[test]
+    fn each_changed_entity_is_one_notification() {
+        let (mut store, heard) = store();
+        store.upsert_many([(2, "b"), (1, "a")]);
+        store.upsert_many([(1, "a"), (2, "B"), (3, "c")]);
+        assert_eq!(
+            *heard.borrow(),
+            [
+                "2 Added(\"b\")",
+                "1 Added(\"a\")",
+                "2 Updated(\"B\")",
+                "3 Added(\"c\")"
+            ]
+        );
+        assert_eq!(store.ids(), [2, 1, 3]);
+        assert_eq!(store.get(&2), Some(&"B"));
+    }
+
+    #[test]
+    fn removing_forgets_the_id() {
+        let (mut store, heard) = store();
+        store.upsert_many([(1, "a"), (2, "b")]);
+        assert_eq!(store.remove(&1), Some("a"));
+        assert_eq!(store.remove(&1), None);
+        assert_eq!(store.ids(), [2]);
+        assert_eq!(heard.borrow().last().unwrap(), "1 Removed");
+    }
+}
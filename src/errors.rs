@@ -0,0 +1,172 @@
+//! Error reports: panics, failed tasks and unhandled promise rejections.
+//!
+//! Each report carries the context needed to make sense of it: which example was
+//! showing, and the last few messages the app handled before things went wrong (fed
+//! in by [`analytics::message`](crate::analytics::message)). Reports are rate-limited,
+//! since one bug in a render loop can fail every frame, and sent with `sendBeacon` to
+//! the endpoint given at [`install`], or just logged when there isn't one.
+
+use std::{cell::RefCell, collections::VecDeque, fmt::Debug, panic, time::Duration};
+
+use serde::Serialize;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{ErrorEvent, Event};
+
+use crate::{
+    app::App,
+    dom::{log, window},
+};
+
+/// How many of the latest messages go along with a report.
+const RECENT_MESSAGES: usize = 10;
+/// At most this many reports are sent per [`RATE_WINDOW`].
+const MAX_REPORTS: usize = 5;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Panic,
+    /// A background task that gave up or is being restarted.
+    Task,
+    /// A promise that was rejected with nothing to catch it.
+    Rejection,
+    /// An exception that reached the top level in JS.
+    Uncaught,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub kind: Kind,
+    pub message: String,
+    /// The URL hash, which says which example was showing.
+    pub route: String,
+    /// Oldest first.
+    pub recent_messages: Vec<String>,
+    pub user_agent: String,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: f64,
+}
+
+#[derive(Default)]
+struct Reporter {
+    endpoint: Option<String>,
+    recent_messages: VecDeque<String>,
+    /// When recent reports were sent, and what they said, oldest first.
+    sent: VecDeque<(f64, Kind, String)>,
+}
+
+thread_local! {
+    static REPORTER: RefCell<Reporter> = RefCell::new(Reporter::default());
+}
+
+/// Reports panics, unhandled rejections and uncaught exceptions to `endpoint` (or the
+/// console) until `app` unmounts.
+pub fn install(app: &mut App, endpoint: Option<String>) {
+    REPORTER.with(|reporter| reporter.borrow_mut().endpoint = endpoint);
+
+    panic::set_hook(Box::new(|info| {
+        console_error_panic_hook::hook(info);
+        report(Kind::Panic, &info.to_string());
+    }));
+    app.listen(&window(), "unhandledrejection", |ev: Event| {
+        let reason = js_sys::Reflect::get(&ev, &"reason".into()).unwrap_or_default();
+        report(Kind::Rejection, &describe(&reason));
+    });
+    app.listen(&window(), "error", |ev: ErrorEvent| {
+        let message = match ev.error() {
+            error if error.is_undefined() || error.is_null() => ev.message(),
+            error => describe(&error),
+        };
+        report(
+            Kind::Uncaught,
+            &format!("{message} at {}:{}", ev.filename(), ev.lineno()),
+        );
+    });
+    app.on_cleanup(|| {
+        // back to just logging them
+        panic::set_hook(Box::new(console_error_panic_hook::hook));
+    });
+}
+
+/// Remembers a message the app handled, to send along with the next report.
+pub fn breadcrumb(msg: &impl Debug) {
+    REPORTER.with(|reporter| {
+        // a message handled from inside a panic's report mustn't panic again
+        let Ok(mut reporter) = reporter.try_borrow_mut() else {
+            return;
+        };
+        if reporter.recent_messages.len() == RECENT_MESSAGES {
+            reporter.recent_messages.pop_front();
+        }
+        reporter.recent_messages.push_back(format!("{msg:?}"));
+    });
+}
+
+/// Reports that the background task `name` failed.
+pub fn task_failed(name: &str, error: &str) {
+    report(Kind::Task, &format!("{name}: {error}"));
+}
+
+pub fn report(kind: Kind, message: &str) {
+    let now = js_sys::Date::now();
+    let report = REPORTER.with(|reporter| {
+        // reporting from a panic that happened mid-report: better to lose this one
+        let mut reporter = reporter.try_borrow_mut().ok()?;
+        let window_start = now - RATE_WINDOW.as_millis() as f64;
+        while reporter
+            .sent
+            .front()
+            .is_some_and(|(sent_at, _, _)| *sent_at < window_start)
+        {
+            reporter.sent.pop_front();
+        }
+        let repeated = reporter
+            .sent
+            .iter()
+            .any(|(_, sent_kind, sent)| *sent_kind == kind && sent == message);
+        if repeated || reporter.sent.len() >= MAX_REPORTS {
+            return None;
+        }
+        reporter.sent.push_back((now, kind, message.to_string()));
+        let location = window().location();
+        let report = Report {
+            kind,
+            message: message.to_string(),
+            route: location.hash().unwrap_or_default(),
+            recent_messages: reporter.recent_messages.iter().cloned().collect(),
+            user_agent: window().navigator().user_agent().unwrap_or_default(),
+            timestamp: now,
+        };
+        Some((report, reporter.endpoint.clone()))
+    });
+    let Some((report, endpoint)) = report else {
+        return;
+    };
+    match endpoint {
+        Some(endpoint) => {
+            let Ok(body) = serde_json::to_string(&report) else {
+                return;
+            };
+            let queued = window()
+                .navigator()
+                .send_beacon_with_opt_str(&endpoint, Some(&body));
+            if queued != Ok(true) {
+                log!("[errors] couldn't send a report to {endpoint}: {report:?}");
+            }
+        }
+        None => log!("[errors] {report:?}"),
+    }
+}
+
+fn describe(value: &JsValue) -> String {
+    match value.dyn_ref::<js_sys::Error>() {
+        Some(error) => {
+            let stack = js_sys::Reflect::get(error, &"stack".into())
+                .ok()
+                .and_then(|stack| stack.as_string());
+            stack.unwrap_or_else(|| error.message().into())
+        }
+        None => value.as_string().unwrap_or_else(|| format!("{value:?}")),
+    }
+}
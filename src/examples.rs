@@ -0,0 +1,459 @@
+//! The example gallery: a nav of grouped links plus whichever example the URL hash points at.
+//!
+//! `index.html#selection` shows the selection example, and so on; an empty or
+//! unknown hash falls back to the first example.
+//!
+//! Above the nav, a banner scrolls away slower than the page (unless motion is
+//! reduced), and then the page's title sticks to the top, shrinking as it goes.
+
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event};
+
+use crate::{
+    accordion::{Accordion, Mode},
+    analytics, animate, api_search,
+    app::App,
+    audio, battery, camera, cards, cart, clock, colors, counter,
+    dom::{create_element, window},
+    experiment, flags, gallery,
+    hotkeys::{self, Scope},
+    hydration, image_filters, install, kanban, multi, network, pair, passkeys, permissions, primes,
+    print, quote, ratings, regex_playground,
+    scroll::{self, px, Effect},
+    selection, spreadsheet, table, text_diff, title, todo, totals, transition, tree_view, versions,
+    widgets,
+};
+
+pub struct Example {
+    pub name: &'static str,
+    pub title: &'static str,
+    /// Which of the [`GROUPS`] it's listed under.
+    pub group: &'static str,
+    /// Pressing g and then this goes to the example.
+    pub shortcut: char,
+    pub mount: fn(&Element) -> App,
+}
+
+const BANNER_HEIGHT: f64 = 120.0;
+/// How far the page scrolls, once the banner's gone, while the header shrinks.
+const HEADER_SHRINK: f64 = 80.0;
+
+/// The banner's backdrop moves down half as fast as the page scrolls it up.
+const PARALLAX: &[Effect] = &[Effect {
+    property: "transform",
+    from: 0.0,
+    to: BANNER_HEIGHT / 2.0,
+    css: |y| format!("translateY({y}px)"),
+}];
+
+const SHRINK: &[Effect] = &[
+    Effect {
+        property: "fontSize",
+        from: 28.0,
+        to: 18.0,
+        css: px,
+    },
+    Effect {
+        property: "paddingBlock",
+        from: 12.0,
+        to: 4.0,
+        css: px,
+    },
+];
+
+/// The sections of the nav, in order.
+pub const GROUPS: &[&str] = &["Basics", "Widgets", "Data", "Browser"];
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        name: "counter",
+        title: "Counter",
+        group: "Basics",
+        shortcut: 'c',
+        mount: counter::mount_to,
+    },
+    Example {
+        name: "multi",
+        title: "Three counters",
+        group: "Basics",
+        shortcut: '2',
+        mount: multi::mount_to,
+    },
+    Example {
+        name: "selection",
+        title: "Selection",
+        group: "Basics",
+        shortcut: 's',
+        mount: selection::mount_to,
+    },
+    Example {
+        name: "clock",
+        title: "Clock",
+        group: "Basics",
+        shortcut: 'o',
+        mount: clock::mount_to,
+    },
+    Example {
+        name: "versions",
+        title: "Versions",
+        group: "Basics",
+        shortcut: 'v',
+        mount: versions::mount_to,
+    },
+    Example {
+        name: "todo",
+        title: "Todo list",
+        group: "Basics",
+        shortcut: 'i',
+        mount: todo::mount_to,
+    },
+    Example {
+        name: "pair",
+        title: "Two counters",
+        group: "Basics",
+        shortcut: 'j',
+        mount: pair::mount_to,
+    },
+    Example {
+        name: "totals",
+        title: "Counters and a total",
+        group: "Basics",
+        shortcut: 'u',
+        mount: totals::mount_to,
+    },
+    Example {
+        name: "hydration",
+        title: "Hydration",
+        group: "Basics",
+        shortcut: 'y',
+        mount: hydration::mount_to,
+    },
+    Example {
+        name: "quote",
+        title: "Quote",
+        group: "Data",
+        shortcut: 'q',
+        mount: quote::mount_to,
+    },
+    Example {
+        name: "gallery",
+        title: "Gallery",
+        group: "Widgets",
+        shortcut: 'g',
+        mount: gallery::mount_to,
+    },
+    Example {
+        name: "cards",
+        title: "10,000 cards",
+        group: "Widgets",
+        shortcut: 'z',
+        mount: cards::mount_to,
+    },
+    Example {
+        name: "colors",
+        title: "Colors",
+        group: "Widgets",
+        shortcut: 'l',
+        mount: colors::mount_to,
+    },
+    Example {
+        name: "widgets",
+        title: "Widgets",
+        group: "Widgets",
+        shortcut: '3',
+        mount: widgets::mount_to,
+    },
+    Example {
+        name: "ratings",
+        title: "Ratings",
+        group: "Widgets",
+        shortcut: 'r',
+        mount: ratings::mount_to,
+    },
+    Example {
+        name: "kanban",
+        title: "Kanban",
+        group: "Data",
+        shortcut: 'k',
+        mount: kanban::mount_to,
+    },
+    Example {
+        name: "tree",
+        title: "Tree",
+        group: "Data",
+        shortcut: 't',
+        mount: tree_view::mount_to,
+    },
+    Example {
+        name: "sheet",
+        title: "Spreadsheet",
+        group: "Data",
+        shortcut: 'x',
+        mount: spreadsheet::mount_to,
+    },
+    Example {
+        name: "table",
+        title: "Table",
+        group: "Data",
+        shortcut: 'b',
+        mount: table::mount_to,
+    },
+    Example {
+        name: "search",
+        title: "Search",
+        group: "Data",
+        shortcut: '/',
+        mount: api_search::mount_to,
+    },
+    Example {
+        name: "diff",
+        title: "Diff",
+        group: "Data",
+        shortcut: 'd',
+        mount: text_diff::mount_to,
+    },
+    Example {
+        name: "regex",
+        title: "Regex",
+        group: "Data",
+        shortcut: 'e',
+        mount: regex_playground::mount_to,
+    },
+    Example {
+        name: "experiments",
+        title: "Experiments",
+        group: "Data",
+        shortcut: 'n',
+        mount: experiment::mount_to,
+    },
+    Example {
+        name: "filters",
+        title: "Filters",
+        group: "Browser",
+        shortcut: 'f',
+        mount: image_filters::mount_to,
+    },
+    Example {
+        name: "primes",
+        title: "Primes in a worker",
+        group: "Browser",
+        shortcut: '1',
+        mount: primes::mount_to,
+    },
+    Example {
+        name: "audio",
+        title: "Audio",
+        group: "Browser",
+        shortcut: 'a',
+        mount: audio::mount_to,
+    },
+    Example {
+        name: "camera",
+        title: "Camera",
+        group: "Browser",
+        shortcut: 'm',
+        mount: camera::mount_to,
+    },
+    Example {
+        name: "permissions",
+        title: "Permissions",
+        group: "Browser",
+        shortcut: 'p',
+        mount: permissions::mount_to,
+    },
+    Example {
+        name: "passkeys",
+        title: "Passkeys",
+        group: "Browser",
+        shortcut: 'w',
+        mount: passkeys::mount_to,
+    },
+    Example {
+        name: "cart",
+        title: "Cart",
+        group: "Browser",
+        shortcut: 'h',
+        mount: cart::mount_to,
+    },
+];
+
+pub fn find(name: &str) -> &'static Example {
+    EXAMPLES
+        .iter()
+        .find(|example| example.name == name)
+        .unwrap_or(&EXAMPLES[0])
+}
+
+/// The example named by the current URL hash.
+pub fn current() -> &'static Example {
+    let hash = window().location().hash().unwrap_or_default();
+    find(hash.trim_start_matches('#'))
+}
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let banner = create_element("div");
+    _ = banner.set_attribute(
+        "style",
+        &format!("position: relative; height: {BANNER_HEIGHT}px; overflow: hidden"),
+    );
+    let backdrop = create_element("div");
+    _ = backdrop.set_attribute(
+        "style",
+        "position: absolute; inset: -50% 0 0; \
+         background: linear-gradient(160deg, #dea584, #f6e3d4 60%, #fff)",
+    );
+    let tagline = create_element("p");
+    _ = tagline.set_attribute("style", "position: relative; margin: 0; padding: 1em");
+    tagline.set_text_content(Some("Rust and WebAssembly, straight onto the DOM."));
+    banner.append_child(&backdrop).unwrap();
+    banner.append_child(&tagline).unwrap();
+    app.append(parent, &banner);
+    if !animate::reduced_motion() {
+        scroll::link(&mut app, &backdrop, 0.0, BANNER_HEIGHT, PARALLAX);
+    }
+
+    let header = create_element("header");
+    _ = header.set_attribute(
+        "style",
+        "position: sticky; top: 0; z-index: 1; background: white; \
+         border-bottom: 1px solid #ddd",
+    );
+    let heading = create_element("h1");
+    _ = heading.set_attribute("style", "margin: 0; font-size: 28px; padding-block: 12px");
+    heading.set_text_content(Some("Rust + Wasm vanilla examples"));
+    header.append_child(&heading).unwrap();
+    app.append(parent, &header);
+    scroll::link(
+        &mut app,
+        &heading,
+        BANNER_HEIGHT,
+        BANNER_HEIGHT + HEADER_SHRINK,
+        SHRINK,
+    );
+
+    let nav = create_element("nav");
+    // one section per group, starting with the current example's open
+    let current_group = current().group;
+    let mut groups = Accordion::new(Mode::Multiple);
+    for group in GROUPS {
+        let links = create_element("p");
+        _ = links.set_attribute("style", "margin: 0 0 0.5em");
+        for example in EXAMPLES.iter().filter(|example| example.group == *group) {
+            let link = create_element("a");
+            _ = link.set_attribute("href", &format!("#{}", example.name));
+            link.set_text_content(Some(example.title));
+            _ = link.set_attribute("style", "margin-right: 1em");
+            links.append_child(&link).unwrap();
+        }
+        groups = groups.section(group, &links, *group == current_group);
+    }
+    groups.mount(&mut app, &nav);
+    install::mount_button(&mut app, &nav);
+    battery::mount_indicator(&mut app, &nav);
+    network::mount_badge(&mut app, &nav);
+    app.append(parent, &nav);
+    print::on_print(
+        &mut app,
+        {
+            let nav = nav.clone();
+            let banner = banner.clone();
+            move || {
+                _ = nav.set_attribute("hidden", "");
+                _ = banner.set_attribute("hidden", "");
+            }
+        },
+        {
+            let nav = nav.clone();
+            move || {
+                _ = nav.remove_attribute("hidden");
+                _ = banner.remove_attribute("hidden");
+            }
+        },
+    );
+
+    title::mount_breadcrumb(&mut app, parent);
+    let main = create_element("main");
+    app.append(parent, &main);
+
+    // the example that's currently showing, so it can be unmounted on navigation, and
+    // the app holding its part of the title
+    let mounted = Rc::new(RefCell::new(None::<(&'static str, App, App)>));
+    // `force` remounts the current example even if it's already showing
+    let show = Rc::new({
+        let mounted = mounted.clone();
+        let nav = nav.clone();
+        move |force: bool| {
+            let example = current();
+            let showing = mounted.borrow().as_ref().map(|(name, ..)| *name);
+            if showing == Some(example.name) && !force {
+                return;
+            }
+            if let Some(from) = showing.filter(|from| *from != example.name) {
+                analytics::route(from, example.name);
+            }
+            let swap = {
+                let mounted = mounted.clone();
+                let main = main.clone();
+                let nav = nav.clone();
+                move || {
+                    let mut mounted = mounted.borrow_mut();
+                    // unmount the old example before mounting the new one
+                    *mounted = None;
+                    // pushed first, so whatever the example pushes goes under it
+                    let mut route = App::new();
+                    title::push(&mut route, example.title).set_href(&format!("#{}", example.name));
+                    *mounted = Some((example.name, (example.mount)(&main), route));
+                    mark_current(&nav, example.name);
+                }
+            };
+            // crossfade from one example to the next, but don't hold up the first one
+            if showing.is_some() {
+                transition::run(swap);
+            } else {
+                swap();
+            }
+        }
+    });
+    show(false);
+    app.listen(&window(), "hashchange", {
+        let show = show.clone();
+        move |_: Event| show(false)
+    });
+    // flags are read when an example mounts, so flipping one means starting it over
+    flags::subscribe(&mut app, move || show(true));
+    for example in EXAMPLES {
+        hotkeys::register(
+            &mut app,
+            Scope::Page,
+            &format!("g {}", example.shortcut),
+            &format!("Go to {}", example.title),
+            move || _ = window().location().set_hash(example.name),
+        );
+    }
+    app.on_cleanup(move || drop(mounted.take()));
+
+    app
+}
+
+fn mark_current(nav: &Element, name: &str) {
+    let Ok(links) = nav.query_selector_all("a[href^='#']") else {
+        return;
+    };
+    for i in 0..links.length() {
+        let Some(link) = links
+            .item(i)
+            .and_then(|link| link.dyn_into::<Element>().ok())
+        else {
+            continue;
+        };
+        if link.get_attribute("href").as_deref() == Some(&format!("#{name}")) {
+            _ = link.set_attribute("aria-current", "page");
+        } else {
+            _ = link.remove_attribute("aria-current");
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! A/B experiments: every visitor sees one variant of each experiment, always the same
+//! one.
+//!
+//! A visitor is a random id kept in `localStorage`, and the variant is a hash of that
+//! id and the experiment's name, so no assignment needs storing and each experiment
+//! splits visitors independently of the others. Exposures (a visitor being shown
+//! their variant) and interaction metrics are counted per variant, in `localStorage`
+//! for the stats page and through [`analytics`] for anything collecting across
+//! visitors.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    rc::Rc,
+};
+
+use serde::{Deserialize, Serialize};
+use web_sys::{Element, Event};
+
+use crate::{
+    analytics::{self, AnalyticsEvent},
+    app::App,
+    dom::create_element,
+    storage, versions,
+};
+
+const VISITOR_KEY: &str = "visitor-id";
+const RESULTS_KEY: &str = "experiments";
+
+pub struct Experiment {
+    pub name: &'static str,
+    pub variants: &'static [&'static str],
+}
+
+/// Every experiment there is, for the stats page.
+pub const EXPERIMENTS: &[&Experiment] = &[&versions::EXPERIMENT];
+
+/// How one variant has done.
+#[derive(Default, Serialize, Deserialize)]
+struct Counts {
+    exposures: u32,
+    metrics: BTreeMap<String, u32>,
+}
+
+/// Experiment name → variant name → counts.
+type Results = BTreeMap<String, BTreeMap<String, Counts>>;
+
+/// Which variant of `experiment` this visitor gets, recorded as an exposure.
+pub fn expose(experiment: &Experiment) -> usize {
+    let variant = bucket(experiment.name, &visitor_id(), experiment.variants.len());
+    update(experiment, variant, |counts| counts.exposures += 1);
+    analytics::track(AnalyticsEvent::Experiment {
+        experiment: experiment.name.to_string(),
+        variant: experiment.variants[variant].to_string(),
+        metric: None,
+    });
+    variant
+}
+
+/// Counts `metric` (a click, say) for a visitor shown `variant`.
+pub fn record(experiment: &Experiment, variant: usize, metric: &str) {
+    update(experiment, variant, |counts| {
+        *counts.metrics.entry(metric.to_string()).or_default() += 1
+    });
+    analytics::track(AnalyticsEvent::Experiment {
+        experiment: experiment.name.to_string(),
+        variant: experiment.variants[variant].to_string(),
+        metric: Some(metric.to_string()),
+    });
+}
+
+/// Which of `variants` buckets a visitor falls in: FNV-1a over the experiment and
+/// visitor, which spreads even similar ids evenly.
+fn bucket(experiment: &str, visitor: &str, variants: usize) -> usize {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in experiment.bytes().chain([b':']).chain(visitor.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    (hash % variants as u64) as usize
+}
+
+/// This visitor's id, made up on their first visit.
+fn visitor_id() -> String {
+    // the id is stored bare rather than as JSON
+    let storage = storage::local_storage();
+    if let Some(id) = storage
+        .as_ref()
+        .and_then(|storage| storage.get_item(VISITOR_KEY).ok().flatten())
+    {
+        return id;
+    }
+    let id = format!(
+        "{:08x}{:08x}",
+        (js_sys::Math::random() * u32::MAX as f64) as u32,
+        (js_sys::Math::random() * u32::MAX as f64) as u32
+    );
+    if let Some(storage) = storage {
+        _ = storage.set_item(VISITOR_KEY, &id);
+    }
+    id
+}
+
+fn load() -> Results {
+    storage::load(RESULTS_KEY).unwrap_or_default()
+}
+
+fn update(experiment: &Experiment, variant: usize, f: impl FnOnce(&mut Counts)) {
+    let mut results = load();
+    f(results
+        .entry(experiment.name.to_string())
+        .or_default()
+        .entry(experiment.variants[variant].to_string())
+        .or_default());
+    _ = storage::save(RESULTS_KEY, &results);
+}
+
+/// The stats page: each experiment's variants side by side.
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let section = create_element("section");
+    let visitor = create_element("p");
+    let tables = create_element("div");
+    let controls = create_element("p");
+    let new_visitor = create_element("button");
+    new_visitor.set_text_content(Some("Be a new visitor"));
+    let clear = create_element("button");
+    clear.set_text_content(Some("Clear the results"));
+    controls.append_child(&new_visitor).unwrap();
+    _ = controls.append_with_str_1(" ");
+    controls.append_child(&clear).unwrap();
+    for el in [&visitor, &tables, &controls] {
+        section.append_child(el).unwrap();
+    }
+    app.append(parent, &section);
+
+    let render = move || {
+        let id = visitor_id();
+        visitor.set_text_content(Some(&format!(
+            "You're visitor {id}. These results are the ones recorded in this browser."
+        )));
+        tables.set_inner_html("");
+        let results = load();
+        for experiment in EXPERIMENTS {
+            let yours = bucket(experiment.name, &id, experiment.variants.len());
+            tables
+                .append_child(&table(experiment, results.get(experiment.name), yours))
+                .unwrap();
+        }
+    };
+    render();
+    let render = Rc::new(render);
+    app.listen(&new_visitor, "click", {
+        let render = render.clone();
+        move |_: Event| {
+            storage::remove(VISITOR_KEY);
+            render();
+        }
+    });
+    app.listen(&clear, "click", move |_: Event| {
+        storage::remove(RESULTS_KEY);
+        render();
+    });
+
+    app
+}
+
+fn table(
+    experiment: &Experiment,
+    results: Option<&BTreeMap<String, Counts>>,
+    yours: usize,
+) -> Element {
+    let table = create_element("table");
+    let caption = create_element("caption");
+    caption.set_text_content(Some(&format!("Experiment: {}", experiment.name)));
+    table.append_child(&caption).unwrap();
+
+    let empty = BTreeMap::new();
+    let results = results.unwrap_or(&empty);
+    let metrics = results
+        .values()
+        .flat_map(|counts| counts.metrics.keys())
+        .collect::<BTreeSet<_>>();
+    let header = create_element("tr");
+    let mut columns = vec!["Variant".to_string(), "Exposures".to_string()];
+    columns.extend(
+        metrics
+            .iter()
+            .map(|metric| format!("{metric} (per exposure)")),
+    );
+    for column in columns {
+        let th = create_element("th");
+        th.set_text_content(Some(&column));
+        header.append_child(&th).unwrap();
+    }
+    table.append_child(&header).unwrap();
+
+    for (i, variant) in experiment.variants.iter().enumerate() {
+        let counts = results.get(*variant);
+        let exposures = counts.map_or(0, |counts| counts.exposures);
+        let mut cells = vec![
+            if i == yours {
+                format!("{variant} (yours)")
+            } else {
+                variant.to_string()
+            },
+            exposures.to_string(),
+        ];
+        for metric in &metrics {
+            let count = counts
+                .and_then(|counts| counts.metrics.get(*metric))
+                .copied()
+                .unwrap_or(0);
+            cells.push(match exposures {
+                0 => count.to_string(),
+                _ => format!("{count} ({:.2})", count as f64 / exposures as f64),
+            });
+        }
+        let row = create_element("tr");
+        for cell in cells {
+            let td = create_element("td");
+            td.set_text_content(Some(&cell));
+            row.append_child(&td).unwrap();
+        }
+        table.append_child(&row).unwrap();
+    }
+    table
+}
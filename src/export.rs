@@ -0,0 +1,156 @@
+//! Pictures of what's on the page, as PNGs.
+//!
+//! A canvas can already encode itself, through a callback; [`canvas_to_blob`] makes
+//! that a future. Anything else goes through [`rasterize`]: a copy of the element
+//! (with every style it's computed inlined, since stylesheets don't reach inside an
+//! image) goes into an SVG `<foreignObject>`, the SVG is loaded as an image, and the
+//! image is drawn onto a canvas. An `<svg>` is an image already, so its copy only
+//! needs a size in pixels, and the color its `currentColor` stands for.
+//!
+//! What's drawn is only the element's own markup: no external images or fonts, which
+//! an SVG loaded as an image isn't allowed to fetch.
+
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, CanvasRenderingContext2d, Element, HtmlCanvasElement, HtmlImageElement};
+
+use crate::{
+    dom::{create_element, window},
+    downloads,
+    svg::SVG_NS,
+};
+
+/// Encodes what's on `canvas` as `mime` (`"image/png"`, say).
+pub async fn canvas_to_blob(canvas: &HtmlCanvasElement, mime: &str) -> Result<Blob, String> {
+    let mut started = Ok(());
+    let encoded = js_sys::Promise::new(&mut |resolve, _| {
+        started = canvas.to_blob_with_type(&resolve, mime);
+    });
+    // a canvas with anything cross-origin drawn on it refuses to be read
+    started.map_err(describe)?;
+    JsFuture::from(encoded)
+        .await
+        .map_err(describe)?
+        .dyn_into::<Blob>()
+        .map_err(|_| "the canvas is empty".to_string())
+}
+
+/// Draws `el`, all of it (including what it's scrolled away), onto a new canvas at the
+/// screen's pixel density.
+pub async fn rasterize(el: &Element) -> Result<HtmlCanvasElement, String> {
+    if el.namespace_uri().as_deref() == Some(SVG_NS) {
+        return rasterize_svg(el).await;
+    }
+    let (width, height) = (el.scroll_width(), el.scroll_height());
+    if width == 0 || height == 0 {
+        return Err("there's nothing to draw".to_string());
+    }
+    let copy = el
+        .clone_node_with_deep(true)
+        .map_err(describe)?
+        .unchecked_into::<Element>();
+    inline_styles(el, &copy);
+    // the copy isn't scrolled, so it has to be tall enough to show everything
+    if let Some(copy) = copy.dyn_ref::<web_sys::HtmlElement>() {
+        let style = copy.style();
+        _ = style.set_property("max-height", "none");
+        _ = style.set_property("height", &format!("{height}px"));
+        _ = style.set_property("overflow", "visible");
+    }
+    let markup = web_sys::XmlSerializer::new()
+        .and_then(|serializer| serializer.serialize_to_string(&copy))
+        .map_err(describe)?;
+    let svg = format!(
+        "<svg xmlns=\"{SVG_NS}\" width=\"{width}\" height=\"{height}\">\
+         <foreignObject width=\"100%\" height=\"100%\">{markup}</foreignObject></svg>"
+    );
+    draw(&svg, width as f64, height as f64).await
+}
+
+/// [`rasterize`], for an `<svg>` (which scrolls nothing away): at the size it's shown.
+async fn rasterize_svg(el: &Element) -> Result<HtmlCanvasElement, String> {
+    let rect = el.get_bounding_client_rect();
+    let (width, height) = (rect.width(), rect.height());
+    if width == 0.0 || height == 0.0 {
+        return Err("there's nothing to draw".to_string());
+    }
+    let copy = el
+        .clone_node_with_deep(true)
+        .map_err(describe)?
+        .unchecked_into::<Element>();
+    // its size on the page may be in ems or percentages, which mean nothing in an image
+    _ = copy.remove_attribute("style");
+    _ = copy.set_attribute("width", &width.to_string());
+    _ = copy.set_attribute("height", &height.to_string());
+    if let Ok(Some(computed)) = window().get_computed_style(el) {
+        let color = computed.get_property_value("color").unwrap_or_default();
+        _ = copy.set_attribute("color", &color);
+    }
+    let svg = web_sys::XmlSerializer::new()
+        .and_then(|serializer| serializer.serialize_to_string(&copy))
+        .map_err(describe)?;
+    draw(&svg, width, height).await
+}
+
+/// Loads the SVG markup `svg` as an image, and draws it onto a new canvas.
+async fn draw(svg: &str, width: f64, height: f64) -> Result<HtmlCanvasElement, String> {
+    let image = create_element("img").unchecked_into::<HtmlImageElement>();
+    image.set_src(&format!(
+        "data:image/svg+xml;charset=utf-8,{}",
+        js_sys::encode_uri_component(svg)
+    ));
+    JsFuture::from(image.decode())
+        .await
+        .map_err(|_| "the browser couldn't draw it".to_string())?;
+
+    let scale = window().device_pixel_ratio();
+    let canvas = create_element("canvas").unchecked_into::<HtmlCanvasElement>();
+    canvas.set_width((width * scale) as u32);
+    canvas.set_height((height * scale) as u32);
+    let ctx = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .and_then(|ctx| ctx.dyn_into::<CanvasRenderingContext2d>().ok())
+        .ok_or("there's no 2D canvas")?;
+    _ = ctx.scale(scale, scale);
+    ctx.draw_image_with_html_image_element(&image, 0.0, 0.0)
+        .map_err(describe)?;
+    Ok(canvas)
+}
+
+/// Downloads a picture of `el` as `filename`.
+pub async fn save_png(el: &Element, filename: &str) -> Result<(), String> {
+    let canvas = rasterize(el).await?;
+    let blob = canvas_to_blob(&canvas, "image/png").await?;
+    downloads::download_blob(filename, &blob);
+    Ok(())
+}
+
+/// Copies each of `from`'s computed styles onto `to`, its deep copy, and so on down.
+fn inline_styles(from: &Element, to: &Element) {
+    if let (Ok(Some(computed)), Some(to)) = (
+        window().get_computed_style(from),
+        to.dyn_ref::<web_sys::HtmlElement>(),
+    ) {
+        let style = to.style();
+        for i in 0..computed.length() {
+            let name = computed.item(i);
+            let value = computed.get_property_value(&name).unwrap_or_default();
+            _ = style.set_property(&name, &value);
+        }
+    }
+    let (from, to) = (from.children(), to.children());
+    for i in 0..from.length().min(to.length()) {
+        if let (Some(from), Some(to)) = (from.item(i), to.item(i)) {
+            inline_styles(&from, &to);
+        }
+    }
+}
+
+fn describe(error: JsValue) -> String {
+    match error.dyn_ref::<js_sys::Error>() {
+        Some(error) => String::from(error.message()),
+        None => format!("{error:?}"),
+    }
+}
@@ -0,0 +1,270 @@
+//! The fetch layer: reads (`get_text`) and writes (`mutate`).
+//!
+//! Writes made while offline aren't lost: they're queued and sent, in order, as soon
+//! as [`network`] reports that we're back online. The queue is kept in IndexedDB too,
+//! so a tab closed while offline sends its writes the next time the site is opened.
+//!
+//! A write the server refuses is dropped, since sending it again won't change its
+//! mind; a conflict (someone else changed the thing first) says so in its message.
+
+use std::{cell::RefCell, collections::VecDeque};
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{RequestInit, Response};
+
+use crate::{
+    app::App,
+    dom::{log, spawn_local, window},
+    idb, network, toast,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Mutation {
+    pub method: String,
+    pub url: String,
+    /// Sent as `application/json`.
+    pub body: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Sent {
+    Done,
+    /// We're offline; it'll go out when we're back.
+    Queued,
+}
+
+/// A mutation waiting to be sent, under the key it's kept at.
+#[derive(Serialize, Deserialize)]
+struct Queued {
+    id: u64,
+    mutation: Mutation,
+}
+
+impl Queued {
+    /// Padded, since IndexedDB sorts string keys as strings.
+    fn key(&self) -> String {
+        format!("{:016}", self.id)
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    pending: VecDeque<Queued>,
+    flushing: bool,
+    /// The id of the last mutation queued, in this visit or before.
+    last_id: u64,
+    outbox: Option<idb::Store>,
+}
+
+thread_local! {
+    static QUEUE: RefCell<Queue> = RefCell::new(Queue::default());
+}
+
+/// Sends the queue kept from earlier visits, and flushes it again whenever the
+/// connection comes back.
+pub fn install(app: &mut App) {
+    network::subscribe(app, |status| {
+        if status.online {
+            spawn_local(flush());
+        }
+    });
+    spawn_local(async {
+        let outbox = match idb::Store::open("outbox", "mutations").await {
+            Ok(outbox) => outbox,
+            Err(error) => {
+                log!("[fetch] the offline queue won't outlast the tab: {error}");
+                return;
+            }
+        };
+        let mut pending: VecDeque<Queued> = outbox
+            .values()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|json| serde_json::from_str(json).ok())
+            .collect();
+        let kept = pending.len();
+        let queued_meanwhile = QUEUE.with(|queue| {
+            let mut queue = queue.borrow_mut();
+            // anything kept is older than what was queued since the page loaded
+            pending.append(&mut queue.pending);
+            queue.last_id = pending
+                .iter()
+                .map(|queued| queued.id)
+                .fold(queue.last_id, u64::max);
+            queue.pending = pending;
+            queue.outbox = Some(outbox.clone());
+            queue
+                .pending
+                .iter()
+                .skip(kept)
+                .map(|queued| (queued.key(), serde_json::to_string(queued)))
+                .collect::<Vec<_>>()
+        });
+        for (key, json) in queued_meanwhile {
+            if let Ok(json) = json {
+                _ = outbox.put(&key, &json).await;
+            }
+        }
+        if network::status().online {
+            flush().await;
+        }
+    });
+}
+
+pub async fn get_text(url: &str) -> Result<String, String> {
+    let response = fetch(url, &RequestInit::new())
+        .await
+        .map_err(|failure| failure.message(url))?;
+    let text = response
+        .text()
+        .map_err(|_| "couldn't read the response".to_string())?;
+    JsFuture::from(text)
+        .await
+        .ok()
+        .and_then(|text| text.as_string())
+        .ok_or_else(|| "couldn't read the response".to_string())
+}
+
+/// Sends `mutation` now, or queues it if we're offline (or the network fails on the way).
+pub async fn mutate(mutation: Mutation) -> Result<Sent, String> {
+    let queued = QUEUE.with(|queue| !queue.borrow().pending.is_empty());
+    // don't overtake mutations that are already waiting
+    if !network::status().online || queued {
+        enqueue(mutation);
+        return Ok(Sent::Queued);
+    }
+    match send(&mutation).await {
+        Ok(()) => Ok(Sent::Done),
+        Err(Failure::Network) => {
+            enqueue(mutation);
+            Ok(Sent::Queued)
+        }
+        Err(Failure::Conflict(error) | Failure::Rejected(error)) => Err(error),
+    }
+}
+
+fn enqueue(mutation: Mutation) {
+    let (queued, outbox) = QUEUE.with(|queue| {
+        let mut queue = queue.borrow_mut();
+        // a timestamp, so ids keep going up from one visit to the next
+        let id = (js_sys::Date::now() as u64).max(queue.last_id + 1);
+        queue.last_id = id;
+        let queued = Queued { id, mutation };
+        let json = serde_json::to_string(&queued);
+        let key = queued.key();
+        queue.pending.push_back(queued);
+        (json.map(|json| (key, json)), queue.outbox.clone())
+    });
+    if let (Ok((key, json)), Some(outbox)) = (queued, outbox) {
+        spawn_local(async move {
+            if let Err(error) = outbox.put(&key, &json).await {
+                log!("[fetch] couldn't keep a queued change: {error}");
+            }
+        });
+    }
+}
+
+/// Takes mutation `id` off the queue, here and in the outbox. (By id rather than off
+/// the front, since loading the outbox can put older ones in front while it's sent.)
+async fn dequeue(id: u64) {
+    let (key, outbox) = QUEUE.with(|queue| {
+        let mut queue = queue.borrow_mut();
+        let index = queue.pending.iter().position(|queued| queued.id == id);
+        let key = index
+            .and_then(|index| queue.pending.remove(index))
+            .map(|queued| queued.key());
+        (key, queue.outbox.clone())
+    });
+    if let (Some(key), Some(outbox)) = (key, outbox) {
+        _ = outbox.delete(&key).await;
+    }
+}
+
+/// Sends the queued mutations in order, stopping at the first network failure.
+async fn flush() {
+    let already_flushing =
+        QUEUE.with(|queue| std::mem::replace(&mut queue.borrow_mut().flushing, true));
+    if already_flushing {
+        return;
+    }
+    let mut sent = 0;
+    while let Some((id, mutation)) = QUEUE.with(|queue| {
+        let queue = queue.borrow();
+        let front = queue.pending.front();
+        front.map(|queued| (queued.id, queued.mutation.clone()))
+    }) {
+        match send(&mutation).await {
+            Err(Failure::Network) => break,
+            Err(Failure::Conflict(error)) => {
+                toast::error(&format!(
+                    "{} {} wasn't applied: {error}",
+                    mutation.method, mutation.url
+                ));
+            }
+            Err(Failure::Rejected(error)) => {
+                // retrying won't help, so drop it, but say so
+                toast::error(&format!(
+                    "{} {} failed: {error}",
+                    mutation.method, mutation.url
+                ));
+            }
+            Ok(()) => sent += 1,
+        }
+        dequeue(id).await;
+    }
+    QUEUE.with(|queue| queue.borrow_mut().flushing = false);
+    if sent > 0 {
+        toast::info(&format!("Sent {sent} change(s) made while offline"));
+    }
+}
+
+enum Failure {
+    /// Never got a response: worth retrying later.
+    Network,
+    /// The server has a newer version than the one this change was made to.
+    Conflict(String),
+    /// The server said no.
+    Rejected(String),
+}
+
+impl Failure {
+    fn message(self, url: &str) -> String {
+        match self {
+            Failure::Network => format!("couldn't reach {url}"),
+            Failure::Conflict(message) | Failure::Rejected(message) => message,
+        }
+    }
+}
+
+async fn send(mutation: &Mutation) -> Result<(), Failure> {
+    let headers = js_sys::Object::new();
+    _ = js_sys::Reflect::set(&headers, &"Content-Type".into(), &"application/json".into());
+    let mut init = RequestInit::new();
+    init.method(&mutation.method)
+        .headers(&headers)
+        .body(Some(&JsValue::from_str(&mutation.body)));
+    fetch(&mutation.url, &init).await.map(drop)
+}
+
+async fn fetch(url: &str, init: &RequestInit) -> Result<Response, Failure> {
+    // fetch only rejects when there's no response at all
+    let response = JsFuture::from(window().fetch_with_str_and_init(url, init))
+        .await
+        .map_err(|_| Failure::Network)?
+        .unchecked_into::<Response>();
+    if response.ok() {
+        Ok(response)
+    } else if matches!(response.status(), 409 | 412) {
+        Err(Failure::Conflict(
+            "it conflicts with a change made elsewhere".to_string(),
+        ))
+    } else {
+        Err(Failure::Rejected(format!(
+            "the server answered {}",
+            response.status()
+        )))
+    }
+}
@@ -0,0 +1,92 @@
+//! Image filters over RGBA pixel buffers, as `ImageData` hands them out.
+//!
+//! Each works in place on `width × height` pixels of four bytes each and leaves alpha
+//! alone. They don't touch the DOM, so they run the same on the main thread or in a
+//! worker.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Filter {
+    Grayscale,
+    /// Blur radius in pixels.
+    Blur(u32),
+    /// Pixels at least this bright turn white, the rest black.
+    Threshold(u8),
+}
+
+impl Filter {
+    pub fn apply(self, pixels: &mut [u8], width: usize, height: usize) {
+        match self {
+            Filter::Grayscale => grayscale(pixels),
+            Filter::Blur(radius) => blur(pixels, width, height, radius as usize),
+            Filter::Threshold(level) => threshold(pixels, level),
+        }
+    }
+}
+
+/// Perceived brightness, with the Rec. 601 weights.
+pub fn luma(pixel: &[u8]) -> u8 {
+    let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(u32::from);
+    ((r * 299 + g * 587 + b * 114) / 1000) as u8
+}
+
+pub fn grayscale(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let y = luma(pixel);
+        pixel[..3].fill(y);
+    }
+}
+
+pub fn threshold(pixels: &mut [u8], level: u8) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let y = if luma(pixel) >= level { 255 } else { 0 };
+        pixel[..3].fill(y);
+    }
+}
+
+/// Three box blurs in a row, which comes out close to a gaussian one.
+pub fn blur(pixels: &mut [u8], width: usize, height: usize, radius: usize) {
+    if radius == 0 || width == 0 || height == 0 {
+        return;
+    }
+    let mut scratch = vec![0; pixels.len()];
+    for _ in 0..3 {
+        // rows into the scratch buffer, then columns back again
+        box_blur(pixels, &mut scratch, height, width, radius, 4, width * 4);
+        box_blur(&scratch, pixels, width, height, radius, width * 4, 4);
+    }
+}
+
+/// Averages each pixel with the `radius` either side of it along one direction.
+///
+/// `lines` runs of `len` pixels each, `step` bytes between the pixels of a run and
+/// `stride` bytes between runs; pixels past the ends count as the end ones.
+fn box_blur(
+    from: &[u8],
+    to: &mut [u8],
+    lines: usize,
+    len: usize,
+    radius: usize,
+    step: usize,
+    stride: usize,
+) {
+    let window = (2 * radius + 1) as u32;
+    for line in 0..lines {
+        let at = |i: usize| line * stride + i.min(len - 1) * step;
+        for channel in 0..3 {
+            // a running sum over the window, so any radius costs the same
+            let mut sum = from[at(0) + channel] as u32 * (radius as u32 + 1);
+            for i in 1..=radius {
+                sum += from[at(i) + channel] as u32;
+            }
+            for i in 0..len {
+                to[at(i) + channel] = (sum / window) as u8;
+                let leaving = from[at(i.saturating_sub(radius)) + channel] as u32;
+                let entering = from[at(i + radius + 1) + channel] as u32;
+                sum = sum + entering - leaving;
+            }
+        }
+        for i in 0..len {
+            to[at(i) + 3] = from[at(i) + 3];
+        }
+    }
+}
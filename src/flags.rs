@@ -0,0 +1,208 @@
+//! Runtime feature flags.
+//!
+//! Every flag has a default, which can be overridden per browser (saved in
+//! `localStorage` from the flags panel) or per page load with a query parameter:
+//! `?flags=wheel-adjust,-context-menu` turns one flag on and one off.
+//! The panel is hidden until you press Alt+Shift+F.
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use wasm_bindgen::JsCast;
+use web_sys::{Event, HtmlInputElement, KeyboardEvent, UrlSearchParams};
+
+use crate::{
+    app::App,
+    dom::{body, create_element, document, window},
+    storage,
+};
+
+const STORAGE_KEY: &str = "flags";
+
+pub struct FlagDef {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub default: bool,
+}
+
+pub const FLAGS: &[FlagDef] = &[
+    FlagDef {
+        name: "press-repeat",
+        description: "Holding a counter button keeps counting",
+        default: true,
+    },
+    FlagDef {
+        name: "wheel-adjust",
+        description: "Scrolling over the count changes it",
+        default: true,
+    },
+    FlagDef {
+        name: "context-menu",
+        description: "Custom right-click menu on the counter",
+        default: true,
+    },
+    FlagDef {
+        name: "session-replay",
+        description: "Toolbar to record and replay a session",
+        default: false,
+    },
+    FlagDef {
+        name: "devtools",
+        description: "State inspector for the reducer version of the counter",
+        default: false,
+    },
+    FlagDef {
+        name: "server-push",
+        description: "The reducer version hears the server over SSE, not a WebSocket",
+        default: false,
+    },
+    FlagDef {
+        name: "new-renderer",
+        description: "The virtual DOM version patches what changed, not the whole view",
+        default: true,
+    },
+    FlagDef {
+        name: "optimistic-updates",
+        description: "The server version shows a change before the server takes it",
+        default: true,
+    },
+    FlagDef {
+        name: "animations",
+        description: "Animations and view transitions (off skips to the end state)",
+        default: true,
+    },
+];
+
+#[derive(Default)]
+pub struct Flags {
+    /// Overrides saved from the panel.
+    stored: BTreeMap<String, bool>,
+    /// Overrides from the query string; these win, but are never saved.
+    query: BTreeMap<String, bool>,
+    listeners: Vec<(usize, Rc<dyn Fn()>)>,
+    next_listener: usize,
+}
+
+thread_local! {
+    static FLAGS_STORE: RefCell<Flags> = RefCell::new(Flags::load());
+}
+
+impl Flags {
+    fn load() -> Self {
+        let stored = storage::load(STORAGE_KEY).unwrap_or_default();
+
+        let search = window().location().search().unwrap_or_default();
+        let query = UrlSearchParams::new_with_str(&search)
+            .ok()
+            .and_then(|params| params.get("flags"))
+            .map(|list| parse_list(&list))
+            .unwrap_or_default();
+
+        Flags {
+            stored,
+            query,
+            ..Default::default()
+        }
+    }
+
+    fn get(&self, name: &str) -> bool {
+        self.query
+            .get(name)
+            .or_else(|| self.stored.get(name))
+            .copied()
+            .unwrap_or_else(|| FLAGS.iter().any(|flag| flag.name == name && flag.default))
+    }
+}
+
+/// `"a,-b"` → `{a: true, b: false}`
+fn parse_list(list: &str) -> BTreeMap<String, bool> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| match name.strip_prefix('-') {
+            Some(name) => (name.to_string(), false),
+            None => (name.to_string(), true),
+        })
+        .collect()
+}
+
+pub fn enabled(name: &str) -> bool {
+    FLAGS_STORE.with(|flags| flags.borrow().get(name))
+}
+
+/// Saves an override for `name` and notifies subscribers.
+pub fn set(name: &str, value: bool) {
+    let listeners = FLAGS_STORE.with(|flags| {
+        let mut flags = flags.borrow_mut();
+        flags.stored.insert(name.to_string(), value);
+        // an explicit choice in the panel beats whatever the URL said
+        flags.query.remove(name);
+        // without storage the choice still holds until the page goes
+        _ = storage::save(STORAGE_KEY, &flags.stored);
+        flags
+            .listeners
+            .iter()
+            .map(|(_, listener)| listener.clone())
+            .collect::<Vec<_>>()
+    });
+    for listener in listeners {
+        listener();
+    }
+}
+
+/// Calls `listener` whenever a flag changes, until the app unmounts.
+pub fn subscribe(app: &mut App, listener: impl Fn() + 'static) {
+    let id = FLAGS_STORE.with(|flags| {
+        let mut flags = flags.borrow_mut();
+        let id = flags.next_listener;
+        flags.next_listener += 1;
+        flags.listeners.push((id, Rc::new(listener)));
+        id
+    });
+    app.on_cleanup(move || {
+        FLAGS_STORE.with(|flags| {
+            flags
+                .borrow_mut()
+                .listeners
+                .retain(|(other, _)| *other != id)
+        })
+    });
+}
+
+/// Adds the (initially hidden) flags panel to the page.
+pub fn mount_panel(app: &mut App) {
+    let panel = create_element("fieldset");
+    _ = panel.set_attribute("hidden", "");
+    _ = panel.set_attribute(
+        "style",
+        "position: fixed; left: 16px; bottom: 16px; background: white; z-index: 1000",
+    );
+    let legend = create_element("legend");
+    legend.set_text_content(Some("Feature flags"));
+    panel.append_child(&legend).unwrap();
+
+    for flag in FLAGS {
+        let label = create_element("label");
+        _ = label.set_attribute("style", "display: block");
+        let checkbox = create_element("input").unchecked_into::<HtmlInputElement>();
+        checkbox.set_type("checkbox");
+        checkbox.set_checked(enabled(flag.name));
+        label.append_child(&checkbox).unwrap();
+        label
+            .append_child(&document().create_text_node(&format!(" {}", flag.description)))
+            .unwrap();
+        _ = label.set_attribute("title", flag.name);
+        panel.append_child(&label).unwrap();
+
+        app.listen(&checkbox.clone(), "change", move |_: Event| {
+            set(flag.name, checkbox.checked());
+        });
+    }
+    app.append(&body().unwrap(), &panel);
+
+    app.listen(&window(), "keydown", move |ev: KeyboardEvent| {
+        if ev.alt_key() && ev.shift_key() && ev.code() == "KeyF" {
+            ev.prevent_default();
+            _ = panel.toggle_attribute("hidden");
+        }
+    });
+}
@@ -0,0 +1,364 @@
+//! The formula language of the spreadsheet example: `=A1+B2*2`, `=SUM(A1:A5)/2`, ...
+//!
+//! Numbers, cell references, `+ - * /`, parentheses, unary minus, and `SUM`/`MIN`/`MAX`
+//! over ranges or lists. Parsing yields an [`Expr`], which knows which cells it reads
+//! (for dependency tracking) and can be evaluated against any cell lookup.
+
+use std::fmt;
+
+/// The most cells one call can read once its ranges are expanded, so a formula like
+/// `SUM(A1:Z65535)` is a parse error rather than 1.7 million references.
+const MAX_CALL_CELLS: usize = 10_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CellRef {
+    pub col: u8,
+    pub row: u16,
+}
+
+impl CellRef {
+    /// `"B3"` → column 1, row 2 (both zero-based).
+    pub fn parse(name: &str) -> Option<CellRef> {
+        let mut chars = name.chars();
+        let letter = chars.next()?.to_ascii_uppercase();
+        if !letter.is_ascii_uppercase() {
+            return None;
+        }
+        let row = chars.as_str().parse::<u16>().ok()?.checked_sub(1)?;
+        Some(CellRef {
+            col: letter as u8 - b'A',
+            row,
+        })
+    }
+}
+
+impl fmt::Display for CellRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", (b'A' + self.col) as char, self.row + 1)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellError {
+    /// The formula doesn't parse.
+    Parse,
+    /// A referenced cell holds text.
+    Value,
+    DivZero,
+    /// The cell depends on itself.
+    Cycle,
+}
+
+impl fmt::Display for CellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CellError::Parse => "#PARSE!",
+            CellError::Value => "#VALUE!",
+            CellError::DivZero => "#DIV/0!",
+            CellError::Cycle => "#CYCLE!",
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum Expr {
+    Number(f64),
+    Ref(CellRef),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, char, Box<Expr>),
+    /// `SUM`, `MIN` or `MAX` over its arguments, with ranges already expanded.
+    Call(String, Vec<Expr>),
+}
+
+impl Expr {
+    pub fn parse(source: &str) -> Result<Expr, CellError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.sum()?;
+        if parser.pos == parser.tokens.len() {
+            Ok(expr)
+        } else {
+            Err(CellError::Parse)
+        }
+    }
+
+    /// Every cell this expression reads.
+    pub fn refs(&self, out: &mut Vec<CellRef>) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Ref(cell) => out.push(*cell),
+            Expr::Neg(expr) => expr.refs(out),
+            Expr::Binary(left, _, right) => {
+                left.refs(out);
+                right.refs(out);
+            }
+            Expr::Call(_, args) => args.iter().for_each(|arg| arg.refs(out)),
+        }
+    }
+
+    pub fn eval(
+        &self,
+        lookup: &impl Fn(CellRef) -> Result<f64, CellError>,
+    ) -> Result<f64, CellError> {
+        Ok(match self {
+            Expr::Number(n) => *n,
+            Expr::Ref(cell) => lookup(*cell)?,
+            Expr::Neg(expr) => -expr.eval(lookup)?,
+            Expr::Binary(left, op, right) => {
+                let (left, right) = (left.eval(lookup)?, right.eval(lookup)?);
+                match op {
+                    '+' => left + right,
+                    '-' => left - right,
+                    '*' => left * right,
+                    _ if right == 0.0 => return Err(CellError::DivZero),
+                    _ => left / right,
+                }
+            }
+            Expr::Call(name, args) => {
+                let values = args
+                    .iter()
+                    .map(|arg| arg.eval(lookup))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match name.as_str() {
+                    "SUM" => values.iter().sum(),
+                    "MIN" => values.iter().copied().fold(f64::INFINITY, f64::min),
+                    "MAX" => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                    _ => return Err(CellError::Parse),
+                }
+            }
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Op(char),
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, CellError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                number.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Number(number.parse().map_err(|_| CellError::Parse)?));
+        } else if c.is_ascii_alphabetic() {
+            let mut name = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphanumeric()) {
+                name.push(c.to_ascii_uppercase());
+                chars.next();
+            }
+            tokens.push(Token::Name(name));
+        } else if "+-*/():,".contains(c) {
+            tokens.push(Token::Op(c));
+            chars.next();
+        } else {
+            return Err(CellError::Parse);
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, op: char) -> bool {
+        let matches = self.tokens.get(self.pos) == Some(&Token::Op(op));
+        if matches {
+            self.pos += 1;
+        }
+        matches
+    }
+
+    /// sum := product (('+' | '-') product)*
+    fn sum(&mut self) -> Result<Expr, CellError> {
+        let mut expr = self.product()?;
+        loop {
+            let op = if self.eat('+') {
+                '+'
+            } else if self.eat('-') {
+                '-'
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.product()?));
+        }
+    }
+
+    /// product := unary (('*' | '/') unary)*
+    fn product(&mut self) -> Result<Expr, CellError> {
+        let mut expr = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                '*'
+            } else if self.eat('/') {
+                '/'
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.unary()?));
+        }
+    }
+
+    /// unary := '-' unary | atom
+    fn unary(&mut self) -> Result<Expr, CellError> {
+        if self.eat('-') {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.atom()
+    }
+
+    /// atom := number | cell | name '(' args ')' | '(' sum ')'
+    fn atom(&mut self) -> Result<Expr, CellError> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Number(*n)),
+            Some(Token::Op('(')) => {
+                let expr = self.sum()?;
+                self.eat(')').then_some(expr).ok_or(CellError::Parse)
+            }
+            Some(Token::Name(name)) => {
+                let name = name.clone();
+                if self.eat('(') {
+                    let args = self.args()?;
+                    Ok(Expr::Call(name, args))
+                } else {
+                    CellRef::parse(&name).map(Expr::Ref).ok_or(CellError::Parse)
+                }
+            }
+            _ => Err(CellError::Parse),
+        }
+    }
+
+    /// args := (arg (',' arg)*)? ')', where an arg is a range `A1:B3` or an expression
+    fn args(&mut self) -> Result<Vec<Expr>, CellError> {
+        let mut args = Vec::new();
+        if self.eat(')') {
+            return Ok(args);
+        }
+        loop {
+            let arg = self.sum()?;
+            match arg {
+                Expr::Ref(from) if self.eat(':') => {
+                    let Expr::Ref(to) = self.atom()? else {
+                        return Err(CellError::Parse);
+                    };
+                    let cols = from.col.abs_diff(to.col) as usize + 1;
+                    let rows = from.row.abs_diff(to.row) as usize + 1;
+                    if args.len() + cols * rows > MAX_CALL_CELLS {
+                        return Err(CellError::Parse);
+                    }
+                    for col in from.col.min(to.col)..=from.col.max(to.col) {
+                        for row in from.row.min(to.row)..=from.row.max(to.row) {
+                            args.push(Expr::Ref(CellRef { col, row }));
+                        }
+                    }
+                }
+                _ if args.len() == MAX_CALL_CELLS => return Err(CellError::Parse),
+                arg => args.push(arg),
+            }
+            if self.eat(')') {
+                return Ok(args);
+            }
+            if !self.eat(',') {
+                return Err(CellError::Parse);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(name: &str) -> CellRef {
+        CellRef::parse(name).unwrap()
+    }
+
+    /// Evaluates `source` with every cell holding its row number.
+    fn eval(source: &str) -> Result<f64, CellError> {
+        Expr::parse(source)?.eval(&|cell: CellRef| Ok(f64::from(cell.row) + 1.0))
+    }
+
+    #[test]
+    fn names_cells() {
+        assert_eq!(cell("B3"), CellRef { col: 1, row: 2 });
+        assert_eq!(cell("a1"), CellRef { col: 0, row: 0 });
+        assert_eq!(cell("H20").to_string(), "H20");
+        for bad in ["", "A", "A0", "1A", "A-1", "A65537", "AA1"] {
+            assert_eq!(CellRef::parse(bad), None, "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn follows_precedence() {
+        assert_eq!(eval("1 + 2 * 3"), Ok(7.0));
+        assert_eq!(eval("(1 + 2) * 3"), Ok(9.0));
+        assert_eq!(eval("8 - 4 - 2"), Ok(2.0));
+        assert_eq!(eval("8 / 4 / 2"), Ok(1.0));
+        assert_eq!(eval("-2 * -3"), Ok(6.0));
+        assert_eq!(eval("--1.5"), Ok(1.5));
+        assert_eq!(eval("A2 * b3 + 1"), Ok(7.0));
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert_eq!(eval("1 / (A1 - 1)"), Err(CellError::DivZero));
+        for bad in [
+            "", "1 +", "(1", "1)", "1 2", "A1:A2", "FOO(1)", "SUM(1,)", "1 $ 2", "1..2",
+        ] {
+            assert_eq!(eval(bad), Err(CellError::Parse), "{bad:?}");
+        }
+        let failing = Expr::parse("1 + A1").unwrap();
+        assert_eq!(
+            failing.eval(&|_| Err(CellError::Value)),
+            Err(CellError::Value)
+        );
+    }
+
+    #[test]
+    fn expands_ranges_in_calls() {
+        let mut refs = Vec::new();
+        Expr::parse("SUM(B2:A1, C1) + A1").unwrap().refs(&mut refs);
+        let names = refs.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(names, ["A1", "A2", "B1", "B2", "C1", "A1"]);
+
+        assert_eq!(eval("SUM(A1:A4)"), Ok(10.0));
+        assert_eq!(eval("SUM(A1:B2) / 2"), Ok(3.0));
+        assert_eq!(eval("MIN(A3:A5, 10)"), Ok(3.0));
+        assert_eq!(eval("MAX(A3:A5, 10)"), Ok(10.0));
+        assert_eq!(eval("SUM()"), Ok(0.0));
+    }
+
+    #[test]
+    fn caps_the_cells_a_call_reads() {
+        // ten columns of a thousand rows is exactly the cap, one more row is over it
+        assert!(Expr::parse("SUM(A1:J1000)").is_ok());
+        assert_eq!(Expr::parse("SUM(A1:J1001)").err(), Some(CellError::Parse));
+        assert_eq!(Expr::parse("SUM(A1:Z65535)").err(), Some(CellError::Parse));
+        assert_eq!(
+            Expr::parse("SUM(A1:J1000, 1)").err(),
+            Some(CellError::Parse)
+        );
+        assert_eq!(
+            Expr::parse("SUM(A1:J500, A1:J501)").err(),
+            Some(CellError::Parse)
+        );
+    }
+}
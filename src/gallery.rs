@@ -0,0 +1,232 @@
+//! Image gallery: drop, paste or pick image files to see thumbnails.
+//!
+//! Each file is shown through an object URL, which pins the file's data in memory
+//! until it's revoked, so removing a thumbnail (or unmounting the example) revokes
+//! its URL. Thumbnails only get a `src` once an `IntersectionObserver` sees them
+//! scroll into view, so dropping a few hundred photos doesn't decode them all at once.
+//! Names too long for their thumbnail are cut from the middle, keeping the extension.
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{
+    DragEvent, Element, Event, File, FileList, HtmlInputElement, IntersectionObserver,
+    IntersectionObserverEntry, IntersectionObserverInit, MouseEvent, Url,
+};
+
+use crate::{
+    app::App,
+    clipboard::ClipboardEvent,
+    dom::{create_element, document},
+    ellipsis::MiddleEllipsis,
+    hotkeys::is_editable,
+};
+
+/// Start loading thumbnails a little before they scroll into view.
+const ROOT_MARGIN: &str = "200px";
+
+/// Object URLs that haven't been revoked yet, by thumbnail id.
+type Urls = Rc<RefCell<BTreeMap<u32, String>>>;
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let section = create_element("section");
+    let zone = create_element("label");
+    _ = zone.set_attribute(
+        "style",
+        "display: block; padding: 2em; border: 2px dashed #999; border-radius: 8px; \
+         text-align: center; cursor: pointer",
+    );
+    zone.set_text_content(Some("Drop or paste images here, or click to choose some"));
+    let picker = create_element("input").unchecked_into::<HtmlInputElement>();
+    picker.set_type("file");
+    picker.set_accept("image/*");
+    picker.set_multiple(true);
+    _ = picker.set_attribute("hidden", "");
+    zone.append_child(&picker).unwrap();
+    let grid = create_element("ul");
+    _ = grid.set_attribute(
+        "style",
+        "display: grid; grid-template-columns: repeat(auto-fill, minmax(120px, 1fr)); \
+         gap: 8px; padding: 0; list-style: none",
+    );
+    section.append_child(&zone).unwrap();
+    section.append_child(&grid).unwrap();
+    app.append(parent, &section);
+
+    let urls: Urls = Default::default();
+    let loader = lazy_loader();
+    let ellipsis = Rc::new(MiddleEllipsis::new());
+    let next_id = Rc::new(RefCell::new(0));
+
+    let add_files = Rc::new({
+        let grid = grid.clone();
+        let urls = urls.clone();
+        let observer = loader.observer.clone();
+        let ellipsis = ellipsis.clone();
+        move |files: Vec<File>| {
+            for file in files {
+                if !file.type_().starts_with("image/") {
+                    continue;
+                }
+                let id = {
+                    let mut next_id = next_id.borrow_mut();
+                    *next_id += 1;
+                    *next_id
+                };
+                add_thumbnail(&grid, &observer, &ellipsis, &urls, id, &file);
+            }
+        }
+    });
+
+    // the browser only allows a drop if dragover was cancelled
+    app.listen(&zone, "dragover", |ev: DragEvent| ev.prevent_default());
+    app.listen(&zone, "drop", {
+        let add_files = add_files.clone();
+        move |ev: DragEvent| {
+            ev.prevent_default();
+            if let Some(files) = ev.data_transfer().and_then(|data| data.files()) {
+                add_files(file_list(&files));
+            }
+        }
+    });
+    app.listen(&picker.clone(), "change", {
+        let add_files = add_files.clone();
+        move |_: Event| {
+            if let Some(files) = picker.files() {
+                add_files(file_list(&files));
+            }
+            // so picking the same file again still fires change
+            picker.set_value("");
+        }
+    });
+    // a pasted screenshot arrives as a file, like a dropped one
+    app.listen(&document(), "paste", move |ev: ClipboardEvent| {
+        let typing = ev
+            .target()
+            .and_then(|target| target.dyn_into::<Element>().ok())
+            .is_some_and(|target| is_editable(&target));
+        let images = ev.images();
+        if typing || images.is_empty() {
+            return;
+        }
+        ev.prevent_default();
+        add_files(images);
+    });
+
+    app.listen(&grid, "click", {
+        let urls = urls.clone();
+        move |ev: MouseEvent| {
+            let Some(button) = ev
+                .target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+                .filter(|target| target.has_attribute("data-remove"))
+            else {
+                return;
+            };
+            if let Some(item) = button.closest("li").ok().flatten() {
+                let id = item.get_attribute("data-id").and_then(|id| id.parse().ok());
+                if let Some(url) = id.and_then(|id| urls.borrow_mut().remove(&id)) {
+                    _ = Url::revoke_object_url(&url);
+                }
+                if let Ok(Some(name)) = item.query_selector("p") {
+                    ellipsis.forget(&name);
+                }
+                item.remove();
+            }
+        }
+    });
+
+    app.on_cleanup(move || {
+        loader.observer.disconnect();
+        drop(loader);
+        for url in urls.borrow_mut().values() {
+            _ = Url::revoke_object_url(url);
+        }
+        urls.borrow_mut().clear();
+    });
+
+    app
+}
+
+/// The observer, plus the closure it calls (which has to outlive it).
+struct LazyLoader {
+    observer: IntersectionObserver,
+    _callback: Closure<dyn FnMut(js_sys::Array, IntersectionObserver)>,
+}
+
+/// Gives each observed `<img data-src>` its real `src` once it's near the viewport.
+fn lazy_loader() -> LazyLoader {
+    let callback = Closure::wrap(Box::new(
+        |entries: js_sys::Array, observer: IntersectionObserver| {
+            for entry in entries.iter() {
+                let entry = entry.unchecked_into::<IntersectionObserverEntry>();
+                if !entry.is_intersecting() {
+                    continue;
+                }
+                let img = entry.target();
+                if let Some(src) = img.get_attribute("data-src") {
+                    _ = img.set_attribute("src", &src);
+                    _ = img.remove_attribute("data-src");
+                }
+                observer.unobserve(&img);
+            }
+        },
+    ) as Box<dyn FnMut(js_sys::Array, IntersectionObserver)>);
+    let mut options = IntersectionObserverInit::new();
+    options.root_margin(ROOT_MARGIN);
+    let observer =
+        IntersectionObserver::new_with_options(callback.as_ref().unchecked_ref(), &options)
+            .expect("to create an IntersectionObserver");
+    LazyLoader {
+        observer,
+        _callback: callback,
+    }
+}
+
+fn file_list(files: &FileList) -> Vec<File> {
+    (0..files.length()).filter_map(|i| files.get(i)).collect()
+}
+
+fn add_thumbnail(
+    grid: &Element,
+    observer: &IntersectionObserver,
+    ellipsis: &MiddleEllipsis,
+    urls: &Urls,
+    id: u32,
+    file: &File,
+) {
+    let Ok(url) = Url::create_object_url_with_blob(file) else {
+        return;
+    };
+    urls.borrow_mut().insert(id, url.clone());
+
+    let item = create_element("li");
+    _ = item.set_attribute("data-id", &id.to_string());
+    _ = item.set_attribute("style", "position: relative");
+    let img = create_element("img");
+    _ = img.set_attribute("data-src", &url);
+    _ = img.set_attribute("alt", &file.name());
+    _ = img.set_attribute(
+        "style",
+        "width: 100%; aspect-ratio: 1; object-fit: cover; background: #eee; border-radius: 4px",
+    );
+    let remove = create_element("button");
+    _ = remove.set_attribute("data-remove", "");
+    _ = remove.set_attribute("aria-label", &format!("Remove {}", file.name()));
+    _ = remove.set_attribute("style", "position: absolute; top: 4px; right: 4px");
+    remove.set_text_content(Some("×"));
+    let name = create_element("p");
+    _ = name.set_attribute(
+        "style",
+        "margin: 4px 0 0; font-size: 12px; white-space: nowrap; overflow: hidden",
+    );
+    item.append_child(&img).unwrap();
+    item.append_child(&name).unwrap();
+    item.append_child(&remove).unwrap();
+    ellipsis.show(&name, &file.name());
+    grid.append_child(&item).unwrap();
+
+    observer.observe(&img);
+}
@@ -0,0 +1,175 @@
+//! Measuring elements and placing floating ones next to them.
+//!
+//! Everything here is in viewport coordinates (what `getBoundingClientRect` and
+//! `position: fixed` use) unless it says otherwise. [`place`] is the bit the tooltip,
+//! the context menu and the selection bubble share: put a box on one side of an
+//! anchor, flip to the other side if it doesn't fit, and shift it along so it stays
+//! on screen.
+
+use wasm_bindgen::JsCast;
+use web_sys::{DomRect, Element, HtmlElement};
+
+use crate::dom::window;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub left: f64,
+    pub top: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    /// A zero-size rect, for anchoring to a point like the mouse position.
+    pub fn point(x: f64, y: f64) -> Self {
+        Rect {
+            left: x,
+            top: y,
+            ..Default::default()
+        }
+    }
+
+    pub fn right(&self) -> f64 {
+        self.left + self.width
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.top + self.height
+    }
+}
+
+impl From<DomRect> for Rect {
+    fn from(rect: DomRect) -> Self {
+        Rect {
+            left: rect.left(),
+            top: rect.top(),
+            width: rect.width(),
+            height: rect.height(),
+        }
+    }
+}
+
+pub fn bounding_rect(el: &Element) -> Rect {
+    el.get_bounding_client_rect().into()
+}
+
+/// The visible part of the page.
+pub fn viewport() -> Rect {
+    let size = |value: Result<wasm_bindgen::JsValue, _>| {
+        value.ok().and_then(|value| value.as_f64()).unwrap_or(0.0)
+    };
+    Rect {
+        left: 0.0,
+        top: 0.0,
+        width: size(window().inner_width()),
+        height: size(window().inner_height()),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Side {
+    fn opposite(self) -> Side {
+        match self {
+            Side::Top => Side::Bottom,
+            Side::Bottom => Side::Top,
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
+    }
+}
+
+/// Where along the anchor's side the box lines up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Start,
+    Center,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Placement {
+    pub side: Side,
+    pub align: Align,
+    /// Space between the anchor and the box.
+    pub gap: f64,
+}
+
+/// Where a `width` × `height` box goes next to `anchor`, kept inside `bounds`, and
+/// which side it ended up on (so an arrow can point the right way).
+///
+/// The box flips to the opposite side if it doesn't fit on the one asked for and
+/// there's more room over there; then it's shifted along the side until it's inside
+/// `bounds`. A box bigger than `bounds` sticks to the top/left edge.
+pub fn place(
+    anchor: Rect,
+    width: f64,
+    height: f64,
+    placement: Placement,
+    bounds: Rect,
+) -> (Rect, Side) {
+    let Placement { side, align, gap } = placement;
+    // room between the anchor and the edge of the bounds, on each side
+    let room = |side: Side| match side {
+        Side::Top => anchor.top - bounds.top,
+        Side::Bottom => bounds.bottom() - anchor.bottom(),
+        Side::Left => anchor.left - bounds.left,
+        Side::Right => bounds.right() - anchor.right(),
+    };
+    let needed = match side {
+        Side::Top | Side::Bottom => height + gap,
+        Side::Left | Side::Right => width + gap,
+    };
+    let side = if room(side) < needed && room(side.opposite()) > room(side) {
+        side.opposite()
+    } else {
+        side
+    };
+
+    let aligned = |start: f64, length: f64, size: f64| match align {
+        Align::Start => start,
+        Align::Center => start + (length - size) / 2.0,
+    };
+    let (left, top) = match side {
+        Side::Top => (
+            aligned(anchor.left, anchor.width, width),
+            anchor.top - gap - height,
+        ),
+        Side::Bottom => (
+            aligned(anchor.left, anchor.width, width),
+            anchor.bottom() + gap,
+        ),
+        Side::Left => (
+            anchor.left - gap - width,
+            aligned(anchor.top, anchor.height, height),
+        ),
+        Side::Right => (
+            anchor.right() + gap,
+            aligned(anchor.top, anchor.height, height),
+        ),
+    };
+    let shift = |position: f64, size: f64, min: f64, max: f64| position.min(max - size).max(min);
+    let rect = Rect {
+        left: shift(left, width, bounds.left, bounds.right()),
+        top: shift(top, height, bounds.top, bounds.bottom()),
+        width,
+        height,
+    };
+    (rect, side)
+}
+
+/// Places `floating` (a `position: fixed` element that's already in the document,
+/// so it can be measured) next to `anchor`, within the viewport.
+pub fn place_next_to(floating: &Element, anchor: Rect, placement: Placement) -> Side {
+    let size = bounding_rect(floating);
+    let (rect, side) = place(anchor, size.width, size.height, placement, viewport());
+    let style = floating.unchecked_ref::<HtmlElement>().style();
+    _ = style.set_property("left", &format!("{}px", rect.left));
+    _ = style.set_property("top", &format!("{}px", rect.top));
+    side
+}
@@ -0,0 +1,167 @@
+//! Pointer gestures built from raw pointer events and timers.
+//!
+//! Listeners go through the [`App`], so they're removed on unmount like any other.
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
+
+use web_sys::{AddEventListenerOptions, Element, MouseEvent, PointerEvent, WheelEvent};
+
+use crate::{
+    app::App,
+    time::{Interval, Timeout},
+};
+
+/// Two taps closer together than this (in time) make a double click.
+const DOUBLE_CLICK_WINDOW_MS: f64 = 300.0;
+/// ...and closer together than this (in CSS pixels).
+const DOUBLE_CLICK_SLOP_PX: i32 = 10;
+
+#[derive(Default)]
+struct Press {
+    timeout: Option<Timeout>,
+    repeat: Option<Interval>,
+    fired: bool,
+}
+
+/// Calls `handler` once the pointer has been held down on `el` for `delay`, then
+/// every `period` until the pointer is released or leaves the element.
+///
+/// Register this *before* any `click` listener on the same element: the click that
+/// ends such a press is swallowed so it doesn't count as a normal click too.
+pub fn on_press_repeat(
+    app: &mut App,
+    el: &Element,
+    delay: Duration,
+    period: Duration,
+    handler: impl FnMut() + 'static,
+) {
+    let press = Rc::new(RefCell::new(Press::default()));
+    let handler = Rc::new(RefCell::new(handler));
+
+    app.listen(el, "pointerdown", {
+        let press = press.clone();
+        move |ev: PointerEvent| {
+            if ev.button() != 0 {
+                return;
+            }
+            // the timer lives inside `press`, so it only gets a weak reference back
+            let weak = Rc::downgrade(&press);
+            let handler = handler.clone();
+            let timeout = Timeout::new(delay, move || {
+                let Some(press) = weak.upgrade() else { return };
+                (handler.borrow_mut())();
+                let mut press = press.borrow_mut();
+                press.fired = true;
+                press.repeat = Some(Interval::new(period, move || (handler.borrow_mut())()));
+            });
+            *press.borrow_mut() = Press {
+                timeout: Some(timeout),
+                repeat: None,
+                fired: false,
+            };
+        }
+    });
+
+    for event_name in ["pointerup", "pointerleave", "pointercancel"] {
+        app.listen(el, event_name, {
+            let press = press.clone();
+            move |_: PointerEvent| {
+                let mut press = press.borrow_mut();
+                press.timeout = None;
+                press.repeat = None;
+            }
+        });
+    }
+
+    app.listen(el, "click", move |ev: MouseEvent| {
+        if std::mem::take(&mut press.borrow_mut().fired) {
+            ev.stop_immediate_propagation();
+            ev.prevent_default();
+        }
+    });
+}
+
+/// Calls `handler` when `el` is tapped or clicked twice in quick succession.
+///
+/// Unlike `dblclick` this works the same for mouse, pen and touch input.
+pub fn on_double_click(
+    app: &mut App,
+    el: &Element,
+    mut handler: impl FnMut(PointerEvent) + 'static,
+) {
+    // time stamp and position of the previous tap
+    let last = Rc::new(Cell::new(None::<(f64, i32, i32)>));
+
+    app.listen(el, "pointerup", {
+        let last = last.clone();
+        move |ev: PointerEvent| {
+            if ev.button() != 0 {
+                return;
+            }
+            let now = (ev.time_stamp(), ev.client_x(), ev.client_y());
+            match last.take() {
+                Some((t, x, y))
+                    if now.0 - t <= DOUBLE_CLICK_WINDOW_MS
+                        && (now.1 - x).abs() <= DOUBLE_CLICK_SLOP_PX
+                        && (now.2 - y).abs() <= DOUBLE_CLICK_SLOP_PX =>
+                {
+                    handler(ev)
+                }
+                _ => last.set(Some(now)),
+            }
+        }
+    });
+
+    app.listen(el, "pointerleave", move |_: PointerEvent| last.set(None));
+}
+
+/// How far (in pixels) a wheel has to travel to count as one notch. Mouse wheels
+/// usually send ~100px per notch, trackpads send a stream of much smaller deltas.
+const WHEEL_NOTCH_PX: f64 = 50.0;
+
+/// Turns wheel movement over `el` into whole notches: `handler` gets `+1` per notch
+/// scrolled up and `-1` per notch scrolled down, along with the triggering event.
+pub fn on_wheel_notches(
+    app: &mut App,
+    el: &Element,
+    mut handler: impl FnMut(i32, &WheelEvent) + 'static,
+) {
+    let mut accumulated = 0.0;
+
+    // Wheel listeners are passive by default when they're on window/document/body,
+    // and browsers are allowed to scroll without waiting for passive listeners, which
+    // is what keeps scrolling smooth. Here we *want* to stop the page from scrolling
+    // (and ctrl+wheel from zooming) while the pointer is over the counter, and
+    // `prevent_default()` is ignored in passive listeners, so opt out explicitly.
+    // That's only cheap because the listener is on one small element; on the whole
+    // page it would delay every scroll until our handler has run.
+    let mut options = AddEventListenerOptions::new();
+    options.passive(false);
+
+    app.listen_with_options(el, "wheel", &options, move |ev: WheelEvent| {
+        ev.prevent_default();
+
+        // shift+wheel scrolls horizontally on most platforms, so take either axis
+        let delta = if ev.delta_y() != 0.0 {
+            ev.delta_y()
+        } else {
+            ev.delta_x()
+        };
+        accumulated += match ev.delta_mode() {
+            WheelEvent::DOM_DELTA_PIXEL => delta,
+            // lines and pages: one is plenty for a notch
+            _ => delta.signum() * WHEEL_NOTCH_PX,
+        };
+
+        let notches = (accumulated / WHEEL_NOTCH_PX).trunc();
+        if notches != 0.0 {
+            accumulated -= notches * WHEEL_NOTCH_PX;
+            // wheel deltas are positive when scrolling down
+            handler(-notches as i32, &ev);
+        }
+    });
+}
@@ -0,0 +1,201 @@
+//! A scrolling log of dispatched messages and the count they produced.
+//!
+//! Only the newest `capacity` entries are kept: appending past that evicts the
+//! oldest one, so the DOM stays the same size however long the page is open.
+//!
+//! Entries say how long ago they were ("3 minutes ago"), kept up to date while the
+//! log is showing; the exact time is in each one's tooltip. "Save as PNG" downloads a
+//! picture of every entry, scrolled out of sight or not, and "Save chart as PNG" one
+//! of the chart.
+//!
+//! Above the entries, an SVG line charts the counts they ended with. It's drawn in a
+//! view box one unit per entry wide and one per count tall, and stretched to whatever
+//! width the log has, so it needs no redrawing when that changes.
+
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, MouseEvent};
+
+use crate::{
+    app::App,
+    dom::{create_element, spawn_local},
+    export,
+    intl::{keep_relative_times_fresh, relative_time},
+    print,
+    svg::{create_svg_element, set_path, set_view_box},
+    toast,
+};
+
+pub struct HistoryLog {
+    list: Element,
+    capacity: usize,
+    chart: Element,
+    line: Element,
+    /// The count after each entry, oldest first.
+    counts: RefCell<VecDeque<i32>>,
+}
+
+impl HistoryLog {
+    pub fn mount(app: &mut App, parent: &Element, capacity: usize) -> Rc<Self> {
+        let section = create_element("section");
+        let heading = create_element("h3");
+        heading.set_text_content(Some("History"));
+        let clear = create_element("button");
+        clear.set_text_content(Some("Clear"));
+        let save = create_element("button");
+        save.set_text_content(Some("Save as PNG"));
+        let save_chart = create_element("button");
+        save_chart.set_text_content(Some("Save chart as PNG"));
+        let chart = create_svg_element("svg");
+        // stretched to fit both ways, with the line staying the same thickness
+        _ = chart.set_attribute("preserveAspectRatio", "none");
+        _ = chart.set_attribute("style", "display: block; width: 100%; height: 4em");
+        _ = chart.set_attribute("role", "img");
+        _ = chart.set_attribute("aria-label", "The count over the history");
+        let line = create_svg_element("path");
+        for (name, value) in [
+            ("fill", "none"),
+            ("stroke", "currentColor"),
+            ("stroke-width", "2"),
+            ("vector-effect", "non-scaling-stroke"),
+        ] {
+            _ = line.set_attribute(name, value);
+        }
+        chart.append_child(&line).unwrap();
+        let list = create_element("ol");
+        _ = list.set_attribute(
+            "style",
+            "max-height: 12em; overflow-y: auto; font-family: monospace; padding-left: 2em",
+        );
+        section.append_child(&heading).unwrap();
+        section.append_child(&clear).unwrap();
+        section.append_child(&save).unwrap();
+        section.append_child(&save_chart).unwrap();
+        section.append_child(&chart).unwrap();
+        section.append_child(&list).unwrap();
+        app.append(parent, &section);
+
+        keep_relative_times_fresh(app, &list);
+        let log = Rc::new(HistoryLog {
+            list,
+            capacity,
+            chart,
+            line,
+            counts: RefCell::default(),
+        });
+
+        app.listen(&clear, "click", {
+            let log = log.clone();
+            move |_: MouseEvent| log.clear()
+        });
+        for (button, el, filename) in [
+            (&save, &log.list, "history.png"),
+            (&save_chart, &log.chart, "history-chart.png"),
+        ] {
+            let el = el.clone();
+            app.listen(button, "click", move |_: MouseEvent| {
+                let el = el.clone();
+                spawn_local(async move {
+                    if let Err(error) = export::save_png(&el, filename).await {
+                        toast::error(&format!("Couldn't save the history: {error}"));
+                    }
+                });
+            });
+        }
+
+        // on paper there's no scrolling, so show every entry
+        let style = log.list.unchecked_ref::<HtmlElement>().style();
+        print::on_print(
+            app,
+            {
+                let style = style.clone();
+                move || _ = style.set_property("max-height", "none")
+            },
+            move || _ = style.set_property("max-height", "12em"),
+        );
+
+        log
+    }
+
+    pub fn push(&self, message: &str, count: i32) {
+        // only follow new entries if the user hasn't scrolled up to read older ones
+        let at_bottom =
+            self.list.scroll_top() + self.list.client_height() >= self.list.scroll_height() - 1;
+
+        let entry = create_element("li");
+        entry
+            .append_child(&relative_time(js_sys::Date::now()))
+            .unwrap();
+        entry
+            .append_with_str_1(&format!("  {message} → {count}"))
+            .unwrap();
+        self.list.append_child(&entry).unwrap();
+
+        while self.list.child_element_count() as usize > self.capacity {
+            if let Some(oldest) = self.list.first_element_child() {
+                oldest.remove();
+            }
+        }
+
+        if at_bottom {
+            self.list.set_scroll_top(self.list.scroll_height());
+        }
+
+        let mut counts = self.counts.borrow_mut();
+        counts.push_back(count);
+        while counts.len() > self.capacity {
+            counts.pop_front();
+        }
+        drop(counts);
+        self.draw();
+    }
+
+    pub fn clear(&self) {
+        self.list.set_inner_html("");
+        self.counts.borrow_mut().clear();
+        self.draw();
+    }
+
+    fn draw(&self) {
+        let mut counts = self.counts.borrow_mut();
+        let (points, width, height) = chart(counts.make_contiguous());
+        set_view_box(&self.chart, 0.0, 0.0, width, height);
+        set_path(&self.line, &points);
+    }
+}
+
+/// `counts` as points, one unit apart, with the highest count at the top, and the width
+/// and height they take up. A flat line goes across the middle.
+fn chart(counts: &[i32]) -> (Vec<(f64, f64)>, f64, f64) {
+    let max = counts.iter().copied().max().unwrap_or(0) as f64;
+    let min = counts.iter().copied().min().unwrap_or(0) as f64;
+    let (height, offset) = if max > min {
+        (max - min, 0.0)
+    } else {
+        (1.0, 0.5)
+    };
+    let width = counts.len().saturating_sub(1).max(1) as f64;
+    let points = counts
+        .iter()
+        .enumerate()
+        .map(|(i, count)| (i as f64, max - *count as f64 + offset))
+        .collect();
+    (points, width, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn charts_highest_at_the_top() {
+        let (points, width, height) = chart(&[0, 5, -5]);
+        assert_eq!(points, [(0.0, 5.0), (1.0, 0.0), (2.0, 10.0)]);
+        assert_eq!((width, height), (2.0, 10.0));
+
+        let (points, width, height) = chart(&[3, 3]);
+        assert_eq!(points, [(0.0, 0.5), (1.0, 0.5)]);
+        assert_eq!((width, height), (1.0, 1.0));
+    }
+}
@@ -0,0 +1,115 @@
+//! Keeps state alive across reloads while developing.
+//!
+//! Before the page goes away the state is written to `sessionStorage`; on startup it is
+//! read back. The key includes a hash of the state's *shape* (field names and value
+//! kinds), so a reload after changing `State` starts fresh instead of failing to parse.
+//! In release builds this is all a no-op.
+
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use web_sys::{Event, Storage};
+
+use crate::{app::App, dom::window};
+
+const PREFIX: &str = "hot-state";
+
+/// Restores the state saved under `name`, if it was saved with the same schema.
+pub fn restore<T>(name: &str) -> Option<T>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    if !cfg!(debug_assertions) {
+        return None;
+    }
+    let storage = session_storage()?;
+    let key = key::<T>(name);
+    remove_stale(&storage, name, &key);
+
+    let json = storage.get_item(&key).ok()??;
+    serde_json::from_str(&json).ok()
+}
+
+/// Saves `state` under `name` whenever the page is about to be unloaded.
+pub fn preserve_on_unload<T>(app: &mut App, name: &str, state: &Rc<RefCell<T>>)
+where
+    T: Serialize + Default + 'static,
+{
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let key = key::<T>(name);
+    let state = Rc::clone(state);
+    // `pagehide` also fires when the page goes into the back/forward cache,
+    // where `beforeunload` doesn't
+    app.listen(&window(), "pagehide", move |_: Event| {
+        if let (Some(storage), Ok(json)) =
+            (session_storage(), serde_json::to_string(&*state.borrow()))
+        {
+            _ = storage.set_item(&key, &json);
+        }
+    });
+}
+
+fn session_storage() -> Option<Storage> {
+    window().session_storage().ok().flatten()
+}
+
+fn key<T: Serialize + Default>(name: &str) -> String {
+    format!("{PREFIX}:{name}:{:016x}", schema_hash::<T>())
+}
+
+/// Drops entries for `name` that were written with a different schema.
+fn remove_stale(storage: &Storage, name: &str, current_key: &str) {
+    let prefix = format!("{PREFIX}:{name}:");
+    let len = storage.length().unwrap_or(0);
+    let stale = (0..len)
+        .filter_map(|i| storage.key(i).ok().flatten())
+        .filter(|key| key.starts_with(&prefix) && key != current_key)
+        .collect::<Vec<_>>();
+    for key in stale {
+        _ = storage.remove_item(&key);
+    }
+}
+
+/// Hash of the shape of `T::default()`, stable across builds and compiler versions.
+pub fn schema_hash<T: Serialize + Default>() -> u64 {
+    let value = serde_json::to_value(T::default()).unwrap_or(Value::Null);
+    let mut shape = String::new();
+    write_shape(&value, &mut shape);
+    fnv1a(shape.as_bytes())
+}
+
+fn write_shape(value: &Value, out: &mut String) {
+    match value {
+        Value::Null => out.push('n'),
+        Value::Bool(_) => out.push('b'),
+        Value::Number(_) => out.push('0'),
+        Value::String(_) => out.push('s'),
+        Value::Array(items) => {
+            out.push('[');
+            if let Some(first) = items.first() {
+                write_shape(first, out);
+            }
+            out.push(']');
+        }
+        Value::Object(fields) => {
+            // serde_json's map is sorted by key, so field order doesn't matter
+            out.push('{');
+            for (name, value) in fields {
+                out.push_str(name);
+                out.push(':');
+                write_shape(value, out);
+                out.push(',');
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
@@ -0,0 +1,341 @@
+//! Keyboard shortcuts, all handled by one `keydown` listener.
+//!
+//! A shortcut is written as space-separated steps, each a key with optional
+//! modifiers: `"?"`, `"mod+k"` (Ctrl, or ⌘ on a Mac), `"g c"` (g, then c within a
+//! second). Single characters match whatever it takes to type them, so `"?"` doesn't
+//! need a `shift+`.
+//!
+//! Shortcuts belong to a [`Scope`]. Scopes stack: only the top one's shortcuts (plus
+//! the global ones) are live, so opening a modal, which pushes [`Scope::Modal`],
+//! switches the page's shortcuts off until it closes. Registering a shortcut that
+//! clashes with another in the same scope logs a warning, and "?" shows a cheat sheet
+//! of everything registered.
+
+use std::{cell::RefCell, fmt, rc::Rc, time::Duration};
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, KeyboardEvent};
+
+use crate::{
+    app::App,
+    dialog::Dialog,
+    dom::{create_element, document, log, spawn_local, window},
+    time::Timeout,
+};
+
+/// How long to wait for the next step of a chord like `"g c"`.
+const CHORD_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scope {
+    /// Live whatever else is going on.
+    Global,
+    /// The page itself; the bottom of the stack.
+    Page,
+    /// A modal dialog is open.
+    Modal,
+}
+
+/// One step of a shortcut: a key plus modifiers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Combo {
+    /// Lowercased, as in `KeyboardEvent.key`: `"k"`, `"?"`, `"arrowup"`.
+    key: String,
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    meta: bool,
+}
+
+impl Combo {
+    fn parse(step: &str) -> Option<Combo> {
+        let mut combo = Combo {
+            key: String::new(),
+            ctrl: false,
+            alt: false,
+            shift: false,
+            meta: false,
+        };
+        // rsplit so that "+" on its own, or "shift++", still has a key
+        let (modifiers, key) = match step.rsplit_once('+') {
+            Some((modifiers, "")) => (modifiers.strip_suffix('+').unwrap_or(""), "+"),
+            Some((modifiers, key)) => (modifiers, key),
+            None => ("", step),
+        };
+        for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" => combo.ctrl = true,
+                "alt" => combo.alt = true,
+                "shift" => combo.shift = true,
+                "meta" => combo.meta = true,
+                "mod" if is_mac() => combo.meta = true,
+                "mod" => combo.ctrl = true,
+                _ => return None,
+            }
+        }
+        if key.is_empty() {
+            return None;
+        }
+        combo.key = key.to_lowercase();
+        Some(combo)
+    }
+
+    fn matches(&self, ev: &KeyboardEvent) -> bool {
+        let key = ev.key().to_lowercase();
+        // shift is part of typing "?" or "A", so it only counts for named keys
+        let printable = key.chars().count() == 1;
+        key == self.key
+            && ev.ctrl_key() == self.ctrl
+            && ev.alt_key() == self.alt
+            && ev.meta_key() == self.meta
+            && (printable || ev.shift_key() == self.shift)
+    }
+
+    fn has_modifier(&self) -> bool {
+        self.ctrl || self.alt || self.meta
+    }
+}
+
+impl fmt::Display for Combo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (on, name) in [
+            (self.ctrl, "Ctrl+"),
+            (self.alt, "Alt+"),
+            (self.shift, "Shift+"),
+            (self.meta, "⌘+"),
+        ] {
+            if on {
+                f.write_str(name)?;
+            }
+        }
+        f.write_str(&self.key)
+    }
+}
+
+type Action = Rc<dyn Fn()>;
+
+struct Hotkey {
+    id: usize,
+    scope: Scope,
+    keys: Vec<Combo>,
+    description: String,
+    action: Action,
+}
+
+#[derive(Default)]
+struct Registry {
+    hotkeys: Vec<Hotkey>,
+    /// Pushed scopes, innermost last; `Page` when it's empty.
+    scopes: Vec<(usize, Scope)>,
+    next_id: usize,
+    /// The steps of a chord typed so far, and when to give up on it.
+    pending: Vec<Combo>,
+    pending_timeout: Option<Timeout>,
+}
+
+impl Registry {
+    fn next_id(&mut self) -> usize {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn scope(&self) -> Scope {
+        self.scopes.last().map_or(Scope::Page, |(_, scope)| *scope)
+    }
+
+    fn is_live(&self, hotkey: &Hotkey) -> bool {
+        hotkey.scope == Scope::Global || hotkey.scope == self.scope()
+    }
+}
+
+thread_local! {
+    static REGISTRY: RefCell<Registry> = RefCell::new(Registry::default());
+}
+
+/// Listens for shortcuts, and registers "?" for the cheat sheet.
+pub fn install(app: &mut App) {
+    app.listen(&document(), "keydown", on_keydown);
+
+    // the cheat sheet is a modal, so "?" inside it does nothing rather than stacking another
+    register(app, Scope::Page, "?", "Show keyboard shortcuts", || {
+        spawn_local(show_cheat_sheet())
+    });
+}
+
+/// Calls `action` when `keys` are pressed while `scope` is live, until `app` unmounts.
+pub fn register(
+    app: &mut App,
+    scope: Scope,
+    keys: &str,
+    description: &str,
+    action: impl Fn() + 'static,
+) {
+    let Some(combos) = keys
+        .split_whitespace()
+        .map(Combo::parse)
+        .collect::<Option<Vec<_>>>()
+    else {
+        log!("ignoring the shortcut {keys:?}: can't parse it");
+        return;
+    };
+    let id = REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        // "g" would fire before "g c" ever could, so a prefix clashes as much as a match
+        let clash = registry.hotkeys.iter().find(|other| {
+            let shared = other.keys.len().min(combos.len());
+            (other.scope == scope || other.scope == Scope::Global || scope == Scope::Global)
+                && other.keys[..shared] == combos[..shared]
+        });
+        if let Some(other) = clash {
+            log!(
+                "shortcut {keys:?} ({description}) clashes with {:?} ({})",
+                describe(&other.keys),
+                other.description
+            );
+        }
+        let id = registry.next_id();
+        registry.hotkeys.push(Hotkey {
+            id,
+            scope,
+            keys: combos,
+            description: description.to_string(),
+            action: Rc::new(action),
+        });
+        id
+    });
+    app.on_cleanup(move || {
+        REGISTRY.with(|registry| {
+            registry
+                .borrow_mut()
+                .hotkeys
+                .retain(|hotkey| hotkey.id != id)
+        })
+    });
+}
+
+/// Makes `scope` the live one until `app` unmounts.
+pub fn push_scope(app: &mut App, scope: Scope) {
+    let id = REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        let id = registry.next_id();
+        registry.scopes.push((id, scope));
+        registry.pending.clear();
+        id
+    });
+    app.on_cleanup(move || {
+        REGISTRY.with(|registry| {
+            registry
+                .borrow_mut()
+                .scopes
+                .retain(|(other, _)| *other != id)
+        })
+    });
+}
+
+fn on_keydown(ev: KeyboardEvent) {
+    if ev.repeat() || matches!(ev.key().as_str(), "Shift" | "Control" | "Alt" | "Meta") {
+        return;
+    }
+    let typing = ev
+        .target()
+        .and_then(|target| target.dyn_into::<Element>().ok())
+        .is_some_and(|target| is_editable(&target));
+
+    let action = REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        let mut pending = std::mem::take(&mut registry.pending);
+        registry.pending_timeout = None;
+
+        // try continuing the chord in progress first, then starting afresh
+        for start_over in [false, true] {
+            if start_over {
+                if pending.is_empty() {
+                    break;
+                }
+                pending.clear();
+            }
+            let step = pending.len();
+            let candidates = registry
+                .hotkeys
+                .iter()
+                .filter(|hotkey| registry.is_live(hotkey))
+                .filter(|hotkey| hotkey.keys.len() > step && hotkey.keys[..step] == pending[..])
+                .filter(|hotkey| hotkey.keys[step].matches(&ev))
+                // plain keys are for the text field when one is focused
+                .filter(|hotkey| !typing || hotkey.keys[step].has_modifier())
+                .collect::<Vec<_>>();
+            if let Some(done) = candidates
+                .iter()
+                .find(|hotkey| hotkey.keys.len() == step + 1)
+            {
+                return Some(done.action.clone());
+            }
+            if let Some(next) = candidates.first() {
+                pending.push(next.keys[step].clone());
+                registry.pending = pending;
+                registry.pending_timeout = Some(Timeout::new(CHORD_TIMEOUT, || {
+                    REGISTRY.with(|registry| registry.borrow_mut().pending.clear())
+                }));
+                return None;
+            }
+        }
+        None
+    });
+    let chord_in_progress = REGISTRY.with(|registry| !registry.borrow().pending.is_empty());
+    if let Some(action) = action {
+        ev.prevent_default();
+        action();
+    } else if chord_in_progress {
+        ev.prevent_default();
+    }
+}
+
+fn describe(keys: &[Combo]) -> String {
+    keys.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+async fn show_cheat_sheet() {
+    let dialog = Dialog::new("Keyboard shortcuts").button("close", "Close");
+    let table = create_element("table");
+    REGISTRY.with(|registry| {
+        let registry = registry.borrow();
+        for hotkey in &registry.hotkeys {
+            let row = create_element("tr");
+            let keys = create_element("td");
+            _ = keys.set_attribute("style", "padding-right: 2em; white-space: nowrap");
+            for (i, combo) in hotkey.keys.iter().enumerate() {
+                if i > 0 {
+                    _ = keys.append_with_str_1(" then ");
+                }
+                let kbd = create_element("kbd");
+                kbd.set_text_content(Some(&combo.to_string()));
+                keys.append_child(&kbd).unwrap();
+            }
+            let description = create_element("td");
+            description.set_text_content(Some(&hotkey.description));
+            row.append_child(&keys).unwrap();
+            row.append_child(&description).unwrap();
+            table.append_child(&row).unwrap();
+        }
+    });
+    dialog.body().append_child(&table).unwrap();
+    dialog.show_modal().await;
+}
+
+/// Whether `el` takes typing, so plain keys pressed in it aren't shortcuts.
+pub fn is_editable(el: &Element) -> bool {
+    matches!(el.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+        || el
+            .dyn_ref::<HtmlElement>()
+            .is_some_and(|el| el.is_content_editable())
+}
+
+fn is_mac() -> bool {
+    window()
+        .navigator()
+        .platform()
+        .is_ok_and(|platform| platform.starts_with("Mac"))
+}
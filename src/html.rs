@@ -0,0 +1,1039 @@
+//! A builder for elements, so a bit of UI reads as one expression in the shape of
+//! the markup it makes, rather than a `create_element`, a `set_text_content` and an
+//! `append_child` for every node:
+//!
+//! ```ignore
+//! el!(div)
+//!     .child(el!(button).text("+1").on("click", |_: MouseEvent| log!("+1")))
+//!     .child(el!(p).text("0"))
+//!     .mount(&mut app, parent);
+//! ```
+//!
+//! [`el!`] takes the element's name as an identifier and won't compile if there's no
+//! such HTML element, so `el!(buton)` is caught before it can become a `<buton>` that
+//! shows up as nothing. Attributes given to it after the name are checked too, against
+//! the global ones, `data-*`, `aria-*` and that element's own, so neither a misspelt
+//! one nor one for some other element gets through:
+//!
+//! ```ignore
+//! el!(input, type = "text", placeholder = "Name", "aria-label" = "Name")
+//! ```
+//!
+//! ```compile_fail
+//! el!(div, href = "/"); // there's no href on a <div>
+//! ```
+//!
+//! ```compile_fail
+//! el!(input, palceholder = "Name"); // nor a palceholder on anything
+//! ```
+//!
+//! A void element (an `<input>`, say) from [`el!`] has no [`El::child`] at all, since
+//! the parser would never have put children there:
+//!
+//! ```compile_fail
+//! el!(input).child(el!(span)); // no method named `child` on `El<true>`
+//! ```
+//!
+//! [`el`] takes any name, for custom elements and the like, and [`El::attr`] any
+//! attribute, for names only known when it runs; giving children to a void element
+//! made by [`el`] panics in debug builds.
+//!
+//! Each element is created as soon as `el` is called, so the tree is really built as
+//! it's written down. Listeners are the exception: they're only attached on
+//! [`El::build`] (or [`El::mount`]), through the [`App`], which then removes them on
+//! unmount like any of its own.
+//!
+//! HTML is only the default. [`svg`] and [`math`] start elements in the SVG and
+//! MathML namespaces (an SVG `<path>` made by [`el`] is an HTML element that draws
+//! nothing), [`el_ns`] in any other, and [`El::attr_ns`] sets an attribute in a
+//! namespace of its own, such as `xlink:href`. The known namespaces are names rather
+//! than strings, so a misspelt one doesn't compile, and an attribute's prefix always
+//! goes with its namespace.
+//!
+//! An event's name can be followed by modifiers, `:`-separated, so a handler needn't
+//! start with the same few lines: `"submit:prevent"` calls `prevent_default` first,
+//! `:stop` calls `stop_propagation`, `:self` skips events from inside the element,
+//! `:enter`, `:escape` and other key names skip the rest of the keys, `:once` calls
+//! the handler for the first event the others let through and for none after it, and
+//! `:capture` and `:passive` are the listener options of the same names.
+//!
+//! The same view code can also take over markup a server already rendered, rather
+//! than making it again. [`render_to_string`] numbers every element it builds, in the
+//! order they're built, with a `data-hk` attribute; inside [`hydrate`], the element
+//! with the next number is claimed instead of a new one made, and text and places are
+//! checked rather than set. A mismatch is logged and fixed by making that element
+//! anew, so the page ends up right either way, only slower.
+
+use std::cell::RefCell;
+
+use wasm_bindgen::JsCast;
+use web_sys::{AddEventListenerOptions, Element, Event, KeyboardEvent};
+
+use crate::{
+    app::App,
+    dom::{create_element, document, log, set_attribute},
+    svg::create_svg_element,
+};
+
+const MATHML_NS: &str = "http://www.w3.org/1998/Math/MathML";
+
+/// A namespace for attributes, with the prefix they're written with.
+#[derive(Clone, Copy)]
+pub struct AttrNs {
+    uri: &'static str,
+    prefix: &'static str,
+}
+
+/// For `xlink:href`, which older SVG (and some of what reads it) still wants.
+pub const XLINK: AttrNs = AttrNs {
+    uri: "http://www.w3.org/1999/xlink",
+    prefix: "xlink",
+};
+
+type Listener = (Element, &'static str, Modifiers, Box<dyn FnMut(Event)>);
+
+/// What can follow an event's name.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Modifiers {
+    prevent: bool,
+    stop: bool,
+    only_self: bool,
+    /// The `KeyboardEvent::key` to let through.
+    key: Option<&'static str>,
+    once: bool,
+    capture: bool,
+    passive: bool,
+}
+
+/// Splits `"keydown:enter:prevent"` into the event's name and its modifiers.
+fn parse_modifiers(spec: &'static str) -> (&'static str, Modifiers) {
+    let mut parts = spec.split(':');
+    let event_name = parts.next().unwrap_or_default();
+    let mut modifiers = Modifiers::default();
+    for modifier in parts {
+        match modifier {
+            "prevent" => modifiers.prevent = true,
+            "stop" => modifiers.stop = true,
+            "self" => modifiers.only_self = true,
+            "once" => modifiers.once = true,
+            "capture" => modifiers.capture = true,
+            "passive" => modifiers.passive = true,
+            key => {
+                modifiers.key = Some(match key {
+                    "enter" => "Enter",
+                    "escape" => "Escape",
+                    "space" => " ",
+                    "tab" => "Tab",
+                    "delete" => "Delete",
+                    "up" => "ArrowUp",
+                    "down" => "ArrowDown",
+                    "left" => "ArrowLeft",
+                    "right" => "ArrowRight",
+                    _ => panic!("there's no event modifier :{key} (in {spec:?})"),
+                })
+            }
+        }
+    }
+    (event_name, modifiers)
+}
+
+impl Modifiers {
+    /// Whether `ev` gets to the handler, after doing what the modifiers ask of it.
+    fn admit(&self, ev: &Event) -> bool {
+        if self.only_self && ev.target() != ev.current_target() {
+            return false;
+        }
+        if let Some(key) = self.key {
+            if ev
+                .dyn_ref::<KeyboardEvent>()
+                .map(KeyboardEvent::key)
+                .as_deref()
+                != Some(key)
+            {
+                return false;
+            }
+        }
+        if self.prevent {
+            ev.prevent_default();
+        }
+        if self.stop {
+            ev.stop_propagation();
+        }
+        true
+    }
+
+    /// Not the browser's `once`, which would go with the first event, even one the
+    /// other modifiers turn away: that's up to the handler.
+    fn options(&self) -> AddEventListenerOptions {
+        let mut options = AddEventListenerOptions::new();
+        options.capture(self.capture).passive(self.passive);
+        options
+    }
+}
+
+/// An element being built, with the listeners for it and everything in it. `VOID` is
+/// set for the void elements [`el!`] makes, which can't take children.
+#[must_use = "an element does nothing until it's built or mounted"]
+pub struct El<const VOID: bool = false> {
+    element: Element,
+    listeners: Vec<Listener>,
+    /// Taken over from server-rendered markup rather than made.
+    claimed: bool,
+}
+
+pub fn el(tag_name: &str) -> El {
+    keyed(tag_name, || create_element(tag_name))
+}
+
+/// [`el`], for an element whose name, and the names of the attributes that follow
+/// it (as identifiers, or as strings for those with a `-`), are checked when it's
+/// compiled.
+macro_rules! checked_el {
+    ($tag:ident $(, $name:tt = $value:expr)* $(,)?) => {{
+        const TAG: &str = {
+            let tag = stringify!($tag);
+            assert!(
+                $crate::html::is_element(tag),
+                concat!(
+                    "there's no <",
+                    stringify!($tag),
+                    "> element (el(\"...\") takes any name)"
+                )
+            );
+            tag
+        };
+        $crate::html::el(TAG)
+            .void::<{ $crate::html::is_void(stringify!($tag)) }>()
+            $(.attr(
+                const {
+                    let name = $crate::html::attr_name!($name);
+                    assert!(
+                        $crate::html::is_attribute(TAG, name),
+                        concat!(
+                            "<",
+                            stringify!($tag),
+                            "> has no attribute ",
+                            stringify!($name),
+                            " (.attr(..) takes any name)"
+                        )
+                    );
+                    name
+                },
+                $value,
+            ))*
+    }};
+}
+// `use el` would bring the function along too, which is already here
+pub(crate) use checked_el as el;
+
+/// An attribute's name in [`el!`]: `type` or `"aria-label"`.
+macro_rules! attr_name {
+    ($name:ident) => {
+        stringify!($name)
+    };
+    ($name:literal) => {
+        $name
+    };
+}
+pub(crate) use attr_name;
+/// The HTML elements, bar the obsolete ones.
+const ELEMENTS: &[&str] = &[
+    "a",
+    "abbr",
+    "address",
+    "area",
+    "article",
+    "aside",
+    "audio",
+    "b",
+    "base",
+    "bdi",
+    "bdo",
+    "blockquote",
+    "body",
+    "br",
+    "button",
+    "canvas",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "data",
+    "datalist",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "embed",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "i",
+    "iframe",
+    "img",
+    "input",
+    "ins",
+    "kbd",
+    "label",
+    "legend",
+    "li",
+    "link",
+    "main",
+    "map",
+    "mark",
+    "menu",
+    "meta",
+    "meter",
+    "nav",
+    "noscript",
+    "object",
+    "ol",
+    "optgroup",
+    "option",
+    "output",
+    "p",
+    "picture",
+    "pre",
+    "progress",
+    "q",
+    "rp",
+    "rt",
+    "ruby",
+    "s",
+    "samp",
+    "script",
+    "search",
+    "section",
+    "select",
+    "slot",
+    "small",
+    "source",
+    "span",
+    "strong",
+    "style",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "template",
+    "textarea",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "title",
+    "tr",
+    "track",
+    "u",
+    "ul",
+    "var",
+    "video",
+    "wbr",
+];
+
+/// The elements that can't have children.
+const VOID: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+pub const fn is_element(tag_name: &str) -> bool {
+    contains(ELEMENTS, tag_name)
+}
+
+pub const fn is_void(tag_name: &str) -> bool {
+    contains(VOID, tag_name)
+}
+
+/// The attributes any element can have, bar the `on*` handlers, which go through
+/// [`El::on`].
+const GLOBAL_ATTRIBUTES: &[&str] = &[
+    "accesskey",
+    "autocapitalize",
+    "autofocus",
+    "class",
+    "contenteditable",
+    "dir",
+    "draggable",
+    "enterkeyhint",
+    "hidden",
+    "id",
+    "inert",
+    "inputmode",
+    "is",
+    "itemid",
+    "itemprop",
+    "itemref",
+    "itemscope",
+    "itemtype",
+    "lang",
+    "nonce",
+    "popover",
+    "role",
+    "slot",
+    "spellcheck",
+    "style",
+    "tabindex",
+    "title",
+    "translate",
+];
+
+/// Each element's own attributes, for those that have any.
+const ATTRIBUTES: &[(&str, &[&str])] = &[
+    (
+        "a",
+        &[
+            "download",
+            "href",
+            "hreflang",
+            "ping",
+            "referrerpolicy",
+            "rel",
+            "target",
+            "type",
+        ],
+    ),
+    (
+        "area",
+        &[
+            "alt",
+            "coords",
+            "download",
+            "href",
+            "ping",
+            "referrerpolicy",
+            "rel",
+            "shape",
+            "target",
+        ],
+    ),
+    (
+        "audio",
+        &[
+            "autoplay",
+            "controls",
+            "crossorigin",
+            "loop",
+            "muted",
+            "preload",
+            "src",
+        ],
+    ),
+    ("base", &["href", "target"]),
+    ("blockquote", &["cite"]),
+    (
+        "button",
+        &[
+            "disabled",
+            "form",
+            "formaction",
+            "formenctype",
+            "formmethod",
+            "formnovalidate",
+            "formtarget",
+            "name",
+            "popovertarget",
+            "popovertargetaction",
+            "type",
+            "value",
+        ],
+    ),
+    ("canvas", &["height", "width"]),
+    ("col", &["span"]),
+    ("colgroup", &["span"]),
+    ("data", &["value"]),
+    ("del", &["cite", "datetime"]),
+    ("details", &["name", "open"]),
+    ("dialog", &["open"]),
+    ("embed", &["height", "src", "type", "width"]),
+    ("fieldset", &["disabled", "form", "name"]),
+    (
+        "form",
+        &[
+            "accept-charset",
+            "action",
+            "autocomplete",
+            "enctype",
+            "method",
+            "name",
+            "novalidate",
+            "rel",
+            "target",
+        ],
+    ),
+    (
+        "iframe",
+        &[
+            "allow",
+            "allowfullscreen",
+            "height",
+            "loading",
+            "name",
+            "referrerpolicy",
+            "sandbox",
+            "src",
+            "srcdoc",
+            "width",
+        ],
+    ),
+    (
+        "img",
+        &[
+            "alt",
+            "crossorigin",
+            "decoding",
+            "fetchpriority",
+            "height",
+            "ismap",
+            "loading",
+            "referrerpolicy",
+            "sizes",
+            "src",
+            "srcset",
+            "usemap",
+            "width",
+        ],
+    ),
+    (
+        "input",
+        &[
+            "accept",
+            "alt",
+            "autocomplete",
+            "checked",
+            "dirname",
+            "disabled",
+            "form",
+            "formaction",
+            "formenctype",
+            "formmethod",
+            "formnovalidate",
+            "formtarget",
+            "height",
+            "list",
+            "max",
+            "maxlength",
+            "min",
+            "minlength",
+            "multiple",
+            "name",
+            "pattern",
+            "placeholder",
+            "popovertarget",
+            "popovertargetaction",
+            "readonly",
+            "required",
+            "size",
+            "src",
+            "step",
+            "type",
+            "value",
+            "width",
+        ],
+    ),
+    ("ins", &["cite", "datetime"]),
+    ("label", &["for"]),
+    ("li", &["value"]),
+    (
+        "link",
+        &[
+            "as",
+            "blocking",
+            "crossorigin",
+            "disabled",
+            "fetchpriority",
+            "href",
+            "hreflang",
+            "imagesizes",
+            "imagesrcset",
+            "integrity",
+            "media",
+            "referrerpolicy",
+            "rel",
+            "sizes",
+            "type",
+        ],
+    ),
+    ("map", &["name"]),
+    (
+        "meta",
+        &["charset", "content", "http-equiv", "media", "name"],
+    ),
+    ("meter", &["high", "low", "max", "min", "optimum", "value"]),
+    (
+        "object",
+        &["data", "form", "height", "name", "type", "width"],
+    ),
+    ("ol", &["reversed", "start", "type"]),
+    ("optgroup", &["disabled", "label"]),
+    ("option", &["disabled", "label", "selected", "value"]),
+    ("output", &["for", "form", "name"]),
+    ("progress", &["max", "value"]),
+    ("q", &["cite"]),
+    (
+        "script",
+        &[
+            "async",
+            "blocking",
+            "crossorigin",
+            "defer",
+            "fetchpriority",
+            "integrity",
+            "nomodule",
+            "referrerpolicy",
+            "src",
+            "type",
+        ],
+    ),
+    (
+        "select",
+        &[
+            "autocomplete",
+            "disabled",
+            "form",
+            "multiple",
+            "name",
+            "required",
+            "size",
+        ],
+    ),
+    ("slot", &["name"]),
+    (
+        "source",
+        &["height", "media", "sizes", "src", "srcset", "type", "width"],
+    ),
+    ("style", &["blocking", "media"]),
+    ("td", &["colspan", "headers", "rowspan"]),
+    (
+        "template",
+        &[
+            "shadowrootclonable",
+            "shadowrootdelegatesfocus",
+            "shadowrootmode",
+            "shadowrootserializable",
+        ],
+    ),
+    (
+        "textarea",
+        &[
+            "autocomplete",
+            "cols",
+            "dirname",
+            "disabled",
+            "form",
+            "maxlength",
+            "minlength",
+            "name",
+            "placeholder",
+            "readonly",
+            "required",
+            "rows",
+            "wrap",
+        ],
+    ),
+    ("th", &["abbr", "colspan", "headers", "rowspan", "scope"]),
+    ("time", &["datetime"]),
+    ("track", &["default", "kind", "label", "src", "srclang"]),
+    (
+        "video",
+        &[
+            "autoplay",
+            "controls",
+            "crossorigin",
+            "height",
+            "loop",
+            "muted",
+            "playsinline",
+            "poster",
+            "preload",
+            "src",
+            "width",
+        ],
+    ),
+];
+
+/// Whether a `tag_name` element can have an attribute called `name`.
+pub const fn is_attribute(tag_name: &str, name: &str) -> bool {
+    if starts_with(name, "data-") || starts_with(name, "aria-") {
+        return true;
+    }
+    if contains(GLOBAL_ATTRIBUTES, name) {
+        return true;
+    }
+    let mut i = 0;
+    while i < ATTRIBUTES.len() {
+        let (tag, names) = ATTRIBUTES[i];
+        if eq(tag, tag_name) {
+            return contains(names, name);
+        }
+        i += 1;
+    }
+    false
+}
+
+const fn starts_with(s: &str, prefix: &str) -> bool {
+    s.len() > prefix.len() && eq(s.split_at(prefix.len()).0, prefix)
+}
+
+// loops by hand, since iterators and `==` on strings aren't allowed in a const fn
+const fn contains(names: &[&str], name: &str) -> bool {
+    let mut i = 0;
+    while i < names.len() {
+        if eq(names[i], name) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+const fn eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// An element in the namespace `uri`.
+pub fn el_ns(uri: &str, tag_name: &str) -> El {
+    keyed(tag_name, || {
+        document()
+            .create_element_ns(Some(uri), tag_name)
+            .expect("to create the element")
+    })
+}
+
+pub fn svg(tag_name: &str) -> El {
+    keyed(tag_name, || create_svg_element(tag_name))
+}
+
+pub fn math(tag_name: &str) -> El {
+    el_ns(MATHML_NS, tag_name)
+}
+
+/// For an element made some other way, to put in a tree.
+impl From<Element> for El {
+    fn from(element: Element) -> Self {
+        El {
+            element,
+            listeners: Vec::new(),
+            claimed: false,
+        }
+    }
+}
+
+/// So a void element can be a child.
+impl From<El<true>> for El {
+    fn from(el: El<true>) -> Self {
+        el.void()
+    }
+}
+
+const HYDRATION_KEY: &str = "data-hk";
+
+/// While rendering for hydration, or hydrating: the next element's number, and (when
+/// hydrating) the markup to claim elements from.
+struct Keys {
+    next: u32,
+    claim_from: Option<Element>,
+}
+
+thread_local! {
+    static KEYS: RefCell<Option<Keys>> = const { RefCell::new(None) };
+}
+
+/// Builds what `view` appends to a parent, as HTML with every element keyed, the way a
+/// server would render it for [`hydrate`].
+pub fn render_to_string(view: impl FnOnce(&mut App, &Element)) -> String {
+    let parent = create_element("div");
+    let mut app = App::new();
+    with_keys(None, || view(&mut app, &parent));
+    // before `app` goes, taking what it appended with it
+    parent.inner_html()
+}
+
+/// Runs `view` on `parent`'s server-rendered markup: the elements it builds are the ones
+/// already there, which it gets listeners on, and `app` removes on unmount.
+pub fn hydrate(app: &mut App, parent: &Element, view: impl FnOnce(&mut App, &Element)) {
+    with_keys(Some(parent.clone()), || view(app, parent));
+}
+
+fn with_keys(claim_from: Option<Element>, f: impl FnOnce()) {
+    KEYS.with(|keys| {
+        *keys.borrow_mut() = Some(Keys {
+            next: 0,
+            claim_from,
+        })
+    });
+    f();
+    KEYS.with(|keys| *keys.borrow_mut() = None);
+}
+
+fn hydrating() -> bool {
+    KEYS.with(|keys| {
+        matches!(
+            &*keys.borrow(),
+            Some(Keys {
+                claim_from: Some(_),
+                ..
+            })
+        )
+    })
+}
+
+/// A new element from `create`, or while hydrating, the server-rendered one in its place.
+fn keyed(tag_name: &str, create: impl FnOnce() -> Element) -> El {
+    let Some((key, claim_from)) = KEYS.with(|keys| {
+        let mut keys = keys.borrow_mut();
+        let keys = keys.as_mut()?;
+        keys.next += 1;
+        Some((keys.next - 1, keys.claim_from.clone()))
+    }) else {
+        return El::from(create());
+    };
+    if let Some(root) = claim_from {
+        let selector = format!("[{HYDRATION_KEY}=\"{key}\"]");
+        match root.query_selector(&selector).ok().flatten() {
+            Some(element) if element.local_name() == tag_name => {
+                return El {
+                    claimed: true,
+                    ..El::from(element)
+                };
+            }
+            Some(element) => log!(
+                "[hydrate] element {key} should be a <{tag_name}>, not a <{}>",
+                element.local_name()
+            ),
+            None => log!("[hydrate] there's no element {key} (a <{tag_name}>)"),
+        }
+    }
+    let element = create();
+    _ = element.set_attribute(HYDRATION_KEY, &key.to_string());
+    El::from(element)
+}
+
+impl El {
+    pub fn child(mut self, child: impl Into<El>) -> Self {
+        debug_assert!(
+            !is_void(&self.element.local_name()),
+            "<{}> can't have children",
+            self.element.local_name()
+        );
+        let child = child.into();
+        // (what's claimed is already where it goes, even if it was built before this)
+        if !(hydrating() && child.element.parent_element().as_ref() == Some(&self.element)) {
+            if child.claimed {
+                log!(
+                    "[hydrate] a <{}> was out of place",
+                    child.element.local_name()
+                );
+            }
+            self.element.append_child(&child.element).unwrap();
+        }
+        self.listeners.extend(child.listeners);
+        self
+    }
+}
+
+impl<const VOID: bool> El<VOID> {
+    /// This element, as an `El<V>`, for [`el!`].
+    pub(crate) fn void<const V: bool>(self) -> El<V> {
+        El {
+            element: self.element,
+            listeners: self.listeners,
+            claimed: self.claimed,
+        }
+    }
+
+    pub fn text(self, text: &str) -> Self {
+        if self.claimed && self.element.text_content().as_deref() == Some(text) {
+            return self;
+        }
+        if self.claimed {
+            log!(
+                "[hydrate] <{}>'s text should be {text:?}",
+                self.element.local_name()
+            );
+        }
+        self.element.set_text_content(Some(text));
+        self
+    }
+
+    pub fn attr(self, name: &str, value: &str) -> Self {
+        set_attribute(&self.element, name, value);
+        self
+    }
+
+    /// Sets `ns`'s attribute `name`, written with its prefix: `attr_ns(XLINK, "href", ..)`
+    /// sets `xlink:href`.
+    pub fn attr_ns(self, ns: AttrNs, name: &str, value: &str) -> Self {
+        let qualified = format!("{}:{name}", ns.prefix);
+        _ = self
+            .element
+            .set_attribute_ns(Some(ns.uri), &qualified, value);
+        self
+    }
+
+    /// Calls `cb` with every `event` event, once built; `event` may have modifiers, as
+    /// in `"keydown:enter"`.
+    ///
+    /// # Panics
+    ///
+    /// On a modifier that isn't one of those listed in the module docs.
+    pub fn on<E>(mut self, event: &'static str, mut cb: impl FnMut(E) + 'static) -> Self
+    where
+        E: JsCast + 'static,
+    {
+        let (event_name, modifiers) = parse_modifiers(event);
+        let mut done = false;
+        self.listeners.push((
+            self.element.clone(),
+            event_name,
+            modifiers,
+            Box::new(move |ev: Event| {
+                if done || !modifiers.admit(&ev) {
+                    return;
+                }
+                done = modifiers.once;
+                cb(ev.unchecked_into())
+            }),
+        ));
+        self
+    }
+
+    /// Attaches the listeners (for `app` to remove) and hands back the element.
+    pub fn build(self, app: &mut App) -> Element {
+        for (target, event_name, modifiers, cb) in self.listeners {
+            app.listen_with_options(&target, event_name, &modifiers.options(), cb);
+        }
+        self.element
+    }
+
+    /// Builds the element and appends it to `parent`, until `app` unmounts.
+    pub fn mount(self, app: &mut App, parent: &Element) -> Element {
+        let element = self.build(app);
+        if hydrating() && element.parent_element().as_ref() == Some(parent) {
+            app.adopt(&element);
+        } else {
+            app.append(parent, &element);
+        }
+        element
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_modifiers() {
+        assert_eq!(parse_modifiers("click"), ("click", Modifiers::default()));
+        assert_eq!(
+            parse_modifiers("keydown:enter:prevent:once"),
+            (
+                "keydown",
+                Modifiers {
+                    key: Some("Enter"),
+                    prevent: true,
+                    once: true,
+                    ..Modifiers::default()
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn knows_elements() {
+        assert!(is_element("button") && is_element("h6") && is_element("wbr"));
+        assert!(!is_element("buton") && !is_element("my-widget") && !is_element(""));
+        assert!(is_void("input") && !is_void("div"));
+        // every void element is an element
+        assert!(VOID.iter().all(|tag| is_element(tag)));
+    }
+
+    #[test]
+    fn knows_attributes() {
+        assert!(is_attribute("input", "placeholder") && is_attribute("label", "for"));
+        assert!(is_attribute("div", "id") && is_attribute("p", "aria-live"));
+        assert!(is_attribute("aside", "data-widget"));
+        assert!(!is_attribute("div", "href") && !is_attribute("input", "palceholder"));
+        // a prefix alone isn't an attribute
+        assert!(!is_attribute("div", "data-"));
+        // every element listed is an element
+        assert!(ATTRIBUTES.iter().all(|(tag, _)| is_element(tag)));
+    }
+
+    #[test]
+    #[should_panic(expected = ":prevnet")]
+    fn rejects_unknown_modifiers() {
+        parse_modifiers("submit:prevnet");
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod browser_tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use wasm_bindgen_test::*;
+    use web_sys::{KeyboardEvent, KeyboardEventInit};
+
+    use super::*;
+    use crate::dom::body;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn once_waits_for_an_event_it_lets_through() {
+        let mut app = App::new();
+        let calls = Rc::new(Cell::new(0));
+        let input = el("input")
+            .on("keydown:enter:once", {
+                let calls = calls.clone();
+                move |_: KeyboardEvent| calls.set(calls.get() + 1)
+            })
+            .mount(&mut app, &body().unwrap());
+        let press = |key| {
+            let mut init = KeyboardEventInit::new();
+            init.key(key);
+            let ev = KeyboardEvent::new_with_keyboard_event_init_dict("keydown", &init).unwrap();
+            input.dispatch_event(&ev).unwrap();
+        };
+        // filtered out, and not the one `:once` is about
+        press("a");
+        assert_eq!(calls.get(), 0);
+        press("Enter");
+        assert_eq!(calls.get(), 1);
+        press("Enter");
+        assert_eq!(calls.get(), 1);
+        app.unmount();
+    }
+}
@@ -0,0 +1,73 @@
+//! A counter rendered to HTML, as a server would send it, and then hydrated: the same
+//! view code runs again over that markup, claiming its elements instead of making new
+//! ones, and attaching the listeners a string of HTML can't carry.
+//!
+//! There's no server here, so the HTML comes from [`render_to_string`] in the browser;
+//! what the page does with it afterwards is the same either way.
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use web_sys::{Element, MouseEvent};
+
+use crate::{
+    app::App,
+    html::{el, hydrate, render_to_string},
+};
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+    el!(p)
+        .text("The counter below arrived as HTML, and only counts once it's hydrated.")
+        .mount(&mut app, parent);
+    let hydrate_button = el!(button).text("Hydrate").mount(&mut app, parent);
+    let host = el!(div).mount(&mut app, parent);
+
+    // the "server" renders the view as it'd first look
+    host.set_inner_html(&render_to_string(view));
+
+    // hydrated later, so with an `App` of its own, which goes when this one does
+    let hydrated = Rc::new(RefCell::new(None::<App>));
+    app.listen(&hydrate_button, "click", {
+        let hydrated = hydrated.clone();
+        let button = hydrate_button.clone();
+        move |_: MouseEvent| {
+            let mut hydrated = hydrated.borrow_mut();
+            if hydrated.is_none() {
+                let mut app = App::new();
+                hydrate(&mut app, &host, view);
+                *hydrated = Some(app);
+                button.set_text_content(Some("Hydrated"));
+            }
+        }
+    });
+    app.on_cleanup(move || drop(hydrated.take()));
+    app
+}
+
+/// A counter that starts at 0, the same on the server and in the browser.
+fn view(app: &mut App, parent: &Element) {
+    let count = Rc::new(Cell::new(0));
+    let output = el!(output).text("0").build(app);
+    let add = Rc::new({
+        let output = output.clone();
+        move |delta: i32| {
+            count.set(count.get() + delta);
+            output.set_text_content(Some(&count.get().to_string()));
+        }
+    });
+    el!(div)
+        .child(el!(button).text("-1").on("click", {
+            let add = add.clone();
+            move |_: MouseEvent| add(-1)
+        }))
+        .child(output)
+        .child(
+            el!(button)
+                .text("+1")
+                .on("click", move |_: MouseEvent| add(1)),
+        )
+        .mount(app, parent);
+}
@@ -0,0 +1,102 @@
+//! Just enough IndexedDB for a key/value store: one database with one object store,
+//! holding strings.
+//!
+//! IndexedDB reports back through `onsuccess`/`onerror` on each request; [`done`] turns
+//! that into a future. Anything that goes wrong comes back as a message, since callers
+//! only ever log it: storage that isn't there or fails just means nothing is kept.
+
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Event, IdbDatabase, IdbObjectStore, IdbRequest, IdbTransactionMode};
+
+use crate::dom::window;
+
+#[derive(Clone)]
+pub struct Store {
+    db: IdbDatabase,
+    name: &'static str,
+}
+
+impl Store {
+    /// Opens (and the first time, creates) database `db` with the object store `name`.
+    pub async fn open(db: &str, name: &'static str) -> Result<Store, String> {
+        let factory = window()
+            .indexed_db()
+            .ok()
+            .flatten()
+            .ok_or("IndexedDB isn't available")?;
+        let request = factory
+            .open_with_u32(db, 1)
+            .map_err(|error| format!("couldn't open {db}: {error:?}"))?;
+        // only called when the database is new (or, later, at a new version)
+        let upgrade = Closure::once_into_js({
+            let request = request.clone();
+            move |_: Event| {
+                if let Ok(db) = request.result() {
+                    _ = db.unchecked_into::<IdbDatabase>().create_object_store(name);
+                }
+            }
+        });
+        request.set_onupgradeneeded(Some(upgrade.unchecked_ref()));
+        let db = done(&request).await?.unchecked_into();
+        Ok(Store { db, name })
+    }
+
+    /// Every value, in key order.
+    pub async fn values(&self) -> Result<Vec<String>, String> {
+        let request = self
+            .object_store(IdbTransactionMode::Readonly)?
+            .get_all()
+            .map_err(message)?;
+        let values = done(&request).await?;
+        Ok(js_sys::Array::from(&values)
+            .iter()
+            .filter_map(|value| value.as_string())
+            .collect())
+    }
+
+    pub async fn put(&self, key: &str, value: &str) -> Result<(), String> {
+        let request = self
+            .object_store(IdbTransactionMode::Readwrite)?
+            .put_with_key(&JsValue::from_str(value), &JsValue::from_str(key))
+            .map_err(message)?;
+        done(&request).await.map(drop)
+    }
+
+    pub async fn delete(&self, key: &str) -> Result<(), String> {
+        let request = self
+            .object_store(IdbTransactionMode::Readwrite)?
+            .delete(&JsValue::from_str(key))
+            .map_err(message)?;
+        done(&request).await.map(drop)
+    }
+
+    fn object_store(&self, mode: IdbTransactionMode) -> Result<IdbObjectStore, String> {
+        self.db
+            .transaction_with_str_and_mode(self.name, mode)
+            .and_then(|transaction| transaction.object_store(self.name))
+            .map_err(message)
+    }
+}
+
+/// The request's result, once it has one.
+async fn done(request: &IdbRequest) -> Result<JsValue, String> {
+    let settled = js_sys::Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    let outcome = JsFuture::from(settled).await;
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+    match outcome {
+        Ok(_) => request.result().map_err(message),
+        Err(_) => Err(match request.error() {
+            Ok(Some(error)) => error.message(),
+            _ => "the request failed".to_string(),
+        }),
+    }
+}
+
+fn message(error: JsValue) -> String {
+    format!("{error:?}")
+}
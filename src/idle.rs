@@ -0,0 +1,205 @@
+//! Noticing when nobody's there.
+//!
+//! The user counts as idle after a stretch with no pointer, keyboard or scroll
+//! activity. Where the Idle Detection API is available *and* already permitted it's
+//! used as well, since it also sees activity outside the page (it never asks for
+//! permission itself: that needs a click and a good reason).
+//!
+//! While idle, `<html data-idle>` pauses CSS animations and Rust code that runs on a
+//! timer should check [`is_idle`] and skip its work.
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{AddEventListenerOptions, Element, Event, HtmlElement};
+
+use crate::{
+    app::App,
+    dom::{create_element, document, window},
+    time::Timeout,
+};
+
+const IDLE_ATTRIBUTE: &str = "data-idle";
+const IDLE_STYLE: &str = "html[data-idle] *, html[data-idle] *::before, \
+     html[data-idle] *::after { animation-play-state: paused !important; }";
+const ACTIVITY: [&str; 5] = ["pointermove", "pointerdown", "keydown", "wheel", "scroll"];
+/// The shortest threshold the Idle Detection API accepts.
+const MIN_DETECTOR_THRESHOLD: Duration = Duration::from_secs(60);
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends = web_sys::EventTarget)]
+    #[derive(Clone)]
+    type IdleDetector;
+
+    #[wasm_bindgen(constructor, catch)]
+    fn new() -> Result<IdleDetector, JsValue>;
+
+    /// `"active"` or `"idle"`.
+    #[wasm_bindgen(method, getter, js_name = userState)]
+    fn user_state(this: &IdleDetector) -> Option<String>;
+
+    /// Rejects unless the `idle-detection` permission has been granted.
+    #[wasm_bindgen(method)]
+    fn start(this: &IdleDetector, options: &js_sys::Object) -> js_sys::Promise;
+}
+
+type Listener = Rc<dyn Fn(bool)>;
+
+#[derive(Default)]
+struct Store {
+    idle: bool,
+    listeners: Vec<(usize, Listener)>,
+    next_listener: usize,
+}
+
+thread_local! {
+    static STORE: RefCell<Store> = RefCell::new(Store::default());
+}
+
+pub fn is_idle() -> bool {
+    STORE.with(|store| store.borrow().idle)
+}
+
+/// Calls `listener` with the new state whenever the user goes idle or comes back,
+/// until the app unmounts.
+pub fn subscribe(app: &mut App, listener: impl Fn(bool) + 'static) {
+    let id = STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        let id = store.next_listener;
+        store.next_listener += 1;
+        store.listeners.push((id, Rc::new(listener)));
+        id
+    });
+    app.on_cleanup(move || {
+        STORE.with(|store| {
+            store
+                .borrow_mut()
+                .listeners
+                .retain(|(other, _)| *other != id)
+        })
+    });
+}
+
+/// Starts watching for activity; the user is idle after `after` without any.
+pub fn install(app: &mut App, after: Duration) {
+    let style = create_element("style");
+    style.set_text_content(Some(IDLE_STYLE));
+    if let Some(head) = document().head() {
+        app.append(&head, &style);
+    }
+
+    // every bit of activity pushes the deadline back
+    let deadline = Rc::new(RefCell::new(None::<Timeout>));
+    let active = {
+        let deadline = deadline.clone();
+        move || {
+            set_idle(false);
+            *deadline.borrow_mut() = Some(Timeout::new(after, || set_idle(true)));
+        }
+    };
+    active();
+    // scroll doesn't bubble, and none of these need to block scrolling
+    let mut options = AddEventListenerOptions::new();
+    options.capture(true).passive(true);
+    for event in ACTIVITY {
+        let active = active.clone();
+        app.listen_with_options(&window(), event, &options, move |_: Event| active());
+    }
+    app.on_cleanup(move || {
+        deadline.borrow_mut().take();
+        set_idle(false);
+    });
+
+    app.spawn(async move {
+        let Some(detector) = start_detector(after.max(MIN_DETECTOR_THRESHOLD)).await else {
+            return;
+        };
+        // the listener belongs to this task: it goes when the task is aborted
+        let mut listeners = App::new();
+        listeners.listen(&detector.clone(), "change", move |_: Event| match detector
+            .user_state()
+            .as_deref()
+        {
+            Some("idle") => set_idle(true),
+            _ => active(),
+        });
+        futures::future::pending::<()>().await;
+        drop(listeners);
+    });
+}
+
+async fn start_detector(threshold: Duration) -> Option<IdleDetector> {
+    let available = js_sys::Reflect::has(&window(), &"IdleDetector".into()).unwrap_or(false);
+    if !available {
+        return None;
+    }
+    let detector = IdleDetector::new().ok()?;
+    let options = js_sys::Object::new();
+    _ = js_sys::Reflect::set(
+        &options,
+        &"threshold".into(),
+        &(threshold.as_millis() as f64).into(),
+    );
+    JsFuture::from(detector.start(&options)).await.ok()?;
+    Some(detector)
+}
+
+fn set_idle(idle: bool) {
+    let listeners = STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        if store.idle == idle {
+            return Vec::new();
+        }
+        store.idle = idle;
+        store
+            .listeners
+            .iter()
+            .map(|(_, listener)| listener.clone())
+            .collect()
+    });
+    if let Some(root) = document().document_element() {
+        _ = root.toggle_attribute_with_force(IDLE_ATTRIBUTE, idle);
+    }
+    for listener in listeners {
+        listener(idle);
+    }
+}
+
+/// An "are you still there?" overlay that covers the page while the user is idle.
+/// Any activity dismisses it, so the button is only there to have something to press.
+pub fn mount_overlay(app: &mut App, parent: &Element) {
+    let overlay = create_element("div").unchecked_into::<HtmlElement>();
+    _ = overlay.set_attribute("role", "alertdialog");
+    _ = overlay.set_attribute("aria-labelledby", "idle-title");
+    _ = overlay.set_attribute(
+        "style",
+        "position: fixed; inset: 0; z-index: 1000; place-content: center; \
+         gap: 1em; text-align: center; background: rgba(255, 255, 255, 0.9)",
+    );
+    let title = create_element("p");
+    title.set_id("idle-title");
+    _ = title.set_attribute("style", "font-size: 1.5em; margin: 0");
+    title.set_text_content(Some("Are you still there?"));
+    let note = create_element("p");
+    note.set_text_content(Some("Everything's paused until you're back."));
+    let resume = create_element("button");
+    resume.set_text_content(Some("I'm here"));
+    overlay.append_child(&title).unwrap();
+    overlay.append_child(&note).unwrap();
+    overlay.append_child(&resume).unwrap();
+    app.append(parent, &overlay);
+
+    let render = move |idle: bool| {
+        // not `hidden`: the overlay's own `display` would win over it
+        _ = overlay
+            .style()
+            .set_property("display", if idle { "grid" } else { "none" });
+        if idle {
+            _ = resume.unchecked_ref::<HtmlElement>().focus();
+        }
+    };
+    render(is_idle());
+    subscribe(app, render);
+}
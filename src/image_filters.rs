@@ -0,0 +1,367 @@
+//! The filters example: an image on a canvas, run through [`filters`] in Rust.
+//!
+//! The canvas starts with a generated picture, or shows whichever image is picked.
+//! Each filter starts again from the original pixels: they come out of the canvas as
+//! `ImageData`, get filtered, and go back in. Big images can be filtered on a
+//! [`Worker`] instead, so the page keeps responding while it works.
+
+use std::{cell::RefCell, rc::Rc};
+
+use js_sys::{Array, Reflect, Uint8ClampedArray};
+use wasm_bindgen::{Clamped, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    CanvasRenderingContext2d, Element, Event, HtmlCanvasElement, HtmlImageElement,
+    HtmlInputElement, HtmlSelectElement, ImageData, Url,
+};
+
+use crate::{
+    app::App,
+    dom::{create_element, log, spawn_local, window},
+    filters::Filter,
+    worker::{get_or_spawn, Reply, Worker},
+};
+
+/// Images with more pixels than this are filtered on the worker by default.
+const LARGE: u32 = 1_000_000;
+/// Bigger images are scaled down to fit, to keep the buffers to a sensible size.
+const MAX_SIDE: u32 = 4096;
+const SAMPLE_WIDTH: u32 = 480;
+const SAMPLE_HEIGHT: u32 = 320;
+
+/// The image as loaded, which every filter starts from.
+struct Original {
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let section = create_element("section");
+    let controls = create_element("p");
+    _ = controls.set_attribute(
+        "style",
+        "display: flex; flex-wrap: wrap; gap: 1em; align-items: center",
+    );
+    let picker = create_element("input").unchecked_into::<HtmlInputElement>();
+    picker.set_type("file");
+    picker.set_accept("image/*");
+    _ = picker.set_attribute("aria-label", "Image");
+    let filter = create_element("select").unchecked_into::<HtmlSelectElement>();
+    _ = filter.set_attribute("aria-label", "Filter");
+    filter.set_inner_html(
+        "<option value=none>No filter</option>\
+         <option value=grayscale>Grayscale</option>\
+         <option value=blur>Blur</option>\
+         <option value=threshold>Threshold</option>",
+    );
+    let amount = create_element("input").unchecked_into::<HtmlInputElement>();
+    amount.set_type("range");
+    _ = amount.set_attribute("aria-label", "Amount");
+    amount.set_disabled(true);
+    let offload = create_element("input").unchecked_into::<HtmlInputElement>();
+    offload.set_type("checkbox");
+    let offload_label = create_element("label");
+    offload_label.append_child(&offload).unwrap();
+    _ = offload_label.append_with_str_1(" Use a worker");
+    for el in [
+        picker.unchecked_ref::<Element>(),
+        filter.unchecked_ref(),
+        amount.unchecked_ref(),
+        &offload_label,
+    ] {
+        controls.append_child(el).unwrap();
+    }
+    let canvas = create_element("canvas").unchecked_into::<HtmlCanvasElement>();
+    _ = canvas.set_attribute("style", "max-width: 100%; height: auto");
+    let ctx = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .expect("a 2d canvas context")
+        .unchecked_into::<CanvasRenderingContext2d>();
+    let status = create_element("p");
+    _ = status.set_attribute("aria-live", "polite");
+    section.append_child(&controls).unwrap();
+    section.append_child(&canvas).unwrap();
+    section.append_child(&status).unwrap();
+    app.append(parent, &section);
+
+    draw_sample(&canvas, &ctx);
+    let original = Rc::new(RefCell::new(read(&canvas, &ctx)));
+    // started the first time it's needed
+    let worker = Rc::new(RefCell::new(None::<Rc<Worker>>));
+    // bumped on every run, so a slow run finishing late doesn't overwrite a newer one
+    let generation = Rc::new(RefCell::new(0));
+
+    let run = Rc::new({
+        let (filter, amount, offload) = (filter.clone(), amount.clone(), offload.clone());
+        let (canvas, ctx, status) = (canvas.clone(), ctx.clone(), status.clone());
+        let original = original.clone();
+        move || {
+            let chosen = chosen(&filter.value(), &amount.value());
+            let Original {
+                pixels,
+                width,
+                height,
+            } = &*original.borrow();
+            let (width, height) = (*width, *height);
+            let mut pixels = pixels.clone();
+            *generation.borrow_mut() += 1;
+            let this_run = *generation.borrow();
+
+            let Some(chosen) = chosen else {
+                write(&canvas, &ctx, &pixels, width, height);
+                status.set_text_content(None);
+                return;
+            };
+            if !offload.checked() {
+                let started = now();
+                chosen.apply(&mut pixels, width as usize, height as usize);
+                write(&canvas, &ctx, &pixels, width, height);
+                status.set_text_content(Some(&format!(
+                    "{chosen:?} on {width}×{height} took {:.0} ms",
+                    now() - started
+                )));
+                return;
+            }
+
+            let worker = match get_or_spawn(&worker) {
+                Ok(worker) => worker,
+                Err(e) => {
+                    status.set_text_content(Some(&format!("Couldn't start a worker: {e}")));
+                    return;
+                }
+            };
+            status.set_text_content(Some(&format!(
+                "{chosen:?} on {width}×{height}, in a worker…"
+            )));
+            let (canvas, ctx, status) = (canvas.clone(), ctx.clone(), status.clone());
+            let generation = generation.clone();
+            spawn_local(async move {
+                let started = now();
+                let pixels = Uint8ClampedArray::from(&pixels[..]);
+                let argument = job_argument(chosen, width, height, &pixels);
+                let result = worker
+                    .call("filter", &argument, &Array::of1(&pixels.buffer()))
+                    .await;
+                if *generation.borrow() != this_run {
+                    return;
+                }
+                match result {
+                    Ok(pixels) => {
+                        let pixels = pixels.unchecked_into::<Uint8ClampedArray>().to_vec();
+                        write(&canvas, &ctx, &pixels, width, height);
+                        status.set_text_content(Some(&format!(
+                            "{chosen:?} on {width}×{height} took {:.0} ms, in a worker",
+                            now() - started
+                        )));
+                    }
+                    Err(e) => status.set_text_content(Some(&format!("The worker failed: {e}"))),
+                }
+            });
+        }
+    });
+
+    app.listen(&filter.clone(), "change", {
+        let amount = amount.clone();
+        let run = run.clone();
+        move |_: Event| {
+            let (min, max, value, disabled) = match filter.value().as_str() {
+                "blur" => ("1", "20", "4", false),
+                "threshold" => ("0", "255", "128", false),
+                _ => ("0", "0", "0", true),
+            };
+            amount.set_min(min);
+            amount.set_max(max);
+            amount.set_value(value);
+            amount.set_disabled(disabled);
+            run();
+        }
+    });
+    // on release rather than on every step, since a big blur takes a while
+    app.listen(&amount, "change", {
+        let run = run.clone();
+        move |_: Event| run()
+    });
+    app.listen(&offload, "change", {
+        let run = run.clone();
+        move |_: Event| run()
+    });
+
+    app.listen(&picker.clone(), "change", move |_: Event| {
+        let Some(file) = picker.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        let (canvas, ctx, status) = (canvas.clone(), ctx.clone(), status.clone());
+        let (original, offload, run) = (original.clone(), offload.clone(), run.clone());
+        spawn_local(async move {
+            match load(&file).await {
+                Ok(image) => {
+                    draw_scaled(&canvas, &ctx, &image);
+                    *original.borrow_mut() = read(&canvas, &ctx);
+                    offload.set_checked(canvas.width() * canvas.height() > LARGE);
+                    run();
+                }
+                Err(e) => status.set_text_content(Some(&format!("Couldn't load that image: {e}"))),
+            }
+        });
+    });
+
+    app
+}
+
+/// The worker's side of filtering: `{ filter, amount, width, height, pixels }` in,
+/// the filtered pixels out.
+pub fn filter_job(argument: JsValue) -> Result<Reply, String> {
+    let get = |key: &str| Reflect::get(&argument, &key.into()).unwrap_or(JsValue::UNDEFINED);
+    let name = get("filter").as_string().unwrap_or_default();
+    let amount = get("amount").as_string().unwrap_or_default();
+    let width = get("width").as_f64().unwrap_or(0.0) as usize;
+    let height = get("height").as_f64().unwrap_or(0.0) as usize;
+    let pixels = get("pixels")
+        .dyn_into::<Uint8ClampedArray>()
+        .map_err(|_| "no pixels")?;
+    let filter = chosen(&name, &amount).ok_or_else(|| format!("no such filter: {name}"))?;
+    if pixels.length() as usize != width * height * 4 {
+        return Err("the pixels don't fit the size".to_string());
+    }
+
+    let mut pixels = pixels.to_vec();
+    filter.apply(&mut pixels, width, height);
+    let pixels = Uint8ClampedArray::from(&pixels[..]);
+    Ok(Reply {
+        transfer: Array::of1(&pixels.buffer()),
+        value: pixels.into(),
+    })
+}
+
+fn job_argument(filter: Filter, width: u32, height: u32, pixels: &Uint8ClampedArray) -> JsValue {
+    let (name, amount) = match filter {
+        Filter::Grayscale => ("grayscale", String::new()),
+        Filter::Blur(radius) => ("blur", radius.to_string()),
+        Filter::Threshold(level) => ("threshold", level.to_string()),
+    };
+    let argument = js_sys::Object::new();
+    for (key, value) in [
+        ("filter", JsValue::from(name)),
+        ("amount", amount.into()),
+        ("width", width.into()),
+        ("height", height.into()),
+        ("pixels", pixels.into()),
+    ] {
+        _ = Reflect::set(&argument, &key.into(), &value);
+    }
+    argument.into()
+}
+
+/// The filter the controls have picked, if any.
+fn chosen(name: &str, amount: &str) -> Option<Filter> {
+    match name {
+        "grayscale" => Some(Filter::Grayscale),
+        "blur" => Some(Filter::Blur(amount.parse().unwrap_or(4))),
+        "threshold" => Some(Filter::Threshold(amount.parse().unwrap_or(128))),
+        _ => None,
+    }
+}
+
+async fn load(file: &web_sys::File) -> Result<HtmlImageElement, String> {
+    let url = Url::create_object_url_with_blob(file).map_err(|e| format!("{e:?}"))?;
+    let image = HtmlImageElement::new().map_err(|e| format!("{e:?}"))?;
+    image.set_src(&url);
+    let decoded = JsFuture::from(image.decode()).await;
+    _ = Url::revoke_object_url(&url);
+    decoded.map_err(|_| "it isn't an image the browser can read".to_string())?;
+    Ok(image)
+}
+
+fn draw_scaled(
+    canvas: &HtmlCanvasElement,
+    ctx: &CanvasRenderingContext2d,
+    image: &HtmlImageElement,
+) {
+    let (width, height) = (image.natural_width(), image.natural_height());
+    let scale = (MAX_SIDE as f64 / width.max(height) as f64).min(1.0);
+    let (width, height) = (
+        (width as f64 * scale) as u32,
+        (height as f64 * scale) as u32,
+    );
+    canvas.set_width(width);
+    canvas.set_height(height);
+    if ctx
+        .draw_image_with_html_image_element_and_dw_and_dh(
+            image,
+            0.0,
+            0.0,
+            width as f64,
+            height as f64,
+        )
+        .is_err()
+    {
+        log!("couldn't draw the image");
+    }
+}
+
+/// Something with colors and edges to try the filters on.
+fn draw_sample(canvas: &HtmlCanvasElement, ctx: &CanvasRenderingContext2d) {
+    canvas.set_width(SAMPLE_WIDTH);
+    canvas.set_height(SAMPLE_HEIGHT);
+    let (width, height) = (SAMPLE_WIDTH as f64, SAMPLE_HEIGHT as f64);
+    let sky = ctx.create_linear_gradient(0.0, 0.0, 0.0, height);
+    _ = sky.add_color_stop(0.0, "#4a90d9");
+    _ = sky.add_color_stop(1.0, "#f5c16c");
+    ctx.set_fill_style(&sky);
+    ctx.fill_rect(0.0, 0.0, width, height);
+    ctx.set_fill_style(&"#ffe066".into());
+    ctx.begin_path();
+    _ = ctx.arc(width * 0.75, height * 0.3, 40.0, 0.0, std::f64::consts::TAU);
+    ctx.fill();
+    ctx.set_fill_style(&"#2d6a4f".into());
+    ctx.begin_path();
+    ctx.move_to(0.0, height);
+    ctx.line_to(width * 0.3, height * 0.45);
+    ctx.line_to(width * 0.55, height * 0.8);
+    ctx.line_to(width * 0.8, height * 0.55);
+    ctx.line_to(width, height);
+    ctx.fill();
+    ctx.set_fill_style(&"white".into());
+    ctx.set_font("bold 32px sans-serif");
+    _ = ctx.fill_text("Rust + Wasm", 24.0, 56.0);
+}
+
+fn read(canvas: &HtmlCanvasElement, ctx: &CanvasRenderingContext2d) -> Original {
+    let (width, height) = (canvas.width(), canvas.height());
+    let pixels = ctx
+        .get_image_data(0.0, 0.0, width as f64, height as f64)
+        .map(|data| data.data().0)
+        .unwrap_or_default();
+    Original {
+        pixels,
+        width,
+        height,
+    }
+}
+
+fn write(
+    canvas: &HtmlCanvasElement,
+    ctx: &CanvasRenderingContext2d,
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+) {
+    canvas.set_width(width);
+    canvas.set_height(height);
+    match ImageData::new_with_u8_clamped_array_and_sh(Clamped(pixels), width, height) {
+        Ok(data) => _ = ctx.put_image_data(&data, 0.0, 0.0),
+        Err(e) => log!("couldn't make ImageData: {e:?}"),
+    }
+}
+
+/// Milliseconds, to a fraction of one.
+fn now() -> f64 {
+    window()
+        .performance()
+        .map_or(0.0, |performance| performance.now())
+}
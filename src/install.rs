@@ -0,0 +1,119 @@
+//! "Install app" support for browsers that fire `beforeinstallprompt`.
+//!
+//! The browser's own install UI is easy to miss, so we stash the event it gives us
+//! and show our own button that replays the prompt when clicked.
+
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Element, Event, MouseEvent};
+
+use crate::{
+    app::App,
+    dom::{create_element, log, spawn_local, window},
+    toast,
+};
+
+#[wasm_bindgen]
+extern "C" {
+    /// Not in `web_sys`, since it's Chromium-only.
+    #[wasm_bindgen(extends = Event)]
+    pub type BeforeInstallPromptEvent;
+
+    /// Shows the install prompt; can only be called once per event.
+    #[wasm_bindgen(method, catch)]
+    fn prompt(this: &BeforeInstallPromptEvent) -> Result<js_sys::Promise, JsValue>;
+
+    /// Resolves to `{ outcome: "accepted" | "dismissed", platform }`.
+    #[wasm_bindgen(method, getter, js_name = userChoice)]
+    fn user_choice(this: &BeforeInstallPromptEvent) -> js_sys::Promise;
+}
+
+#[derive(Default)]
+pub enum InstallState {
+    /// The browser hasn't offered installation (yet).
+    #[default]
+    Unavailable,
+    Available(BeforeInstallPromptEvent),
+    Installed,
+}
+
+/// Adds an "Install app" button to `parent` that's only visible while installing
+/// is possible.
+pub fn mount_button(app: &mut App, parent: &Element) {
+    let state = Rc::new(RefCell::new(InstallState::default()));
+
+    let button = create_element("button");
+    button.set_text_content(Some("Install app"));
+    _ = button.set_attribute("hidden", "");
+    app.append(parent, &button);
+
+    let render = {
+        let button = button.clone();
+        let state = state.clone();
+        move || {
+            let available = matches!(*state.borrow(), InstallState::Available(_));
+            _ = button.toggle_attribute_with_force("hidden", !available);
+        }
+    };
+
+    app.listen(&window(), "beforeinstallprompt", {
+        let state = state.clone();
+        let render = render.clone();
+        move |ev: BeforeInstallPromptEvent| {
+            // keeps the browser from showing its own mini-infobar
+            ev.prevent_default();
+            *state.borrow_mut() = InstallState::Available(ev);
+            render();
+        }
+    });
+
+    app.listen(&window(), "appinstalled", {
+        let state = state.clone();
+        let render = render.clone();
+        move |_: Event| {
+            *state.borrow_mut() = InstallState::Installed;
+            render();
+            toast::info("Installed!");
+        }
+    });
+
+    app.listen(&button, "click", move |_: MouseEvent| {
+        // the event can only prompt once, so take it out of the state
+        let InstallState::Available(ev) = std::mem::take(&mut *state.borrow_mut()) else {
+            return;
+        };
+        render();
+
+        let state = state.clone();
+        let render = render.clone();
+        spawn_local(async move {
+            let outcome = match prompt_and_wait(&ev).await {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    log!("install prompt failed: {e:?}");
+                    return;
+                }
+            };
+            // on "dismissed" the browser fires another `beforeinstallprompt` later
+            if outcome != "accepted" {
+                return;
+            }
+            // `appinstalled` may already have been handled by now
+            let mut state = state.borrow_mut();
+            if !matches!(*state, InstallState::Installed) {
+                *state = InstallState::Unavailable;
+            }
+            drop(state);
+            render();
+        });
+    });
+}
+
+async fn prompt_and_wait(ev: &BeforeInstallPromptEvent) -> Result<String, JsValue> {
+    JsFuture::from(ev.prompt()?).await?;
+    let choice = JsFuture::from(ev.user_choice()).await?;
+    let outcome = js_sys::Reflect::get(&choice, &"outcome".into())?;
+    Ok(outcome.as_string().unwrap_or_default())
+}
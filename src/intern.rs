@@ -0,0 +1,107 @@
+//! Interning for the strings render paths hand to the DOM over and over: tag names,
+//! attribute names, event names and class lists.
+//!
+//! Every `&str` passed to a `web_sys` method is copied into a new JS string on its way
+//! across. With wasm-bindgen's `enable-interning` feature on, a string given to
+//! [`wasm_bindgen::intern`] is made into a JS string once and kept, and later copies
+//! of an equal string are looked up instead of made. [`intern`] does that, and counts
+//! how often the string was already there, for the debug panel.
+//!
+//! Interned strings are never freed, so only the first [`CAPACITY`] distinct ones are
+//! kept; anything after that is passed through as it is.
+
+use std::{cell::RefCell, collections::HashSet};
+
+/// How many distinct strings are kept.
+const CAPACITY: usize = 1_000;
+
+/// How well interning is going.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// Distinct strings kept.
+    pub interned: usize,
+    /// Times a string was already kept.
+    pub hits: u64,
+    /// Times it wasn't, whether it was kept then or there was no room.
+    pub misses: u64,
+}
+
+impl Stats {
+    /// The share of lookups that were hits, from 0 to 1.
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            total => self.hits as f64 / total as f64,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Cache {
+    strings: HashSet<String>,
+    stats: Stats,
+}
+
+impl Cache {
+    /// Looks `s` up, and says whether it's new and was kept.
+    fn lookup(&mut self, s: &str) -> bool {
+        if self.strings.contains(s) {
+            self.stats.hits += 1;
+            return false;
+        }
+        self.stats.misses += 1;
+        if self.strings.len() >= CAPACITY {
+            return false;
+        }
+        self.strings.insert(s.to_string());
+        self.stats.interned = self.strings.len();
+        true
+    }
+}
+
+thread_local! {
+    static CACHE: RefCell<Cache> = RefCell::new(Cache::default());
+}
+
+/// `s`, which from now on crosses over to JS without being copied each time.
+pub fn intern(s: &str) -> &str {
+    let new = CACHE.with(|cache| cache.borrow_mut().lookup(s));
+    if new {
+        wasm_bindgen::intern(s);
+    }
+    s
+}
+
+pub fn stats() -> Stats {
+    CACHE.with(|cache| cache.borrow().stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_hits_and_stops_at_capacity() {
+        let mut cache = Cache::default();
+        assert!(cache.lookup("class"));
+        assert!(!cache.lookup("class"));
+        assert!(cache.lookup("click"));
+        assert_eq!(
+            cache.stats,
+            Stats {
+                interned: 2,
+                hits: 1,
+                misses: 2,
+            }
+        );
+        assert!((cache.stats.hit_rate() - 1.0 / 3.0).abs() < 1e-9);
+
+        for i in cache.strings.len()..CAPACITY {
+            assert!(cache.lookup(&i.to_string()));
+        }
+        assert!(!cache.lookup("one too many"));
+        assert!(!cache.lookup("one too many"));
+        assert_eq!(cache.stats.interned, CAPACITY);
+        assert_eq!(cache.stats.misses, CAPACITY as u64 + 2);
+    }
+}
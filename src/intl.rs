@@ -0,0 +1,155 @@
+//! Thin wrappers over `Intl.DateTimeFormat` and `Intl.RelativeTimeFormat`, so dates
+//! and "3 minutes ago" are shown in the user's locale, plurals and all.
+//!
+//! `RelativeTimeFormat` isn't in our version of `js_sys`, so it's bound by hand here.
+
+use std::time::Duration;
+
+use futures::StreamExt;
+use js_sys::{Array, Date, Function, Object, Reflect};
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
+use web_sys::Element;
+
+use crate::{app::App, dom::create_element, time};
+
+/// How often [`keep_relative_times_fresh`] re-renders; the smallest unit shown is a
+/// minute, so there's no point doing it more often.
+const REFRESH_PERIOD: Duration = Duration::from_secs(60);
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = Intl, js_name = RelativeTimeFormat)]
+    type JsRelativeTimeFormat;
+
+    #[wasm_bindgen(constructor, js_namespace = Intl, js_class = "RelativeTimeFormat")]
+    fn new(locales: &Array, options: &Object) -> JsRelativeTimeFormat;
+
+    #[wasm_bindgen(method, js_class = "RelativeTimeFormat")]
+    fn format(this: &JsRelativeTimeFormat, value: f64, unit: &str) -> String;
+}
+
+/// A reusable formatter; building one is much slower than using it, so keep it around.
+pub struct DateTimeFormat {
+    format: Function,
+}
+
+impl DateTimeFormat {
+    /// `options` are `Intl.DateTimeFormat` options, e.g. `[("hour", "2-digit")]`.
+    pub fn new(options: &[(&str, &str)]) -> Self {
+        let object = Object::new();
+        for (key, value) in options {
+            _ = Reflect::set(&object, &JsValue::from_str(key), &JsValue::from_str(value));
+        }
+        // no locales means the browser's default locale
+        let formatter = js_sys::Intl::DateTimeFormat::new(&Array::new(), &object);
+        DateTimeFormat {
+            format: formatter.format(),
+        }
+    }
+
+    /// Hours, minutes and seconds, e.g. `14:03:27` or `2:03:27 PM`.
+    pub fn time() -> Self {
+        Self::new(&[
+            ("hour", "numeric"),
+            ("minute", "2-digit"),
+            ("second", "2-digit"),
+        ])
+    }
+
+    /// The full date, e.g. `Thursday, 15 October 2026`.
+    pub fn long_date() -> Self {
+        Self::new(&[("dateStyle", "full")])
+    }
+
+    pub fn format(&self, date: &Date) -> String {
+        self.format
+            .call1(&JsValue::NULL, date)
+            .ok()
+            .and_then(|formatted| formatted.as_string())
+            .unwrap_or_default()
+    }
+}
+
+thread_local! {
+    static RELATIVE: JsRelativeTimeFormat = {
+        let options = Object::new();
+        // "yesterday" and "now" rather than "1 day ago" and "in 0 seconds"
+        _ = Reflect::set(&options, &"numeric".into(), &"auto".into());
+        JsRelativeTimeFormat::new(&Array::new(), &options)
+    };
+    static TIME: DateTimeFormat = DateTimeFormat::time();
+}
+
+/// How long ago (or how far ahead) `timestamp`, in milliseconds since the epoch, is
+/// from now, in the largest unit that fits: "3 minutes ago", "in 2 days", "now".
+pub fn format_relative_time(timestamp: f64) -> String {
+    let (value, unit) = relative_unit((timestamp - Date::now()) / 1000.0);
+    RELATIVE.with(|format| format.format(value, unit))
+}
+
+/// Seconds from now as a whole number of the largest unit that fits, negative for
+/// the past. Anything under a minute is "now", since that's as often as it updates.
+fn relative_unit(seconds: f64) -> (f64, &'static str) {
+    const UNITS: &[(f64, &str)] = &[
+        (60.0 * 60.0 * 24.0 * 365.0, "year"),
+        (60.0 * 60.0 * 24.0 * 30.0, "month"),
+        (60.0 * 60.0 * 24.0 * 7.0, "week"),
+        (60.0 * 60.0 * 24.0, "day"),
+        (60.0 * 60.0, "hour"),
+        (60.0, "minute"),
+    ];
+    for (length, unit) in UNITS {
+        if seconds.abs() >= *length {
+            return ((seconds / length).trunc(), unit);
+        }
+    }
+    (0.0, "second")
+}
+
+/// A `<time>` showing how long ago `timestamp` was, with the exact time as its tooltip.
+/// [`keep_relative_times_fresh`] keeps it up to date.
+pub fn relative_time(timestamp: f64) -> Element {
+    let date = Date::new(&JsValue::from_f64(timestamp));
+    let time = create_element("time");
+    _ = time.set_attribute("datetime", &String::from(date.to_iso_string()));
+    _ = time.set_attribute("data-timestamp", &timestamp.to_string());
+    _ = time.set_attribute("title", &TIME.with(|format| format.format(&date)));
+    time.set_text_content(Some(&format_relative_time(timestamp)));
+    time
+}
+
+/// Re-renders every [`relative_time`] inside `root` once a minute, until `app` unmounts.
+pub fn keep_relative_times_fresh(app: &mut App, root: &Element) {
+    let root = root.clone();
+    app.spawn(async move {
+        let mut ticks = time::every(REFRESH_PERIOD);
+        while ticks.next().await.is_some() {
+            let Ok(times) = root.query_selector_all("time[data-timestamp]") else {
+                continue;
+            };
+            for time in (0..times.length()).filter_map(|i| times.get(i)) {
+                let timestamp = time
+                    .dyn_ref::<Element>()
+                    .and_then(|time| time.get_attribute("data-timestamp"))
+                    .and_then(|timestamp| timestamp.parse().ok());
+                if let Some(timestamp) = timestamp {
+                    time.set_text_content(Some(&format_relative_time(timestamp)));
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_largest_unit_that_fits() {
+        assert_eq!(relative_unit(-30.0), (0.0, "second"));
+        assert_eq!(relative_unit(-180.0), (-3.0, "minute"));
+        assert_eq!(relative_unit(-(2.0 * 3600.0 + 1800.0)), (-2.0, "hour"));
+        assert_eq!(relative_unit(86_400.0 * 3.0), (3.0, "day"));
+        assert_eq!(relative_unit(-86_400.0 * 400.0), (-1.0, "year"));
+    }
+}
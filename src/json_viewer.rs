@@ -0,0 +1,289 @@
+//! A collapsible, editable view of any JSON value.
+//!
+//! Objects and arrays are `<details>` that can be folded; which ones are folded
+//! survives `set_value`, so a live view doesn't spring open on every update. Clicking a
+//! value edits it in place (type JSON: `42`, `true`, `null`, `"text"`; anything that
+//! isn't valid JSON is taken as a string), and the ⧉ button copies the value's path.
+
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+use serde_json::Value;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    AddEventListenerOptions, Element, Event, HtmlDetailsElement, HtmlElement, HtmlInputElement,
+    KeyboardEvent, MouseEvent,
+};
+
+use crate::{
+    app::App,
+    clipboard,
+    dom::{create_element, log, spawn_local},
+    toast,
+};
+
+type EditHandler = Box<dyn Fn(&JsonViewer, Value)>;
+
+pub struct JsonViewer {
+    root: Element,
+    value: RefCell<Value>,
+    /// JSON pointers of the objects and arrays that are folded.
+    collapsed: RefCell<HashSet<String>>,
+    /// The open editor and the JSON pointer of the value it edits.
+    editing: RefCell<Option<(String, HtmlInputElement)>>,
+    on_edit: EditHandler,
+}
+
+impl JsonViewer {
+    /// `on_edit` gets the viewer and the whole document with the edit applied; the
+    /// viewer only shows it once the owner passes it back to `set_value` (it may well
+    /// reject it).
+    pub fn mount(
+        app: &mut App,
+        parent: &Element,
+        value: Value,
+        on_edit: impl Fn(&JsonViewer, Value) + 'static,
+    ) -> Rc<Self> {
+        let root = create_element("div");
+        _ = root.set_attribute("style", "font-family: monospace; line-height: 1.6");
+        app.append(parent, &root);
+
+        let viewer = Rc::new(JsonViewer {
+            root,
+            value: RefCell::new(Value::Null),
+            collapsed: Default::default(),
+            editing: RefCell::new(None),
+            on_edit: Box::new(on_edit),
+        });
+        viewer.set_value(value);
+
+        // `toggle` doesn't bubble, so catch it on the way down instead
+        let mut capture = AddEventListenerOptions::new();
+        capture.capture(true);
+        app.listen_with_options(&viewer.root, "toggle", &capture, {
+            let viewer = viewer.clone();
+            move |ev: Event| {
+                let Some(details) = ev
+                    .target()
+                    .and_then(|target| target.dyn_into::<HtmlDetailsElement>().ok())
+                else {
+                    return;
+                };
+                let pointer = details.get_attribute("data-pointer").unwrap_or_default();
+                let mut collapsed = viewer.collapsed.borrow_mut();
+                if details.open() {
+                    collapsed.remove(&pointer);
+                } else {
+                    collapsed.insert(pointer);
+                }
+            }
+        });
+
+        app.listen(&viewer.root, "click", {
+            let viewer = viewer.clone();
+            move |ev: MouseEvent| {
+                let Some(target) = ev
+                    .target()
+                    .and_then(|target| target.dyn_into::<Element>().ok())
+                else {
+                    return;
+                };
+                if let Some(path) = target.get_attribute("data-copy") {
+                    spawn_local(async move {
+                        match clipboard::write_text(&path).await {
+                            Ok(()) => toast::info(&format!("Copied {path}")),
+                            Err(e) => log!("couldn't copy to the clipboard: {e:?}"),
+                        }
+                    });
+                } else if target.has_attribute("data-edit") {
+                    viewer.edit(&target);
+                }
+            }
+        });
+
+        app.listen(&viewer.root, "keydown", {
+            let viewer = viewer.clone();
+            move |ev: KeyboardEvent| {
+                let Some(target) = ev
+                    .target()
+                    .and_then(|target| target.dyn_into::<Element>().ok())
+                else {
+                    return;
+                };
+                let editing = target.has_type::<HtmlInputElement>();
+                match ev.key().as_str() {
+                    "Enter" if editing => viewer.finish(true),
+                    "Escape" if editing => viewer.finish(false),
+                    "Enter" if target.has_attribute("data-edit") => viewer.edit(&target),
+                    _ => return,
+                }
+                ev.prevent_default();
+            }
+        });
+
+        app.listen(&viewer.root, "focusout", {
+            let viewer = viewer.clone();
+            move |ev: Event| {
+                if ev
+                    .target()
+                    .is_some_and(|target| target.has_type::<HtmlInputElement>())
+                {
+                    viewer.finish(true);
+                }
+            }
+        });
+
+        viewer
+    }
+
+    pub fn set_value(&self, value: Value) {
+        // an edit in progress is abandoned along with the old tree
+        self.editing.borrow_mut().take();
+        self.root.set_inner_html("");
+        let collapsed = self.collapsed.borrow();
+        let node = render(&value, None, "", "$", &collapsed);
+        self.root.append_child(&node).unwrap();
+        *self.value.borrow_mut() = value;
+    }
+
+    /// Swaps the value under `span` for an input until Enter, Escape or blur.
+    fn edit(&self, span: &Element) {
+        let Some(pointer) = span.get_attribute("data-pointer") else {
+            return;
+        };
+        let Some(current) = self.value.borrow().pointer(&pointer).cloned() else {
+            return;
+        };
+        let input = create_element("input").unchecked_into::<HtmlInputElement>();
+        input.set_value(&current.to_string());
+        _ = input.set_attribute("aria-label", &format!("Edit {pointer}"));
+        _ = input.set_attribute("style", "font: inherit");
+        _ = span.replace_with_with_node_1(&input);
+        _ = input.focus();
+        *self.editing.borrow_mut() = Some((pointer, input));
+    }
+
+    /// Closes the editor, if it's open, handing the edit to `on_edit` when `commit`.
+    fn finish(&self, commit: bool) {
+        // taken before re-rendering: removing the focused input fires another blur
+        let Some((pointer, input)) = self.editing.borrow_mut().take() else {
+            return;
+        };
+        let current = self.value.borrow().clone();
+        // show the old value until the owner accepts the edit (it may not)
+        self.set_value(current.clone());
+        if commit {
+            let text = input.value();
+            let edited = serde_json::from_str(&text).unwrap_or(Value::String(text));
+            let mut document = current;
+            if let Some(slot) = document.pointer_mut(&pointer) {
+                *slot = edited;
+            }
+            (self.on_edit)(self, document);
+        }
+    }
+}
+
+/// `key` is the object key or array index this value sits under, if any.
+fn render(
+    value: &Value,
+    key: Option<&str>,
+    pointer: &str,
+    path: &str,
+    collapsed: &HashSet<String>,
+) -> Element {
+    let children: Vec<(String, String, String, &Value)> = match value {
+        Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| {
+                let escaped = k.replace('~', "~0").replace('/', "~1");
+                (
+                    k.clone(),
+                    format!("{pointer}/{escaped}"),
+                    format!("{path}.{k}"),
+                    v,
+                )
+            })
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                (
+                    i.to_string(),
+                    format!("{pointer}/{i}"),
+                    format!("{path}[{i}]"),
+                    v,
+                )
+            })
+            .collect(),
+        _ => {
+            let row = create_element("div");
+            if let Some(key) = key {
+                row.append_child(&key_span(key)).unwrap();
+            }
+            let span = create_element("span").unchecked_into::<HtmlElement>();
+            span.set_text_content(Some(&value.to_string()));
+            _ = span.set_attribute("data-edit", "");
+            _ = span.set_attribute("data-pointer", pointer);
+            _ = span.set_attribute("tabindex", "0");
+            _ = span.set_attribute("title", "Click to edit");
+            let color = match value {
+                Value::String(_) => "#a31515",
+                Value::Number(_) => "#098658",
+                _ => "#0000ff",
+            };
+            _ = span.set_attribute("style", &format!("color: {color}; cursor: text"));
+            row.append_child(&span).unwrap();
+            row.append_child(&copy_button(path)).unwrap();
+            return row;
+        }
+    };
+
+    let details = create_element("details").unchecked_into::<HtmlDetailsElement>();
+    details.set_open(!collapsed.contains(pointer));
+    _ = details.set_attribute("data-pointer", pointer);
+    let summary = create_element("summary");
+    if let Some(key) = key {
+        summary.append_child(&key_span(key)).unwrap();
+    }
+    let (open, close) = if value.is_array() {
+        ("[", "]")
+    } else {
+        ("{", "}")
+    };
+    let count = create_element("span");
+    _ = count.set_attribute("style", "color: #888");
+    count.set_text_content(Some(&format!("{open}…{close} {}", children.len())));
+    summary.append_child(&count).unwrap();
+    summary.append_child(&copy_button(path)).unwrap();
+    details.append_child(&summary).unwrap();
+
+    let list = create_element("div");
+    _ = list.set_attribute("style", "padding-left: 1.5em; border-left: 1px dotted #ccc");
+    for (key, pointer, path, child) in &children {
+        list.append_child(&render(child, Some(key), pointer, path, collapsed))
+            .unwrap();
+    }
+    details.append_child(&list).unwrap();
+    details.unchecked_into()
+}
+
+fn key_span(key: &str) -> Element {
+    let span = create_element("span");
+    _ = span.set_attribute("style", "color: #555");
+    span.set_text_content(Some(&format!("{key}: ")));
+    span
+}
+
+fn copy_button(path: &str) -> Element {
+    let button = create_element("button");
+    button.set_text_content(Some("⧉"));
+    _ = button.set_attribute("data-copy", path);
+    _ = button.set_attribute("aria-label", &format!("Copy path {path}"));
+    _ = button.set_attribute("title", &format!("Copy {path}"));
+    _ = button.set_attribute(
+        "style",
+        "border: none; background: none; cursor: pointer; opacity: 0.5; font-size: 0.8em",
+    );
+    button
+}
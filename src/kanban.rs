@@ -0,0 +1,556 @@
+//! Kanban board: cards in columns, dragged between (and within) columns.
+//!
+//! Same shape as the counter: the DOM only sends `Msg`s, `Board::update` is the only
+//! thing that changes the board, and the view re-renders every column from it. Each
+//! column is a [`KeyedList`], so a moved card keeps its element. The board listens
+//! once for every drag, drop and click instead of once per card.
+//!
+//! The columns' views are kept in a [`Registry`], as the counter examples keep their
+//! counters: the render loop only holds their keys and a weak handle, and they go
+//! when the example unmounts, whatever's still running.
+//!
+//! Every change can be undone: the board sits in a [`Timeline`], which keeps a copy of
+//! it from before each change. Those copies share everything that didn't change (the
+//! columns and cards are behind `Rc`s, and a change copies only the columns it
+//! touches, and only their lists of pointers), so one costs the same however many
+//! cards there are; `cargo test snapshot_cost -- --ignored --nocapture` measures it.
+
+use std::{cell::Cell, rc::Rc};
+
+use futures::StreamExt;
+use wasm_bindgen::JsCast;
+use web_sys::{DragEvent, Element, Event, HtmlInputElement, MouseEvent};
+
+use crate::{
+    analytics,
+    app::App,
+    arena::{Handle, Key, Registry},
+    dom::create_element,
+    keyed::KeyedList,
+    timeline::Timeline,
+};
+
+const COLUMN_STYLE: &str = "background: #f1f1f1; border-radius: 6px; padding: 8px";
+const DROP_TARGET_STYLE: &str = "background: #e0ecff; outline: 2px dashed #69f";
+/// How many changes can be undone.
+const UNDO_LIMIT: usize = 100;
+
+#[derive(Clone)]
+pub struct Card {
+    pub id: u32,
+    pub text: String,
+}
+
+#[derive(Clone)]
+pub struct Column {
+    pub title: &'static str,
+    pub cards: Vec<Rc<Card>>,
+}
+
+/// Cheap to clone: the clone shares its columns with the original until one of them
+/// is changed.
+#[derive(Clone)]
+pub struct Board {
+    pub columns: Vec<Rc<Column>>,
+    next_id: u32,
+}
+
+#[derive(Debug)]
+pub enum Msg {
+    Add {
+        column: usize,
+        text: String,
+    },
+    /// Puts `card` at `index` in `column`, counted without the card itself.
+    Move {
+        card: u32,
+        column: usize,
+        index: usize,
+    },
+    Remove(u32),
+    /// Handled by the [`Timeline`] the board is kept in, not the board.
+    Undo,
+    Redo,
+}
+
+/// A column's part of the page: the list its cards are rendered into.
+struct ColumnView {
+    cards: KeyedList<u32>,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        let mut board = Board {
+            columns: ["To do", "Doing", "Done"]
+                .into_iter()
+                .map(|title| {
+                    Rc::new(Column {
+                        title,
+                        cards: Vec::new(),
+                    })
+                })
+                .collect(),
+            next_id: 0,
+        };
+        for (column, text) in [
+            (0, "Write the docs"),
+            (0, "Add keyboard shortcuts"),
+            (1, "Drag and drop"),
+            (2, "Set up the repo"),
+        ] {
+            board.update(Msg::Add {
+                column,
+                text: text.to_string(),
+            });
+        }
+        board
+    }
+}
+
+impl Board {
+    /// Applies `msg`, returning whether it changed anything.
+    pub fn update(&mut self, msg: Msg) -> bool {
+        match msg {
+            Msg::Add { column, text } => {
+                let text = text.trim();
+                if text.is_empty() || column >= self.columns.len() {
+                    return false;
+                }
+                self.next_id += 1;
+                Rc::make_mut(&mut self.columns[column])
+                    .cards
+                    .push(Rc::new(Card {
+                        id: self.next_id,
+                        text: text.to_string(),
+                    }));
+                true
+            }
+            Msg::Move {
+                card,
+                column,
+                index,
+            } => {
+                if column >= self.columns.len() {
+                    return false;
+                }
+                let Some(card) = self.take(card) else {
+                    return false;
+                };
+                let cards = &mut Rc::make_mut(&mut self.columns[column]).cards;
+                cards.insert(index.min(cards.len()), card);
+                true
+            }
+            Msg::Remove(card) => self.take(card).is_some(),
+            Msg::Undo | Msg::Redo => false,
+        }
+    }
+
+    fn take(&mut self, id: u32) -> Option<Rc<Card>> {
+        // found before anything's made mutable, so only the card's column is copied
+        let (column, index) = self.columns.iter().enumerate().find_map(|(i, column)| {
+            let index = column.cards.iter().position(|card| card.id == id)?;
+            Some((i, index))
+        })?;
+        Some(Rc::make_mut(&mut self.columns[column]).cards.remove(index))
+    }
+}
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+    let mut timeline = Timeline::new(Board::default(), UNDO_LIMIT);
+
+    let toolbar = create_element("p");
+    let undo = create_element("button");
+    undo.set_text_content(Some("Undo"));
+    let redo = create_element("button");
+    redo.set_text_content(Some("Redo"));
+    toolbar.append_child(&undo).unwrap();
+    _ = toolbar.append_with_str_1(" ");
+    toolbar.append_child(&redo).unwrap();
+    app.append(parent, &toolbar);
+
+    let root = create_element("div");
+    _ = root.set_attribute(
+        "style",
+        "display: grid; grid-template-columns: repeat(3, 1fr); gap: 12px; align-items: start",
+    );
+    let views = Registry::<ColumnView>::new();
+    let mut keys = Vec::new();
+    for (i, column) in timeline.present().columns.iter().enumerate() {
+        let section = create_element("section");
+        _ = section.set_attribute("data-column", &i.to_string());
+        _ = section.set_attribute("style", COLUMN_STYLE);
+        let heading = create_element("h3");
+        heading.set_text_content(Some(column.title));
+        let list = create_element("ul");
+        _ = list.set_attribute("style", "list-style: none; padding: 0; min-height: 2em");
+        let form = create_element("form");
+        let input = create_element("input");
+        _ = input.set_attribute("name", "text");
+        _ = input.set_attribute("placeholder", "New card");
+        _ = input.set_attribute("aria-label", &format!("New card in {}", column.title));
+        let add = create_element("button");
+        add.set_text_content(Some("Add"));
+        form.append_child(&input).unwrap();
+        form.append_child(&add).unwrap();
+        section.append_child(&heading).unwrap();
+        section.append_child(&list).unwrap();
+        section.append_child(&form).unwrap();
+        root.append_child(&section).unwrap();
+        keys.push(views.insert(ColumnView {
+            cards: KeyedList::new(list),
+        }));
+    }
+    app.append(parent, &root);
+
+    let (sender, mut receiver) = futures::channel::mpsc::unbounded::<Msg>();
+    let columns = timeline.present().columns.len();
+
+    app.listen(&undo, "click", {
+        let sender = sender.clone();
+        move |_: MouseEvent| _ = sender.unbounded_send(Msg::Undo)
+    });
+    app.listen(&redo, "click", {
+        let sender = sender.clone();
+        move |_: MouseEvent| _ = sender.unbounded_send(Msg::Redo)
+    });
+
+    // the card being dragged; dataTransfer can't be read until the drop itself
+    let dragging = Rc::new(Cell::new(None::<u32>));
+
+    app.listen(&root, "dragstart", {
+        let dragging = dragging.clone();
+        move |ev: DragEvent| {
+            let Some(card) = closest(&ev, "[data-card]") else {
+                return;
+            };
+            let id = data_of(&card, "data-card");
+            dragging.set(id);
+            if let (Some(data), Some(id)) = (ev.data_transfer(), id) {
+                data.set_effect_allowed("move");
+                _ = data.set_data("text/plain", &id.to_string());
+            }
+        }
+    });
+    app.listen(&root, "dragend", {
+        let dragging = dragging.clone();
+        let root = root.clone();
+        move |_: DragEvent| {
+            dragging.set(None);
+            highlight(&root, None);
+        }
+    });
+    app.listen(&root, "dragover", {
+        let dragging = dragging.clone();
+        let root = root.clone();
+        move |ev: DragEvent| {
+            if dragging.get().is_none() {
+                return;
+            }
+            if let Some(column) = closest(&ev, "[data-column]") {
+                // cancelling dragover is what makes this a drop target
+                ev.prevent_default();
+                highlight(&root, Some(&column));
+            }
+        }
+    });
+    app.listen(&root, "drop", {
+        let root = root.clone();
+        let sender = sender.clone();
+        move |ev: DragEvent| {
+            let (Some(card), Some(column)) = (dragging.take(), closest(&ev, "[data-column]"))
+            else {
+                return;
+            };
+            ev.prevent_default();
+            highlight(&root, None);
+            let index = drop_index(&column, card, ev.client_y() as f64);
+            if let Some(column) = data_of(&column, "data-column") {
+                _ = sender.unbounded_send(Msg::Move {
+                    card,
+                    column,
+                    index,
+                });
+            }
+        }
+    });
+
+    app.listen(&root, "submit", {
+        let sender = sender.clone();
+        move |ev: Event| {
+            ev.prevent_default();
+            let Some(form) = ev
+                .target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+            else {
+                return;
+            };
+            let column = data_of(&form, "data-column");
+            let input = form
+                .query_selector("input")
+                .ok()
+                .flatten()
+                .map(JsCast::unchecked_into::<HtmlInputElement>);
+            if let (Some(column), Some(input)) = (column, input) {
+                _ = sender.unbounded_send(Msg::Add {
+                    column,
+                    text: input.value(),
+                });
+                input.set_value("");
+            }
+        }
+    });
+
+    // the buttons on each card; they're also how to move cards without a mouse
+    app.listen(&root, "click", move |ev: MouseEvent| {
+        let Some(button) = closest(&ev, "button[data-action]") else {
+            return;
+        };
+        let Some(card) = data_of(&button, "data-card") else {
+            return;
+        };
+        let column = data_of::<usize>(&button, "data-column");
+        let msg = match (button.get_attribute("data-action").as_deref(), column) {
+            (Some("left"), Some(column)) if column > 0 => Msg::Move {
+                card,
+                column: column - 1,
+                index: usize::MAX,
+            },
+            (Some("right"), Some(column)) if column + 1 < columns => Msg::Move {
+                card,
+                column: column + 1,
+                index: usize::MAX,
+            },
+            (Some("remove"), _) => Msg::Remove(card),
+            _ => return,
+        };
+        _ = sender.unbounded_send(msg);
+    });
+
+    let render =
+        move |timeline: &Timeline<Board>, views: &Handle<ColumnView>, keys: &[Key<ColumnView>]| {
+            _ = undo.toggle_attribute_with_force("disabled", !timeline.can_undo());
+            _ = redo.toggle_attribute_with_force("disabled", !timeline.can_redo());
+            for (column, key) in timeline.present().columns.iter().zip(keys) {
+                views.with(*key, |view| {
+                    view.cards.update(
+                        column.cards.iter().map(|card| (card.id, card)),
+                        |id, _| card_element(*id),
+                        |el, card| {
+                            if let Ok(Some(text)) = el.query_selector("span") {
+                                text.set_text_content(Some(&card.text));
+                            }
+                        },
+                    )
+                });
+            }
+        };
+    let handle = views.handle();
+    render(&timeline, &handle, &keys);
+
+    app.spawn(async move {
+        while let Some(msg) = receiver.next().await {
+            analytics::message(&msg);
+            match msg {
+                Msg::Undo => timeline.undo(),
+                Msg::Redo => timeline.redo(),
+                msg => timeline.update(|board| board.update(msg)),
+            }
+            render(&timeline, &handle, &keys);
+        }
+    });
+    app.on_cleanup(move || drop(views));
+
+    app
+}
+
+fn card_element(id: u32) -> Element {
+    let card = create_element("li");
+    _ = card.set_attribute("data-card", &id.to_string());
+    _ = card.set_attribute("draggable", "true");
+    _ = card.set_attribute(
+        "style",
+        "display: flex; gap: 4px; align-items: center; background: white; margin: 6px 0; \
+         padding: 6px; border-radius: 4px; box-shadow: 0 1px 2px #0003; cursor: grab",
+    );
+    let text = create_element("span");
+    _ = text.set_attribute("style", "flex: 1");
+    card.append_child(&text).unwrap();
+    for (action, label, title) in [
+        ("left", "←", "Move to the previous column"),
+        ("right", "→", "Move to the next column"),
+        ("remove", "×", "Remove"),
+    ] {
+        let button = create_element("button");
+        _ = button.set_attribute("data-action", action);
+        _ = button.set_attribute("aria-label", title);
+        _ = button.set_attribute("title", title);
+        button.set_text_content(Some(label));
+        card.append_child(&button).unwrap();
+    }
+    card
+}
+
+/// The nearest ancestor of the event's target (or the target itself) matching `selector`.
+fn closest(ev: &Event, selector: &str) -> Option<Element> {
+    ev.target()?
+        .dyn_into::<Element>()
+        .ok()?
+        .closest(selector)
+        .ok()
+        .flatten()
+}
+
+/// Parses the `attribute` of `el`'s nearest ancestor (or `el` itself) that has it.
+fn data_of<T: std::str::FromStr>(el: &Element, attribute: &str) -> Option<T> {
+    el.closest(&format!("[{attribute}]"))
+        .ok()
+        .flatten()?
+        .get_attribute(attribute)?
+        .parse()
+        .ok()
+}
+
+/// Where a card dropped at `client_y` lands: before the first other card whose middle
+/// is below the pointer.
+fn drop_index(column: &Element, dragged: u32, client_y: f64) -> usize {
+    let Ok(cards) = column.query_selector_all("[data-card]") else {
+        return 0;
+    };
+    let mut index = 0;
+    for i in 0..cards.length() {
+        let Some(card) = cards
+            .item(i)
+            .and_then(|card| card.dyn_into::<Element>().ok())
+        else {
+            continue;
+        };
+        if card.get_attribute("data-card") == Some(dragged.to_string()) {
+            continue;
+        }
+        let rect = card.get_bounding_client_rect();
+        if client_y < rect.top() + rect.height() / 2.0 {
+            break;
+        }
+        index += 1;
+    }
+    index
+}
+
+fn highlight(root: &Element, column: Option<&Element>) {
+    let Ok(columns) = root.query_selector_all("[data-column]") else {
+        return;
+    };
+    for i in 0..columns.length() {
+        let Some(el) = columns.item(i).and_then(|el| el.dyn_into::<Element>().ok()) else {
+            continue;
+        };
+        if Some(&el) == column {
+            _ = el.set_attribute("data-drop-target", "");
+            _ = el.set_attribute("style", &format!("{COLUMN_STYLE}; {DROP_TARGET_STYLE}"));
+        } else if el.has_attribute("data-drop-target") {
+            _ = el.remove_attribute("data-drop-target");
+            _ = el.set_attribute("style", COLUMN_STYLE);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    fn board_with(cards: usize) -> Board {
+        let mut board = Board::default();
+        for i in 0..cards {
+            board.update(Msg::Add {
+                column: i % 3,
+                text: format!("Card number {i}, with some text to copy"),
+            });
+        }
+        board
+    }
+
+    fn texts(board: &Board) -> Vec<Vec<String>> {
+        board
+            .columns
+            .iter()
+            .map(|column| column.cards.iter().map(|card| card.text.clone()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn snapshots_keep_their_cards_through_later_changes() {
+        let mut board = board_with(0);
+        let before = texts(&board);
+        let snapshot = board.clone();
+        assert!(board.update(Msg::Move {
+            card: 1,
+            column: 2,
+            index: 0,
+        }));
+        assert!(board.update(Msg::Remove(2)));
+        assert_eq!(texts(&snapshot), before);
+        assert_ne!(texts(&board), before);
+        // the column nothing happened to is still shared
+        assert!(Rc::ptr_eq(&snapshot.columns[1], &board.columns[1]));
+    }
+
+    #[test]
+    fn only_changes_can_be_undone() {
+        let mut timeline = Timeline::new(board_with(0), UNDO_LIMIT);
+        timeline.update(|board| {
+            board.update(Msg::Add {
+                column: 0,
+                text: "  ".to_string(),
+            })
+        });
+        assert!(!timeline.can_undo());
+        timeline.update(|board| board.update(Msg::Remove(1)));
+        let after = texts(timeline.present());
+        timeline.undo();
+        assert_eq!(texts(timeline.present()), texts(&board_with(0)));
+        timeline.redo();
+        assert_eq!(texts(timeline.present()), after);
+    }
+
+    /// Compares a snapshot of a big board with a full copy of its cards, which is what
+    /// a snapshot cost before the board shared its structure. The real gap is several
+    /// orders of magnitude, so the margin leaves plenty of room for a noisy machine.
+    #[test]
+    fn snapshots_cost_far_less_than_copying_the_cards() {
+        const SNAPSHOTS: u32 = 20;
+        let board = board_with(10_000);
+        let time = |f: &dyn Fn()| {
+            let start = Instant::now();
+            for _ in 0..SNAPSHOTS {
+                f();
+            }
+            start.elapsed()
+        };
+
+        let shared = time(&|| {
+            std::hint::black_box(board.clone());
+        });
+        let copied = time(&|| {
+            std::hint::black_box(
+                board
+                    .columns
+                    .iter()
+                    .map(|column| {
+                        column
+                            .cards
+                            .iter()
+                            .map(|card| Card::clone(card))
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        });
+        assert!(
+            shared * 20 < copied,
+            "a snapshot took {shared:?}, copying the cards took {copied:?}"
+        );
+    }
+}
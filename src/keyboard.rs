@@ -0,0 +1,53 @@
+//! Keys mapped straight to messages, for driving a reducer without the mouse.
+//!
+//! [`hotkeys`](crate::hotkeys) is for commands: chords, scopes, a cheat sheet, and one
+//! action however long a key is held. This is for controls, where holding ArrowUp
+//! should keep counting, so the key repeats go through too.
+//!
+//! Keys are `KeyboardEvent.key` values as they come (`"ArrowUp"`, `"+"`). They're left
+//! alone with Ctrl, Alt or ⌘ held (those belong to the browser), in a text field or a
+//! dialog, and when a hotkey has already handled them.
+
+use std::collections::HashMap;
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, KeyboardEvent};
+
+use crate::{
+    dom::{add_event_listener, window, EventListenerHandle},
+    hotkeys::is_editable,
+};
+
+/// Which message each key sends.
+pub type Keymap<M> = HashMap<String, M>;
+
+/// Calls `dispatch` with the message for each key in `keymap` pressed anywhere on the
+/// page, until the returned handle is dropped or removed.
+///
+/// The listener is on the window, which outlives whatever is listening, so removing it
+/// when that goes is up to the caller.
+pub fn on_keys<M>(keymap: Keymap<M>, dispatch: impl Fn(M) + 'static) -> EventListenerHandle
+where
+    M: Clone + 'static,
+{
+    add_event_listener(&window(), "keydown", move |ev: KeyboardEvent| {
+        if ev.default_prevented() || ev.ctrl_key() || ev.alt_key() || ev.meta_key() {
+            return;
+        }
+        let Some(msg) = keymap.get(&ev.key()) else {
+            return;
+        };
+        let elsewhere = ev
+            .target()
+            .and_then(|target| target.dyn_into::<Element>().ok())
+            .is_some_and(|target| {
+                is_editable(&target) || target.closest("dialog").ok().flatten().is_some()
+            });
+        if elsewhere {
+            return;
+        }
+        // otherwise the arrows scroll the page, and "+" and "-" might be taken too
+        ev.prevent_default();
+        dispatch(msg.clone());
+    })
+}
@@ -0,0 +1,57 @@
+//! Keyed list rendering: turn a container's children into a new list of items while
+//! reusing the element of every item whose key is still there.
+//!
+//! Reused elements are moved rather than recreated, so they keep focus, scroll
+//! position, running transitions and whatever else lives on the DOM node.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use web_sys::{Element, Node};
+
+pub struct KeyedList<K> {
+    container: Element,
+    nodes: HashMap<K, Element>,
+}
+
+impl<K: Eq + Hash + Clone> KeyedList<K> {
+    pub fn new(container: Element) -> Self {
+        KeyedList {
+            container,
+            nodes: HashMap::new(),
+        }
+    }
+
+    /// Makes the container show `items` in order. `create` builds the element for a key
+    /// seen for the first time; `patch` then brings every element up to date.
+    pub fn update<T>(
+        &mut self,
+        items: impl IntoIterator<Item = (K, T)>,
+        create: impl Fn(&K, &T) -> Element,
+        patch: impl Fn(&Element, &T),
+    ) {
+        let mut next = HashMap::with_capacity(self.nodes.len());
+        // the node that should come after the one we're placing; walking forwards, it's
+        // whatever currently follows the last node placed
+        let mut cursor: Option<Node> = self.container.first_child();
+        for (key, item) in items {
+            let el = match self.nodes.remove(&key) {
+                Some(el) => el,
+                None => create(&key, &item),
+            };
+            patch(&el, &item);
+            let node: &Node = &el;
+            if cursor.as_ref() == Some(node) {
+                cursor = node.next_sibling();
+            } else {
+                // only moves the nodes that are out of place
+                _ = self.container.insert_before(node, cursor.as_ref());
+            }
+            next.insert(key, el);
+        }
+        for (_, stale) in self.nodes.drain() {
+            stale.remove();
+        }
+        self.nodes = next;
+    }
+}
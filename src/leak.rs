@@ -0,0 +1,37 @@
+//! Live-object counters for spotting leaks.
+//!
+//! Anything that should be released on unmount holds a [`Tracked`] guard; if the
+//! counts keep growing across mount/unmount cycles, something is being kept alive.
+
+use std::{cell::RefCell, collections::BTreeMap};
+
+thread_local! {
+    static LIVE: RefCell<BTreeMap<&'static str, usize>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+/// Increments the counter for `kind` while alive, decrements it when dropped.
+#[derive(Debug)]
+pub struct Tracked(&'static str);
+
+impl Tracked {
+    pub fn new(kind: &'static str) -> Self {
+        LIVE.with(|live| *live.borrow_mut().entry(kind).or_default() += 1);
+        Tracked(kind)
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        LIVE.with(|live| {
+            if let Some(count) = live.borrow_mut().get_mut(self.0) {
+                *count -= 1;
+            }
+        });
+    }
+}
+
+/// Current number of live objects of each kind.
+#[cfg(all(test, target_arch = "wasm32"))]
+pub fn snapshot() -> BTreeMap<&'static str, usize> {
+    LIVE.with(|live| live.borrow().clone())
+}
@@ -1,169 +1,160 @@
-use futures::StreamExt;
-use leptos::{add_event_listener, body, create_element, document, log, spawn_local, window};
-use std::{cell::RefCell, rc::Rc};
-use wasm_bindgen::{closure::Closure, JsCast, JsValue};
-use web_sys::{console, Document, MouseEvent, Text, Window};
-
-struct State {
-    count: i32,
-}
-
-enum Msg {
-    Increment,
-    Decrement,
-} // = JS type Msg = "inc" | "dec";
+mod accordion;
+mod analytics;
+mod animate;
+mod api;
+mod api_search;
+mod app;
+mod arena;
+mod audio;
+mod barcode;
+mod battery;
+mod camera;
+mod cards;
+mod cart;
+mod clipboard;
+mod clock;
+mod color_picker;
+mod colors;
+mod component;
+mod context_menu;
+mod counter;
+mod csv;
+mod custom_event;
+mod devtools;
+mod dialog;
+mod diff;
+mod dom;
+mod downloads;
+mod ellipsis;
+mod entities;
+mod errors;
+mod examples;
+mod experiment;
+mod export;
+mod fetch;
+mod filters;
+mod flags;
+mod formula;
+mod gallery;
+mod geometry;
+mod gestures;
+mod history_log;
+mod hot;
+mod hotkeys;
+mod html;
+mod hydration;
+mod idb;
+mod idle;
+mod image_filters;
+mod install;
+mod intern;
+mod intl;
+mod json_viewer;
+mod kanban;
+mod keyboard;
+mod keyed;
+mod leak;
+mod media;
+mod media_session;
+mod multi;
+mod network;
+mod number_spinner;
+mod pair;
+mod passkeys;
+mod permissions;
+mod popover;
+mod primes;
+mod print;
+mod progressive;
+mod qr;
+mod query;
+mod quote;
+mod rating;
+mod ratings;
+mod regex_playground;
+mod replay;
+mod resource;
+mod scroll;
+mod search;
+mod selection;
+mod selector;
+mod share;
+mod signal;
+mod spreadsheet;
+mod sse;
+mod storage;
+mod store;
+mod subscriptions;
+mod supervisor;
+mod svg;
+mod sync;
+mod synth;
+mod table;
+mod text_diff;
+mod time;
+mod timeline;
+mod title;
+mod toast;
+mod todo;
+mod tooltip;
+mod totals;
+mod transition;
+mod tree_view;
+mod vdom;
+mod versions;
+mod widgets;
+mod worker;
+mod ws;
+
+use std::time::Duration;
+
+use app::App;
+use dom::{body, document};
+
+/// How long without any activity before the page pauses and asks if anyone's there.
+const IDLE_AFTER: Duration = Duration::from_secs(120);
 
 fn main() {
     // better error logging
     console_error_panic_hook::set_once();
+    // this same module is what runs in our workers
+    if worker::is_worker() {
+        worker::serve();
+        return;
+    }
 
-    version_1_with_single_button();
-}
-
-fn version_1_with_single_button() {
-    let mut state = State { count: 0 };
-
-    let p = create_element("p");
-    p.set_text_content(Some("Click the button to update this"));
-
-    let increment = create_element("button");
-    increment.set_text_content(Some("+1"));
-
-    let decrement = create_element("button");
-    decrement.set_text_content(Some("-1"));
-
-    let body = body().unwrap();
-    body.append_child(&increment).unwrap();
-    body.append_child(&p).unwrap();
-    body.append_child(&decrement).unwrap();
-
-    add_event_listener(&increment, "click", move |ev: MouseEvent| {
-        log!("clicked +1");
-        state.count += 1;
-        p.set_text_content(Some(&state.count.to_string()));
-    });
-
-    /* add_event_listener(&decrement, "click", |ev: MouseEvent| {
-        log!("clicked -1");
-        state.clicks -= 1;
-        p.set_text_content(Some(&clicks.to_string()));
-    }); */
-}
-
-fn version_2_with_stale_closure() {
-    let mut state = State { count: 0 };
-
-    let p = create_element("p");
-    p.set_text_content(Some("Click the button to update this"));
-
-    let increment = create_element("button");
-    increment.set_text_content(Some("+1"));
-
-    let decrement = create_element("button");
-    decrement.set_text_content(Some("-1"));
-
-    let body = body().unwrap();
-    body.append_child(&increment).unwrap();
-    body.append_child(&p).unwrap();
-    body.append_child(&decrement).unwrap();
-
-    add_event_listener(&increment, "click", {
-        let p = p.clone();
-        move |ev: MouseEvent| {
-            log!("clicked +1");
-            state.count += 1;
-            p.set_text_content(Some(&state.count.to_string()));
-        }
-    });
-
-    add_event_listener(&decrement, "click", move |ev: MouseEvent| {
-        log!("clicked -1");
-        state.count -= 1;
-        p.set_text_content(Some(&state.count.to_string()));
-    });
-}
-
-fn version_3_with_interior_mutability() {
-    // this kind of wrapping is called "interior mutability" in Rust
-    // in a sense, it moves borrow checking from the compile time to runtime
-    let mut state = Rc::new(RefCell::new(State { count: 0 }));
-
-    let p = create_element("p");
-    p.set_text_content(Some("Click the button to update this"));
-
-    let increment = create_element("button");
-    increment.set_text_content(Some("+1"));
-
-    let decrement = create_element("button");
-    decrement.set_text_content(Some("-1"));
-
-    let body = body().unwrap();
-    body.append_child(&increment);
-    body.append_child(&p);
-    body.append_child(&decrement);
-
-    add_event_listener(&increment, "click", {
-        let p = p.clone();
-        let state = state.clone();
-        move |ev: MouseEvent| {
-            log!("clicked +1");
-            state.borrow_mut().count += 1;
-            p.set_text_content(Some(&state.borrow().count.to_string()));
-        }
-    });
+    let mut services = App::new();
+    if cfg!(debug_assertions) {
+        analytics::register(analytics::ConsoleAnalytics);
+    }
+    // <meta name="analytics-endpoint" content="https://..."> turns on reporting to a server
+    if let Some(endpoint) = meta_content("analytics-endpoint") {
+        analytics::register(analytics::BeaconAnalytics { endpoint });
+    }
+    analytics::install(&mut services);
+    // and <meta name="error-endpoint" content="https://..."> sends error reports to one
+    errors::install(&mut services, meta_content("error-endpoint"));
+    network::install(&mut services);
+    fetch::install(&mut services);
+    query::install();
+    hotkeys::install(&mut services);
+    idle::install(&mut services, IDLE_AFTER);
+    idle::mount_overlay(&mut services, &body().unwrap());
+    flags::mount_panel(&mut services);
+    if flags::enabled("session-replay") {
+        replay::install(&mut services);
+    }
+    services.forget();
 
-    add_event_listener(&decrement, "click", move |ev: MouseEvent| {
-        log!("clicked -1");
-        state.borrow_mut().count -= 1;
-        p.set_text_content(Some(&state.borrow().count.to_string()));
-    });
+    examples::mount_to(&body().unwrap()).forget();
 }
 
-fn version_4_with_async_channel_and_reducer_pattern() {
-    let window = web_sys::window().expect("there to be a window");
-    let document = window.document().expect("there to be a document");
-    let body = document.body().expect("there to be a body");
-
-    let p = document.create_element("p").expect("to create the element");
-    p.set_text_content(Some("Hello, Ryan!"));
-
-    let increment = create_element("button");
-    increment.set_text_content(Some("+1"));
-
-    let decrement = create_element("button");
-    decrement.set_text_content(Some("-1"));
-
-    /* let state = Rc::new(
-        RefCell::new(State { count: "0".to_string() })
-    ); */
-
-    let (mut message_sender, mut message_receiver) = futures::channel::mpsc::channel(4);
-
-    body.append_child(&decrement);
-    body.append_child(&p);
-    body.append_child(&increment);
-
-    spawn_local(async move {
-        let mut count = 0;
-        while let Some(msg) = message_receiver.next().await {
-            match msg {
-                Msg::Increment => count += 1,
-                Msg::Decrement => count -= 1,
-            }
-            p.set_text_content(Some(&format!("count is {count}")));
-        }
-    });
-
-    add_event_listener(&increment, "click", {
-        let mut message_sender = message_sender.clone();
-        move |_: web_sys::Event| {
-            message_sender.try_send(Msg::Increment);
-        }
-    });
-
-    add_event_listener(&decrement, "click", move |_: web_sys::Event| {
-        message_sender.try_send(Msg::Decrement);
-    });
+/// The `content` of the page's `<meta name="{name}">`, if it has one.
+fn meta_content(name: &str) -> Option<String> {
+    document()
+        .query_selector(&format!("meta[name={name}]"))
+        .ok()??
+        .get_attribute("content")
 }
 
 // Version 1: with Leptos helpers
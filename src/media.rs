@@ -0,0 +1,82 @@
+//! Getting at the camera and microphone through `navigator.mediaDevices`.
+//!
+//! Errors come back as sentences to show the user, since what went wrong (permission
+//! denied, no such device, device busy) is usually something only they can fix.
+
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    MediaDeviceInfo, MediaDeviceKind, MediaDevices, MediaStream, MediaStreamConstraints,
+    MediaStreamTrack,
+};
+
+use crate::dom::window;
+
+/// Asks for a stream; `device` names the camera or microphone in messages.
+pub async fn user_media(
+    constraints: &MediaStreamConstraints,
+    device: &str,
+) -> Result<MediaStream, String> {
+    let promise = media_devices(device)?
+        .get_user_media_with_constraints(constraints)
+        .map_err(|e| describe(&e, device))?;
+    JsFuture::from(promise)
+        .await
+        .map(|stream| stream.unchecked_into())
+        .map_err(|e| describe(&e, device))
+}
+
+/// The devices of one kind. Their labels are empty until the user has allowed access.
+pub async fn devices(kind: MediaDeviceKind) -> Vec<MediaDeviceInfo> {
+    let Ok(Ok(promise)) = media_devices("device").map(|devices| devices.enumerate_devices()) else {
+        return Vec::new();
+    };
+    let Ok(devices) = JsFuture::from(promise).await else {
+        return Vec::new();
+    };
+    js_sys::Array::from(&devices)
+        .iter()
+        .map(|device| device.unchecked_into::<MediaDeviceInfo>())
+        .filter(|device| device.kind() == kind)
+        .collect()
+}
+
+/// Turns every track of `stream` off, which also turns off the browser's
+/// "recording" indicator.
+pub fn stop(stream: &MediaStream) {
+    for track in stream.get_tracks().iter() {
+        track.unchecked_into::<MediaStreamTrack>().stop();
+    }
+}
+
+/// A stream that's [`stop`]ped when this is dropped.
+pub struct Running(pub MediaStream);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        stop(&self.0);
+    }
+}
+
+/// `navigator.mediaDevices`, which is missing outside secure contexts.
+pub fn media_devices(device: &str) -> Result<MediaDevices, String> {
+    window()
+        .navigator()
+        .media_devices()
+        .map_err(|_| format!("This browser can't use the {device} here (it needs HTTPS)."))
+}
+
+fn describe(e: &JsValue, device: &str) -> String {
+    let error = e.dyn_ref::<js_sys::Error>();
+    match error.map(|e| String::from(e.name())).as_deref() {
+        Some("NotAllowedError") => format!(
+            "Access to the {device} was denied. Allow it in the browser's site settings and \
+             try again."
+        ),
+        Some("NotFoundError" | "OverconstrainedError") => format!("There's no {device} to use."),
+        Some("NotReadableError") => format!("The {device} is in use by something else."),
+        _ => error
+            .map(|e| String::from(e.message()))
+            .unwrap_or_else(|| format!("{e:?}")),
+    }
+}
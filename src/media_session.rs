@@ -0,0 +1,158 @@
+//! The Media Session API: what's playing, for the OS's media controls.
+//!
+//! With metadata set and handlers registered, media keys, the lock screen, headphone
+//! buttons and the browser's own media hub all drive the page's player, and show
+//! what it's playing and how far along it is. `navigator.mediaSession` is still
+//! behind web-sys's unstable flag, so it's bound by hand here; where it's missing,
+//! everything is a no-op.
+
+use wasm_bindgen::{closure::Closure, prelude::wasm_bindgen, JsCast, JsValue};
+
+use crate::{app::App, dom::window};
+
+#[wasm_bindgen]
+extern "C" {
+    type MediaSession;
+
+    #[wasm_bindgen(method, setter)]
+    fn set_metadata(this: &MediaSession, metadata: Option<&MediaMetadata>);
+
+    #[wasm_bindgen(method, setter, js_name = playbackState)]
+    fn set_playback_state(this: &MediaSession, state: &str);
+
+    /// Throws for actions the browser doesn't know.
+    #[wasm_bindgen(method, catch, js_name = setActionHandler)]
+    fn set_action_handler(
+        this: &MediaSession,
+        action: &str,
+        handler: Option<&js_sys::Function>,
+    ) -> Result<(), JsValue>;
+
+    /// Throws if the position is past the duration.
+    #[wasm_bindgen(method, catch, js_name = setPositionState)]
+    fn set_position_state(this: &MediaSession, state: &JsValue) -> Result<(), JsValue>;
+
+    type MediaMetadata;
+
+    #[wasm_bindgen(constructor, catch)]
+    fn new(init: &JsValue) -> Result<MediaMetadata, JsValue>;
+}
+
+/// What the OS controls ask for.
+#[derive(Clone, Copy, Debug)]
+pub enum Action {
+    Play,
+    Pause,
+    Stop,
+    /// Seconds back, if the OS says how far.
+    SeekBackward(Option<f64>),
+    /// Seconds forward, if the OS says how far.
+    SeekForward(Option<f64>),
+    /// To this many seconds in.
+    SeekTo(f64),
+}
+
+const ACTIONS: &[&str] = &[
+    "play",
+    "pause",
+    "stop",
+    "seekbackward",
+    "seekforward",
+    "seekto",
+];
+
+pub struct Metadata<'a> {
+    pub title: &'a str,
+    pub artist: &'a str,
+    pub album: &'a str,
+    /// An image URL for the lock screen and media hub.
+    pub artwork: &'a str,
+}
+
+fn session() -> Option<MediaSession> {
+    let session = js_sys::Reflect::get(&window().navigator(), &"mediaSession".into()).ok()?;
+    (!session.is_undefined()).then(|| session.unchecked_into())
+}
+
+/// Shows `metadata` as what's playing, and sends the OS controls' actions to `handle`,
+/// until `app` unmounts.
+pub fn claim(app: &mut App, metadata: &Metadata, handle: impl Fn(Action) + 'static) {
+    let Some(session) = session() else {
+        return;
+    };
+    let init = js_sys::Object::new();
+    let artwork = js_sys::Object::new();
+    for (key, value) in [("src", metadata.artwork), ("sizes", "any")] {
+        _ = js_sys::Reflect::set(&artwork, &key.into(), &value.into());
+    }
+    for (key, value) in [
+        ("title", JsValue::from(metadata.title)),
+        ("artist", metadata.artist.into()),
+        ("album", metadata.album.into()),
+        ("artwork", js_sys::Array::of1(&artwork).into()),
+    ] {
+        _ = js_sys::Reflect::set(&init, &key.into(), &value);
+    }
+    session.set_metadata(MediaMetadata::new(&init).ok().as_ref());
+
+    let handler = Closure::<dyn Fn(JsValue)>::new(move |details: JsValue| {
+        let get = |key: &str| {
+            js_sys::Reflect::get(&details, &key.into())
+                .ok()
+                .and_then(|value| value.as_f64())
+        };
+        let action = js_sys::Reflect::get(&details, &"action".into())
+            .ok()
+            .and_then(|action| action.as_string());
+        let action = match action.as_deref() {
+            Some("play") => Action::Play,
+            Some("pause") => Action::Pause,
+            Some("stop") => Action::Stop,
+            Some("seekbackward") => Action::SeekBackward(get("seekOffset")),
+            Some("seekforward") => Action::SeekForward(get("seekOffset")),
+            Some("seekto") => match get("seekTime") {
+                Some(time) => Action::SeekTo(time),
+                None => return,
+            },
+            _ => return,
+        };
+        handle(action);
+    });
+    for action in ACTIONS {
+        _ = session.set_action_handler(action, Some(handler.as_ref().unchecked_ref()));
+    }
+    app.on_cleanup(move || {
+        for action in ACTIONS {
+            _ = session.set_action_handler(action, None);
+        }
+        session.set_metadata(None);
+        session.set_playback_state("none");
+        drop(handler);
+    });
+}
+
+/// Tells the OS whether the media is playing or paused.
+pub fn set_playing(playing: bool) {
+    if let Some(session) = session() {
+        session.set_playback_state(if playing { "playing" } else { "paused" });
+    }
+}
+
+/// Tells the OS how far along the media is, for its progress bar, in seconds.
+pub fn set_position(position: f64, duration: f64) {
+    let Some(session) = session() else {
+        return;
+    };
+    if !duration.is_finite() {
+        return;
+    }
+    let state = js_sys::Object::new();
+    for (key, value) in [
+        ("position", position.min(duration)),
+        ("duration", duration),
+        ("playbackRate", 1.0),
+    ] {
+        _ = js_sys::Reflect::set(&state, &key.into(), &value.into());
+    }
+    _ = session.set_position_state(&state);
+}
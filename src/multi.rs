@@ -0,0 +1,46 @@
+//! Three counters on one page, each mounted by selector into an element of its own,
+//! with its own state, channel and settings from the element's data attributes.
+
+use web_sys::Element;
+
+use crate::{app::App, counter, html::el};
+
+/// The mount points, and the `data-` attributes each one starts with.
+const COUNTERS: &[(&str, &[(&str, &str)])] = &[
+    ("counter-a", &[]),
+    (
+        "counter-b",
+        &[
+            ("data-start", "10"),
+            ("data-step", "5"),
+            ("data-min", "0"),
+            ("data-max", "50"),
+            ("data-label", "Apples:"),
+        ],
+    ),
+    (
+        "counter-c",
+        &[("data-start", "-3"), ("data-label", "Temperature")],
+    ),
+];
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+    for (id, data) in COUNTERS {
+        let host = el!(
+            div,
+            id = id,
+            style = "border: 1px solid #ccc; margin: 1em 0; padding: 1em",
+        );
+        data.iter()
+            .fold(host, |host, (name, value)| host.attr(name, value))
+            .mount(&mut app, parent);
+    }
+    // mounted once they're on the page, which is where the selectors look
+    let counters = COUNTERS
+        .iter()
+        .filter_map(|(id, _)| counter::mount(&format!("#{id}")))
+        .collect::<Vec<_>>();
+    app.on_cleanup(move || counters.into_iter().for_each(App::unmount));
+    app
+}
@@ -0,0 +1,165 @@
+//! Whether we're online, and what kind of connection we're on.
+//!
+//! `online`/`offline` events work everywhere; the connection details come from the
+//! Network Information API (`navigator.connection`, Chromium only, bound by hand
+//! since it isn't in `web_sys`) and are `None` elsewhere.
+
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast};
+use web_sys::{Element, Event};
+
+use crate::{
+    app::App,
+    dom::{create_element, window},
+};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends = web_sys::EventTarget)]
+    #[derive(Clone)]
+    type NetworkInformation;
+
+    /// `"slow-2g"`, `"2g"`, `"3g"` or `"4g"`, going by recently measured speeds.
+    #[wasm_bindgen(method, getter, js_name = effectiveType)]
+    fn effective_type(this: &NetworkInformation) -> Option<String>;
+
+    /// Megabits per second.
+    #[wasm_bindgen(method, getter)]
+    fn downlink(this: &NetworkInformation) -> Option<f64>;
+
+    /// The user asked for reduced data usage.
+    #[wasm_bindgen(method, getter, js_name = saveData)]
+    fn save_data(this: &NetworkInformation) -> Option<bool>;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConnectionStatus {
+    pub online: bool,
+    pub effective_type: Option<String>,
+    pub downlink: Option<f64>,
+    pub save_data: bool,
+}
+
+impl ConnectionStatus {
+    fn read() -> Self {
+        let connection = connection();
+        ConnectionStatus {
+            online: window().navigator().on_line(),
+            effective_type: connection
+                .as_ref()
+                .and_then(NetworkInformation::effective_type),
+            downlink: connection.as_ref().and_then(NetworkInformation::downlink),
+            save_data: connection
+                .as_ref()
+                .and_then(NetworkInformation::save_data)
+                .unwrap_or(false),
+        }
+    }
+
+    /// Slow or data-saving connections should skip anything optional.
+    pub fn is_constrained(&self) -> bool {
+        self.save_data || matches!(self.effective_type.as_deref(), Some("slow-2g" | "2g"))
+    }
+}
+
+fn connection() -> Option<NetworkInformation> {
+    let connection = js_sys::Reflect::get(&window().navigator(), &"connection".into()).ok()?;
+    (!connection.is_undefined()).then(|| connection.unchecked_into())
+}
+
+type Listener = Rc<dyn Fn(&ConnectionStatus)>;
+
+struct Store {
+    status: ConnectionStatus,
+    listeners: Vec<(usize, Listener)>,
+    next_listener: usize,
+}
+
+thread_local! {
+    static STORE: RefCell<Store> = RefCell::new(Store {
+        status: ConnectionStatus::read(),
+        listeners: Vec::new(),
+        next_listener: 0,
+    });
+}
+
+pub fn status() -> ConnectionStatus {
+    STORE.with(|store| store.borrow().status.clone())
+}
+
+/// Calls `listener` whenever the status changes, until the app unmounts.
+pub fn subscribe(app: &mut App, listener: impl Fn(&ConnectionStatus) + 'static) {
+    let id = STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        let id = store.next_listener;
+        store.next_listener += 1;
+        store.listeners.push((id, Rc::new(listener)));
+        id
+    });
+    app.on_cleanup(move || {
+        STORE.with(|store| {
+            store
+                .borrow_mut()
+                .listeners
+                .retain(|(other, _)| *other != id)
+        })
+    });
+}
+
+/// Starts tracking the connection; everything else in this module relies on it.
+pub fn install(app: &mut App) {
+    for event in ["online", "offline"] {
+        app.listen(&window(), event, |_: Event| refresh());
+    }
+    if let Some(connection) = connection() {
+        app.listen(&connection, "change", |_: Event| refresh());
+    }
+}
+
+fn refresh() {
+    let status = ConnectionStatus::read();
+    let listeners = STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        if store.status == status {
+            return Vec::new();
+        }
+        store.status = status.clone();
+        store
+            .listeners
+            .iter()
+            .map(|(_, listener)| listener.clone())
+            .collect()
+    });
+    for listener in listeners {
+        listener(&status);
+    }
+}
+
+/// A small badge that's only visible while offline or on a slow connection.
+pub fn mount_badge(app: &mut App, parent: &Element) {
+    let badge = create_element("span");
+    _ = badge.set_attribute("role", "status");
+    _ = badge.set_attribute(
+        "style",
+        "margin-left: 1em; padding: 0 6px; border-radius: 4px; background: #fd5",
+    );
+    app.append(parent, &badge);
+
+    let render = move |status: &ConnectionStatus| {
+        let text = if !status.online {
+            Some("Offline: changes will be sent when you reconnect".to_string())
+        } else if status.is_constrained() {
+            Some(format!(
+                "Slow connection ({})",
+                status.effective_type.as_deref().unwrap_or("data saver")
+            ))
+        } else {
+            None
+        };
+        _ = badge.toggle_attribute_with_force("hidden", text.is_none());
+        badge.set_text_content(text.as_deref());
+    };
+    render(&status());
+    subscribe(app, render);
+}
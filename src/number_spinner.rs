@@ -0,0 +1,181 @@
+//! A keyboard-accessible number spinner: − button, editable input, + button.
+//!
+//! The spinner is *controlled*: it reports the value the user asked for through
+//! `on_change` and only shows a new value once its owner calls `set_value`, the same
+//! way the reducer versions only change the DOM after handling a message.
+//!
+//! Keys (with the input focused): ArrowUp/ArrowDown step, PageUp/PageDown step ×10,
+//! Home/End jump to the minimum/maximum.
+
+use std::{cell::Cell, rc::Rc};
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event, HtmlInputElement, KeyboardEvent, MouseEvent};
+
+use crate::{app::App, dom::create_element};
+
+pub struct SpinnerOptions {
+    pub label: String,
+    pub value: i32,
+    pub step: i32,
+    pub min: Option<i32>,
+    pub max: Option<i32>,
+}
+
+impl Default for SpinnerOptions {
+    fn default() -> Self {
+        SpinnerOptions {
+            label: "Value".to_string(),
+            value: 0,
+            step: 1,
+            min: None,
+            max: None,
+        }
+    }
+}
+
+pub struct NumberSpinner {
+    input: HtmlInputElement,
+    decrement: Element,
+    increment: Element,
+    value: Cell<i32>,
+    step: i32,
+    min: Option<i32>,
+    max: Option<i32>,
+}
+
+impl NumberSpinner {
+    pub fn mount(
+        app: &mut App,
+        parent: &Element,
+        options: SpinnerOptions,
+        on_change: impl Fn(i32) + 'static,
+    ) -> Rc<Self> {
+        let root = create_element("div");
+        _ = root.set_attribute("style", "display: inline-flex; gap: 4px");
+
+        let decrement = create_element("button");
+        decrement.set_text_content(Some("−"));
+        let input = create_element("input").unchecked_into::<HtmlInputElement>();
+        input.set_type("text");
+        _ = input.set_attribute("inputmode", "numeric");
+        _ = input.set_attribute("size", "6");
+        let increment = create_element("button");
+        increment.set_text_content(Some("+"));
+
+        // the input is the spinbutton; the buttons are there for the mouse, so they're
+        // left out of the tab order and just labelled for screen readers
+        _ = input.set_attribute("role", "spinbutton");
+        _ = input.set_attribute("aria-label", &options.label);
+        if let Some(min) = options.min {
+            _ = input.set_attribute("aria-valuemin", &min.to_string());
+        }
+        if let Some(max) = options.max {
+            _ = input.set_attribute("aria-valuemax", &max.to_string());
+        }
+        for (button, label) in [(&decrement, "Decrease"), (&increment, "Increase")] {
+            _ = button.set_attribute("tabindex", "-1");
+            _ = button.set_attribute("aria-label", &format!("{label} {}", options.label));
+        }
+
+        root.append_child(&decrement).unwrap();
+        root.append_child(&input).unwrap();
+        root.append_child(&increment).unwrap();
+        app.append(parent, &root);
+
+        let spinner = Rc::new(NumberSpinner {
+            input,
+            decrement,
+            increment,
+            value: Cell::new(options.value),
+            step: options.step,
+            min: options.min,
+            max: options.max,
+        });
+        spinner.set_value(options.value);
+        let on_change = Rc::new(on_change);
+
+        // asks the owner for a new value, if it's actually different
+        let request = {
+            let spinner = spinner.clone();
+            move |value: i32| {
+                let value = spinner.clamp(value);
+                if value != spinner.value.get() {
+                    on_change(value);
+                }
+            }
+        };
+
+        app.listen(&spinner.decrement, "click", {
+            let spinner = spinner.clone();
+            let request = request.clone();
+            move |_: MouseEvent| request(spinner.value.get().saturating_sub(spinner.step))
+        });
+
+        app.listen(&spinner.increment, "click", {
+            let spinner = spinner.clone();
+            let request = request.clone();
+            move |_: MouseEvent| request(spinner.value.get().saturating_add(spinner.step))
+        });
+
+        app.listen(&spinner.input, "keydown", {
+            let spinner = spinner.clone();
+            let request = request.clone();
+            move |ev: KeyboardEvent| {
+                let value = spinner.value.get();
+                let step = spinner.step;
+                let target = match ev.key().as_str() {
+                    "ArrowUp" => value.saturating_add(step),
+                    "ArrowDown" => value.saturating_sub(step),
+                    "PageUp" => value.saturating_add(step.saturating_mul(10)),
+                    "PageDown" => value.saturating_sub(step.saturating_mul(10)),
+                    "Home" => match spinner.min {
+                        Some(min) => min,
+                        None => return,
+                    },
+                    "End" => match spinner.max {
+                        Some(max) => max,
+                        None => return,
+                    },
+                    _ => return,
+                };
+                ev.prevent_default();
+                request(target);
+            }
+        });
+
+        // typed values are committed on Enter/blur; anything unparseable snaps back
+        app.listen(&spinner.input, "change", {
+            let spinner = spinner.clone();
+            move |_: Event| {
+                if let Ok(value) = spinner.input.value().trim().parse::<i32>() {
+                    request(value);
+                }
+                // if the owner accepted the value it has already called `set_value`;
+                // either way the input should show the real value now
+                spinner.set_value(spinner.value.get());
+            }
+        });
+
+        spinner
+    }
+
+    pub fn set_value(&self, value: i32) {
+        self.value.set(value);
+        self.input.set_value(&value.to_string());
+        _ = self
+            .input
+            .set_attribute("aria-valuenow", &value.to_string());
+        _ = self
+            .decrement
+            .toggle_attribute_with_force("disabled", self.min.is_some_and(|min| value <= min));
+        _ = self
+            .increment
+            .toggle_attribute_with_force("disabled", self.max.is_some_and(|max| value >= max));
+    }
+
+    fn clamp(&self, value: i32) -> i32 {
+        let value = self.min.map_or(value, |min| value.max(min));
+        self.max.map_or(value, |max| value.min(max))
+    }
+}
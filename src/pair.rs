@@ -0,0 +1,86 @@
+//! Two [`Counter`] components side by side, each with a count of its own, and buttons
+//! out here that reset both or take the right one off the page and put it back.
+//!
+//! The counters live in a [`Registry`], and the buttons' closures only hold their
+//! [`Key`](crate::arena::Key)s and a weak [`Handle`](crate::arena::Handle), so
+//! nothing they capture keeps a counter alive once the example's gone. Taking the
+//! right one away removes it from the registry altogether; putting it back makes a
+//! new one, under a new key.
+
+use std::cell::Cell;
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, MouseEvent};
+
+use crate::{
+    app::App,
+    arena::Registry,
+    component::Component,
+    counter::{Counter, Msg},
+    html::el,
+};
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+    // the ids keep the two counts apart in storage
+    let left = el!(div, id = "pair-left").build(&mut app);
+    let right = el!(div, id = "pair-right").build(&mut app);
+    let counters = Registry::<Counter>::new();
+    let left_key = counters.insert(Counter::default());
+    let right_key = counters.insert(Counter::default());
+
+    el!(section)
+        .child(
+            el!(p)
+                .child(el!(button).text("Reset both").on("click", {
+                    let counters = counters.handle();
+                    move |_: MouseEvent| counters.for_each(|counter| counter.update(Msg::Reset))
+                }))
+                .child(el!(button).text("Remove the right one").on("click", {
+                    let counters = counters.handle();
+                    let right = right.clone();
+                    // none while the right one's been taken away
+                    let right_key = Cell::new(Some(right_key));
+                    move |ev: MouseEvent| {
+                        let key = match right_key.take() {
+                            Some(key) => {
+                                if let Some(mut counter) = counters.remove(key) {
+                                    counter.unmount();
+                                }
+                                None
+                            }
+                            None => counters.insert(Counter::default()).inspect(|key| {
+                                counters.with(*key, |counter| counter.mount(&right));
+                            }),
+                        };
+                        right_key.set(key);
+                        if let Some(button) = ev.current_target() {
+                            button.unchecked_into::<Element>().set_text_content(Some(
+                                if key.is_some() {
+                                    "Remove the right one"
+                                } else {
+                                    "Put the right one back"
+                                },
+                            ));
+                        }
+                    }
+                })),
+        )
+        .child(
+            el!(div, style = "display: flex; gap: 2em")
+                .child(left.clone())
+                .child(right.clone()),
+        )
+        .mount(&mut app, parent);
+
+    let handle = counters.handle();
+    for (key, host) in [(left_key, &left), (right_key, &right)] {
+        handle.with(key, |counter| counter.mount(host));
+    }
+    app.on_cleanup(move || {
+        for mut counter in counters.drain() {
+            counter.unmount();
+        }
+    });
+    app
+}
@@ -0,0 +1,329 @@
+//! Passkeys: registering a WebAuthn credential and signing in with it.
+//!
+//! `navigator.credentials.create` and `.get` are bound by hand here, along with the
+//! bits of `PublicKeyCredential` we read. WebAuthn passes every id and challenge as a
+//! buffer, so those go in as `Uint8Array`s and come out as `ArrayBuffer`s, and ids are
+//! kept (in `localStorage`) as base64url, the way a server would usually store them.
+//!
+//! There's no server, so nothing checks the signatures: "signing in" only proves the
+//! authenticator still has a passkey we registered, which is as far as a demo can go.
+//! A real relying party makes the challenge, and verifies what comes back.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Element, HtmlInputElement, MouseEvent};
+
+use crate::{
+    app::App,
+    dom::{create_element, spawn_local, window},
+    storage,
+};
+
+const STORAGE_KEY: &str = "passkeys";
+const RELYING_PARTY: &str = "rust-wasm-vanilla-example";
+const TIMEOUT_MS: u32 = 60_000;
+/// ES256 and RS256, which between them cover every authenticator.
+const ALGORITHMS: [i32; 2] = [-7, -257];
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["navigator", "credentials"], js_name = create, catch)]
+    fn credentials_create(options: &JsValue) -> Result<js_sys::Promise, JsValue>;
+
+    #[wasm_bindgen(js_namespace = ["navigator", "credentials"], js_name = get, catch)]
+    fn credentials_get(options: &JsValue) -> Result<js_sys::Promise, JsValue>;
+
+    type PublicKeyCredential;
+
+    #[wasm_bindgen(method, getter, js_name = rawId)]
+    fn raw_id(this: &PublicKeyCredential) -> js_sys::ArrayBuffer;
+
+    /// "platform" (built into the device) or "cross-platform" (a security key, a phone).
+    #[wasm_bindgen(method, getter, js_name = authenticatorAttachment)]
+    fn authenticator_attachment(this: &PublicKeyCredential) -> Option<String>;
+}
+
+/// A registered passkey, as we remember it.
+#[derive(Clone, Serialize, Deserialize)]
+struct Passkey {
+    /// The credential id, base64url.
+    id: String,
+    name: String,
+    attachment: Option<String>,
+}
+
+pub fn is_supported() -> bool {
+    js_sys::Reflect::has(&window(), &"PublicKeyCredential".into()).unwrap_or(false)
+}
+
+fn saved() -> Vec<Passkey> {
+    storage::load(STORAGE_KEY).unwrap_or_default()
+}
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let section = create_element("section");
+    app.append(parent, &section);
+    if !is_supported() {
+        let message = create_element("p");
+        message.set_text_content(Some("This browser doesn't support passkeys."));
+        section.append_child(&message).unwrap();
+        return app;
+    }
+
+    let name = create_element("input").unchecked_into::<HtmlInputElement>();
+    name.set_placeholder("Your name");
+    _ = name.set_attribute("aria-label", "Name");
+    let register = create_element("button");
+    register.set_text_content(Some("Register a passkey"));
+    let sign_in = create_element("button");
+    sign_in.set_text_content(Some("Sign in"));
+    let status = create_element("p");
+    _ = status.set_attribute("role", "status");
+    let list = create_element("ul");
+    for child in [name.unchecked_ref(), &register, &sign_in, &status, &list] {
+        section.append_child(child).unwrap();
+    }
+    render_list(&list);
+
+    // both calls have to start in the click handler itself, while it's a user gesture
+    app.listen(&register, "click", {
+        let status = status.clone();
+        let list = list.clone();
+        move |_: MouseEvent| {
+            let user = name.value().trim().to_string();
+            if user.is_empty() {
+                status.set_text_content(Some("Enter a name first."));
+                return;
+            }
+            let started = credentials_create(&creation_options(&user));
+            let status = status.clone();
+            let list = list.clone();
+            spawn_local(async move {
+                match settle(started).await {
+                    Ok(credential) => {
+                        let mut passkeys = saved();
+                        passkeys.push(Passkey {
+                            id: base64url_encode(&credential.raw_id()),
+                            name: user.clone(),
+                            attachment: credential.authenticator_attachment(),
+                        });
+                        storage::persist(STORAGE_KEY, &passkeys);
+                        render_list(&list);
+                        let message = format!("Registered a passkey for {user}.");
+                        status.set_text_content(Some(&message));
+                    }
+                    Err(error) => status.set_text_content(Some(&error)),
+                }
+            });
+        }
+    });
+    app.listen(&sign_in, "click", move |_: MouseEvent| {
+        let passkeys = saved();
+        if passkeys.is_empty() {
+            status.set_text_content(Some("Register a passkey first."));
+            return;
+        }
+        let started = credentials_get(&request_options(&passkeys));
+        let status = status.clone();
+        spawn_local(async move {
+            let message = match settle(started).await {
+                Ok(credential) => {
+                    let id = base64url_encode(&credential.raw_id());
+                    match passkeys.iter().find(|passkey| passkey.id == id) {
+                        Some(passkey) => format!("Signed in as {}.", passkey.name),
+                        None => "That passkey isn't one registered here.".to_string(),
+                    }
+                }
+                Err(error) => error,
+            };
+            status.set_text_content(Some(&message));
+        });
+    });
+    app.listen(&list, "click", {
+        let list = list.clone();
+        move |ev: MouseEvent| {
+            let forget = ev
+                .target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+                .and_then(|target| target.get_attribute("data-forget"));
+            if let Some(id) = forget {
+                let passkeys: Vec<_> = saved().into_iter().filter(|key| key.id != id).collect();
+                storage::persist(STORAGE_KEY, &passkeys);
+                render_list(&list);
+            }
+        }
+    });
+
+    app
+}
+
+/// The passkeys we remember; forgetting one here leaves it on the authenticator.
+fn render_list(list: &Element) {
+    list.set_inner_html("");
+    for passkey in saved() {
+        let item = create_element("li");
+        let attachment = passkey.attachment.as_deref().unwrap_or("unknown");
+        item.set_text_content(Some(&format!(
+            "{} ({attachment}) {}… ",
+            passkey.name,
+            &passkey.id[..passkey.id.len().min(12)]
+        )));
+        let forget = create_element("button");
+        forget.set_text_content(Some("Forget"));
+        _ = forget.set_attribute("data-forget", &passkey.id);
+        item.append_child(&forget).unwrap();
+        list.append_child(&item).unwrap();
+    }
+}
+
+async fn settle(started: Result<js_sys::Promise, JsValue>) -> Result<PublicKeyCredential, String> {
+    let promise = started.map_err(|e| describe(&e))?;
+    match JsFuture::from(promise).await {
+        Ok(credential) if !credential.is_null() => Ok(credential.unchecked_into()),
+        Ok(_) => Err("No passkey was chosen.".to_string()),
+        Err(e) => Err(describe(&e)),
+    }
+}
+
+fn creation_options(user: &str) -> JsValue {
+    let params = js_sys::Array::new();
+    for alg in ALGORITHMS {
+        params.push(&object(&[
+            ("type", "public-key".into()),
+            ("alg", alg.into()),
+        ]));
+    }
+    let public_key = object(&[
+        ("challenge", bytes(&random_bytes::<32>())),
+        ("rp", object(&[("name", RELYING_PARTY.into())])),
+        (
+            "user",
+            object(&[
+                ("id", bytes(&random_bytes::<16>())),
+                ("name", user.into()),
+                ("displayName", user.into()),
+            ]),
+        ),
+        ("pubKeyCredParams", params.into()),
+        (
+            "authenticatorSelection",
+            object(&[
+                ("residentKey", "preferred".into()),
+                ("userVerification", "preferred".into()),
+            ]),
+        ),
+        ("timeout", TIMEOUT_MS.into()),
+    ]);
+    object(&[("publicKey", public_key)])
+}
+
+fn request_options(passkeys: &[Passkey]) -> JsValue {
+    let allowed = js_sys::Array::new();
+    for passkey in passkeys {
+        if let Some(id) = base64url_decode(&passkey.id) {
+            allowed.push(&object(&[
+                ("type", "public-key".into()),
+                ("id", bytes(&id)),
+            ]));
+        }
+    }
+    let public_key = object(&[
+        ("challenge", bytes(&random_bytes::<32>())),
+        ("allowCredentials", allowed.into()),
+        ("userVerification", "preferred".into()),
+        ("timeout", TIMEOUT_MS.into()),
+    ]);
+    object(&[("publicKey", public_key)])
+}
+
+fn object(entries: &[(&str, JsValue)]) -> JsValue {
+    let object = js_sys::Object::new();
+    for (key, value) in entries {
+        _ = js_sys::Reflect::set(&object, &(*key).into(), value);
+    }
+    object.into()
+}
+
+fn bytes(data: &[u8]) -> JsValue {
+    js_sys::Uint8Array::from(data).into()
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut data = [0; N];
+    if let Ok(crypto) = window().crypto() {
+        _ = crypto.get_random_values_with_u8_array(&mut data);
+    }
+    data
+}
+
+fn base64url_encode(buffer: &js_sys::ArrayBuffer) -> String {
+    encode(&js_sys::Uint8Array::new(buffer).to_vec())
+}
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// base64url without padding, as WebAuthn uses it.
+fn encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, byte)| n | (*byte as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+        }
+    }
+    out
+}
+
+fn base64url_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.as_bytes().chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut n = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let value = ALPHABET.iter().position(|a| a == c)? as u32;
+            n |= value << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            out.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(out)
+}
+
+fn describe(e: &JsValue) -> String {
+    let error = e.dyn_ref::<js_sys::Error>();
+    match error.map(|e| String::from(e.name())).as_deref() {
+        // also what a timeout looks like, on purpose: sites can't tell the two apart
+        Some("NotAllowedError") => "Cancelled, or it took too long.".to_string(),
+        Some("InvalidStateError") => "This authenticator already has that passkey.".to_string(),
+        Some("SecurityError") => "Passkeys need a secure origin (https or localhost).".to_string(),
+        _ => error
+            .map(|e| String::from(e.message()))
+            .unwrap_or_else(|| format!("{e:?}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64url_round_trips() {
+        for data in [&b""[..], b"f", b"fo", b"foo", b"foob", &[0xfb, 0xff, 0xfe]] {
+            let text = encode(data);
+            assert!(!text.contains(['+', '/', '=']));
+            assert_eq!(base64url_decode(&text).as_deref(), Some(data));
+        }
+        assert_eq!(encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode(&[0xfb, 0xff]), "-_8");
+        assert_eq!(base64url_decode("Z"), None);
+    }
+}
@@ -0,0 +1,112 @@
+//! Where the page stands with each browser permission.
+//!
+//! Every permission is looked up with `navigator.permissions.query` and its row kept
+//! up to date through the status's `change` event, so granting or revoking one from
+//! the browser's site settings shows up here straight away. Browsers only know the
+//! names they support; the others just say so.
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Element, Event, PermissionState, PermissionStatus};
+
+use crate::{
+    app::App,
+    dom::{create_element, window},
+    examples::{Example, EXAMPLES},
+};
+
+/// Each permission and the examples (by name) that can ask for it.
+const PERMISSIONS: &[(&str, &[&str])] = &[
+    ("clipboard-read", &["counter"]),
+    ("clipboard-write", &["counter", "selection"]),
+    ("idle-detection", &["clock"]),
+    ("notifications", &[]),
+    ("geolocation", &[]),
+    ("camera", &["camera"]),
+    ("microphone", &["audio"]),
+    ("persistent-storage", &[]),
+];
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let table = create_element("table");
+    let head = create_element("tr");
+    for title in ["Permission", "State", "Used by"] {
+        let th = create_element("th");
+        th.set_text_content(Some(title));
+        _ = th.set_attribute("style", "text-align: left; padding-right: 2em");
+        head.append_child(&th).unwrap();
+    }
+    table.append_child(&head).unwrap();
+    app.append(parent, &table);
+
+    for (name, examples) in PERMISSIONS {
+        let row = create_element("tr");
+        let label = create_element("td");
+        label.set_text_content(Some(name));
+        let state = create_element("td");
+        // read out when a permission changes while the page is open
+        _ = state.set_attribute("aria-live", "polite");
+        state.set_text_content(Some("…"));
+        let used_by = create_element("td");
+        for example in examples.iter().filter_map(|name| find_example(name)) {
+            let link = create_element("a");
+            _ = link.set_attribute("href", &format!("#{}", example.name));
+            _ = link.set_attribute("style", "margin-right: 0.5em");
+            link.set_text_content(Some(example.title));
+            used_by.append_child(&link).unwrap();
+        }
+        if examples.is_empty() {
+            used_by.set_text_content(Some("—"));
+        }
+        row.append_child(&label).unwrap();
+        row.append_child(&state).unwrap();
+        row.append_child(&used_by).unwrap();
+        table.append_child(&row).unwrap();
+
+        app.spawn(async move {
+            let Some(status) = query(name).await else {
+                state.set_text_content(Some("not supported"));
+                return;
+            };
+            render(&state, &status);
+            // the listener belongs to this task: it goes when it's aborted on unmount
+            let mut listeners = App::new();
+            listeners.listen(&status.clone(), "change", move |_: Event| {
+                render(&state, &status)
+            });
+            futures::future::pending::<()>().await;
+            drop(listeners);
+        });
+    }
+
+    app
+}
+
+fn find_example(name: &str) -> Option<&'static Example> {
+    EXAMPLES.iter().find(|example| example.name == name)
+}
+
+/// `None` if the browser has no Permissions API or doesn't know `name`.
+async fn query(name: &str) -> Option<PermissionStatus> {
+    let permissions = window().navigator().permissions().ok()?;
+    let descriptor = js_sys::Object::new();
+    _ = js_sys::Reflect::set(&descriptor, &"name".into(), &name.into());
+    // unknown names reject with a TypeError
+    let promise = permissions.query(&descriptor).ok()?;
+    JsFuture::from(promise)
+        .await
+        .ok()
+        .map(JsCast::unchecked_into)
+}
+
+fn render(cell: &Element, status: &PermissionStatus) {
+    let (text, color) = match status.state() {
+        PermissionState::Granted => ("granted", "#2a2"),
+        PermissionState::Denied => ("denied", "#c22"),
+        _ => ("will ask", "#888"),
+    };
+    cell.set_text_content(Some(text));
+    _ = cell.set_attribute("style", &format!("color: {color}"));
+}
@@ -0,0 +1,43 @@
+//! The popover attribute API: `popover`, `showPopover()` and `hidePopover()`.
+//!
+//! A popover lives in the top layer, so nothing on the page can cover it, whatever
+//! its `z-index` or the stacking contexts around it. The methods aren't in our
+//! `web_sys` yet, so they're bound by hand.
+
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
+use web_sys::Element;
+
+use crate::dom::document;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = HTMLElement)]
+    type PopoverElement;
+
+    #[wasm_bindgen(method, js_name = showPopover, catch)]
+    fn show_popover(this: &PopoverElement) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(method, js_name = hidePopover, catch)]
+    fn hide_popover(this: &PopoverElement) -> Result<(), JsValue>;
+}
+
+pub fn is_supported() -> bool {
+    document()
+        .document_element()
+        .is_some_and(|root| js_sys::Reflect::has(&root, &"showPopover".into()).unwrap_or(false))
+}
+
+/// Turns `el` into a manual popover, hidden until [`show`]n and only closing when
+/// told to.
+pub fn make(el: &Element) {
+    _ = el.set_attribute("popover", "manual");
+}
+
+/// Showing one that's already showing, or isn't in the document, does nothing.
+pub fn show(el: &Element) {
+    _ = el.unchecked_ref::<PopoverElement>().show_popover();
+}
+
+pub fn hide(el: &Element) {
+    _ = el.unchecked_ref::<PopoverElement>().hide_popover();
+}
@@ -0,0 +1,256 @@
+//! A count whose nth prime is worked out on a [`Worker`], so however long that takes,
+//! the page doesn't stop to wait for it.
+//!
+//! The page and the worker speak in [`Ask`] and [`Answer`], serialized by
+//! [`Worker::request`]. Answers come back into the same loop as the buttons' clicks,
+//! as `Msg`s, and [`update`] is the only thing that changes the `Model`. The worker
+//! is asked one thing at a time: if the count moved on while it was busy, it's asked
+//! again about the new count once it answers, rather than queueing up every count
+//! clicked past on the way.
+
+use std::{cell::RefCell, rc::Rc};
+
+use futures::{channel::mpsc::UnboundedSender, StreamExt};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+use web_sys::{Element, MouseEvent};
+
+use crate::{
+    app::App,
+    dom::spawn_local,
+    html::el,
+    worker::{self, get_or_spawn, Reply, Worker},
+};
+
+const STEPS: [i32; 4] = [-1, 1, 1_000, 100_000];
+
+/// What the page asks the worker.
+#[derive(Serialize, Deserialize)]
+struct Ask {
+    n: u32,
+}
+
+/// What the worker answers.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Answer {
+    n: u32,
+    prime: u64,
+    /// How long it took the worker, in milliseconds.
+    took: f64,
+}
+
+#[derive(Debug)]
+enum Msg {
+    Add(i32),
+    Answered(Result<Answer, String>),
+}
+
+#[derive(Debug, PartialEq)]
+struct Model {
+    n: u32,
+    /// The latest answer, which may be about an earlier count.
+    answer: Option<Answer>,
+    /// What the worker is working out, if anything.
+    asked: Option<u32>,
+    error: Option<String>,
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Model {
+            n: 1,
+            answer: None,
+            asked: None,
+            error: None,
+        }
+    }
+}
+
+/// Applies `msg`; returns the count to ask the worker about, if it should be asked.
+fn update(model: &mut Model, msg: Msg) -> Option<u32> {
+    match msg {
+        Msg::Add(delta) => {
+            model.n = model.n.saturating_add_signed(delta).max(1);
+        }
+        Msg::Answered(Ok(answer)) => {
+            model.asked = None;
+            model.error = None;
+            model.answer = Some(answer);
+        }
+        Msg::Answered(Err(e)) => {
+            model.asked = None;
+            model.error = Some(e);
+            // asking again would most likely fail the same way
+            return None;
+        }
+    }
+    let answered = model
+        .answer
+        .as_ref()
+        .is_some_and(|answer| answer.n == model.n);
+    if model.asked.is_some() || answered {
+        return None;
+    }
+    model.asked = Some(model.n);
+    model.asked
+}
+
+/// The `n`th prime, counting 2 as the first.
+fn nth_prime(n: u32) -> u64 {
+    let mut primes: Vec<u64> = Vec::with_capacity(n as usize);
+    let mut candidate = 2;
+    while primes.len() < n as usize {
+        if primes
+            .iter()
+            .take_while(|p| *p * *p <= candidate)
+            .all(|p| candidate % p != 0)
+        {
+            primes.push(candidate);
+        }
+        candidate += if candidate == 2 { 1 } else { 2 };
+    }
+    primes.last().copied().unwrap_or(2)
+}
+
+/// The worker's side: an [`Ask`] in, and an [`Answer`] out.
+pub fn nth_prime_job(argument: JsValue) -> Result<Reply, String> {
+    worker::json(argument, |Ask { n }| {
+        let started = js_sys::Date::now();
+        let prime = nth_prime(n);
+        Ok(Answer {
+            n,
+            prime,
+            took: js_sys::Date::now() - started,
+        })
+    })
+}
+
+fn ordinal(n: u32) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+    let (sender, mut receiver) = futures::channel::mpsc::unbounded::<Msg>();
+
+    let buttons = el!(p).mount(&mut app, parent);
+    let count = el!(output).build(&mut app);
+    buttons.append_child(&count).unwrap();
+    for step in STEPS {
+        let label = if step < 0 {
+            step.to_string()
+        } else {
+            format!("+{step}")
+        };
+        let sender = sender.clone();
+        el!(button)
+            .text(&label)
+            .on("click", move |_: MouseEvent| {
+                _ = sender.unbounded_send(Msg::Add(step));
+            })
+            .mount(&mut app, &buttons);
+    }
+    let working = el!(p).mount(&mut app, parent);
+    let answer = el!(p).mount(&mut app, parent);
+
+    let render = move |model: &Model| {
+        count.set_text_content(Some(&format!("n = {} ", model.n)));
+        let status = match (&model.error, model.asked) {
+            (Some(e), _) => format!("The worker failed: {e}"),
+            (None, Some(n)) => format!("Working out the {} prime…", ordinal(n)),
+            (None, None) => String::new(),
+        };
+        working.set_text_content(Some(&status));
+        answer.set_text_content(model.answer.as_ref().map(describe).as_deref());
+    };
+
+    let mut model = Model::default();
+    let worker = RefCell::new(None::<Rc<Worker>>);
+    if let Some(n) = update(&mut model, Msg::Add(0)) {
+        ask(&worker, n, &sender);
+    }
+    render(&model);
+    app.spawn(async move {
+        while let Some(msg) = receiver.next().await {
+            if let Some(n) = update(&mut model, msg) {
+                ask(&worker, n, &sender);
+            }
+            render(&model);
+        }
+    });
+    app
+}
+
+/// Asks the worker about `n`, and sends its answer back as a `Msg`.
+fn ask(worker: &RefCell<Option<Rc<Worker>>>, n: u32, sender: &UnboundedSender<Msg>) {
+    let sender = sender.clone();
+    match get_or_spawn(worker) {
+        Ok(worker) => spawn_local(async move {
+            let answer = worker.request("nth-prime", &Ask { n }).await;
+            _ = sender.unbounded_send(Msg::Answered(answer));
+        }),
+        Err(e) => _ = sender.unbounded_send(Msg::Answered(Err(e))),
+    }
+}
+
+fn describe(answer: &Answer) -> String {
+    format!(
+        "The {} prime is {} (worked out in {:.0} ms, in a worker)",
+        ordinal(answer.n),
+        answer.prime,
+        answer.took
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_nth_primes() {
+        assert_eq!(nth_prime(1), 2);
+        assert_eq!(nth_prime(2), 3);
+        assert_eq!(nth_prime(6), 13);
+        assert_eq!(nth_prime(1_000), 7_919);
+    }
+
+    #[test]
+    fn names_ordinals() {
+        assert_eq!(ordinal(1), "1st");
+        assert_eq!(ordinal(12), "12th");
+        assert_eq!(ordinal(23), "23rd");
+        assert_eq!(ordinal(111), "111th");
+    }
+
+    #[test]
+    fn asks_about_one_count_at_a_time() {
+        let mut model = Model::default();
+        assert_eq!(update(&mut model, Msg::Add(0)), Some(1));
+        // still busy with 1
+        assert_eq!(update(&mut model, Msg::Add(1)), None);
+        assert_eq!(update(&mut model, Msg::Add(1)), None);
+        let answer = Answer {
+            n: 1,
+            prime: 2,
+            took: 0.0,
+        };
+        // the count moved on meanwhile
+        assert_eq!(
+            update(&mut model, Msg::Answered(Ok(answer.clone()))),
+            Some(3)
+        );
+        assert_eq!(model.answer, Some(answer));
+        assert_eq!(update(&mut model, Msg::Answered(Err("gone".into()))), None);
+        assert_eq!(model.asked, None);
+        assert_eq!(update(&mut model, Msg::Add(1)), Some(4));
+        // already answered
+        assert_eq!(update(&mut model, Msg::Add(-10)), None);
+    }
+}
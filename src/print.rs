@@ -0,0 +1,40 @@
+//! Hooks for swapping a view into a static, print-friendly form.
+//!
+//! `beforeprint`/`afterprint` cover the browser's print dialog; the `print` media
+//! query additionally catches print previews that don't fire those events.
+
+use std::{cell::Cell, rc::Rc};
+
+use web_sys::Event;
+
+use crate::{app::App, dom::window};
+
+/// Calls `before` when the page is about to be printed and `after` once printing is
+/// done, each exactly once per print even if several of the signals fire.
+pub fn on_print(app: &mut App, before: impl Fn() + 'static, after: impl Fn() + 'static) {
+    let printing = Rc::new(Cell::new(false));
+    let set = Rc::new(move |now_printing: bool| {
+        if printing.replace(now_printing) != now_printing {
+            if now_printing {
+                before()
+            } else {
+                after()
+            }
+        }
+    });
+
+    app.listen(&window(), "beforeprint", {
+        let set = set.clone();
+        move |_: Event| set(true)
+    });
+    app.listen(&window(), "afterprint", {
+        let set = set.clone();
+        move |_: Event| set(false)
+    });
+
+    if let Ok(Some(query)) = window().match_media("print") {
+        app.listen(&query.clone(), "change", move |_: Event| {
+            set(query.matches())
+        });
+    }
+}
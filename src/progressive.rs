@@ -0,0 +1,81 @@
+//! Rendering more than fits in a frame, a frame at a time.
+//!
+//! [`render`] makes two passes over the items, both paced by [`time::frames`]: first a
+//! cheap placeholder for every item, so the whole list is on screen (and scrolls to
+//! its full length) early on, and then each placeholder filled in with the detail. A
+//! frame does work until its budget is spent and then waits for the next, so input
+//! and painting get their turn in between, however long the list.
+
+use std::time::Duration;
+
+use futures::StreamExt;
+use web_sys::Element;
+
+use crate::{dom::window, time};
+
+/// How far [`render`] has got, as of the end of a frame.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Progress {
+    pub total: usize,
+    pub placed: usize,
+    pub filled: usize,
+    /// Milliseconds from starting to the frame after the first placeholders went in:
+    /// about when they were first painted.
+    pub first_paint: Option<f64>,
+    /// Milliseconds since starting.
+    pub elapsed: f64,
+}
+
+impl Progress {
+    pub fn done(&self) -> bool {
+        self.filled == self.total
+    }
+}
+
+/// Appends a `placeholder` for each of `items` to `parent`, and then `fill`s each one
+/// in, spending up to `budget` of each frame on it and calling `report` after each.
+pub async fn render<T>(
+    parent: &Element,
+    items: &[T],
+    budget: Duration,
+    placeholder: impl Fn(usize, &T) -> Element,
+    fill: impl Fn(&T, &Element),
+    mut report: impl FnMut(&Progress),
+) {
+    let start = now();
+    let budget = budget.as_secs_f64() * 1000.0;
+    let mut placeholders = Vec::with_capacity(items.len());
+    let mut progress = Progress {
+        total: items.len(),
+        ..Progress::default()
+    };
+    let mut frames = time::frames();
+    while !progress.done() {
+        let Some(frame) = frames.next().await else {
+            return;
+        };
+        if progress.placed > 0 && progress.first_paint.is_none() {
+            progress.first_paint = Some(frame - start);
+        }
+        let frame_start = now();
+        while now() - frame_start < budget && !progress.done() {
+            if let Some(item) = items.get(progress.placed) {
+                let element = placeholder(progress.placed, item);
+                parent.append_child(&element).unwrap();
+                placeholders.push(element);
+                progress.placed += 1;
+            } else {
+                fill(&items[progress.filled], &placeholders[progress.filled]);
+                progress.filled += 1;
+            }
+        }
+        progress.elapsed = now() - start;
+        report(&progress);
+    }
+}
+
+fn now() -> f64 {
+    window()
+        .performance()
+        .map_or(0.0, |performance| performance.now())
+}
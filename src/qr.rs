@@ -0,0 +1,564 @@
+//! A small QR code encoder: byte mode, error correction level M, versions 1–10
+//! (up to 213 bytes, plenty for a URL).
+//!
+//! Follows ISO/IEC 18004 step by step: encode the data into codewords, add
+//! Reed–Solomon error correction per block, interleave the blocks, place the bits
+//! around the function patterns, then keep whichever of the eight masks scores the
+//! lowest penalty.
+
+/// (blocks, data codewords per block)
+type Group = (usize, usize);
+
+/// Per version 1–10 at level M: EC codewords per block, then the two block groups.
+const BLOCKS: [(usize, Group, Group); 10] = [
+    (10, (1, 16), (0, 0)),
+    (16, (1, 28), (0, 0)),
+    (26, (1, 44), (0, 0)),
+    (18, (2, 32), (0, 0)),
+    (24, (2, 43), (0, 0)),
+    (16, (4, 27), (0, 0)),
+    (18, (4, 31), (0, 0)),
+    (22, (2, 38), (2, 39)),
+    (22, (3, 36), (2, 37)),
+    (26, (4, 43), (1, 44)),
+];
+
+/// Centre coordinates of the alignment patterns, per version.
+const ALIGNMENT: [&[usize]; 10] = [
+    &[],
+    &[6, 18],
+    &[6, 22],
+    &[6, 26],
+    &[6, 30],
+    &[6, 34],
+    &[6, 22, 38],
+    &[6, 24, 42],
+    &[6, 26, 46],
+    &[6, 28, 50],
+];
+
+/// The two format bits for error correction level M.
+const LEVEL_M: u32 = 0b00;
+
+pub struct QrCode {
+    pub size: usize,
+    modules: Vec<bool>,
+    /// Modules that belong to finder/timing/alignment/format patterns, not data.
+    function: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encodes `data`, or returns `None` if it's too long for version 10.
+    pub fn encode(data: &[u8]) -> Option<QrCode> {
+        let version = (1..=10).find(|&version| {
+            let count_bits = if version < 10 { 8 } else { 16 };
+            4 + count_bits + data.len() * 8 <= data_codewords(version) * 8
+        })?;
+        let codewords = add_error_correction(version, &encode_data(version, data));
+
+        let size = version * 4 + 17;
+        let mut qr = QrCode {
+            size,
+            modules: vec![false; size * size],
+            function: vec![false; size * size],
+        };
+        qr.draw_function_patterns(version);
+        qr.draw_codewords(&codewords);
+
+        let best = (0..8)
+            .min_by_key(|&mask| {
+                qr.apply_mask(mask);
+                qr.draw_format_bits(mask);
+                let penalty = qr.penalty();
+                // masks are their own inverse
+                qr.apply_mask(mask);
+                penalty
+            })
+            .unwrap_or(0);
+        qr.apply_mask(best);
+        qr.draw_format_bits(best);
+        Some(qr)
+    }
+
+    /// Whether the module at column `x`, row `y` is dark.
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    fn set(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.set(x, y, dark);
+        self.function[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+
+        // finder patterns, with their light separators
+        for (cx, cy) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4i32..=4 {
+                for dx in -4i32..=4 {
+                    let (x, y) = (cx as i32 + dx, cy as i32 + dy);
+                    if (0..size as i32).contains(&x) && (0..size as i32).contains(&y) {
+                        let distance = dx.abs().max(dy.abs());
+                        self.set_function(x as usize, y as usize, distance != 2 && distance != 4);
+                    }
+                }
+            }
+        }
+
+        // alignment patterns everywhere except on top of the finders
+        let positions = ALIGNMENT[version - 1];
+        let last = positions.len().saturating_sub(1);
+        for (i, &cx) in positions.iter().enumerate() {
+            for (j, &cy) in positions.iter().enumerate() {
+                if (i == 0 && (j == 0 || j == last)) || (i == last && j == 0) {
+                    continue;
+                }
+                for dy in -2i32..=2 {
+                    for dx in -2i32..=2 {
+                        let x = (cx as i32 + dx) as usize;
+                        let y = (cy as i32 + dy) as usize;
+                        self.set_function(x, y, dx.abs().max(dy.abs()) != 1);
+                    }
+                }
+            }
+        }
+
+        // reserve the format areas; the real bits depend on the mask
+        self.draw_format_bits(0);
+
+        if version >= 7 {
+            let bits = version_bits(version);
+            for i in 0..18 {
+                let dark = (bits >> i) & 1 == 1;
+                let (a, b) = (size - 11 + i % 3, i / 3);
+                self.set_function(a, b, dark);
+                self.set_function(b, a, dark);
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, mask: u32) {
+        let bits = format_bits(mask);
+        let bit = |i: usize| (bits >> i) & 1 == 1;
+        let size = self.size;
+
+        // around the top left finder
+        for i in 0..=5 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        // split between the other two finders
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        // always dark
+        self.set_function(8, size - 8, true);
+    }
+
+    /// Fills the data area in two-column strips, zigzagging up and down from the right.
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let total_bits = codewords.len() * 8;
+        let mut i = 0;
+        let mut right = size - 1;
+        loop {
+            // the vertical timing pattern shifts every strip left of it by one
+            if right == 6 {
+                right = 5;
+            }
+            let upward = (right + 1) & 2 == 0;
+            for vertical in 0..size {
+                let y = if upward {
+                    size - 1 - vertical
+                } else {
+                    vertical
+                };
+                for x in [right, right - 1] {
+                    if !self.function[y * size + x] && i < total_bits {
+                        let dark = (codewords[i / 8] >> (7 - i % 8)) & 1 == 1;
+                        self.set(x, y, dark);
+                        i += 1;
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32) {
+        let size = self.size;
+        for y in 0..size {
+            for x in 0..size {
+                let flip = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let index = y * size + x;
+                if flip && !self.function[index] {
+                    self.modules[index] = !self.modules[index];
+                }
+            }
+        }
+    }
+
+    /// The four penalty rules from the spec; lower is easier to scan.
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let mut penalty = 0;
+
+        // 1. runs of five or more same-colored modules; 3. finder-like patterns
+        const FINDER: [bool; 11] = [
+            true, false, true, true, true, false, true, false, false, false, false,
+        ];
+        for transpose in [false, true] {
+            for a in 0..size {
+                let line: Vec<bool> = (0..size)
+                    .map(|b| {
+                        if transpose {
+                            self.get(a, b)
+                        } else {
+                            self.get(b, a)
+                        }
+                    })
+                    .collect();
+                let mut run = 1;
+                for b in 1..=size {
+                    if b < size && line[b] == line[b - 1] {
+                        run += 1;
+                    } else {
+                        if run >= 5 {
+                            penalty += run - 2;
+                        }
+                        run = 1;
+                    }
+                }
+                for window in line.windows(11) {
+                    if window == FINDER || window.iter().rev().eq(FINDER.iter()) {
+                        penalty += 40;
+                    }
+                }
+            }
+        }
+
+        // 2. 2×2 blocks of one color
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let color = self.get(x, y);
+                if self.get(x + 1, y) == color
+                    && self.get(x, y + 1) == color
+                    && self.get(x + 1, y + 1) == color
+                {
+                    penalty += 3;
+                }
+            }
+        }
+
+        // 4. how far the share of dark modules is from half, in steps of 5%
+        let total = size * size;
+        let dark = self.modules.iter().filter(|dark| **dark).count();
+        let deviation = (dark * 20).abs_diff(total * 10);
+        penalty += (deviation.div_ceil(total)).saturating_sub(1) * 10;
+
+        penalty
+    }
+}
+
+/// The 15 format bits for level M and `mask`: BCH(15, 5) coded, then XORed with the
+/// spec's mask so they're never all light.
+fn format_bits(mask: u32) -> u32 {
+    let data = LEVEL_M << 3 | mask;
+    let mut remainder = data;
+    for _ in 0..10 {
+        remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+    }
+    (data << 10 | remainder) ^ 0x5412
+}
+
+/// The 18 version bits drawn from version 7 up, BCH(18, 6) coded.
+fn version_bits(version: usize) -> u32 {
+    let mut remainder = version as u32;
+    for _ in 0..12 {
+        remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1F25);
+    }
+    (version as u32) << 12 | remainder
+}
+
+fn data_codewords(version: usize) -> usize {
+    let (_, (blocks1, data1), (blocks2, data2)) = BLOCKS[version - 1];
+    blocks1 * data1 + blocks2 * data2
+}
+
+/// Byte-mode segment, terminator and padding, exactly filling the version's capacity.
+fn encode_data(version: usize, data: &[u8]) -> Vec<u8> {
+    let capacity = data_codewords(version) * 8;
+    let mut bits = BitBuffer::default();
+    bits.push(0b0100, 4);
+    bits.push(data.len() as u32, if version < 10 { 8 } else { 16 });
+    for byte in data {
+        bits.push(*byte as u32, 8);
+    }
+    bits.push(0, (capacity - bits.len).min(4));
+    bits.push(0, (8 - bits.len % 8) % 8);
+    for pad in [0xEC, 0x11].into_iter().cycle() {
+        if bits.len >= capacity {
+            break;
+        }
+        bits.push(pad, 8);
+    }
+    bits.bytes
+}
+
+#[derive(Default)]
+struct BitBuffer {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitBuffer {
+    /// Appends the low `count` bits of `value`, most significant first.
+    fn push(&mut self, value: u32, count: usize) {
+        for i in (0..count).rev() {
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if (value >> i) & 1 == 1 {
+                *self.bytes.last_mut().unwrap() |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+}
+
+/// Splits the data into blocks, computes each block's error correction, and
+/// interleaves everything column by column.
+fn add_error_correction(version: usize, data: &[u8]) -> Vec<u8> {
+    let (ec_len, (blocks1, data1), (blocks2, data2)) = BLOCKS[version - 1];
+    let generator = rs_generator(ec_len);
+
+    let mut blocks = Vec::new();
+    let mut rest = data;
+    for len in std::iter::repeat_n(data1, blocks1).chain(std::iter::repeat_n(data2, blocks2)) {
+        let (block, tail) = rest.split_at(len);
+        rest = tail;
+        blocks.push((block, rs_remainder(block, &generator)));
+    }
+
+    let mut result = Vec::with_capacity(data.len() + ec_len * blocks.len());
+    for i in 0..data1.max(data2) {
+        result.extend(blocks.iter().filter_map(|(block, _)| block.get(i)));
+    }
+    for i in 0..ec_len {
+        result.extend(blocks.iter().map(|(_, ec)| ec[i]));
+    }
+    result
+}
+
+/// Multiplication in GF(2⁸) modulo x⁸ + x⁴ + x³ + x² + 1.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 == 1 {
+            product ^= a;
+        }
+        let carry = a & 0x80 != 0;
+        a <<= 1;
+        if carry {
+            a ^= 0x1D;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// Coefficients of ∏(x − αⁱ) for i in 0..degree, highest power first (the leading 1
+/// is left out).
+fn rs_generator(degree: usize) -> Vec<u8> {
+    let mut generator = vec![0u8; degree];
+    generator[degree - 1] = 1;
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for j in 0..degree {
+            generator[j] = gf_mul(generator[j], root);
+            if j + 1 < degree {
+                generator[j] ^= generator[j + 1];
+            }
+        }
+        root = gf_mul(root, 0x02);
+    }
+    generator
+}
+
+fn rs_remainder(data: &[u8], generator: &[u8]) -> Vec<u8> {
+    let mut remainder = vec![0u8; generator.len()];
+    for byte in data {
+        let factor = byte ^ remainder[0];
+        remainder.rotate_left(1);
+        *remainder.last_mut().unwrap() = 0;
+        for (r, g) in remainder.iter_mut().zip(generator) {
+            *r ^= gf_mul(*g, factor);
+        }
+    }
+    remainder
+}
+
+/// Draws `code` onto `canvas` with `scale` pixels per module and the 4-module light
+/// border scanners expect, resizing the canvas to fit.
+pub fn draw(canvas: &web_sys::HtmlCanvasElement, code: &QrCode, scale: u32) {
+    use wasm_bindgen::JsCast;
+
+    const QUIET_ZONE: usize = 4;
+    let side = (code.size + QUIET_ZONE * 2) as u32 * scale;
+    canvas.set_width(side);
+    canvas.set_height(side);
+    let Some(ctx) = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .and_then(|ctx| ctx.dyn_into::<web_sys::CanvasRenderingContext2d>().ok())
+    else {
+        return;
+    };
+    ctx.set_fill_style(&"white".into());
+    ctx.fill_rect(0.0, 0.0, side as f64, side as f64);
+    ctx.set_fill_style(&"black".into());
+    let scale = scale as f64;
+    for y in 0..code.size {
+        for x in 0..code.size {
+            if code.get(x, y) {
+                ctx.fill_rect(
+                    (x + QUIET_ZONE) as f64 * scale,
+                    (y + QUIET_ZONE) as f64 * scale,
+                    scale,
+                    scale,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_and_version_bits_match_the_spec_tables() {
+        let format = [
+            0b101010000010010,
+            0b101000100100101,
+            0b101111001111100,
+            0b101101101001011,
+            0b100010111111001,
+            0b100000011001110,
+            0b100111110010111,
+            0b100101010100000,
+        ];
+        for (mask, expected) in format.into_iter().enumerate() {
+            assert_eq!(format_bits(mask as u32), expected, "mask {mask}");
+        }
+        let version = [0x07C94, 0x085BC, 0x09A99, 0x0A4D3];
+        for (version, expected) in (7..=10).zip(version) {
+            assert_eq!(version_bits(version), expected, "version {version}");
+        }
+    }
+
+    #[test]
+    fn draws_the_chosen_format_in_both_places() {
+        let code = QrCode::encode(b"https://example.com").unwrap();
+        let size = code.size;
+        let read = |modules: &[(usize, usize)]| {
+            modules
+                .iter()
+                .enumerate()
+                .fold(0, |bits, (i, &(x, y))| bits | (code.get(x, y) as u32) << i)
+        };
+        let mut top_left = (0..=5).map(|i| (8, i)).collect::<Vec<_>>();
+        top_left.extend([(8, 7), (8, 8), (7, 8)]);
+        top_left.extend((9..15).map(|i| (14 - i, 8)));
+        let mut split = (0..8).map(|i| (size - 1 - i, 8)).collect::<Vec<_>>();
+        split.extend((8..15).map(|i| (8, size - 15 + i)));
+
+        let bits = read(&top_left);
+        assert_eq!(read(&split), bits);
+        assert!((0..8).any(|mask| format_bits(mask) == bits));
+        assert!(code.get(8, size - 8));
+    }
+
+    #[test]
+    fn error_correction_matches_a_worked_example() {
+        // "HELLO WORLD" at 1-M, from the usual walkthrough of the spec
+        let data = [
+            32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17,
+        ];
+        let ec = rs_remainder(&data, &rs_generator(10));
+        assert_eq!(ec, [196, 35, 39, 119, 235, 215, 231, 226, 93, 23]);
+    }
+
+    #[test]
+    fn error_correction_makes_every_root_a_zero() {
+        let data = (0..43u8)
+            .map(|i| i.wrapping_mul(37) ^ 0x5A)
+            .collect::<Vec<_>>();
+        for degree in [10, 16, 18, 22, 26] {
+            let ec = rs_remainder(&data, &rs_generator(degree));
+            let mut root = 1;
+            for _ in 0..degree {
+                // Horner's rule over data followed by EC, highest power first
+                let value = data
+                    .iter()
+                    .chain(&ec)
+                    .fold(0, |value, byte| gf_mul(value, root) ^ byte);
+                assert_eq!(value, 0, "degree {degree}, root {root}");
+                root = gf_mul(root, 0x02);
+            }
+        }
+    }
+
+    #[test]
+    fn pads_byte_mode_data_to_capacity() {
+        let data = encode_data(1, b"hello");
+        assert_eq!(
+            data,
+            [
+                0x40, 0x56, 0x86, 0x56, 0xC6, 0xC6, 0xF0, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC,
+                0x11, 0xEC
+            ]
+        );
+    }
+
+    #[test]
+    fn picks_the_smallest_version_that_fits() {
+        // version 1 holds 14 bytes, version 10 holds 213
+        assert_eq!(QrCode::encode(&[b'a'; 14]).unwrap().size, 21);
+        assert_eq!(QrCode::encode(&[b'a'; 15]).unwrap().size, 25);
+        assert_eq!(QrCode::encode(&[b'a'; 213]).unwrap().size, 57);
+        assert!(QrCode::encode(&[b'a'; 214]).is_none());
+        assert_eq!(QrCode::encode(b"").unwrap().size, 21);
+    }
+}
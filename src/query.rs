@@ -0,0 +1,222 @@
+//! Queries: fetched data cached by key, served through a [`Resource`], and kept across
+//! visits in IndexedDB.
+//!
+//! A query with something cached shows it straight away, with no placeholder, and if
+//! it's older than the query's `stale_after` revalidates it in the background
+//! (stale-while-revalidate). Stale queries also revalidate when the page becomes
+//! visible again and when the connection comes back, since those are when the data has
+//! most likely moved on without us.
+//!
+//! Entries are kept as JSON, so the cache doesn't care about types, and what's in
+//! IndexedDB is exactly what's in memory. [`install`] loads them at startup, and
+//! queries wait for that before deciding whether they have anything to show.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    future::Future,
+    rc::Rc,
+    time::Duration,
+};
+
+use futures::future::{FutureExt, LocalBoxFuture, Shared};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use web_sys::Event;
+
+use crate::{
+    app::App,
+    dom::{document, log, spawn_local},
+    idb, network,
+    resource::{Resource, ResourceState},
+};
+
+const DB: &str = "queries";
+const STORE: &str = "entries";
+
+#[derive(Clone, Copy)]
+pub struct QueryOptions {
+    /// How long fetched data counts as fresh.
+    pub stale_after: Duration,
+    pub refetch_on_focus: bool,
+    pub refetch_on_reconnect: bool,
+}
+
+impl Default for QueryOptions {
+    fn default() -> Self {
+        QueryOptions {
+            stale_after: Duration::from_secs(60),
+            refetch_on_focus: true,
+            refetch_on_reconnect: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    key: String,
+    json: String,
+    /// Milliseconds since the epoch, so it means the same next visit.
+    fetched_at: f64,
+}
+
+type Hydrated = Shared<LocalBoxFuture<'static, ()>>;
+
+thread_local! {
+    static CACHE: RefCell<BTreeMap<String, Entry>> = const { RefCell::new(BTreeMap::new()) };
+    static PERSISTED: RefCell<Option<idb::Store>> = const { RefCell::new(None) };
+    static HYDRATED: RefCell<Option<Hydrated>> = const { RefCell::new(None) };
+}
+
+/// Loads the entries kept from earlier visits, and keeps new ones from now on.
+pub fn install() {
+    let hydrated = async {
+        let store = match idb::Store::open(DB, STORE).await {
+            Ok(store) => store,
+            Err(error) => {
+                log!("[query] not keeping the cache: {error}");
+                return;
+            }
+        };
+        match store.values().await {
+            Ok(values) => CACHE.with(|cache| {
+                let mut cache = cache.borrow_mut();
+                for entry in values
+                    .iter()
+                    .filter_map(|value| serde_json::from_str::<Entry>(value).ok())
+                {
+                    // anything fetched while we were loading is newer
+                    if !cache.contains_key(&entry.key) {
+                        cache.insert(entry.key.clone(), entry);
+                    }
+                }
+            }),
+            Err(error) => log!("[query] couldn't load the cache: {error}"),
+        }
+        PERSISTED.with(|persisted| *persisted.borrow_mut() = Some(store));
+    }
+    .boxed_local()
+    .shared();
+    spawn_local(hydrated.clone());
+    HYDRATED.with(|slot| *slot.borrow_mut() = Some(hydrated));
+}
+
+async fn hydrated() {
+    if let Some(hydrated) = HYDRATED.with(|slot| slot.borrow().clone()) {
+        hydrated.await;
+    }
+}
+
+/// A [`Resource`] for the data under `key`, which `fetcher` loads whenever there's none
+/// cached or it's stale. Refetching it through the resource updates the cache too.
+pub fn create_query<T, Fut>(
+    app: &mut App,
+    key: &str,
+    options: QueryOptions,
+    fetcher: impl Fn() -> Fut + 'static,
+    render: impl Fn(&ResourceState<T>) + 'static,
+) -> Resource<T>
+where
+    T: Serialize + DeserializeOwned + 'static,
+    Fut: Future<Output = Result<T, String>> + 'static,
+{
+    let key: Rc<str> = key.into();
+    let resource = Resource::idle(
+        app,
+        {
+            let key = key.clone();
+            move || {
+                let key = key.clone();
+                let load = fetcher();
+                async move {
+                    let value = load.await?;
+                    cache(&key, &value);
+                    Ok(value)
+                }
+            }
+        },
+        render,
+    );
+
+    app.spawn({
+        let resource = resource.clone();
+        let key = key.clone();
+        async move {
+            hydrated().await;
+            match cached::<T>(&key) {
+                Some((value, fetched_at)) => {
+                    resource.set_ready(value);
+                    if is_stale(fetched_at, options.stale_after) {
+                        resource.revalidate();
+                    }
+                }
+                None => resource.refetch(),
+            }
+        }
+    });
+
+    let catch_up = {
+        let resource = resource.clone();
+        move || match cached::<T>(&key) {
+            // nothing to show yet (it failed, say), so it's worth the placeholder
+            None => resource.refetch(),
+            Some((_, fetched_at)) if is_stale(fetched_at, options.stale_after) => {
+                resource.revalidate()
+            }
+            Some(_) => {}
+        }
+    };
+    if options.refetch_on_focus {
+        let catch_up = catch_up.clone();
+        app.listen(&document(), "visibilitychange", move |_: Event| {
+            if !document().hidden() {
+                catch_up();
+            }
+        });
+    }
+    if options.refetch_on_reconnect {
+        let was_online = Cell::new(network::status().online);
+        network::subscribe(app, move |status| {
+            if status.online && !was_online.replace(status.online) {
+                catch_up();
+            }
+        });
+    }
+
+    resource
+}
+
+/// The value cached under `key`, if it still parses as a `T`, and when it was fetched.
+fn cached<T: DeserializeOwned>(key: &str) -> Option<(T, f64)> {
+    CACHE.with(|cache| {
+        let cache = cache.borrow();
+        let entry = cache.get(key)?;
+        let value = serde_json::from_str(&entry.json).ok()?;
+        Some((value, entry.fetched_at))
+    })
+}
+
+fn is_stale(fetched_at: f64, stale_after: Duration) -> bool {
+    js_sys::Date::now() - fetched_at > stale_after.as_millis() as f64
+}
+
+fn cache<T: Serialize>(key: &str, value: &T) {
+    let Ok(json) = serde_json::to_string(value) else {
+        return;
+    };
+    let entry = Entry {
+        key: key.to_string(),
+        json,
+        fetched_at: js_sys::Date::now(),
+    };
+    if let Some(store) = PERSISTED.with(|persisted| persisted.borrow().clone()) {
+        if let Ok(persisted) = serde_json::to_string(&entry) {
+            let key = key.to_string();
+            spawn_local(async move {
+                if let Err(error) = store.put(&key, &persisted).await {
+                    log!("[query] couldn't keep {key:?}: {error}");
+                }
+            });
+        }
+    }
+    CACHE.with(|cache| cache.borrow_mut().insert(key.to_string(), entry));
+}
@@ -0,0 +1,154 @@
+//! A random quote, fetched on demand.
+//!
+//! Shows a skeleton while the request is in flight and a retry button if it fails;
+//! all of that state lives in a [`Resource`]. The quote is a query, so the last one
+//! shown comes back straight away on the next visit, and is only replaced by itself
+//! once it's a few minutes old.
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, MouseEvent};
+
+use crate::{
+    app::App,
+    dom::{create_element, spawn_local},
+    fetch::{self, Mutation, Sent},
+    query::{create_query, QueryOptions},
+    resource::ResourceState,
+    toast,
+};
+
+const ENDPOINT: &str = "https://dummyjson.com/quotes/random";
+/// Answers any request with 200, which is all a demo save needs.
+const SAVE_ENDPOINT: &str = "https://dummyjson.com/http/200";
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Quote {
+    quote: String,
+    author: String,
+}
+
+async fn fetch_quote() -> Result<Quote, String> {
+    let text = fetch::get_text(ENDPOINT).await?;
+    serde_json::from_str(&text).map_err(|error| format!("unexpected response: {error}"))
+}
+
+/// Saves the quote on screen; made offline, the save waits in the fetch queue.
+async fn save_quote(quote: String) {
+    let mutation = Mutation {
+        method: "POST".to_string(),
+        url: SAVE_ENDPOINT.to_string(),
+        body: serde_json::json!({ "quote": quote }).to_string(),
+    };
+    match fetch::mutate(mutation).await {
+        Ok(Sent::Done) => toast::info("Saved"),
+        Ok(Sent::Queued) => toast::info("You're offline: saving as soon as you're back"),
+        Err(error) => toast::error(&format!("Couldn't save: {error}")),
+    }
+}
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let section = create_element("section");
+    let figure = create_element("figure");
+    _ = figure.set_attribute("style", "min-height: 6em; margin: 1em 0");
+    let next = create_element("button");
+    next.set_text_content(Some("Another quote"));
+    let save = create_element("button");
+    save.set_text_content(Some("Save"));
+    section.append_child(&figure).unwrap();
+    section.append_child(&next).unwrap();
+    section.append_child(&save).unwrap();
+    app.append(parent, &section);
+
+    // the quote on screen, for the save button
+    let shown = Rc::new(RefCell::new(None::<Quote>));
+    let options = QueryOptions {
+        stale_after: Duration::from_secs(5 * 60),
+        ..Default::default()
+    };
+    let resource = create_query(&mut app, "quote", options, fetch_quote, {
+        let figure = figure.clone();
+        let next = next.clone();
+        let save = save.clone();
+        let shown = shown.clone();
+        move |state: &ResourceState<Quote>| {
+            figure.set_inner_html("");
+            _ = next
+                .toggle_attribute_with_force("disabled", matches!(state, ResourceState::Loading));
+            let ready = match state {
+                ResourceState::Ready(quote) => Some(quote.clone()),
+                _ => None,
+            };
+            _ = save.toggle_attribute_with_force("disabled", ready.is_none());
+            *shown.borrow_mut() = ready;
+            match state {
+                ResourceState::Loading => render_skeleton(&figure),
+                ResourceState::Ready(quote) => render_quote(&figure, quote),
+                ResourceState::Failed(error) => render_error(&figure, error),
+            }
+        }
+    });
+
+    app.listen(&next, "click", {
+        let resource = resource.clone();
+        move |_: MouseEvent| resource.refetch()
+    });
+    app.listen(&save, "click", move |_: MouseEvent| {
+        if let Some(quote) = shown.borrow().clone() {
+            spawn_local(save_quote(format!("{} — {}", quote.quote, quote.author)));
+        }
+    });
+    // the retry button is re-created on every failure, so listen on the container
+    app.listen(&figure, "click", move |ev: MouseEvent| {
+        let retry = ev
+            .target()
+            .and_then(|target| target.dyn_into::<Element>().ok())
+            .is_some_and(|target| target.has_attribute("data-retry"));
+        if retry {
+            resource.refetch();
+        }
+    });
+
+    app
+}
+
+fn render_skeleton(figure: &Element) {
+    _ = figure.set_attribute("aria-busy", "true");
+    for width in ["90%", "70%", "30%"] {
+        let bar = create_element("div");
+        _ = bar.set_attribute(
+            "style",
+            &format!(
+                "width: {width}; height: 1em; margin: 0.5em 0; border-radius: 4px; \
+                 background: #ddd"
+            ),
+        );
+        figure.append_child(&bar).unwrap();
+    }
+}
+
+fn render_quote(figure: &Element, quote: &Quote) {
+    _ = figure.remove_attribute("aria-busy");
+    let text = create_element("blockquote");
+    text.set_text_content(Some(&quote.quote));
+    let author = create_element("figcaption");
+    author.set_text_content(Some(&format!("— {}", quote.author)));
+    figure.append_child(&text).unwrap();
+    figure.append_child(&author).unwrap();
+}
+
+fn render_error(figure: &Element, error: &str) {
+    _ = figure.remove_attribute("aria-busy");
+    let message = create_element("p");
+    _ = message.set_attribute("role", "alert");
+    message.set_text_content(Some(&format!("Couldn't load a quote: {error}")));
+    let retry = create_element("button");
+    _ = retry.set_attribute("data-retry", "");
+    retry.set_text_content(Some("Retry"));
+    figure.append_child(&message).unwrap();
+    figure.append_child(&retry).unwrap();
+}
@@ -0,0 +1,165 @@
+//! A star rating in half-star steps.
+//!
+//! Hovering previews a rating without committing it (that's internal state the owner
+//! never sees); clicking or using the keyboard commits it and calls `on_change`.
+//! Keys: ArrowRight/ArrowUp add half a star, ArrowLeft/ArrowDown take one away,
+//! Home clears the rating and End gives full marks.
+
+use std::{cell::Cell, rc::Rc};
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, KeyboardEvent, MouseEvent, PointerEvent};
+
+use crate::{app::App, dom::create_element};
+
+const STEP: f32 = 0.5;
+
+pub struct Rating {
+    root: Element,
+    stars: Vec<HtmlElement>,
+    max: u8,
+    value: Cell<f32>,
+    /// What the pointer is hovering over, shown instead of `value` while it's there.
+    preview: Cell<Option<f32>>,
+}
+
+impl Rating {
+    pub fn mount(
+        app: &mut App,
+        parent: &Element,
+        label: &str,
+        max: u8,
+        value: f32,
+        on_change: impl Fn(f32) + 'static,
+    ) -> Rc<Self> {
+        let root = create_element("span");
+        _ = root.set_attribute("role", "slider");
+        _ = root.set_attribute("tabindex", "0");
+        _ = root.set_attribute("aria-label", label);
+        _ = root.set_attribute("aria-valuemin", "0");
+        _ = root.set_attribute("aria-valuemax", &max.to_string());
+        _ = root.set_attribute(
+            "style",
+            "font-size: 2em; cursor: pointer; user-select: none",
+        );
+
+        let stars = (0..max)
+            .map(|_| {
+                let star = create_element("span").unchecked_into::<HtmlElement>();
+                star.set_text_content(Some("★"));
+                // the star is "text" filled with a hard-stopped gradient, so it can be half full
+                _ = star.set_attribute(
+                    "style",
+                    "color: transparent; -webkit-background-clip: text; background-clip: text",
+                );
+                root.append_child(&star).unwrap();
+                star
+            })
+            .collect();
+        app.append(parent, &root);
+
+        let rating = Rc::new(Rating {
+            root,
+            stars,
+            max,
+            value: Cell::new(snap(value, max)),
+            preview: Cell::new(None),
+        });
+        rating.render();
+        let on_change = Rc::new(on_change);
+
+        let commit = {
+            let rating = rating.clone();
+            move |value: f32| {
+                let value = snap(value, rating.max);
+                if value != rating.value.get() {
+                    rating.set_value(value);
+                    on_change(value);
+                }
+            }
+        };
+
+        app.listen(&rating.root, "pointermove", {
+            let rating = rating.clone();
+            move |ev: PointerEvent| {
+                rating.preview.set(rating.value_at(ev.client_x() as f64));
+                rating.render();
+            }
+        });
+        app.listen(&rating.root, "pointerleave", {
+            let rating = rating.clone();
+            move |_: PointerEvent| {
+                rating.preview.set(None);
+                rating.render();
+            }
+        });
+        app.listen(&rating.root, "click", {
+            let rating = rating.clone();
+            let commit = commit.clone();
+            move |ev: MouseEvent| {
+                if let Some(value) = rating.value_at(ev.client_x() as f64) {
+                    commit(value);
+                }
+            }
+        });
+        app.listen(&rating.root, "keydown", {
+            let rating = rating.clone();
+            move |ev: KeyboardEvent| {
+                let value = rating.value.get();
+                let value = match ev.key().as_str() {
+                    "ArrowRight" | "ArrowUp" => value + STEP,
+                    "ArrowLeft" | "ArrowDown" => value - STEP,
+                    "Home" => 0.0,
+                    "End" => rating.max as f32,
+                    _ => return,
+                };
+                ev.prevent_default();
+                commit(value);
+            }
+        });
+
+        rating
+    }
+
+    pub fn set_value(&self, value: f32) {
+        self.value.set(snap(value, self.max));
+        self.render();
+    }
+
+    /// The rating for a pointer at `client_x`: the left half of a star is half a star.
+    fn value_at(&self, client_x: f64) -> Option<f32> {
+        self.stars.iter().enumerate().find_map(|(i, star)| {
+            let rect = star.get_bounding_client_rect();
+            (client_x >= rect.left() && client_x < rect.right()).then(|| {
+                let half = client_x < rect.left() + rect.width() / 2.0;
+                i as f32 + if half { STEP } else { 1.0 }
+            })
+        })
+    }
+
+    fn render(&self) {
+        let shown = self.preview.get().unwrap_or(self.value.get());
+        for (i, star) in self.stars.iter().enumerate() {
+            let fill = ((shown - i as f32).clamp(0.0, 1.0) * 100.0).round();
+            let color = if self.preview.get().is_some() {
+                "orange"
+            } else {
+                "gold"
+            };
+            _ = star.style().set_property(
+                "background-image",
+                &format!("linear-gradient(90deg, {color} {fill}%, #ccc {fill}%)"),
+            );
+        }
+        let value = self.value.get();
+        _ = self.root.set_attribute("aria-valuenow", &value.to_string());
+        _ = self
+            .root
+            .set_attribute("aria-valuetext", &format!("{value} out of {}", self.max));
+    }
+}
+
+/// Rounds to the nearest half star within `0..=max`.
+fn snap(value: f32, max: u8) -> f32 {
+    ((value / STEP).round() * STEP).clamp(0.0, max as f32)
+}
@@ -0,0 +1,62 @@
+//! The rating example: a few independent ratings and what they add up to.
+
+use std::{cell::RefCell, rc::Rc};
+
+use web_sys::Element;
+
+use crate::{app::App, dom::create_element, rating::Rating};
+
+const ITEMS: &[(&str, f32)] = &[("Coffee", 4.5), ("Tea", 3.0), ("Hot chocolate", 0.0)];
+const STARS: u8 = 5;
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let list = create_element("dl");
+    app.append(parent, &list);
+    let summary = create_element("p");
+    app.append(parent, &summary);
+
+    let ratings = Rc::new(RefCell::new(
+        ITEMS.iter().map(|(_, value)| *value).collect::<Vec<_>>(),
+    ));
+    let render = Rc::new(move |ratings: &[f32]| {
+        let rated = ratings
+            .iter()
+            .filter(|value| **value > 0.0)
+            .collect::<Vec<_>>();
+        let text = if rated.is_empty() {
+            "Nothing rated yet".to_string()
+        } else {
+            let average = rated.iter().copied().sum::<f32>() / rated.len() as f32;
+            format!("Average of {} rated: {average:.1} stars", rated.len())
+        };
+        summary.set_text_content(Some(&text));
+    });
+    render(&ratings.borrow());
+
+    for (i, (name, value)) in ITEMS.iter().enumerate() {
+        let term = create_element("dt");
+        term.set_text_content(Some(name));
+        let definition = create_element("dd");
+        list.append_child(&term).unwrap();
+        list.append_child(&definition).unwrap();
+        Rating::mount(
+            &mut app,
+            &definition,
+            &format!("Rate {name}"),
+            STARS,
+            *value,
+            {
+                let ratings = ratings.clone();
+                let render = render.clone();
+                move |value| {
+                    ratings.borrow_mut()[i] = value;
+                    render(&ratings.borrow());
+                }
+            },
+        );
+    }
+
+    app
+}
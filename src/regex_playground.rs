@@ -0,0 +1,190 @@
+//! The regex example: a pattern, some text, and every match highlighted in it.
+//!
+//! Matching is done by the `regex` crate. The pattern is only compiled again once it
+//! has changed and typing has stopped for a moment; editing just the text reuses the
+//! compiled one. Capture groups are drawn nested inside their match, and a pattern that
+//! doesn't compile shows the error under the input instead.
+
+use std::{cell::RefCell, ops::Range, rc::Rc, time::Duration};
+
+use regex::{Captures, Regex, RegexBuilder};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event, HtmlInputElement, HtmlTextAreaElement};
+
+use crate::{app::App, dom::create_element, time::Debounce};
+
+const DELAY: Duration = Duration::from_millis(200);
+/// Keeps a pattern like `a{1000}{1000}` from eating all the memory.
+const SIZE_LIMIT: usize = 1 << 20;
+/// Matches past this are counted but not drawn.
+const LIMIT: usize = 500;
+const PATTERN: &str = r"(?<user>[\w.]+)@(?<domain>\w+)\.(com|org)";
+const TEXT: &str =
+    "Write to ada@example.com or grace.hopper@navy.org,\nbut not to nobody@localhost.";
+/// Group colors, taken in turn.
+const COLORS: &[&str] = &["#9cf", "#fa9", "#9e9", "#d9f"];
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let section = create_element("section");
+    let pattern = create_element("input").unchecked_into::<HtmlInputElement>();
+    pattern.set_value(PATTERN);
+    pattern.set_placeholder("Pattern, e.g. (?i)h(e|a)llo");
+    _ = pattern.set_attribute("aria-label", "Pattern");
+    _ = pattern.set_attribute("aria-describedby", "regex-error");
+    _ = pattern.set_attribute("spellcheck", "false");
+    _ = pattern.set_attribute("style", "width: 100%; font-family: monospace");
+    let error = create_element("pre");
+    error.set_id("regex-error");
+    _ = error.set_attribute("role", "alert");
+    _ = error.set_attribute("style", "color: #c00; margin: 4px 0");
+    let text = create_element("textarea").unchecked_into::<HtmlTextAreaElement>();
+    text.set_value(TEXT);
+    text.set_rows(5);
+    _ = text.set_attribute("aria-label", "Test string");
+    _ = text.set_attribute("style", "width: 100%");
+    let stats = create_element("p");
+    _ = stats.set_attribute("aria-live", "polite");
+    let output = create_element("pre");
+    _ = output.set_attribute(
+        "style",
+        "white-space: pre-wrap; padding: 1em; background: #f6f6f6; border-radius: 4px",
+    );
+    let matches = create_element("ol");
+    for el in [
+        pattern.unchecked_ref::<Element>(),
+        &error,
+        text.unchecked_ref(),
+        &stats,
+        &output,
+        &matches,
+    ] {
+        section.append_child(el).unwrap();
+    }
+    app.append(parent, &section);
+
+    // the last pattern compiled, and what came of it
+    let compiled = RefCell::new(None::<(String, Result<Regex, regex::Error>)>);
+    let render = Rc::new({
+        let pattern = pattern.clone();
+        let text = text.clone();
+        move || {
+            let source = pattern.value();
+            let mut compiled = compiled.borrow_mut();
+            if compiled.as_ref().map(|(last, _)| last) != Some(&source) {
+                let regex = RegexBuilder::new(&source).size_limit(SIZE_LIMIT).build();
+                *compiled = Some((source, regex));
+            }
+            match &compiled.as_ref().unwrap().1 {
+                Ok(regex) => {
+                    _ = pattern.remove_attribute("aria-invalid");
+                    error.set_text_content(None);
+                    render(regex, &text.value(), &stats, &output, &matches);
+                }
+                // keep showing the last matches, so a half-typed pattern doesn't blank them
+                Err(e) => {
+                    _ = pattern.set_attribute("aria-invalid", "true");
+                    error.set_text_content(Some(&e.to_string()));
+                }
+            }
+        }
+    });
+    render();
+
+    let debounce = Rc::new(Debounce::new(DELAY));
+    for input in [pattern.unchecked_ref::<Element>(), text.unchecked_ref()] {
+        let render = render.clone();
+        let debounce = debounce.clone();
+        app.listen(input, "input", move |_: Event| {
+            let render = render.clone();
+            debounce.call(move || render());
+        });
+    }
+
+    app
+}
+
+fn render(regex: &Regex, text: &str, stats: &Element, output: &Element, matches: &Element) {
+    output.set_inner_html("");
+    matches.set_inner_html("");
+    let names = regex.capture_names().collect::<Vec<_>>();
+
+    let mut count = 0;
+    let mut last = 0;
+    for captures in regex.captures_iter(text) {
+        count += 1;
+        if count > LIMIT {
+            continue;
+        }
+        let whole = captures.get(0).unwrap();
+        _ = output.append_with_str_1(&text[last..whole.start()]);
+        let mark = create_element("mark");
+        append_groups(&mark, text, whole.range(), &groups(&captures));
+        output.append_child(&mark).unwrap();
+        last = whole.end();
+
+        let item = create_element("li");
+        let code = create_element("code");
+        code.set_text_content(Some(whole.as_str()));
+        item.append_child(&code).unwrap();
+        _ = item.append_with_str_1(&format!(" at {}–{}", whole.start(), whole.end()));
+        for (i, name) in names.iter().enumerate().skip(1) {
+            let value = captures.get(i).map_or("(no match)", |group| group.as_str());
+            let label = name.map_or_else(|| i.to_string(), str::to_string);
+            _ = item.append_with_str_1(&format!(" · {label}: {value:?}"));
+        }
+        matches.append_child(&item).unwrap();
+    }
+    _ = output.append_with_str_1(&text[last..]);
+
+    stats.set_text_content(Some(&match count {
+        0 => "No matches".to_string(),
+        1 => "1 match".to_string(),
+        n if n > LIMIT => format!("{n} matches, the first {LIMIT} shown"),
+        n => format!("{n} matches"),
+    }));
+}
+
+/// The groups that took part in a match, outermost first where they start together.
+fn groups(captures: &Captures) -> Vec<(usize, Range<usize>)> {
+    let mut groups = (1..captures.len())
+        .filter_map(|i| captures.get(i).map(|group| (i, group.range())))
+        .collect::<Vec<_>>();
+    groups.sort_by_key(|(_, range)| (range.start, std::cmp::Reverse(range.end)));
+    groups
+}
+
+/// Appends `text[range]` to `parent`, with each of `groups` (which are all inside
+/// `range`, sorted by [`groups`]) wrapped in a span, and groups inside groups nested.
+fn append_groups(
+    parent: &Element,
+    text: &str,
+    range: Range<usize>,
+    groups: &[(usize, Range<usize>)],
+) {
+    let mut at = range.start;
+    let mut rest = groups;
+    while let Some(((i, group), after)) = rest.split_first() {
+        // the ones that follow it and fit inside it are its own
+        let inside = after
+            .iter()
+            .take_while(|(_, other)| other.start < group.end || other.end <= group.end)
+            .count();
+        _ = parent.append_with_str_1(&text[at..group.start]);
+        let span = create_element("span");
+        _ = span.set_attribute("title", &format!("Group {i}"));
+        _ = span.set_attribute(
+            "style",
+            &format!(
+                "background: {}; border-radius: 2px; padding: 1px 0",
+                COLORS[(i - 1) % COLORS.len()]
+            ),
+        );
+        append_groups(&span, text, group.clone(), &after[..inside]);
+        parent.append_child(&span).unwrap();
+        at = group.end;
+        rest = &after[inside..];
+    }
+    _ = parent.append_with_str_1(&text[at..range.end]);
+}
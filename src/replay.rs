@@ -0,0 +1,497 @@
+//! Session replay: record what the user does, then play it back.
+//!
+//! Behind the `session-replay` flag, a small toolbar records clicks, key presses,
+//! text input, scrolling and pointer movement, each with its time since the
+//! recording started, into a compact log that can be saved as JSON and loaded again.
+//! Playback moves a fake cursor around the page and re-dispatches the events at the
+//! same elements, found again by a CSS path, so a demo or a bug can be re-run at the
+//! pace it was recorded.
+
+use std::{
+    cell::RefCell,
+    ops::Not,
+    rc::{Rc, Weak},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    AddEventListenerOptions, Element, Event, EventInit, EventTarget, HtmlButtonElement,
+    HtmlElement, HtmlInputElement, KeyboardEvent, KeyboardEventInit, MouseEvent, MouseEventInit,
+    Node,
+};
+
+use crate::{
+    app::App,
+    dom::{body, create_element, document, spawn_local, window},
+    downloads,
+    time::sleep,
+    toast,
+};
+
+/// Pointer moves and scrolls closer together than this are dropped while recording.
+const SAMPLE_INTERVAL: f64 = 50.0;
+const CURSOR_SIZE: u32 = 14;
+
+/// One thing the user did, `at` milliseconds into the recording.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Step {
+    pub at: u32,
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "t", rename_all = "lowercase")]
+pub enum Action {
+    Move {
+        x: i32,
+        y: i32,
+    },
+    Click {
+        x: i32,
+        y: i32,
+        /// A CSS path to the element clicked.
+        el: String,
+    },
+    Key {
+        key: String,
+        code: String,
+        el: String,
+        #[serde(default, skip_serializing_if = "Not::not")]
+        ctrl: bool,
+        #[serde(default, skip_serializing_if = "Not::not")]
+        alt: bool,
+        #[serde(default, skip_serializing_if = "Not::not")]
+        shift: bool,
+        #[serde(default, skip_serializing_if = "Not::not")]
+        meta: bool,
+    },
+    /// A form field's new value.
+    Input {
+        el: String,
+        value: String,
+    },
+    Scroll {
+        x: i32,
+        y: i32,
+    },
+}
+
+enum Mode {
+    Idle,
+    Recording {
+        /// The listeners doing the recording.
+        _recorder: App,
+        started: f64,
+    },
+    Replaying {
+        /// The playback task and cursor.
+        _player: App,
+    },
+}
+
+struct Session {
+    log: Vec<Step>,
+    mode: Mode,
+}
+
+#[derive(Clone)]
+struct Toolbar {
+    root: Element,
+    record: HtmlButtonElement,
+    replay: HtmlButtonElement,
+    save: HtmlButtonElement,
+    load: HtmlInputElement,
+    status: Element,
+}
+
+impl Toolbar {
+    fn render(&self, session: &Session) {
+        let (recording, replaying) = match session.mode {
+            Mode::Idle => (false, false),
+            Mode::Recording { .. } => (true, false),
+            Mode::Replaying { .. } => (false, true),
+        };
+        self.record
+            .set_text_content(Some(if recording { "■ Stop" } else { "● Record" }));
+        self.replay
+            .set_text_content(Some(if replaying { "■ Stop" } else { "▶ Replay" }));
+        let empty = session.log.is_empty();
+        self.record.set_disabled(replaying);
+        self.replay.set_disabled(recording || empty);
+        self.save.set_disabled(recording || replaying || empty);
+        self.load.set_disabled(recording || replaying);
+        let seconds = session.log.last().map_or(0, |step| step.at) as f64 / 1000.0;
+        self.status.set_text_content(Some(&match session.mode {
+            Mode::Recording { .. } => format!("Recording… {} events", session.log.len()),
+            _ => format!("{} events, {seconds:.1} s", session.log.len()),
+        }));
+    }
+}
+
+/// Mounts the recording toolbar, until `app` unmounts.
+pub fn install(app: &mut App) {
+    let button = |label: &str| {
+        let button = create_element("button").unchecked_into::<HtmlButtonElement>();
+        button.set_text_content(Some(label));
+        button
+    };
+    let toolbar = Toolbar {
+        root: create_element("div"),
+        record: button("● Record"),
+        replay: button("▶ Replay"),
+        save: button("Save"),
+        load: create_element("input").unchecked_into(),
+        status: create_element("span"),
+    };
+    toolbar.load.set_type("file");
+    toolbar.load.set_accept(".json,application/json");
+    _ = toolbar.load.set_attribute("aria-label", "Load a recording");
+    _ = toolbar.root.set_attribute("role", "toolbar");
+    _ = toolbar.root.set_attribute("aria-label", "Session replay");
+    _ = toolbar.root.set_attribute(
+        "style",
+        "position: fixed; left: 50%; bottom: 16px; transform: translateX(-50%); \
+         display: flex; gap: 8px; align-items: center; padding: 6px 12px; \
+         background: white; border: 1px solid #ccc; border-radius: 4px; z-index: 1000",
+    );
+    for el in [
+        toolbar.record.unchecked_ref::<Element>(),
+        toolbar.replay.unchecked_ref(),
+        toolbar.save.unchecked_ref(),
+        toolbar.load.unchecked_ref(),
+        &toolbar.status,
+    ] {
+        toolbar.root.append_child(el).unwrap();
+    }
+    app.append(&body().unwrap(), &toolbar.root);
+
+    let session = Rc::new(RefCell::new(Session {
+        log: Vec::new(),
+        mode: Mode::Idle,
+    }));
+    toolbar.render(&session.borrow());
+
+    app.listen(&toolbar.record.clone(), "click", {
+        let session = Rc::downgrade(&session);
+        let toolbar = toolbar.clone();
+        move |_: Event| {
+            let Some(session) = session.upgrade() else {
+                return;
+            };
+            let stopping = matches!(session.borrow().mode, Mode::Recording { .. });
+            if stopping {
+                session.borrow_mut().mode = Mode::Idle;
+            } else {
+                let recorder = record(Rc::downgrade(&session), toolbar.clone());
+                let mut session = session.borrow_mut();
+                session.log.clear();
+                session.mode = Mode::Recording {
+                    _recorder: recorder,
+                    started: js_sys::Date::now(),
+                };
+            }
+            toolbar.render(&session.borrow());
+        }
+    });
+    app.listen(&toolbar.replay.clone(), "click", {
+        let session = Rc::downgrade(&session);
+        let toolbar = toolbar.clone();
+        move |_: Event| {
+            let Some(session) = session.upgrade() else {
+                return;
+            };
+            let stopping = matches!(session.borrow().mode, Mode::Replaying { .. });
+            if stopping {
+                session.borrow_mut().mode = Mode::Idle;
+            } else {
+                let log = session.borrow().log.clone();
+                let player = play(log, Rc::downgrade(&session), toolbar.clone());
+                session.borrow_mut().mode = Mode::Replaying { _player: player };
+            }
+            toolbar.render(&session.borrow());
+        }
+    });
+    app.listen(&toolbar.save.clone(), "click", {
+        let session = Rc::downgrade(&session);
+        move |_: Event| {
+            let Some(session) = session.upgrade() else {
+                return;
+            };
+            match serde_json::to_vec(&session.borrow().log) {
+                Ok(json) => downloads::download("session.json", &json, "application/json"),
+                Err(e) => toast::error(&format!("Couldn't save the recording: {e}")),
+            };
+        }
+    });
+    app.listen(&toolbar.load.clone(), "change", {
+        let session = Rc::downgrade(&session);
+        let toolbar = toolbar.clone();
+        move |_: Event| {
+            let Some(file) = toolbar.load.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            toolbar.load.set_value("");
+            let (session, toolbar) = (session.clone(), toolbar.clone());
+            spawn_local(async move {
+                let text = JsFuture::from(file.text())
+                    .await
+                    .ok()
+                    .and_then(|t| t.as_string());
+                let Some(session) = session.upgrade() else {
+                    return;
+                };
+                match text.as_deref().map(serde_json::from_str::<Vec<Step>>) {
+                    Some(Ok(log)) => session.borrow_mut().log = log,
+                    _ => toast::error("That isn't a saved recording"),
+                }
+                toolbar.render(&session.borrow());
+            });
+        }
+    });
+    app.on_cleanup(move || drop(session));
+}
+
+/// Starts recording into `session`'s log; stops when the returned app is dropped.
+fn record(session: Weak<RefCell<Session>>, toolbar: Toolbar) -> App {
+    let mut app = App::new();
+    let push = Rc::new({
+        let toolbar = toolbar.clone();
+        let last_sample = RefCell::new(0.0);
+        move |action: Action, target: Option<&Node>| {
+            // the toolbar's own buttons aren't part of the session
+            if target.is_some_and(|target| toolbar.root.contains(Some(target))) {
+                return;
+            }
+            let Some(session) = session.upgrade() else {
+                return;
+            };
+            let mut session = session.borrow_mut();
+            let Mode::Recording { started, .. } = session.mode else {
+                return;
+            };
+            let now = js_sys::Date::now();
+            if matches!(action, Action::Move { .. } | Action::Scroll { .. }) {
+                if now - *last_sample.borrow() < SAMPLE_INTERVAL {
+                    return;
+                }
+                *last_sample.borrow_mut() = now;
+            }
+            session.log.push(Step {
+                at: (now - started) as u32,
+                action,
+            });
+            toolbar.render(&session);
+        }
+    });
+    let target_of = |ev: &Event| {
+        ev.target()
+            .and_then(|target| target.dyn_into::<Node>().ok())
+    };
+    let element_of = |ev: &Event| {
+        ev.target()
+            .and_then(|target| target.dyn_into::<Element>().ok())
+    };
+
+    // capturing, so nothing that stops propagation can hide an event from the recording
+    let mut capture = AddEventListenerOptions::new();
+    capture.capture(true).passive(true);
+    app.listen_with_options(&document(), "pointermove", &capture, {
+        let push = push.clone();
+        move |ev: MouseEvent| {
+            let action = Action::Move {
+                x: ev.client_x(),
+                y: ev.client_y(),
+            };
+            push(action, target_of(&ev).as_ref())
+        }
+    });
+    app.listen_with_options(&document(), "click", &capture, {
+        let push = push.clone();
+        move |ev: MouseEvent| {
+            let Some(el) = element_of(&ev) else {
+                return;
+            };
+            let action = Action::Click {
+                x: ev.client_x(),
+                y: ev.client_y(),
+                el: css_path(&el),
+            };
+            push(action, Some(&el))
+        }
+    });
+    app.listen_with_options(&document(), "keydown", &capture, {
+        let push = push.clone();
+        move |ev: KeyboardEvent| {
+            let Some(el) = element_of(&ev) else {
+                return;
+            };
+            let action = Action::Key {
+                key: ev.key(),
+                code: ev.code(),
+                el: css_path(&el),
+                ctrl: ev.ctrl_key(),
+                alt: ev.alt_key(),
+                shift: ev.shift_key(),
+                meta: ev.meta_key(),
+            };
+            push(action, Some(&el))
+        }
+    });
+    app.listen_with_options(&document(), "input", &capture, {
+        let push = push.clone();
+        move |ev: Event| {
+            let Some(el) = element_of(&ev) else {
+                return;
+            };
+            let Some(value) = js_sys::Reflect::get(&el, &"value".into())
+                .ok()
+                .and_then(|value| value.as_string())
+            else {
+                return;
+            };
+            let action = Action::Input {
+                el: css_path(&el),
+                value,
+            };
+            push(action, Some(&el))
+        }
+    });
+    app.listen_with_options(&window(), "scroll", &capture, move |_: Event| {
+        let action = Action::Scroll {
+            x: window().scroll_x().unwrap_or_default() as i32,
+            y: window().scroll_y().unwrap_or_default() as i32,
+        };
+        push(action, None)
+    });
+    app
+}
+
+/// Plays `log` back; stops early if the returned app is dropped.
+fn play(log: Vec<Step>, session: Weak<RefCell<Session>>, toolbar: Toolbar) -> App {
+    let mut app = App::new();
+    let cursor = create_element("div");
+    _ = cursor.set_attribute(
+        "style",
+        &format!(
+            "position: fixed; left: 0; top: 0; z-index: 1001; pointer-events: none; \
+             width: {CURSOR_SIZE}px; height: {CURSOR_SIZE}px; margin: -{half}px 0 0 -{half}px; \
+             border-radius: 50%; background: rgba(220, 0, 80, 0.6); \
+             transition: translate 50ms linear",
+            half = CURSOR_SIZE / 2
+        ),
+    );
+    app.append(&body().unwrap(), &cursor);
+    app.spawn(async move {
+        let mut elapsed = 0;
+        for step in log {
+            sleep(Duration::from_millis(step.at.saturating_sub(elapsed) as u64)).await;
+            elapsed = step.at;
+            replay_step(&step.action, &cursor);
+        }
+        // done: back to idle, which drops this app (and the cursor with it)
+        let Some(session) = session.upgrade() else {
+            return;
+        };
+        session.borrow_mut().mode = Mode::Idle;
+        toolbar.render(&session.borrow());
+    });
+    app
+}
+
+fn replay_step(action: &Action, cursor: &Element) {
+    let move_cursor = |x: i32, y: i32| {
+        _ = cursor
+            .unchecked_ref::<HtmlElement>()
+            .style()
+            .set_property("translate", &format!("{x}px {y}px"));
+    };
+    match action {
+        Action::Move { x, y } => move_cursor(*x, *y),
+        Action::Click { x, y, el } => {
+            move_cursor(*x, *y);
+            let Some(target) = find(el) else {
+                return;
+            };
+            let mut init = MouseEventInit::new();
+            init.client_x(*x)
+                .client_y(*y)
+                .bubbles(true)
+                .cancelable(true);
+            if let Ok(ev) = MouseEvent::new_with_mouse_event_init_dict("click", &init) {
+                _ = target.dispatch_event(&ev);
+            }
+        }
+        Action::Key {
+            key,
+            code,
+            el,
+            ctrl,
+            alt,
+            shift,
+            meta,
+        } => {
+            let target: EventTarget = match find(el) {
+                Some(el) => el.into(),
+                None => document().into(),
+            };
+            let mut init = KeyboardEventInit::new();
+            init.key(key)
+                .code(code)
+                .ctrl_key(*ctrl)
+                .alt_key(*alt)
+                .shift_key(*shift)
+                .meta_key(*meta)
+                .bubbles(true)
+                .cancelable(true);
+            if let Ok(ev) = KeyboardEvent::new_with_keyboard_event_init_dict("keydown", &init) {
+                _ = target.dispatch_event(&ev);
+            }
+        }
+        Action::Input { el, value } => {
+            let Some(target) = find(el) else {
+                return;
+            };
+            _ = js_sys::Reflect::set(&target, &"value".into(), &value.into());
+            let mut init = EventInit::new();
+            init.bubbles(true);
+            if let Ok(ev) = Event::new_with_event_init_dict("input", &init) {
+                _ = target.dispatch_event(&ev);
+            }
+        }
+        Action::Scroll { x, y } => window().scroll_to_with_x_and_y(*x as f64, *y as f64),
+    }
+}
+
+fn find(path: &str) -> Option<Element> {
+    document().query_selector(path).ok().flatten()
+}
+
+/// A selector for `el`: `:nth-child` steps up to the nearest ancestor with an id.
+fn css_path(el: &Element) -> String {
+    let mut steps = Vec::new();
+    let mut current = Some(el.clone());
+    while let Some(el) = current {
+        let id = el.id();
+        if !id.is_empty() {
+            steps.push(format!("[id=\"{}\"]", id.replace('"', "\\\"")));
+            break;
+        }
+        let tag = el.tag_name().to_lowercase();
+        let Some(parent) = el.parent_element() else {
+            steps.push(tag);
+            break;
+        };
+        let children = parent.children();
+        let index = (0..children.length())
+            .position(|i| children.item(i).as_ref() == Some(&el))
+            .unwrap_or_default();
+        steps.push(format!("{tag}:nth-child({})", index + 1));
+        current = Some(parent);
+    }
+    steps.reverse();
+    steps.join(" > ")
+}
@@ -0,0 +1,117 @@
+//! Async data tied to an [`App`]: a loading/ready/failed state machine around a fetcher.
+//!
+//! Every (re)fetch starts in [`ResourceState::Loading`], so the view can show a
+//! placeholder ("suspend") until the data arrives. Refetching cancels a load that's
+//! still in flight, and so does unmounting the app, so a slow response can never
+//! render over a newer one or into a page that's gone.
+//!
+//! A background [`Resource::revalidate`] skips the placeholder instead: whatever is
+//! showing stays until fresher data replaces it, which is how [`crate::query`] serves
+//! cached data while it checks for newer.
+
+use std::{cell::RefCell, future::Future, rc::Rc};
+
+use futures::future::{abortable, AbortHandle, LocalBoxFuture};
+
+use crate::{
+    app::App,
+    dom::{log, spawn_local},
+};
+
+pub enum ResourceState<T> {
+    Loading,
+    Ready(T),
+    Failed(String),
+}
+
+type Fetcher<T> = Box<dyn Fn() -> LocalBoxFuture<'static, Result<T, String>>>;
+type Render<T> = Box<dyn Fn(&ResourceState<T>)>;
+
+struct Inner<T> {
+    fetcher: Fetcher<T>,
+    render: Render<T>,
+    in_flight: RefCell<Option<AbortHandle>>,
+}
+
+pub struct Resource<T> {
+    inner: Rc<Inner<T>>,
+}
+
+impl<T> Clone for Resource<T> {
+    fn clone(&self) -> Self {
+        Resource {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: 'static> Resource<T> {
+    /// Renders nothing and loads nothing until asked to; `render` is then called with
+    /// every state change.
+    pub fn idle<Fut>(
+        app: &mut App,
+        fetcher: impl Fn() -> Fut + 'static,
+        render: impl Fn(&ResourceState<T>) + 'static,
+    ) -> Self
+    where
+        Fut: Future<Output = Result<T, String>> + 'static,
+    {
+        let resource = Resource {
+            inner: Rc::new(Inner {
+                fetcher: Box::new(move || Box::pin(fetcher())),
+                render: Box::new(render),
+                in_flight: RefCell::new(None),
+            }),
+        };
+        app.on_cleanup({
+            let resource = resource.clone();
+            move || resource.cancel()
+        });
+        resource
+    }
+
+    /// Loads again, abandoning any load that hasn't finished yet.
+    pub fn refetch(&self) {
+        self.load(true);
+    }
+
+    /// Loads again without going back to [`ResourceState::Loading`]. If it fails, what
+    /// was showing stays, and the failure is only logged.
+    pub fn revalidate(&self) {
+        self.load(false);
+    }
+
+    /// Shows `value` as if it had just loaded, abandoning any load in flight.
+    pub fn set_ready(&self, value: T) {
+        self.cancel();
+        (self.inner.render)(&ResourceState::Ready(value));
+    }
+
+    fn load(&self, suspend: bool) {
+        self.cancel();
+        if suspend {
+            (self.inner.render)(&ResourceState::Loading);
+        }
+
+        let (load, abort) = abortable((self.inner.fetcher)());
+        *self.inner.in_flight.borrow_mut() = Some(abort);
+        let inner = self.inner.clone();
+        spawn_local(async move {
+            // an aborted load renders nothing: whoever aborted it has moved on
+            if let Ok(result) = load.await {
+                inner.in_flight.borrow_mut().take();
+                match result {
+                    Ok(value) => (inner.render)(&ResourceState::Ready(value)),
+                    Err(error) if suspend => (inner.render)(&ResourceState::Failed(error)),
+                    Err(error) => log!("[resource] couldn't revalidate: {error}"),
+                }
+            }
+        });
+    }
+
+    fn cancel(&self) {
+        if let Some(abort) = self.inner.in_flight.borrow_mut().take() {
+            abort.abort();
+        }
+    }
+}
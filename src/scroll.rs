@@ -0,0 +1,165 @@
+//! Effects tied to how far the page is scrolled: a style that goes from one value to
+//! another as the scroll position crosses a range of pixels.
+//!
+//! Where the browser has `ScrollTimeline`, an effect is a Web Animation on the page's
+//! scroll timeline, and the browser runs it (off the main thread, for transforms).
+//! Elsewhere a passive `scroll` listener asks for at most one animation frame however
+//! many scroll events come before it, and on that frame the values are worked out
+//! here and set. Either way, an effect is written down once, as numbers.
+
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{AddEventListenerOptions, Element, Event, HtmlElement};
+
+use crate::{
+    animate::{self, Keyframe},
+    app::App,
+    dom::{document, window},
+};
+
+/// One style, as a number from `from` to `to`.
+pub struct Effect {
+    /// In camelCase, as in a keyframe: `"fontSize"`.
+    pub property: &'static str,
+    pub from: f64,
+    pub to: f64,
+    /// Writes a value of the number as CSS.
+    pub css: fn(f64) -> String,
+}
+
+impl Effect {
+    fn at(&self, progress: f64) -> String {
+        (self.css)(self.from + (self.to - self.from) * progress)
+    }
+}
+
+pub fn px(value: f64) -> String {
+    format!("{value}px")
+}
+
+/// How far `scroll` is through `start..end`, from 0 to 1.
+pub fn progress(scroll: f64, start: f64, end: f64) -> f64 {
+    if end <= start {
+        return if scroll < start { 0.0 } else { 1.0 };
+    }
+    ((scroll - start) / (end - start)).clamp(0.0, 1.0)
+}
+
+/// Calls `f` with the page's scroll position now, and then at most once a frame while
+/// it scrolls, until `app` unmounts.
+pub fn on_scroll(app: &mut App, f: impl FnMut(f64) + 'static) {
+    let f = Rc::new(RefCell::new(f));
+    (f.borrow_mut())(scroll_y());
+    let waiting = Rc::new(RefCell::new(false));
+    // passive, since it never cancels scrolling: the browser needn't wait to find out
+    let mut options = AddEventListenerOptions::new();
+    options.passive(true);
+    app.listen_with_options(&window(), "scroll", &options, move |_: Event| {
+        if waiting.replace(true) {
+            return;
+        }
+        let frame = Closure::once_into_js({
+            let f = f.clone();
+            let waiting = waiting.clone();
+            move |_: f64| {
+                *waiting.borrow_mut() = false;
+                (f.borrow_mut())(scroll_y());
+            }
+        });
+        _ = window().request_animation_frame(frame.unchecked_ref());
+    });
+}
+
+/// Applies `effects` to `el` as the page scrolls from `start` to `end` pixels down,
+/// until `app` unmounts.
+pub fn link(app: &mut App, el: &Element, start: f64, end: f64, effects: &'static [Effect]) {
+    if let Some(animation) = scroll_timeline_animation(el, start, end, effects) {
+        app.on_cleanup(move || animation.cancel());
+        return;
+    }
+    let style = el.unchecked_ref::<HtmlElement>().style();
+    on_scroll(app, move |scroll| {
+        let progress = progress(scroll, start, end);
+        for effect in effects {
+            let value = JsValue::from(effect.at(progress));
+            _ = js_sys::Reflect::set(&style, &effect.property.into(), &value);
+        }
+    });
+}
+
+fn scroll_timeline_animation(
+    el: &Element,
+    start: f64,
+    end: f64,
+    effects: &[Effect],
+) -> Option<web_sys::Animation> {
+    let constructor = js_sys::Reflect::get(&window(), &"ScrollTimeline".into())
+        .ok()?
+        .dyn_into::<js_sys::Function>()
+        .ok()?;
+    let root = document().document_element()?;
+    let source = js_sys::Object::new();
+    _ = js_sys::Reflect::set(&source, &"source".into(), &root);
+    let timeline = js_sys::Reflect::construct(&constructor, &js_sys::Array::of1(&source)).ok()?;
+
+    let options = js_sys::Object::new();
+    for (key, value) in [
+        ("timeline", timeline),
+        ("rangeStart", px(start).into()),
+        ("rangeEnd", px(end).into()),
+        ("fill", "both".into()),
+    ] {
+        _ = js_sys::Reflect::set(&options, &key.into(), &value);
+    }
+    let values = |progress| -> Vec<(&str, String)> {
+        effects
+            .iter()
+            .map(|effect| (effect.property, effect.at(progress)))
+            .collect()
+    };
+    let (from, to) = (values(0.0), values(1.0));
+    let (from, to) = (borrowed(&from), borrowed(&to));
+    let keyframes: [Keyframe; 2] = [&from, &to];
+    animate::animate_with_options(el, &keyframes, &options)
+}
+
+fn borrowed<'a>(values: &'a [(&'a str, String)]) -> Vec<(&'a str, &'a str)> {
+    values
+        .iter()
+        .map(|(property, value)| (*property, value.as_str()))
+        .collect()
+}
+
+fn scroll_y() -> f64 {
+    window().scroll_y().unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_is_clamped() {
+        assert_eq!(progress(-10.0, 0.0, 100.0), 0.0);
+        assert_eq!(progress(25.0, 0.0, 100.0), 0.25);
+        assert_eq!(progress(150.0, 100.0, 200.0), 0.5);
+        assert_eq!(progress(500.0, 100.0, 200.0), 1.0);
+        // an empty range is a step
+        assert_eq!(progress(99.0, 100.0, 100.0), 0.0);
+        assert_eq!(progress(100.0, 100.0, 100.0), 1.0);
+    }
+
+    #[test]
+    fn effects_interpolate() {
+        let shrink = Effect {
+            property: "fontSize",
+            from: 28.0,
+            to: 18.0,
+            css: px,
+        };
+        assert_eq!(shrink.at(0.0), "28px");
+        assert_eq!(shrink.at(0.5), "23px");
+        assert_eq!(shrink.at(1.0), "18px");
+    }
+}
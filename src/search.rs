@@ -0,0 +1,279 @@
+//! A small full-text index: tokenizing, prefix matching and ranking.
+//!
+//! Terms live in a sorted map, so every term starting with a prefix is one range
+//! scan away and results can update on every keystroke. A document matches when each
+//! query word is (a prefix of) one of its terms; matches are ranked by TF-IDF, with
+//! title matches and whole-word matches counting for more.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+};
+
+/// Title matches count this many times as much as body matches.
+const TITLE_WEIGHT: f64 = 3.0;
+/// A query word that's a whole term beats one that's only its prefix.
+const EXACT_BONUS: f64 = 1.5;
+
+pub struct Document {
+    pub title: String,
+    pub body: String,
+}
+
+/// How often a term shows up in one document.
+#[derive(Default)]
+struct Posting {
+    title: u32,
+    body: u32,
+}
+
+#[derive(Default)]
+pub struct Index {
+    documents: Vec<Document>,
+    /// Term → the documents it's in, by position in `documents`.
+    terms: BTreeMap<String, HashMap<usize, Posting>>,
+}
+
+pub struct Hit<'a> {
+    pub document: &'a Document,
+    pub score: f64,
+}
+
+impl Index {
+    pub fn new(documents: Vec<Document>) -> Self {
+        let mut index = Index::default();
+        for document in documents {
+            index.add(document);
+        }
+        index
+    }
+
+    pub fn add(&mut self, document: Document) {
+        let id = self.documents.len();
+        for (term, _) in tokens(&document.title) {
+            self.terms
+                .entry(term)
+                .or_default()
+                .entry(id)
+                .or_default()
+                .title += 1;
+        }
+        for (term, _) in tokens(&document.body) {
+            self.terms
+                .entry(term)
+                .or_default()
+                .entry(id)
+                .or_default()
+                .body += 1;
+        }
+        self.documents.push(document);
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// The documents matching every word of `query`, best first.
+    pub fn search(&self, query: &str) -> Vec<Hit<'_>> {
+        let words = tokens(query)
+            .into_iter()
+            .map(|(word, _)| word)
+            .collect::<Vec<_>>();
+        if words.is_empty() {
+            return Vec::new();
+        }
+
+        let mut scores: Option<HashMap<usize, f64>> = None;
+        for word in &words {
+            let mut word_scores = HashMap::<usize, f64>::new();
+            for (term, postings) in self.with_prefix(word) {
+                // rarer terms say more about a document
+                let idf = (self.documents.len() as f64 / postings.len() as f64).ln() + 1.0;
+                let bonus = if term == word { EXACT_BONUS } else { 1.0 };
+                for (id, posting) in postings {
+                    let tf = posting.title as f64 * TITLE_WEIGHT + posting.body as f64;
+                    *word_scores.entry(*id).or_default() += tf * idf * bonus;
+                }
+            }
+            // every word has to match somewhere
+            scores = Some(match scores {
+                None => word_scores,
+                Some(scores) => scores
+                    .into_iter()
+                    .filter_map(|(id, score)| word_scores.get(&id).map(|more| (id, score + more)))
+                    .collect(),
+            });
+        }
+
+        let mut hits = scores
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(id, score)| Hit {
+                document: &self.documents[id],
+                score,
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(a.document.title.cmp(&b.document.title))
+        });
+        hits
+    }
+
+    fn with_prefix<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = (&'a String, &'a HashMap<usize, Posting>)> + 'a {
+        self.terms
+            .range(prefix.to_string()..)
+            .take_while(move |(term, _)| term.starts_with(prefix))
+    }
+}
+
+/// The lowercased words of `text`, with where each one is.
+pub fn tokens(text: &str) -> Vec<(String, Range<usize>)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(from)) => {
+                tokens.push((text[from..i].to_lowercase(), from..i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// Splits `text` into pieces, each marked with whether it's a word that `query`
+/// matched, for highlighting.
+pub fn highlight<'a>(text: &'a str, query: &str) -> Vec<(&'a str, bool)> {
+    let words = tokens(query)
+        .into_iter()
+        .map(|(word, _)| word)
+        .collect::<Vec<_>>();
+    let mut pieces = Vec::new();
+    let mut last = 0;
+    for (term, range) in tokens(text) {
+        if let Some(word) = words.iter().find(|word| term.starts_with(word.as_str())) {
+            // only the matched prefix is highlighted, as far as the original text goes
+            let end = text[range.start..]
+                .char_indices()
+                .nth(word.chars().count())
+                .map_or(range.end, |(i, _)| range.start + i)
+                .min(range.end);
+            pieces.push((&text[last..range.start], false));
+            pieces.push((&text[range.start..end], true));
+            last = end;
+        }
+    }
+    pieces.push((&text[last..], false));
+    pieces.retain(|(piece, _)| !piece.is_empty());
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(documents: &[(&str, &str)]) -> Index {
+        Index::new(
+            documents
+                .iter()
+                .map(|(title, body)| Document {
+                    title: title.to_string(),
+                    body: body.to_string(),
+                })
+                .collect(),
+        )
+    }
+
+    fn titles<'a>(index: &'a Index, query: &str) -> Vec<&'a str> {
+        index
+            .search(query)
+            .iter()
+            .map(|hit| hit.document.title.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn tokenizes_into_lowercase_words() {
+        let text = "Hello, WORLD! it's café-42";
+        let words = tokens(text);
+        assert_eq!(
+            words
+                .iter()
+                .map(|(word, _)| word.as_str())
+                .collect::<Vec<_>>(),
+            ["hello", "world", "it", "s", "café", "42"]
+        );
+        for (word, range) in &words {
+            assert_eq!(&text[range.clone()].to_lowercase(), word);
+        }
+        assert!(tokens(" ,.! ").is_empty());
+    }
+
+    #[test]
+    fn matches_every_word_by_prefix() {
+        let index = index(&[
+            ("Rust ownership", "borrowing and moves"),
+            ("Borrow checker", "rust rust"),
+            ("Gardening", "rusty tools"),
+        ]);
+        assert_eq!(index.len(), 3);
+        assert_eq!(
+            titles(&index, "RUST borrow"),
+            ["Borrow checker", "Rust ownership"]
+        );
+        assert_eq!(titles(&index, "too"), ["Gardening"]);
+        assert!(titles(&index, "borrow tools").is_empty());
+        assert!(titles(&index, "ownerships").is_empty());
+        assert!(titles(&index, " !? ").is_empty());
+    }
+
+    #[test]
+    fn ranks_by_tf_idf() {
+        let ranked = index(&[
+            ("Gardening", "rusty tools"),
+            ("Borrow checker", "rust rust"),
+            ("Rust ownership", "borrowing and moves"),
+        ]);
+        // a title match beats two body matches, and both whole words beat a prefix
+        assert_eq!(
+            titles(&ranked, "rust"),
+            ["Rust ownership", "Borrow checker", "Gardening"]
+        );
+
+        // with the same counts, a rarer term scores higher, and ties go by title
+        let rarity = index(&[("c", "rare"), ("b", "rain"), ("a", "rain")]);
+        let hits = rarity.search("r");
+        assert!(hits[0].score > hits[1].score);
+        assert_eq!(hits[1].score, hits[2].score);
+        assert_eq!(titles(&rarity, "r"), ["c", "a", "b"]);
+    }
+
+    #[test]
+    fn highlights_the_matched_prefixes() {
+        assert_eq!(
+            highlight("Rusty tools, rust!", "RUST to"),
+            [
+                ("Rust", true),
+                ("y ", false),
+                ("to", true),
+                ("ols, ", false),
+                ("rust", true),
+                ("!", false),
+            ]
+        );
+        assert_eq!(highlight("Café au lait", "cafe"), [("Café au lait", false)]);
+        assert_eq!(
+            highlight("Café au lait", "caf"),
+            [("Caf", true), ("é au lait", false)]
+        );
+        assert_eq!(highlight("CAFÉ", "café"), [("CAFÉ", true)]);
+        assert_eq!(highlight("nothing here", ""), [("nothing here", false)]);
+    }
+}
@@ -0,0 +1,141 @@
+//! Selection API example: select some text and a small action bubble appears above it.
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event, HtmlElement, MouseEvent, PointerEvent, Range};
+
+use crate::{
+    app::App,
+    clipboard,
+    dom::{create_element, document, spawn_local, window},
+    geometry::{self, Align, Placement, Side},
+    toast,
+};
+
+const TEXT: &[&str] = &[
+    "Select any part of this text with the mouse or keyboard. A small bubble will pop up \
+     next to the selection, positioned from the selection's client rects, with a couple \
+     of actions that work on whatever you selected.",
+    "Everything here is driven from Rust: the selectionchange listener reads \
+     window.getSelection(), asks the selected Range where it is on screen, and moves an \
+     absolutely positioned element there.",
+];
+
+/// Space between the selection and the bubble, in pixels.
+const GAP: f64 = 8.0;
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let article = create_element("article");
+    for paragraph in TEXT {
+        let p = create_element("p");
+        p.set_text_content(Some(paragraph));
+        article.append_child(&p).unwrap();
+    }
+    app.append(parent, &article);
+
+    let bubble = create_element("div");
+    _ = bubble.set_attribute("role", "toolbar");
+    _ = bubble.set_attribute(
+        "style",
+        "position: fixed; display: none; padding: 4px; gap: 4px; background: #333; \
+         border-radius: 4px; box-shadow: 0 2px 6px #0005; z-index: 1000",
+    );
+    let count = create_element("button");
+    count.set_text_content(Some("Count characters"));
+    let copy = create_element("button");
+    copy.set_text_content(Some("Copy"));
+    bubble.append_child(&count).unwrap();
+    bubble.append_child(&copy).unwrap();
+    app.append(parent, &bubble);
+
+    // pressing a button would normally collapse the selection before the click lands
+    app.listen(&bubble, "pointerdown", |ev: PointerEvent| {
+        ev.prevent_default()
+    });
+
+    app.listen(&count, "click", |_: MouseEvent| {
+        let text = selected_text();
+        let chars = text.chars().count();
+        let words = text.split_whitespace().count();
+        toast::info(&format!("{chars} characters, {words} words"));
+    });
+
+    app.listen(&copy, "click", |_: MouseEvent| {
+        let text = selected_text();
+        spawn_local(async move {
+            match clipboard::write_text(&text).await {
+                Ok(()) => toast::info("Copied"),
+                Err(_) => toast::error("Couldn't copy to the clipboard"),
+            }
+        });
+    });
+
+    let reposition = {
+        let article = article.clone();
+        let bubble = bubble.clone();
+        move || match selected_range_in(&article) {
+            Some(range) => show_near(&bubble, &range),
+            None => hide(&bubble),
+        }
+    };
+
+    app.listen(&document(), "selectionchange", {
+        let reposition = reposition.clone();
+        move |_: Event| reposition()
+    });
+    // the bubble is position: fixed, so it has to follow the selection when scrolling
+    app.listen(&window(), "scroll", move |_: Event| reposition());
+
+    app
+}
+
+fn selected_text() -> String {
+    window()
+        .get_selection()
+        .ok()
+        .flatten()
+        .and_then(|selection| selection.to_string().as_string())
+        .unwrap_or_default()
+}
+
+/// The current selection, if it's non-empty and entirely inside `container`.
+fn selected_range_in(container: &Element) -> Option<Range> {
+    let selection = window().get_selection().ok()??;
+    if selection.is_collapsed() || selection.range_count() == 0 {
+        return None;
+    }
+    let range = selection.get_range_at(0).ok()?;
+    let ancestor = range.common_ancestor_container().ok()?;
+    container.contains(Some(&ancestor)).then_some(range)
+}
+
+fn show_near(bubble: &Element, range: &Range) {
+    // a selection across several lines has one rect per line; center the bubble over the first
+    let rects = range.get_client_rects();
+    let Some(first) = rects.and_then(|rects| rects.get(0)) else {
+        return hide(bubble);
+    };
+
+    _ = bubble
+        .unchecked_ref::<HtmlElement>()
+        .style()
+        .set_property("display", "flex");
+    // above the selection if there's room, otherwise below it
+    geometry::place_next_to(
+        bubble,
+        first.into(),
+        Placement {
+            side: Side::Top,
+            align: Align::Center,
+            gap: GAP,
+        },
+    );
+}
+
+fn hide(bubble: &Element) {
+    _ = bubble
+        .unchecked_ref::<HtmlElement>()
+        .style()
+        .set_property("display", "none");
+}
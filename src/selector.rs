@@ -0,0 +1,96 @@
+//! Memoized derived values, reselect-style.
+//!
+//! A selector remembers its last input and output, and only computes again when it's
+//! asked with a different input. That makes the input the selector's whole contract:
+//! everything the computation depends on has to be in it (or, for something big like
+//! a table's rows, a revision number that changes whenever it does).
+//!
+//! Hits and misses are counted per selector name, for debug views.
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: u32,
+    pub misses: u32,
+}
+
+thread_local! {
+    static STATS: RefCell<BTreeMap<&'static str, Stats>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+pub struct Selector<I, O> {
+    name: &'static str,
+    compute: Box<dyn Fn(&I) -> O>,
+    last: RefCell<Option<(I, Rc<O>)>>,
+}
+
+pub fn create_selector<I, O>(
+    name: &'static str,
+    compute: impl Fn(&I) -> O + 'static,
+) -> Selector<I, O> {
+    Selector {
+        name,
+        compute: Box::new(compute),
+        last: RefCell::new(None),
+    }
+}
+
+impl<I: Clone + PartialEq, O> Selector<I, O> {
+    /// The output for `input`, computed only if the input changed since last time.
+    pub fn get(&self, input: &I) -> Rc<O> {
+        if let Some((last_input, output)) = &*self.last.borrow() {
+            if last_input == input {
+                record(self.name, |stats| stats.hits += 1);
+                return output.clone();
+            }
+        }
+        record(self.name, |stats| stats.misses += 1);
+        let output = Rc::new((self.compute)(input));
+        *self.last.borrow_mut() = Some((input.clone(), output.clone()));
+        output
+    }
+}
+
+fn record(name: &'static str, f: impl FnOnce(&mut Stats)) {
+    STATS.with(|stats| f(stats.borrow_mut().entry(name).or_default()));
+}
+
+/// Every selector's hits and misses so far, by name.
+pub fn stats() -> Vec<(&'static str, Stats)> {
+    STATS.with(|stats| {
+        stats
+            .borrow()
+            .iter()
+            .map(|(name, stats)| (*name, *stats))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn only_a_new_input_computes_again() {
+        let runs = Rc::new(Cell::new(0));
+        let doubled = create_selector("test-doubled", {
+            let runs = runs.clone();
+            move |n: &i32| {
+                runs.set(runs.get() + 1);
+                n * 2
+            }
+        });
+        assert_eq!(*doubled.get(&2), 4);
+        assert_eq!(*doubled.get(&2), 4);
+        assert_eq!(*doubled.get(&3), 6);
+        assert_eq!(runs.get(), 2);
+        let stats = stats()
+            .into_iter()
+            .find(|(name, _)| *name == "test-doubled")
+            .map(|(_, stats)| stats);
+        assert_eq!(stats, Some(Stats { hits: 1, misses: 2 }));
+    }
+}
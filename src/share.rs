@@ -0,0 +1,86 @@
+//! Sharing through the system share sheet (`navigator.share`), falling back to copying
+//! the link where there's no share sheet (most desktop browsers).
+//!
+//! Browsers only open the share sheet during a user gesture, so [`share`] has to be
+//! called straight from a click (or key) handler: it calls `navigator.share` right
+//! away and only then returns a future for the outcome.
+
+use std::future::Future;
+
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{clipboard, dom::window};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = navigator, js_name = share, catch)]
+    fn navigator_share(data: &JsValue) -> Result<js_sys::Promise, JsValue>;
+}
+
+pub struct ShareData<'a> {
+    pub title: &'a str,
+    pub text: &'a str,
+    pub url: &'a str,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// Handed to the share sheet.
+    Shared,
+    /// No share sheet here, so the link went to the clipboard instead.
+    Copied,
+    /// The user closed the share sheet without picking anything.
+    Cancelled,
+}
+
+/// Whether this browser has a share sheet at all.
+pub fn is_supported() -> bool {
+    let navigator = window().navigator();
+    js_sys::Reflect::has(&navigator, &"share".into()).unwrap_or(false)
+}
+
+/// Shares `data`; call this directly from an event handler (see the module docs).
+pub fn share(data: &ShareData) -> impl Future<Output = Result<Outcome, String>> {
+    // start the browser call now, while the gesture still counts
+    let (started, outcome) = if is_supported() {
+        (navigator_share(&to_js(data)), Outcome::Shared)
+    } else {
+        (clipboard::write_text_promise(data.url), Outcome::Copied)
+    };
+    async move {
+        let promise = started.map_err(|e| describe(&e))?;
+        match JsFuture::from(promise).await {
+            Ok(_) => Ok(outcome),
+            Err(e) if error_name(&e).as_deref() == Some("AbortError") => Ok(Outcome::Cancelled),
+            Err(e) => Err(describe(&e)),
+        }
+    }
+}
+
+fn to_js(data: &ShareData) -> JsValue {
+    let object = js_sys::Object::new();
+    for (key, value) in [
+        ("title", data.title),
+        ("text", data.text),
+        ("url", data.url),
+    ] {
+        _ = js_sys::Reflect::set(&object, &key.into(), &value.into());
+    }
+    object.into()
+}
+
+fn error_name(e: &JsValue) -> Option<String> {
+    e.dyn_ref::<js_sys::Error>().map(|e| String::from(e.name()))
+}
+
+fn describe(e: &JsValue) -> String {
+    match error_name(e).as_deref() {
+        // called outside a user gesture, or blocked by a permissions policy
+        Some("NotAllowedError") => "sharing isn't allowed right now".to_string(),
+        _ => e
+            .dyn_ref::<js_sys::Error>()
+            .map(|e| String::from(e.message()))
+            .unwrap_or_else(|| format!("{e:?}")),
+    }
+}
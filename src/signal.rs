@@ -0,0 +1,193 @@
+//! Signals: values that know who reads them.
+//!
+//! An effect runs once straight away, and every signal it reads while running
+//! remembers it; setting one of those signals runs the effect again. That's the whole
+//! of fine-grained reactivity: nothing re-renders, each effect updates the one thing it
+//! was written to keep in sync.
+//!
+//! Signals only hold weak references to their effects, so an effect lives exactly as
+//! long as the [`Effect`] handle [`create_effect`] returns.
+
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+thread_local! {
+    /// The effects currently running, innermost last: whichever one is running is
+    /// the one a signal read subscribes.
+    static RUNNING: RefCell<Vec<Rc<EffectInner>>> = const { RefCell::new(Vec::new()) };
+}
+
+struct EffectInner {
+    f: Box<dyn Fn()>,
+}
+
+impl EffectInner {
+    fn run(self: &Rc<Self>) {
+        RUNNING.with(|running| running.borrow_mut().push(self.clone()));
+        (self.f)();
+        RUNNING.with(|running| running.borrow_mut().pop());
+    }
+}
+
+/// A running effect, which stops when this is dropped.
+#[must_use = "the effect stops as soon as this is dropped"]
+pub struct Effect {
+    _inner: Rc<EffectInner>,
+}
+
+/// Runs `f` now, and again whenever a signal it read last time changes.
+pub fn create_effect(f: impl Fn() + 'static) -> Effect {
+    let inner = Rc::new(EffectInner { f: Box::new(f) });
+    inner.run();
+    Effect { _inner: inner }
+}
+
+struct SignalInner<T> {
+    value: RefCell<T>,
+    subscribers: RefCell<Vec<Weak<EffectInner>>>,
+}
+
+impl<T> SignalInner<T> {
+    fn subscribe_running(&self) {
+        let Some(running) = RUNNING.with(|running| running.borrow().last().map(Rc::downgrade))
+        else {
+            return;
+        };
+        let mut subscribers = self.subscribers.borrow_mut();
+        if !subscribers.iter().any(|effect| effect.ptr_eq(&running)) {
+            subscribers.push(running);
+        }
+    }
+
+    fn notify(&self) {
+        // collected first: an effect is free to read (and so subscribe to) this signal
+        let effects = {
+            let mut subscribers = self.subscribers.borrow_mut();
+            subscribers.retain(|effect| effect.strong_count() > 0);
+            subscribers
+                .iter()
+                .filter_map(Weak::upgrade)
+                .collect::<Vec<_>>()
+        };
+        for effect in effects {
+            effect.run();
+        }
+    }
+}
+
+/// The reading half of a signal.
+pub struct ReadSignal<T> {
+    inner: Rc<SignalInner<T>>,
+}
+
+/// The writing half of a signal.
+pub struct WriteSignal<T> {
+    inner: Rc<SignalInner<T>>,
+}
+
+// not derived, which would needlessly ask for `T: Clone`
+impl<T> Clone for ReadSignal<T> {
+    fn clone(&self) -> Self {
+        ReadSignal {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Clone for WriteSignal<T> {
+    fn clone(&self) -> Self {
+        WriteSignal {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+pub fn create_signal<T>(value: T) -> (ReadSignal<T>, WriteSignal<T>) {
+    let inner = Rc::new(SignalInner {
+        value: RefCell::new(value),
+        subscribers: RefCell::new(Vec::new()),
+    });
+    (
+        ReadSignal {
+            inner: inner.clone(),
+        },
+        WriteSignal { inner },
+    )
+}
+
+impl<T: Clone> ReadSignal<T> {
+    /// The current value, subscribing the running effect (if any) to changes.
+    pub fn get(&self) -> T {
+        self.with(T::clone)
+    }
+}
+
+impl<T> ReadSignal<T> {
+    /// Calls `f` with the current value, subscribing the running effect (if any).
+    pub fn with<U>(&self, f: impl FnOnce(&T) -> U) -> U {
+        self.inner.subscribe_running();
+        f(&self.inner.value.borrow())
+    }
+}
+
+impl<T> WriteSignal<T> {
+    pub fn set(&self, value: T) {
+        self.update(|current| *current = value);
+    }
+
+    /// Changes the value in place, then runs the effects that read it.
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        f(&mut self.inner.value.borrow_mut());
+        self.inner.notify();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn effects_rerun_when_what_they_read_changes() {
+        let (count, set_count) = create_signal(0);
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let _effect = create_effect({
+            let seen = seen.clone();
+            move || seen.borrow_mut().push(count.get())
+        });
+        set_count.set(1);
+        set_count.update(|count| *count += 1);
+        assert_eq!(*seen.borrow(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn effects_ignore_signals_they_never_read() {
+        let (_, set_other) = create_signal(0);
+        let runs = Rc::new(Cell::new(0));
+        let _effect = create_effect({
+            let runs = runs.clone();
+            move || runs.set(runs.get() + 1)
+        });
+        set_other.set(1);
+        assert_eq!(runs.get(), 1);
+    }
+
+    #[test]
+    fn dropped_effects_stop() {
+        let (count, set_count) = create_signal(0);
+        let runs = Rc::new(Cell::new(0));
+        let effect = create_effect({
+            let runs = runs.clone();
+            move || {
+                count.get();
+                runs.set(runs.get() + 1);
+            }
+        });
+        drop(effect);
+        set_count.set(1);
+        assert_eq!(runs.get(), 1);
+    }
+}
@@ -0,0 +1,532 @@
+//! A small spreadsheet: click a cell (or press Enter or start typing) to edit it;
+//! formulas start with `=`.
+//!
+//! The sheet keeps, for every cell, the cells it reads and the cells that read it.
+//! Changing a cell recomputes only that cell and whatever depends on it (in dependency
+//! order), and only the `<td>`s whose displayed value actually changed are touched.
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap, HashSet},
+    rc::Rc,
+};
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event, HtmlElement, HtmlInputElement, KeyboardEvent, MouseEvent};
+
+use crate::{
+    app::App,
+    dom::create_element,
+    formula::{CellError, CellRef, Expr},
+    title::{self, Title},
+};
+
+const COLS: u8 = 8;
+const ROWS: u16 = 20;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Empty,
+    Number(f64),
+    Text(String),
+    Error(CellError),
+}
+
+impl Value {
+    fn display(&self) -> String {
+        match self {
+            Value::Empty => String::new(),
+            Value::Number(n) => format!("{}", (n * 1e9).round() / 1e9),
+            Value::Text(text) => text.clone(),
+            Value::Error(error) => error.to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Sheet {
+    raw: HashMap<CellRef, String>,
+    formulas: HashMap<CellRef, Result<Expr, CellError>>,
+    values: HashMap<CellRef, Value>,
+    /// cell → the cells its formula reads
+    reads: HashMap<CellRef, Vec<CellRef>>,
+    /// cell → the cells whose formulas read it
+    readers: HashMap<CellRef, HashSet<CellRef>>,
+}
+
+impl Sheet {
+    pub fn raw(&self, cell: CellRef) -> &str {
+        self.raw.get(&cell).map_or("", String::as_str)
+    }
+
+    pub fn value(&self, cell: CellRef) -> &Value {
+        self.values.get(&cell).unwrap_or(&Value::Empty)
+    }
+
+    /// Sets a cell's contents; returns every cell whose value changed.
+    pub fn set(&mut self, cell: CellRef, raw: &str) -> Vec<CellRef> {
+        let raw = raw.trim();
+        for read in self.reads.remove(&cell).unwrap_or_default() {
+            if let Some(readers) = self.readers.get_mut(&read) {
+                readers.remove(&cell);
+            }
+        }
+        self.formulas.remove(&cell);
+        if raw.is_empty() {
+            self.raw.remove(&cell);
+        } else {
+            self.raw.insert(cell, raw.to_string());
+        }
+
+        if let Some(source) = raw.strip_prefix('=') {
+            let formula = Expr::parse(source);
+            if let Ok(expr) = &formula {
+                let mut reads = Vec::new();
+                expr.refs(&mut reads);
+                for read in &reads {
+                    self.readers.entry(*read).or_default().insert(cell);
+                }
+                self.reads.insert(cell, reads);
+            }
+            self.formulas.insert(cell, formula);
+        }
+
+        self.recompute(cell)
+    }
+
+    /// Recomputes `changed` and everything downstream of it, each cell after the cells
+    /// it reads. Cells that are left over can only be part of a cycle.
+    fn recompute(&mut self, changed: CellRef) -> Vec<CellRef> {
+        let mut affected = BTreeSet::from([changed]);
+        let mut stack = vec![changed];
+        while let Some(cell) = stack.pop() {
+            for reader in self.readers.get(&cell).into_iter().flatten() {
+                if affected.insert(*reader) {
+                    stack.push(*reader);
+                }
+            }
+        }
+
+        // Kahn's algorithm over just the affected cells
+        let mut pending: HashMap<CellRef, usize> = affected
+            .iter()
+            .map(|cell| {
+                let reads = self.reads.get(cell).into_iter().flatten();
+                (*cell, reads.filter(|read| affected.contains(read)).count())
+            })
+            .collect();
+        let mut ready: Vec<CellRef> = pending
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(cell, _)| *cell)
+            .collect();
+        let mut updated = Vec::new();
+        while let Some(cell) = ready.pop() {
+            pending.remove(&cell);
+            let value = self.evaluate(cell);
+            if self.set_value(cell, value) {
+                updated.push(cell);
+            }
+            for reader in self.readers.get(&cell).cloned().into_iter().flatten() {
+                if let Some(count) = pending.get_mut(&reader) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(reader);
+                    }
+                }
+            }
+        }
+        for (cell, _) in pending {
+            if self.set_value(cell, Value::Error(CellError::Cycle)) {
+                updated.push(cell);
+            }
+        }
+        updated
+    }
+
+    fn evaluate(&self, cell: CellRef) -> Value {
+        let raw = self.raw(cell);
+        match self.formulas.get(&cell) {
+            Some(Ok(expr)) => {
+                let lookup = |cell: CellRef| match self.value(cell) {
+                    Value::Empty => Ok(0.0),
+                    Value::Number(n) => Ok(*n),
+                    Value::Text(_) => Err(CellError::Value),
+                    Value::Error(error) => Err(*error),
+                };
+                match expr.eval(&lookup) {
+                    Ok(n) => Value::Number(n),
+                    Err(error) => Value::Error(error),
+                }
+            }
+            Some(Err(error)) => Value::Error(*error),
+            None if raw.is_empty() => Value::Empty,
+            None => raw
+                .parse()
+                .map(Value::Number)
+                .unwrap_or_else(|_| Value::Text(raw.to_string())),
+        }
+    }
+
+    /// Returns whether the value actually changed.
+    fn set_value(&mut self, cell: CellRef, value: Value) -> bool {
+        if self.value(cell) == &value {
+            return false;
+        }
+        if value == Value::Empty {
+            self.values.remove(&cell);
+        } else {
+            self.values.insert(cell, value);
+        }
+        true
+    }
+}
+
+struct Grid {
+    sheet: RefCell<Sheet>,
+    cells: HashMap<CellRef, HtmlElement>,
+    selected: RefCell<CellRef>,
+    /// The open editor and the cell it edits.
+    editing: RefCell<Option<(CellRef, HtmlInputElement)>>,
+    formula_bar: Element,
+    /// The selected cell's name, in the page title.
+    title: Title,
+}
+
+impl Grid {
+    fn select(&self, cell: CellRef) {
+        let previous = self.selected.replace(cell);
+        if let Some(td) = self.cells.get(&previous) {
+            _ = td.set_attribute("tabindex", "-1");
+            _ = td.style().remove_property("outline");
+        }
+        if let Some(td) = self.cells.get(&cell) {
+            _ = td.set_attribute("tabindex", "0");
+            _ = td.style().set_property("outline", "2px solid #36c");
+            _ = td.focus();
+        }
+        self.formula_bar
+            .set_text_content(Some(&format!("{cell}: {}", self.sheet.borrow().raw(cell))));
+        self.title.set(&cell.to_string());
+    }
+
+    fn start_editing(&self, cell: CellRef, initial: Option<String>) {
+        self.cancel_editing();
+        let Some(td) = self.cells.get(&cell) else {
+            return;
+        };
+        let input = create_element("input").unchecked_into::<HtmlInputElement>();
+        _ = input.set_attribute(
+            "style",
+            "width: 100%; box-sizing: border-box; font: inherit",
+        );
+        _ = input.set_attribute("aria-label", &format!("Edit {cell}"));
+        input.set_value(&initial.unwrap_or_else(|| self.sheet.borrow().raw(cell).to_string()));
+        td.set_text_content(None);
+        td.append_child(&input).unwrap();
+        _ = input.focus();
+        *self.editing.borrow_mut() = Some((cell, input));
+    }
+
+    /// Commits the editor, if one is open.
+    fn commit(&self) {
+        let Some((cell, input)) = self.editing.borrow_mut().take() else {
+            return;
+        };
+        input.remove();
+        let changed = self.sheet.borrow_mut().set(cell, &input.value());
+        // the edited cell always needs its text back, even if its value didn't change
+        self.render(cell);
+        for cell in changed {
+            self.render(cell);
+        }
+    }
+
+    fn cancel_editing(&self) {
+        if let Some((cell, input)) = self.editing.borrow_mut().take() {
+            input.remove();
+            self.render(cell);
+        }
+    }
+
+    fn render(&self, cell: CellRef) {
+        let Some(td) = self.cells.get(&cell) else {
+            return;
+        };
+        let sheet = self.sheet.borrow();
+        let value = sheet.value(cell);
+        td.set_text_content(Some(&value.display()));
+        let align = if matches!(value, Value::Number(_)) {
+            "right"
+        } else {
+            "left"
+        };
+        _ = td.style().set_property("text-align", align);
+        let color = if matches!(value, Value::Error(_)) {
+            "#c00"
+        } else {
+            "inherit"
+        };
+        _ = td.style().set_property("color", color);
+        // the raw contents are one hover away
+        _ = td.set_attribute("title", sheet.raw(cell));
+    }
+
+    fn moved(&self, d_col: i32, d_row: i32) -> CellRef {
+        let cell = *self.selected.borrow();
+        CellRef {
+            col: (cell.col as i32 + d_col).clamp(0, COLS as i32 - 1) as u8,
+            row: (cell.row as i32 + d_row).clamp(0, ROWS as i32 - 1) as u16,
+        }
+    }
+}
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let section = create_element("section");
+    let formula_bar = create_element("output");
+    _ = formula_bar.set_attribute(
+        "style",
+        "display: block; font-family: monospace; min-height: 1.5em; margin-bottom: 4px",
+    );
+    let table = create_element("table");
+    _ = table.set_attribute("role", "grid");
+    _ = table.set_attribute("style", "border-collapse: collapse; font-family: monospace");
+
+    let header = create_element("tr");
+    header.append_child(&create_element("th")).unwrap();
+    for col in 0..COLS {
+        let th = create_element("th");
+        th.set_text_content(Some(&((b'A' + col) as char).to_string()));
+        header.append_child(&th).unwrap();
+    }
+    table.append_child(&header).unwrap();
+
+    let mut cells = HashMap::new();
+    for row in 0..ROWS {
+        let tr = create_element("tr");
+        let th = create_element("th");
+        th.set_text_content(Some(&(row + 1).to_string()));
+        tr.append_child(&th).unwrap();
+        for col in 0..COLS {
+            let cell = CellRef { col, row };
+            let td = create_element("td").unchecked_into::<HtmlElement>();
+            _ = td.set_attribute("data-cell", &cell.to_string());
+            _ = td.set_attribute("tabindex", "-1");
+            _ = td.set_attribute(
+                "style",
+                "border: 1px solid #ccc; width: 6em; height: 1.5em; padding: 0 4px",
+            );
+            tr.append_child(&td).unwrap();
+            cells.insert(cell, td);
+        }
+        table.append_child(&tr).unwrap();
+    }
+    section.append_child(&formula_bar).unwrap();
+    section.append_child(&table).unwrap();
+    app.append(parent, &section);
+
+    let grid = Rc::new(Grid {
+        sheet: Default::default(),
+        cells,
+        selected: RefCell::new(CellRef { col: 0, row: 0 }),
+        editing: RefCell::new(None),
+        formula_bar,
+        title: title::push(&mut app, ""),
+    });
+    for (name, raw) in [
+        ("A1", "Apples"),
+        ("B1", "3"),
+        ("A2", "Pears"),
+        ("B2", "5"),
+        ("A3", "Total"),
+        ("B3", "=SUM(B1:B2)"),
+        ("C3", "=B3*1.2"),
+    ] {
+        let cell = CellRef::parse(name).unwrap();
+        for changed in grid.sheet.borrow_mut().set(cell, raw) {
+            grid.render(changed);
+        }
+    }
+    grid.select(CellRef { col: 0, row: 0 });
+
+    app.listen(&table, "click", {
+        let grid = grid.clone();
+        move |ev: MouseEvent| {
+            let cell = ev
+                .target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+                .and_then(|target| target.closest("td").ok().flatten())
+                .and_then(|td| td.get_attribute("data-cell"))
+                .and_then(|name| CellRef::parse(&name));
+            let Some(cell) = cell else { return };
+            if grid
+                .editing
+                .borrow()
+                .as_ref()
+                .is_some_and(|(editing, _)| *editing == cell)
+            {
+                return;
+            }
+            grid.commit();
+            grid.select(cell);
+            grid.start_editing(cell, None);
+        }
+    });
+
+    app.listen(&table, "keydown", {
+        let grid = grid.clone();
+        move |ev: KeyboardEvent| {
+            let key = ev.key();
+            let editing = grid.editing.borrow().is_some();
+            let next = if editing {
+                match key.as_str() {
+                    "Enter" => Some(grid.moved(0, if ev.shift_key() { -1 } else { 1 })),
+                    "Tab" => Some(grid.moved(if ev.shift_key() { -1 } else { 1 }, 0)),
+                    "Escape" => {
+                        grid.cancel_editing();
+                        Some(*grid.selected.borrow())
+                    }
+                    _ => return,
+                }
+            } else {
+                match key.as_str() {
+                    "ArrowUp" => Some(grid.moved(0, -1)),
+                    "ArrowDown" => Some(grid.moved(0, 1)),
+                    "ArrowLeft" => Some(grid.moved(-1, 0)),
+                    "ArrowRight" => Some(grid.moved(1, 0)),
+                    "Tab" => Some(grid.moved(if ev.shift_key() { -1 } else { 1 }, 0)),
+                    "Enter" | "F2" => {
+                        grid.start_editing(*grid.selected.borrow(), None);
+                        None
+                    }
+                    "Delete" | "Backspace" => {
+                        let cell = *grid.selected.borrow();
+                        for changed in grid.sheet.borrow_mut().set(cell, "") {
+                            grid.render(changed);
+                        }
+                        grid.select(cell);
+                        None
+                    }
+                    // typing over a selected cell replaces its contents, like any spreadsheet
+                    _ if key.chars().count() == 1 && !ev.ctrl_key() && !ev.meta_key() => {
+                        grid.start_editing(*grid.selected.borrow(), Some(key));
+                        None
+                    }
+                    _ => return,
+                }
+            };
+            ev.prevent_default();
+            if let Some(next) = next {
+                grid.commit();
+                grid.select(next);
+            }
+        }
+    });
+
+    // clicking away from the editor keeps what was typed
+    app.listen(&table, "focusout", move |ev: Event| {
+        let leaving_editor = ev
+            .target()
+            .is_some_and(|target| target.has_type::<HtmlInputElement>());
+        if leaving_editor {
+            grid.commit();
+            let cell = *grid.selected.borrow();
+            grid.select(cell);
+        }
+    });
+
+    app
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(name: &str) -> CellRef {
+        CellRef::parse(name).unwrap()
+    }
+
+    fn set(sheet: &mut Sheet, name: &str, raw: &str) -> Vec<String> {
+        let mut changed = sheet.set(cell(name), raw);
+        changed.sort();
+        changed.iter().map(ToString::to_string).collect()
+    }
+
+    fn value(sheet: &Sheet, name: &str) -> Value {
+        sheet.value(cell(name)).clone()
+    }
+
+    #[test]
+    fn holds_numbers_text_and_formulas() {
+        let mut sheet = Sheet::default();
+        set(&mut sheet, "A1", " 2.5 ");
+        set(&mut sheet, "A2", "apples");
+        set(&mut sheet, "A3", "=A1*2");
+        set(&mut sheet, "A4", "=A2+1");
+        set(&mut sheet, "A5", "=1+");
+        assert_eq!(sheet.raw(cell("A1")), "2.5");
+        assert_eq!(value(&sheet, "A1"), Value::Number(2.5));
+        assert_eq!(value(&sheet, "A2"), Value::Text("apples".into()));
+        assert_eq!(value(&sheet, "A3"), Value::Number(5.0));
+        assert_eq!(value(&sheet, "A4"), Value::Error(CellError::Value));
+        assert_eq!(value(&sheet, "A5").display(), "#PARSE!");
+        assert_eq!(value(&sheet, "B1"), Value::Empty);
+        assert_eq!(Value::Number(0.1 + 0.2).display(), "0.3");
+    }
+
+    #[test]
+    fn recomputes_only_what_changed() {
+        let mut sheet = Sheet::default();
+        set(&mut sheet, "A1", "1");
+        set(&mut sheet, "A2", "=A1+1");
+        set(&mut sheet, "A3", "=A2*10");
+        set(&mut sheet, "B1", "=SUM(A1:A3)");
+        set(&mut sheet, "C1", "=MIN(A1, 0)");
+        assert_eq!(value(&sheet, "B1"), Value::Number(23.0));
+
+        // C1 reads A1 but stays 0, so it isn't reported
+        assert_eq!(set(&mut sheet, "A1", "2"), ["A1", "A2", "A3", "B1"]);
+        assert_eq!(value(&sheet, "A3"), Value::Number(30.0));
+        assert_eq!(value(&sheet, "B1"), Value::Number(35.0));
+
+        // A2 no longer reads A1, so A1 changing leaves it alone
+        assert_eq!(set(&mut sheet, "A2", "5"), ["A2", "A3", "B1"]);
+        assert_eq!(set(&mut sheet, "A1", "3"), ["A1", "B1"]);
+        assert_eq!(set(&mut sheet, "A1", "3"), Vec::<String>::new());
+
+        // an error flows downstream, and so does its fix
+        set(&mut sheet, "A2", "=1/0");
+        assert_eq!(value(&sheet, "B1"), Value::Error(CellError::DivZero));
+        assert_eq!(set(&mut sheet, "A2", ""), ["A2", "A3", "B1"]);
+        assert_eq!(value(&sheet, "B1"), Value::Number(3.0));
+    }
+
+    #[test]
+    fn detects_cycles() {
+        let mut sheet = Sheet::default();
+        set(&mut sheet, "A1", "=A1");
+        assert_eq!(value(&sheet, "A1"), Value::Error(CellError::Cycle));
+
+        set(&mut sheet, "A1", "=B1+1");
+        set(&mut sheet, "B1", "=C1+1");
+        set(&mut sheet, "D1", "=A1");
+        assert_eq!(value(&sheet, "D1"), Value::Number(2.0));
+        assert_eq!(set(&mut sheet, "C1", "=A1"), ["A1", "B1", "C1", "D1"]);
+        for name in ["A1", "B1", "C1"] {
+            assert_eq!(
+                value(&sheet, name),
+                Value::Error(CellError::Cycle),
+                "{name}"
+            );
+        }
+        // D1 only reads the cycle
+        assert_eq!(value(&sheet, "D1"), Value::Error(CellError::Cycle));
+
+        // breaking it recomputes the whole loop
+        set(&mut sheet, "C1", "1");
+        assert_eq!(value(&sheet, "A1"), Value::Number(3.0));
+        assert_eq!(value(&sheet, "D1"), Value::Number(3.0));
+    }
+}
@@ -0,0 +1,74 @@
+//! Server-Sent Events as a [`Stream`]: one `M` for each message pushed down an
+//! `EventSource`, parsed from its JSON `data`.
+//!
+//! This is for backends that only push. The browser reconnects a dropped stream by
+//! itself (from the last event id, if the server sends them), so there's no backoff
+//! here; the stream only ends once the browser gives up, as it does when the server
+//! answers with an error instead of an event stream.
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{
+    channel::mpsc::{self, UnboundedReceiver},
+    Stream, StreamExt,
+};
+use serde::de::DeserializeOwned;
+use web_sys::{Event, EventSource, MessageEvent};
+
+use crate::dom::{add_event_listener, log, EventListenerHandle};
+
+/// The messages from one `EventSource`, which is closed when this is dropped.
+pub struct Events<M> {
+    receiver: UnboundedReceiver<M>,
+    source: EventSource,
+    _listeners: [EventListenerHandle; 2],
+}
+
+pub fn subscribe<M: DeserializeOwned + 'static>(url: &str) -> Result<Events<M>, String> {
+    let source = EventSource::new(url).map_err(|error| format!("{error:?}"))?;
+    let (sender, receiver) = mpsc::unbounded();
+    let listeners = [
+        add_event_listener(&source, "message", {
+            let sender = sender.clone();
+            move |ev: MessageEvent| match parse(&ev) {
+                Some(msg) => _ = sender.unbounded_send(msg),
+                None => log!("[sse] ignoring a message that doesn't parse"),
+            }
+        }),
+        add_event_listener(&source, "error", {
+            let source = source.clone();
+            move |_: Event| {
+                // otherwise it's reconnecting, and there's more to come
+                if source.ready_state() == EventSource::CLOSED {
+                    sender.close_channel();
+                }
+            }
+        }),
+    ];
+    Ok(Events {
+        receiver,
+        source,
+        _listeners: listeners,
+    })
+}
+
+fn parse<M: DeserializeOwned>(ev: &MessageEvent) -> Option<M> {
+    serde_json::from_str(&ev.data().as_string()?).ok()
+}
+
+impl<M> Stream for Events<M> {
+    type Item = M;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<M>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
+
+impl<M> Drop for Events<M> {
+    fn drop(&mut self) {
+        self.source.close();
+    }
+}
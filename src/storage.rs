@@ -0,0 +1,73 @@
+//! Values kept as JSON in `localStorage`, for state that should outlast the page.
+//!
+//! Storage can be missing (some private modes, sandboxed iframes), full, or hold what
+//! an older build wrote in a different shape. None of that should stop an example from
+//! working, so a read that can't be done is just `None`, and a failed write is an
+//! error the caller is free to ignore.
+
+use std::fmt;
+
+use serde::{de::DeserializeOwned, Serialize};
+use web_sys::Storage;
+
+use crate::dom::{log, window};
+
+#[derive(Debug)]
+pub enum StorageError {
+    /// There's no `localStorage` here, or we're not allowed to use it.
+    Unavailable,
+    Serialize(String),
+    /// Usually the quota.
+    Write(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Unavailable => write!(f, "storage isn't available"),
+            StorageError::Serialize(error) => write!(f, "couldn't serialize: {error}"),
+            StorageError::Write(error) => write!(f, "couldn't write: {error}"),
+        }
+    }
+}
+
+/// `localStorage`, if we can use it. (Just asking for it throws when we can't.)
+pub fn local_storage() -> Option<Storage> {
+    window().local_storage().ok().flatten()
+}
+
+/// The value saved under `key`, if there is one that still parses as a `T`.
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let json = local_storage()?.get_item(key).ok()??;
+    match serde_json::from_str(&json) {
+        Ok(value) => Some(value),
+        Err(error) => {
+            log!("[storage] ignoring what's saved under {key:?}: {error}");
+            None
+        }
+    }
+}
+
+pub fn save<T: Serialize + ?Sized>(key: &str, value: &T) -> Result<(), StorageError> {
+    let json =
+        serde_json::to_string(value).map_err(|error| StorageError::Serialize(error.to_string()))?;
+    local_storage()
+        .ok_or(StorageError::Unavailable)?
+        .set_item(key, &json)
+        .map_err(|error| StorageError::Write(format!("{error:?}")))
+}
+
+pub fn remove(key: &str) {
+    if let Some(storage) = local_storage() {
+        _ = storage.remove_item(key);
+    }
+}
+
+/// Saves `value`, logging anything that goes wrong except there being no storage at
+/// all, which isn't news after the first time.
+pub fn persist<T: Serialize + ?Sized>(key: &str, value: &T) {
+    match save(key, value) {
+        Ok(()) | Err(StorageError::Unavailable) => {}
+        Err(error) => log!("[storage] couldn't save {key:?}: {error}"),
+    }
+}
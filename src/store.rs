@@ -0,0 +1,310 @@
+//! A state and its reducer behind a channel: version 4's loop, packaged.
+//!
+//! Everything that wants a change dispatches a message, and one loop applies them in
+//! order, calling back after each so the view can render. A [`Store::transaction`]
+//! sends several messages as one: they're applied to a copy of the state, which only
+//! replaces the real one (with a single callback) if none of them was rejected.
+//!
+//! Every message passes through the store's [`Middleware`] on the way: each one can
+//! look at it (and the state) before it's applied, drop it, and look again after. The
+//! built-in ones log, time, filter and throttle; they're where cross-cutting concerns
+//! go, so the reducer stays about the state.
+
+use std::{cell::RefCell, fmt::Debug, future::Future, rc::Rc, time::Duration};
+
+use futures::{
+    channel::{
+        mpsc::{self, UnboundedSender},
+        oneshot,
+    },
+    StreamExt,
+};
+
+use crate::{
+    app::App,
+    dom::{log, window},
+};
+
+/// A state that changes only through messages. It's cloned for every dispatch, so
+/// transactions have something to roll back to: keep it small, or cheap to clone.
+pub trait Reducer: Clone + 'static {
+    type Msg: Clone + Debug + 'static;
+
+    /// Applies `msg`, or says why it can't be applied.
+    fn reduce(&mut self, msg: Self::Msg) -> Result<(), String>;
+}
+
+/// Hooks around every message a store applies. They see each message as it's applied,
+/// so in a transaction that's rolled back they'll have seen states that never became
+/// the store's.
+pub trait Middleware<S: Reducer> {
+    /// Called before `msg` is applied to `state`; returning `false` drops it.
+    fn before(&mut self, _msg: &S::Msg, _state: &S) -> bool {
+        true
+    }
+
+    /// Called with the state `msg` made, once it's applied.
+    fn after(&mut self, _msg: &S::Msg, _state: &S) {}
+}
+
+/// Logs every message applied, and the state it made.
+pub struct Logger;
+
+impl<S: Reducer + Debug> Middleware<S> for Logger {
+    fn after(&mut self, msg: &S::Msg, state: &S) {
+        log!("[store] {msg:?} -> {state:?}");
+    }
+}
+
+/// Logs how long each message takes to apply, if it's longer than `slower_than`.
+pub struct Timing {
+    pub slower_than: Duration,
+    started: f64,
+}
+
+impl Timing {
+    pub fn new(slower_than: Duration) -> Self {
+        Timing {
+            slower_than,
+            started: 0.0,
+        }
+    }
+}
+
+impl<S: Reducer> Middleware<S> for Timing {
+    fn before(&mut self, _: &S::Msg, _: &S) -> bool {
+        self.started = now();
+        true
+    }
+
+    fn after(&mut self, msg: &S::Msg, _: &S) {
+        let took = now() - self.started;
+        if took > self.slower_than.as_secs_f64() * 1000.0 {
+            log!("[store] {msg:?} took {took:.2}ms");
+        }
+    }
+}
+
+/// Drops the messages its function says no to, given the state they'd be applied to.
+pub struct Filter<F>(pub F);
+
+impl<S: Reducer, F: FnMut(&S::Msg, &S) -> bool> Middleware<S> for Filter<F> {
+    fn before(&mut self, msg: &S::Msg, state: &S) -> bool {
+        (self.0)(msg, state)
+    }
+}
+
+/// Lets through at most one of the messages `matches` picks out every `period`, and
+/// drops the rest; other messages aren't affected.
+pub struct Throttle<M> {
+    period: Duration,
+    matches: fn(&M) -> bool,
+    last: Option<f64>,
+    now: fn() -> f64,
+}
+
+impl<M> Throttle<M> {
+    pub fn new(period: Duration, matches: fn(&M) -> bool) -> Self {
+        Throttle {
+            period,
+            matches,
+            last: None,
+            now,
+        }
+    }
+}
+
+impl<S: Reducer> Middleware<S> for Throttle<S::Msg> {
+    fn before(&mut self, msg: &S::Msg, _: &S) -> bool {
+        if !(self.matches)(msg) {
+            return true;
+        }
+        let now = (self.now)();
+        let period = self.period.as_secs_f64() * 1000.0;
+        if self.last.is_some_and(|last| now - last < period) {
+            return false;
+        }
+        self.last = Some(now);
+        true
+    }
+}
+
+/// Milliseconds, from the page's high-resolution clock.
+fn now() -> f64 {
+    window()
+        .performance()
+        .map_or(0.0, |performance| performance.now())
+}
+
+/// The messages of one transaction.
+pub struct Transaction<M> {
+    messages: Vec<M>,
+}
+
+impl<M> Transaction<M> {
+    pub fn dispatch(&mut self, msg: M) {
+        self.messages.push(msg);
+    }
+}
+
+struct Batch<M> {
+    messages: Vec<M>,
+    /// Told how it went, for transactions.
+    done: Option<oneshot::Sender<Result<(), String>>>,
+}
+
+pub type Pipeline<S> = Vec<Box<dyn Middleware<S>>>;
+
+#[derive(Clone)]
+pub struct Store<S: Reducer> {
+    sender: UnboundedSender<Batch<S::Msg>>,
+}
+
+impl<S: Reducer> Store<S> {
+    /// Applies messages to `state` until `app` unmounts, calling `on_change` with the
+    /// new state and the messages that made it after every change that went through.
+    /// Every message passes through `middleware`, in order, on its way in.
+    pub fn new(
+        app: &mut App,
+        state: Rc<RefCell<S>>,
+        mut middleware: Pipeline<S>,
+        mut on_change: impl FnMut(&S, &[S::Msg]) + 'static,
+    ) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded::<Batch<S::Msg>>();
+        app.spawn(async move {
+            while let Some(Batch { messages, done }) = receiver.next().await {
+                let result = apply(&*state.borrow(), messages, &mut middleware);
+                let result = match result {
+                    Ok((next, applied)) => {
+                        // all of them dropped is nothing to tell anyone about
+                        if !applied.is_empty() {
+                            *state.borrow_mut() = next;
+                            on_change(&state.borrow(), &applied);
+                        }
+                        Ok(())
+                    }
+                    Err(error) => Err(error),
+                };
+                match (done, result) {
+                    (Some(done), result) => _ = done.send(result),
+                    (None, Err(error)) => log!("[store] rejected {error}"),
+                    (None, Ok(())) => {}
+                }
+            }
+        });
+        Store { sender }
+    }
+
+    /// Sends `msg` to be applied after everything dispatched before it.
+    pub fn dispatch(&self, msg: S::Msg) {
+        _ = self.sender.unbounded_send(Batch {
+            messages: vec![msg],
+            done: None,
+        });
+    }
+
+    /// Applies every message `f` dispatches, or, if any of them is rejected, none of
+    /// them. The messages are sent straight away; the returned future says how it went.
+    pub fn transaction(
+        &self,
+        f: impl FnOnce(&mut Transaction<S::Msg>),
+    ) -> impl Future<Output = Result<(), String>> {
+        let mut tx = Transaction {
+            messages: Vec::new(),
+        };
+        f(&mut tx);
+        let (done, result) = oneshot::channel();
+        _ = self.sender.unbounded_send(Batch {
+            messages: tx.messages,
+            done: Some(done),
+        });
+        async move {
+            result
+                .await
+                .unwrap_or_else(|_| Err("the store is gone".to_string()))
+        }
+    }
+}
+
+/// Applies `messages` to a copy of `state`, through `middleware`, returning the new
+/// state and the messages that weren't dropped.
+fn apply<S: Reducer>(
+    state: &S,
+    messages: Vec<S::Msg>,
+    middleware: &mut Pipeline<S>,
+) -> Result<(S, Vec<S::Msg>), String> {
+    let mut next = state.clone();
+    let mut applied = Vec::with_capacity(messages.len());
+    for msg in messages {
+        if !middleware.iter_mut().all(|m| m.before(&msg, &next)) {
+            continue;
+        }
+        next.reduce(msg.clone())
+            .map_err(|error| format!("{msg:?}: {error}"))?;
+        for m in middleware.iter_mut() {
+            m.after(&msg, &next);
+        }
+        applied.push(msg);
+    }
+    Ok((next, applied))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Total(i32);
+
+    impl Reducer for Total {
+        type Msg = i32;
+
+        fn reduce(&mut self, msg: i32) -> Result<(), String> {
+            if msg > 100 {
+                return Err("too much at once".to_string());
+            }
+            self.0 += msg;
+            Ok(())
+        }
+    }
+
+    /// Every state it's seen, after each message.
+    struct Seen(Rc<RefCell<Vec<i32>>>);
+
+    impl Middleware<Total> for Seen {
+        fn after(&mut self, _: &i32, state: &Total) {
+            self.0.borrow_mut().push(state.0);
+        }
+    }
+
+    #[test]
+    fn dropped_messages_are_skipped_not_rejected() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut middleware: Pipeline<Total> = vec![
+            Box::new(Filter(|msg: &i32, _: &Total| *msg > 0)),
+            Box::new(Seen(seen.clone())),
+        ];
+        let (next, applied) = apply(&Total(0), vec![1, -5, 2], &mut middleware).unwrap();
+        assert_eq!((next, applied), (Total(3), vec![1, 2]));
+        assert_eq!(*seen.borrow(), [1, 3]);
+        assert!(apply(&Total(0), vec![1, 200], &mut middleware).is_err());
+    }
+
+    thread_local! {
+        static CLOCK: Cell<f64> = const { Cell::new(0.0) };
+    }
+
+    #[test]
+    fn throttling_only_touches_matching_messages() {
+        let mut throttle = Throttle::new(Duration::from_millis(100), |msg: &i32| *msg > 10);
+        throttle.now = || CLOCK.with(Cell::get);
+        let mut middleware: Pipeline<Total> = vec![Box::new(throttle)];
+        let (_, applied) = apply(&Total(0), vec![20, 20, 1, 30], &mut middleware).unwrap();
+        assert_eq!(applied, [20, 1]);
+        CLOCK.with(|clock| clock.set(150.0));
+        let (_, applied) = apply(&Total(0), vec![40], &mut middleware).unwrap();
+        assert_eq!(applied, [40]);
+    }
+}
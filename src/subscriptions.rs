@@ -0,0 +1,93 @@
+//! Subscriptions: events from outside the page, as a reducer's messages.
+//!
+//! A reducer loop only hears about what's sent down its channel. A [`Sub`] pairs a
+//! long-lived source (a window event, a timer) with the message it stands for, and
+//! [`subscribe`] forwards each of them into the channel until the app unmounts, so a
+//! resize or the connection dropping is handled exactly like a click.
+
+use std::{cell::Cell, time::Duration};
+
+use futures::{channel::mpsc::Sender, StreamExt};
+use web_sys::Event;
+
+use crate::{
+    app::App,
+    dom::{document, window},
+    network, time,
+};
+
+type Attach<M> = Box<dyn FnOnce(&mut App, Sender<M>)>;
+
+/// An event source and the message it sends.
+pub struct Sub<M>(Attach<M>);
+
+impl<M: 'static> Sub<M> {
+    /// The window's inner width and height in CSS pixels, after every resize.
+    pub fn resize(to_msg: impl Fn(f64, f64) -> M + 'static) -> Self {
+        Sub(Box::new(|app, mut sender| {
+            app.listen(&window(), "resize", move |_: Event| {
+                let (width, height) = window_size();
+                _ = sender.try_send(to_msg(width, height));
+            });
+        }))
+    }
+
+    /// Whether we're online, whenever that changes.
+    pub fn online(to_msg: impl Fn(bool) -> M + 'static) -> Self {
+        Sub(Box::new(|app, sender| {
+            // the status also changes with the connection type, which isn't news here
+            let was_online = Cell::new(network::status().online);
+            network::subscribe(app, move |status| {
+                if was_online.replace(status.online) != status.online {
+                    _ = sender.clone().try_send(to_msg(status.online));
+                }
+            });
+        }))
+    }
+
+    /// Whether the page is visible, whenever that changes.
+    pub fn visibility(to_msg: impl Fn(bool) -> M + 'static) -> Self {
+        Sub(Box::new(|app, mut sender| {
+            app.listen(&document(), "visibilitychange", move |_: Event| {
+                _ = sender.try_send(to_msg(!document().hidden()));
+            });
+        }))
+    }
+
+    /// Once every `period`.
+    pub fn every(period: Duration, to_msg: impl Fn() -> M + 'static) -> Self {
+        Sub(Box::new(move |app, mut sender| {
+            app.spawn(async move {
+                let mut ticks = time::every(period);
+                while ticks.next().await.is_some() {
+                    _ = sender.try_send(to_msg());
+                }
+            });
+        }))
+    }
+}
+
+/// Sends every message of `subs` into `sender` until `app` unmounts.
+pub fn subscribe<M: 'static>(
+    app: &mut App,
+    sender: &Sender<M>,
+    subs: impl IntoIterator<Item = Sub<M>>,
+) {
+    for Sub(attach) in subs {
+        attach(app, sender.clone());
+    }
+}
+
+/// The window's inner width and height in CSS pixels.
+pub fn window_size() -> (f64, f64) {
+    let dimension = |value: Result<wasm_bindgen::JsValue, _>| {
+        value
+            .ok()
+            .and_then(|value| value.as_f64())
+            .unwrap_or_default()
+    };
+    (
+        dimension(window().inner_width()),
+        dimension(window().inner_height()),
+    )
+}
@@ -0,0 +1,351 @@
+//! Keeps long-lived background tasks alive.
+//!
+//! A task is registered as a factory that builds a fresh future on every (re)start.
+//! When the future finishes with an error the supervisor waits according to its
+//! backoff and starts it again, reporting every transition to status listeners. A
+//! restart always waits at least the backoff's initial delay, even after a clean exit
+//! under [`RestartPolicy::Always`], so a task that ends straight away can't spin.
+
+use std::{cell::RefCell, collections::BTreeMap, future::Future, rc::Rc, time::Duration};
+
+use futures::future::{abortable, AbortHandle};
+
+use crate::{
+    dom::{log, spawn_local},
+    errors,
+    time::sleep,
+};
+
+pub type TaskResult = Result<(), String>;
+
+type StatusListener = Rc<dyn Fn(&str, &TaskStatus)>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Restart after errors, giving up after `max_restarts` consecutive failures (if set;
+    /// `Some(0)` runs the task just once).
+    OnError { max_restarts: Option<u32> },
+    /// Restart after errors and after clean exits.
+    Always,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub factor: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            initial: Duration::from_millis(250),
+            max: Duration::from_secs(30),
+            factor: 2,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before restart number `attempt` (starting at 1).
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let multiplier = self.factor.saturating_pow(attempt.saturating_sub(1));
+        self.initial.saturating_mul(multiplier).min(self.max)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TaskStatus {
+    Running {
+        restarts: u32,
+    },
+    Restarting {
+        /// Failures in a row so far, 0 after a clean exit.
+        attempt: u32,
+        delay: Duration,
+        /// `None` after a clean exit.
+        error: Option<String>,
+    },
+    Finished,
+    Failed(String),
+    Stopped,
+}
+
+/// What to do once a run of a task has ended.
+#[derive(Debug, PartialEq)]
+enum Next {
+    Finish,
+    GiveUp(String),
+    Restart {
+        attempt: u32,
+        delay: Duration,
+        error: Option<String>,
+    },
+}
+
+/// Decides what follows a run that ended with `result`, given `failures`, the errors
+/// in a row before it, which this updates.
+fn next(policy: RestartPolicy, backoff: &Backoff, result: TaskResult, failures: &mut u32) -> Next {
+    let error = match result {
+        Ok(()) if policy != RestartPolicy::Always => return Next::Finish,
+        Ok(()) => {
+            *failures = 0;
+            None
+        }
+        Err(error) => {
+            *failures += 1;
+            let give_up = match policy {
+                RestartPolicy::OnError { max_restarts } => {
+                    max_restarts.is_some_and(|max| *failures > max)
+                }
+                RestartPolicy::Always => false,
+            };
+            if give_up {
+                return Next::GiveUp(error);
+            }
+            Some(error)
+        }
+    };
+    Next::Restart {
+        attempt: *failures,
+        delay: backoff.delay(*failures),
+        error,
+    }
+}
+
+struct Task {
+    status: TaskStatus,
+    abort: AbortHandle,
+}
+
+impl Task {
+    fn is_live(&self) -> bool {
+        matches!(
+            self.status,
+            TaskStatus::Running { .. } | TaskStatus::Restarting { .. }
+        )
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    tasks: BTreeMap<&'static str, Task>,
+    listeners: Vec<StatusListener>,
+}
+
+#[derive(Clone, Default)]
+pub struct Supervisor {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Logs every status change to the console.
+    pub fn with_console_reporter(self) -> Self {
+        self.on_status(|name, status| log!("[supervisor] {name}: {status:?}"));
+        self
+    }
+
+    pub fn on_status(&self, listener: impl Fn(&str, &TaskStatus) + 'static) {
+        self.inner.borrow_mut().listeners.push(Rc::new(listener));
+    }
+
+    /// Starts supervising `name`. Spawning a name that is already supervised
+    /// stops the old task first.
+    pub fn spawn<F, Fut>(
+        &self,
+        name: &'static str,
+        policy: RestartPolicy,
+        backoff: Backoff,
+        factory: F,
+    ) where
+        F: Fn() -> Fut + 'static,
+        Fut: Future<Output = TaskResult> + 'static,
+    {
+        self.stop(name);
+
+        let this = self.clone();
+        let (run, abort) = abortable(async move {
+            let mut failures = 0;
+            let mut restarts = 0;
+            loop {
+                this.set_status(name, TaskStatus::Running { restarts });
+                let result = factory().await;
+                if let Err(error) = &result {
+                    errors::task_failed(name, error);
+                }
+                match next(policy, &backoff, result, &mut failures) {
+                    Next::Finish => {
+                        this.set_status(name, TaskStatus::Finished);
+                        return;
+                    }
+                    Next::GiveUp(error) => {
+                        this.set_status(name, TaskStatus::Failed(error));
+                        return;
+                    }
+                    Next::Restart {
+                        attempt,
+                        delay,
+                        error,
+                    } => {
+                        this.set_status(
+                            name,
+                            TaskStatus::Restarting {
+                                attempt,
+                                delay,
+                                error,
+                            },
+                        );
+                        sleep(delay).await;
+                    }
+                }
+                restarts += 1;
+            }
+        });
+
+        self.inner.borrow_mut().tasks.insert(
+            name,
+            Task {
+                status: TaskStatus::Running { restarts: 0 },
+                abort,
+            },
+        );
+        spawn_local(async move {
+            _ = run.await;
+        });
+    }
+
+    /// Cancels a supervised task; it will not be restarted.
+    pub fn stop(&self, name: &'static str) {
+        let abort = match self.inner.borrow().tasks.get(name) {
+            Some(task) if task.is_live() => task.abort.clone(),
+            _ => return,
+        };
+        abort.abort();
+        self.set_status(name, TaskStatus::Stopped);
+    }
+
+    pub fn stop_all(&self) {
+        let names = self
+            .inner
+            .borrow()
+            .tasks
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        for name in names {
+            self.stop(name);
+        }
+    }
+
+    pub fn statuses(&self) -> Vec<(&'static str, TaskStatus)> {
+        self.inner
+            .borrow()
+            .tasks
+            .iter()
+            .map(|(name, task)| (*name, task.status.clone()))
+            .collect()
+    }
+
+    fn set_status(&self, name: &'static str, status: TaskStatus) {
+        // clone the listeners out so they're free to call back into the supervisor
+        let listeners = {
+            let mut inner = self.inner.borrow_mut();
+            if let Some(task) = inner.tasks.get_mut(name) {
+                task.status = status.clone();
+            }
+            inner.listeners.clone()
+        };
+        for listener in listeners {
+            listener(name, &status);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BACKOFF: Backoff = Backoff {
+        initial: Duration::from_millis(100),
+        max: Duration::from_secs(1),
+        factor: 2,
+    };
+
+    #[test]
+    fn backoff_grows_up_to_its_max() {
+        let delays = (0..=6).map(|attempt| BACKOFF.delay(attempt).as_millis());
+        assert_eq!(
+            delays.collect::<Vec<_>>(),
+            [100, 100, 200, 400, 800, 1000, 1000]
+        );
+        assert_eq!(BACKOFF.delay(u32::MAX), BACKOFF.max);
+    }
+
+    #[test]
+    fn restarts_after_errors_until_it_gives_up() {
+        let policy = RestartPolicy::OnError {
+            max_restarts: Some(2),
+        };
+        let mut failures = 0;
+        for attempt in 1..=2 {
+            assert_eq!(
+                next(policy, &BACKOFF, Err("down".into()), &mut failures),
+                Next::Restart {
+                    attempt,
+                    delay: BACKOFF.delay(attempt),
+                    error: Some("down".into()),
+                }
+            );
+        }
+        assert_eq!(
+            next(policy, &BACKOFF, Err("still down".into()), &mut failures),
+            Next::GiveUp("still down".into())
+        );
+        let once = RestartPolicy::OnError {
+            max_restarts: Some(0),
+        };
+        assert_eq!(
+            next(once, &BACKOFF, Err("down".into()), &mut 0),
+            Next::GiveUp("down".into())
+        );
+    }
+
+    #[test]
+    fn finishes_or_waits_after_a_clean_exit() {
+        let mut failures = 3;
+        assert_eq!(
+            next(
+                RestartPolicy::OnError {
+                    max_restarts: Some(0)
+                },
+                &BACKOFF,
+                Ok(()),
+                &mut failures
+            ),
+            Next::Finish
+        );
+        assert_eq!(
+            next(
+                RestartPolicy::OnError { max_restarts: None },
+                &BACKOFF,
+                Ok(()),
+                &mut failures
+            ),
+            Next::Finish
+        );
+        // never straight back in, and the failures before it are forgiven
+        assert_eq!(
+            next(RestartPolicy::Always, &BACKOFF, Ok(()), &mut failures),
+            Next::Restart {
+                attempt: 0,
+                delay: BACKOFF.initial,
+                error: None,
+            }
+        );
+        assert_eq!(failures, 0);
+    }
+}
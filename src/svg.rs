@@ -0,0 +1,53 @@
+//! SVG from Rust.
+//!
+//! `create_element("path")` makes an HTML element that happens to be called `path`,
+//! which draws nothing: SVG elements have to be created in the SVG namespace. Their
+//! attributes are plain ones, but a path's `d` is a little language of its own, which
+//! [`path_data`] writes.
+
+use web_sys::Element;
+
+use crate::dom::document;
+
+pub const SVG_NS: &str = "http://www.w3.org/2000/svg";
+
+pub fn create_svg_element(tag_name: &str) -> Element {
+    document()
+        .create_element_ns(Some(SVG_NS), tag_name)
+        .expect("to create the SVG element")
+}
+
+/// The user units `el` (an `<svg>`) draws in: `width` by `height`, from `(x, y)`.
+pub fn set_view_box(el: &Element, x: f64, y: f64, width: f64, height: f64) {
+    _ = el.set_attribute("viewBox", &format!("{x} {y} {width} {height}"));
+}
+
+/// Makes `el` (a `<path>`) a line through `points`.
+pub fn set_path(el: &Element, points: &[(f64, f64)]) {
+    _ = el.set_attribute("d", &path_data(points));
+}
+
+/// A path's `d` for a line through `points`: a move to the first, then lines on.
+pub fn path_data(points: &[(f64, f64)]) -> String {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, (x, y))| format!("{}{x} {y}", if i == 0 { 'M' } else { 'L' }))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_path_data() {
+        assert_eq!(path_data(&[]), "");
+        assert_eq!(path_data(&[(0.0, 1.5)]), "M0 1.5");
+        assert_eq!(
+            path_data(&[(0.0, 2.0), (1.0, 0.0), (2.0, 1.0)]),
+            "M0 2 L1 0 L2 1"
+        );
+    }
+}
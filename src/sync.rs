@@ -0,0 +1,108 @@
+//! Messages between tabs of the same site.
+//!
+//! `BroadcastChannel` is the way to do it: every other tab (or window, or worker) that
+//! opened a channel with the same name gets the message, and the sender doesn't. Where
+//! there's no `BroadcastChannel` (older Safari), writing to `localStorage` does much the
+//! same, since every *other* tab gets a `storage` event for it; the value carries a
+//! random id so sending the same message twice still changes it.
+
+use std::{marker::PhantomData, rc::Rc};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+use web_sys::{BroadcastChannel, MessageEvent, StorageEvent};
+
+use crate::{
+    app::App,
+    dom::{log, window},
+    storage,
+};
+
+enum Transport {
+    Channel(BroadcastChannel),
+    Storage { key: String },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope<M> {
+    id: u32,
+    msg: M,
+}
+
+/// The other tabs listening on one channel name.
+pub struct Tabs<M> {
+    transport: Rc<Transport>,
+    _msg: PhantomData<M>,
+}
+
+// not derived, which would ask for `M: Clone`
+impl<M> Clone for Tabs<M> {
+    fn clone(&self) -> Self {
+        Tabs {
+            transport: self.transport.clone(),
+            _msg: PhantomData,
+        }
+    }
+}
+
+impl<M: Serialize + DeserializeOwned + 'static> Tabs<M> {
+    /// Joins the channel `name`, calling `on_message` with everything the other tabs
+    /// send on it until `app` unmounts.
+    pub fn connect(app: &mut App, name: &str, on_message: impl Fn(M) + 'static) -> Self {
+        let transport = match BroadcastChannel::new(name) {
+            Ok(channel) => {
+                app.listen(&channel, "message", move |ev: MessageEvent| {
+                    match parse(ev.data()) {
+                        Some(msg) => on_message(msg),
+                        None => log!("[sync] ignoring a message that doesn't parse"),
+                    }
+                });
+                app.on_cleanup({
+                    let channel = channel.clone();
+                    move || channel.close()
+                });
+                Transport::Channel(channel)
+            }
+            Err(_) => {
+                let key = format!("sync:{name}");
+                app.listen(&window(), "storage", {
+                    let key = key.clone();
+                    move |ev: StorageEvent| {
+                        if ev.key().as_deref() != Some(&key) {
+                            return;
+                        }
+                        let envelope = ev
+                            .new_value()
+                            .and_then(|json| serde_json::from_str::<Envelope<M>>(&json).ok());
+                        if let Some(Envelope { msg, .. }) = envelope {
+                            on_message(msg);
+                        }
+                    }
+                });
+                Transport::Storage { key }
+            }
+        };
+        Tabs {
+            transport: Rc::new(transport),
+            _msg: PhantomData,
+        }
+    }
+
+    pub fn send(&self, msg: &M) {
+        let Ok(json) = serde_json::to_string(msg) else {
+            return;
+        };
+        match &*self.transport {
+            Transport::Channel(channel) => _ = channel.post_message(&JsValue::from_str(&json)),
+            Transport::Storage { key } => {
+                let id = (js_sys::Math::random() * u32::MAX as f64) as u32;
+                storage::persist(key, &Envelope { id, msg });
+            }
+        }
+    }
+}
+
+/// Messages go over the channel as JSON strings, the same as through storage.
+fn parse<M: DeserializeOwned>(data: JsValue) -> Option<M> {
+    serde_json::from_str(&data.as_string()?).ok()
+}
@@ -0,0 +1,54 @@
+//! A tiny synthesizer: notes to samples, and samples to a WAV file an `<audio>`
+//! element can play.
+
+use std::f32::consts::TAU;
+
+pub const SAMPLE_RATE: u32 = 22_050;
+
+/// A MIDI note number (60 is middle C), or `None` for a rest, and a length in beats.
+pub type Note = (Option<u8>, f32);
+
+/// Mono samples for `notes` played at `tempo` beats a minute.
+pub fn render(notes: &[Note], tempo: f32) -> Vec<i16> {
+    let beat = 60.0 / tempo;
+    let mut samples = Vec::new();
+    for &(pitch, beats) in notes {
+        let len = (beats * beat * SAMPLE_RATE as f32) as usize;
+        let frequency = pitch.map(|pitch| 440.0 * 2f32.powf((pitch as f32 - 69.0) / 12.0));
+        for i in 0..len {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let value = frequency.map_or(0.0, |f| {
+                // a quick attack and a fade to silence by the end, so notes don't click
+                let envelope = (t / 0.01).min(1.0) * (1.0 - i as f32 / len as f32);
+                let wave = (TAU * f * t).sin() + 0.3 * (2.0 * TAU * f * t).sin();
+                wave / 1.3 * envelope
+            });
+            samples.push((value * 0.6 * i16::MAX as f32) as i16);
+        }
+    }
+    samples
+}
+
+/// A 16-bit mono PCM WAV file of `samples`.
+pub fn wav(samples: &[i16]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, one channel
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    // bytes a second, bytes a sample, bits a sample
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    bytes
+}
@@ -0,0 +1,725 @@
+//! A data table with CSV import and export.
+//!
+//! Drop a `.csv` file onto the table to import it: the file is read and parsed in
+//! chunks, then a mapping step asks which CSV column goes into which table column
+//! (guessing from the header row). Rows are kept by email (or name, without one), so
+//! an import updates the rows it already has and adds the rest. "Export CSV" downloads
+//! the table as it is.
+//!
+//! The rows shown are filtered and sorted by a [`Selector`], so they're only worked
+//! out again when the rows, the filter or the sort change; debug builds show how often
+//! that was. Cells too narrow for their text cut it from the middle, so a long email
+//! still shows its domain.
+//!
+//! "Add 100,000 rows" makes it a big table, and a big table is filtered and sorted on
+//! a [`Worker`] instead, which keeps its own copy of the rows so only the query is
+//! sent each time. Each query has a generation, so a result that comes back after a
+//! newer query has been asked is dropped. Under the table, how long it took and the
+//! longest frame meanwhile, to compare the two. Only the first [`MAX_SHOWN`] rows
+//! are laid out, since a hundred thousand `<tr>`s would take longer than any sort.
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use futures::{channel::mpsc, StreamExt};
+use js_sys::Array;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    DragEvent, Element, Event, File, HtmlInputElement, HtmlSelectElement, MouseEvent,
+    TextDecodeOptions, TextDecoder,
+};
+
+use crate::{
+    app::App,
+    csv::{self, CsvParser},
+    dom::{create_element, spawn_local, window},
+    downloads,
+    ellipsis::MiddleEllipsis,
+    entities::EntityStore,
+    selector::{self, create_selector, Selector},
+    time, toast,
+    worker::{self, Reply, Worker},
+};
+
+const COLUMNS: [&str; 3] = ["Name", "Email", "City"];
+/// Files are read this many bytes at a time.
+const CHUNK_SIZE: f64 = 64.0 * 1024.0;
+/// Rows past this many aren't shown (but are still exported).
+const MAX_SHOWN: usize = 500;
+/// With more rows than this, filtering and sorting go to the worker by default.
+const LARGE: usize = 20_000;
+/// How many rows "Add 100,000 rows" adds.
+const GENERATED: usize = 100_000;
+
+type Row = [String; 3];
+
+/// What the rows on screen are derived from.
+#[derive(Clone, Default, PartialEq)]
+struct Query {
+    /// Bumped whenever the rows change, so the rows themselves needn't be compared.
+    revision: u32,
+    filter: String,
+    /// The column sorted by, and whether it's ascending.
+    sort: Option<(usize, bool)>,
+}
+
+/// A query for the worker, with the rows when its copy is out of date.
+#[derive(Serialize, Deserialize)]
+struct Request<R> {
+    revision: u32,
+    rows: Option<Vec<R>>,
+    filter: String,
+    sort: Option<(usize, bool)>,
+}
+
+thread_local! {
+    /// The worker's copy of the rows, and the revision they're from.
+    static WORKER_ROWS: RefCell<(u32, Vec<Row>)> = const { RefCell::new((0, Vec::new())) };
+}
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let section = create_element("section");
+    let toolbar = create_element("div");
+    let export = create_element("button");
+    export.set_text_content(Some("Export CSV"));
+    let hint = create_element("span");
+    hint.set_text_content(Some(" or drop a .csv file onto the table to import it "));
+    let generate = create_element("button");
+    generate.set_text_content(Some("Add 100,000 rows"));
+    let offload = create_element("input").unchecked_into::<HtmlInputElement>();
+    offload.set_type("checkbox");
+    let offload_label = create_element("label");
+    offload_label.append_child(&offload).unwrap();
+    _ = offload_label.append_with_str_1(" Filter and sort in a worker");
+    let filter = create_element("input").unchecked_into::<HtmlInputElement>();
+    filter.set_type("search");
+    filter.set_placeholder("Filter");
+    _ = filter.set_attribute("aria-label", "Filter the rows");
+    toolbar.append_child(&filter).unwrap();
+    _ = toolbar.append_with_str_1(" ");
+    toolbar.append_child(&export).unwrap();
+    toolbar.append_child(&hint).unwrap();
+    toolbar.append_child(&generate).unwrap();
+    _ = toolbar.append_with_str_1(" ");
+    toolbar.append_child(&offload_label).unwrap();
+    let status = create_element("p");
+    let mapping = create_element("form");
+    _ = mapping.set_attribute("hidden", "");
+    let table = create_element("table");
+    _ = table.set_attribute(
+        "style",
+        "border-collapse: collapse; table-layout: fixed; width: 100%; min-width: 30em; \
+         max-width: 48em; margin-top: 8px",
+    );
+    let head = create_element("tr");
+    let headers = COLUMNS
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            let th = create_element("th");
+            _ = th.set_attribute("data-column", &i.to_string());
+            _ = th.set_attribute(
+                "style",
+                "text-align: left; border-bottom: 2px solid #999; cursor: pointer",
+            );
+            _ = th.set_attribute("title", &format!("Sort by {}", column.to_lowercase()));
+            head.append_child(&th).unwrap();
+            th
+        })
+        .collect::<Vec<_>>();
+    // over the remove buttons
+    let remove_column = create_element("th");
+    _ = remove_column.set_attribute("style", "width: 2em");
+    head.append_child(&remove_column).unwrap();
+    let thead = create_element("thead");
+    thead.append_child(&head).unwrap();
+    let tbody = create_element("tbody");
+    table.append_child(&thead).unwrap();
+    table.append_child(&tbody).unwrap();
+    section.append_child(&toolbar).unwrap();
+    section.append_child(&mapping).unwrap();
+    section.append_child(&table).unwrap();
+    section.append_child(&status).unwrap();
+    // like the counter's state inspector, only there while developing
+    let stats = cfg!(debug_assertions).then(|| {
+        let details = create_element("details");
+        let summary = create_element("summary");
+        summary.set_text_content(Some("Selector cache"));
+        let pre = create_element("pre");
+        details.append_child(&summary).unwrap();
+        details.append_child(&pre).unwrap();
+        section.append_child(&details).unwrap();
+        pre
+    });
+    app.append(parent, &section);
+
+    let query = Rc::new(RefCell::new(Query::default()));
+    let mut store = EntityStore::new();
+    store.subscribe({
+        let query = query.clone();
+        move |_: &String, _| query.borrow_mut().revision += 1
+    });
+    store.upsert_many(
+        [
+            row(["Ada Lovelace", "ada@example.com", "London"]),
+            row(["Grace Hopper", "grace@example.com", "New York"]),
+            row(["Alan Turing", "alan@example.com", "Wilmslow"]),
+        ]
+        .map(|row| (key(&row), row)),
+    );
+    let rows = Rc::new(RefCell::new(store));
+    let visible: Rc<Selector<Query, Vec<Row>>> = Rc::new(create_selector("table rows", {
+        let rows = rows.clone();
+        move |query| visible_rows(&rows.borrow(), query)
+    }));
+
+    // the longest gap between frames since it was last reset
+    let longest_frame = Rc::new(Cell::new(0.0));
+    app.spawn({
+        let longest_frame = longest_frame.clone();
+        async move {
+            let mut frames = time::frames();
+            let mut last = None;
+            while let Some(frame) = frames.next().await {
+                if let Some(last) = last {
+                    longest_frame.set(f64::max(longest_frame.get(), frame - last));
+                }
+                last = Some(frame);
+            }
+        }
+    });
+
+    let worker = Rc::new(RefCell::new(None::<Rc<Worker>>));
+    // the revision of the rows the worker has
+    let worker_revision = Rc::new(Cell::new(None::<u32>));
+    let generation = Rc::new(Cell::new(0));
+    let refresh = Rc::new({
+        let tbody = tbody.clone();
+        let query = query.clone();
+        let rows = rows.clone();
+        let visible = visible.clone();
+        let offload = offload.clone();
+        let ellipsis = Rc::new(MiddleEllipsis::new());
+        move || {
+            generation.set(generation.get() + 1);
+            let this_run = generation.get();
+            let started = now();
+            longest_frame.set(0.0);
+            let query = query.borrow();
+            if !offload.checked() {
+                let visible = visible.get(&query);
+                render(&tbody, &visible, &ellipsis);
+                spawn_local(report(
+                    status.clone(),
+                    visible.len(),
+                    started,
+                    longest_frame.clone(),
+                    "",
+                ));
+            } else {
+                match worker::get_or_spawn(&worker) {
+                    Ok(worker) => {
+                        let request = worker_request(
+                            &rows.borrow(),
+                            &query,
+                            worker_revision.get() != Some(query.revision),
+                        );
+                        worker_revision.set(Some(query.revision));
+                        let (tbody, status, ellipsis) =
+                            (tbody.clone(), status.clone(), ellipsis.clone());
+                        let (rows, generation) = (rows.clone(), generation.clone());
+                        let (worker_revision, longest_frame) =
+                            (worker_revision.clone(), longest_frame.clone());
+                        spawn_local(async move {
+                            let result = worker.call("table-rows", &request, &Array::new()).await;
+                            // a newer query has been asked since
+                            if generation.get() != this_run {
+                                return;
+                            }
+                            let indices = result.and_then(|value| {
+                                serde_json::from_str::<Vec<usize>>(
+                                    &value.as_string().unwrap_or_default(),
+                                )
+                                .map_err(|error| error.to_string())
+                            });
+                            match indices {
+                                Ok(indices) => {
+                                    let visible = {
+                                        let rows = rows.borrow();
+                                        let ids = rows.ids();
+                                        indices
+                                            .iter()
+                                            .filter_map(|i| rows.get(ids.get(*i)?).cloned())
+                                            .collect::<Vec<_>>()
+                                    };
+                                    render(&tbody, &visible, &ellipsis);
+                                    report(
+                                        status,
+                                        visible.len(),
+                                        started,
+                                        longest_frame,
+                                        " in a worker",
+                                    )
+                                    .await;
+                                }
+                                Err(e) => {
+                                    // it may have lost its copy of the rows with it
+                                    worker_revision.set(None);
+                                    status
+                                        .set_text_content(Some(&format!("The worker failed: {e}")));
+                                }
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        status.set_text_content(Some(&format!("Couldn't start a worker: {e}")));
+                    }
+                }
+            }
+            for (i, th) in headers.iter().enumerate() {
+                let arrow = match query.sort {
+                    Some((column, true)) if column == i => " ▲",
+                    Some((column, false)) if column == i => " ▼",
+                    _ => "",
+                };
+                th.set_text_content(Some(&format!("{}{arrow}", COLUMNS[i])));
+            }
+            if let Some(stats) = &stats {
+                let text = selector::stats()
+                    .iter()
+                    .map(|(name, stats)| {
+                        format!("{name}: {} hits, {} misses", stats.hits, stats.misses)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                stats.set_text_content(Some(&text));
+            }
+        }
+    });
+    refresh();
+
+    app.listen(&filter.clone(), "input", {
+        let query = query.clone();
+        let refresh = refresh.clone();
+        move |_: Event| {
+            // "ada " and "ada" show the same rows, so they're the same query
+            query.borrow_mut().filter = filter.value().trim().to_lowercase();
+            refresh();
+        }
+    });
+    app.listen(&head, "click", {
+        let query = query.clone();
+        let refresh = refresh.clone();
+        move |ev: MouseEvent| {
+            let Some(column) = ev
+                .target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+                .and_then(|target| target.closest("th").ok().flatten())
+                .and_then(|th| th.get_attribute("data-column")?.parse::<usize>().ok())
+            else {
+                return;
+            };
+            let mut query = query.borrow_mut();
+            query.sort = match query.sort {
+                Some((sorted, true)) if sorted == column => Some((column, false)),
+                _ => Some((column, true)),
+            };
+            drop(query);
+            refresh();
+        }
+    });
+
+    app.listen(&tbody, "click", {
+        let rows = rows.clone();
+        let refresh = refresh.clone();
+        move |ev: MouseEvent| {
+            let Some(key) = ev
+                .target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+                .and_then(|target| target.get_attribute("data-remove"))
+            else {
+                return;
+            };
+            rows.borrow_mut().remove(&key);
+            refresh();
+        }
+    });
+
+    app.listen(&generate, "click", {
+        let rows = rows.clone();
+        let refresh = refresh.clone();
+        let offload = offload.clone();
+        move |_: MouseEvent| {
+            let mut rows = rows.borrow_mut();
+            let start = rows.ids().len();
+            rows.upsert_many((start..start + GENERATED).map(|i| {
+                let row = generated_row(i);
+                (key(&row), row)
+            }));
+            offload.set_checked(rows.ids().len() > LARGE);
+            drop(rows);
+            refresh();
+        }
+    });
+    app.listen(&offload.clone(), "change", {
+        let refresh = refresh.clone();
+        move |_: Event| refresh()
+    });
+
+    // the rows as shown, which the selector has ready
+    app.listen(&export, "click", {
+        let query = query.clone();
+        move |_: MouseEvent| download_csv(&visible.get(&query.borrow()))
+    });
+
+    // files go through a channel so the import runs in a task the app owns
+    let (file_sender, mut files) = mpsc::unbounded::<File>();
+    let (answer_sender, mut answers) = mpsc::unbounded::<bool>();
+
+    app.listen(&table, "dragover", |ev: DragEvent| ev.prevent_default());
+    app.listen(&table, "drop", move |ev: DragEvent| {
+        ev.prevent_default();
+        let file = ev
+            .data_transfer()
+            .and_then(|data| data.files())
+            .and_then(|files| files.get(0));
+        if let Some(file) = file {
+            _ = file_sender.unbounded_send(file);
+        }
+    });
+
+    app.listen(&mapping, "submit", {
+        let answer_sender = answer_sender.clone();
+        move |ev: Event| {
+            ev.prevent_default();
+            _ = answer_sender.unbounded_send(true);
+        }
+    });
+    app.listen(&mapping, "click", move |ev: MouseEvent| {
+        let cancel = ev
+            .target()
+            .and_then(|target| target.dyn_into::<Element>().ok())
+            .is_some_and(|target| target.has_attribute("data-cancel"));
+        if cancel {
+            _ = answer_sender.unbounded_send(false);
+        }
+    });
+
+    app.spawn(async move {
+        while let Some(file) = files.next().await {
+            let records = match read_csv(&file).await {
+                Ok(records) => records,
+                Err(e) => {
+                    toast::error(&format!("Couldn't read {}: {e}", file.name()));
+                    continue;
+                }
+            };
+            let Some((header, records)) = records.split_first() else {
+                toast::error(&format!("{} is empty", file.name()));
+                continue;
+            };
+
+            show_mapping(&mapping, header);
+            let confirmed = answers.next().await.unwrap_or(false);
+            _ = mapping.set_attribute("hidden", "");
+            if !confirmed {
+                continue;
+            }
+            let columns = chosen_columns(&mapping);
+
+            let imported = records
+                .iter()
+                .map(|record| {
+                    columns.map(|column| {
+                        column
+                            .and_then(|column| record.get(column).cloned())
+                            .unwrap_or_default()
+                    })
+                })
+                .collect::<Vec<Row>>();
+            // every row that changes bumps the revision
+            let before = query.borrow().revision;
+            let count = imported.len();
+            rows.borrow_mut()
+                .upsert_many(imported.into_iter().map(|row| (key(&row), row)));
+            let changed = query.borrow().revision - before;
+            toast::info(&format!(
+                "Imported {count} rows, {changed} of them new or changed"
+            ));
+            refresh();
+        }
+    });
+
+    app
+}
+
+fn row(fields: [&str; 3]) -> Row {
+    fields.map(str::to_string)
+}
+
+/// What a row is kept by: its email, or its name if it has no email.
+fn key(row: &Row) -> String {
+    match row[1].trim() {
+        "" => row[0].trim().to_lowercase(),
+        email => email.to_lowercase(),
+    }
+}
+
+/// A row for "Add 100,000 rows", different for every `i`.
+fn generated_row(i: usize) -> Row {
+    const FIRST: [&str; 6] = ["Ada", "Alan", "Barbara", "Edsger", "Grace", "Linus"];
+    const LAST: [&str; 5] = ["Hopper", "Knuth", "Liskov", "Ritchie", "Wirth"];
+    const CITIES: [&str; 5] = ["Berlin", "Lagos", "Lima", "Osaka", "Toronto"];
+    [
+        format!("{} {}", FIRST[i % FIRST.len()], LAST[i / 7 % LAST.len()]),
+        format!("person{i}@example.com"),
+        CITIES[i / 3 % CITIES.len()].to_string(),
+    ]
+}
+
+/// The rows in the store, in its order.
+fn ordered(rows: &EntityStore<String, Row>) -> Vec<&Row> {
+    rows.ids().iter().filter_map(|id| rows.get(id)).collect()
+}
+
+/// The rows matching the query's filter, in its order.
+fn visible_rows(rows: &EntityStore<String, Row>, query: &Query) -> Vec<Row> {
+    let rows = ordered(rows);
+    visible(&rows, &query.filter, query.sort)
+        .into_iter()
+        .map(|i| rows[i].clone())
+        .collect()
+}
+
+/// Where the rows matching `filter` are in `rows`, in the order `sort` puts them.
+fn visible(rows: &[&Row], filter: &str, sort: Option<(usize, bool)>) -> Vec<usize> {
+    let mut visible = (0..rows.len())
+        .filter(|i| {
+            filter.is_empty()
+                || rows[*i]
+                    .iter()
+                    .any(|field| field.to_lowercase().contains(filter))
+        })
+        .collect::<Vec<_>>();
+    if let Some((column, ascending)) = sort {
+        visible.sort_by(|a, b| {
+            let order = rows[*a][column]
+                .to_lowercase()
+                .cmp(&rows[*b][column].to_lowercase());
+            if ascending {
+                order
+            } else {
+                order.reverse()
+            }
+        });
+    }
+    visible
+}
+
+/// The worker's request for `query`, with the rows only if `with_rows`. It's made
+/// into JSON here rather than by [`Worker::request`], so as not to hold on to (or
+/// copy) the rows while the worker's busy.
+fn worker_request(rows: &EntityStore<String, Row>, query: &Query, with_rows: bool) -> JsValue {
+    let request = Request {
+        revision: query.revision,
+        rows: with_rows.then(|| ordered(rows)),
+        filter: query.filter.clone(),
+        sort: query.sort,
+    };
+    serde_json::to_string(&request).unwrap_or_default().into()
+}
+
+/// The worker's side of filtering and sorting: a [`Request`] in, as JSON, and the
+/// visible rows' positions out.
+pub fn rows_job(argument: JsValue) -> Result<Reply, String> {
+    worker::json(argument, |request: Request<Row>| {
+        WORKER_ROWS.with(|kept| {
+            let mut kept = kept.borrow_mut();
+            if let Some(rows) = request.rows {
+                *kept = (request.revision, rows);
+            } else if kept.0 != request.revision {
+                return Err("the worker's rows are out of date".to_string());
+            }
+            let rows = kept.1.iter().collect::<Vec<_>>();
+            Ok(visible(&rows, &request.filter, request.sort))
+        })
+    })
+}
+
+/// Says how many rows matched, how long that took, and the longest frame meanwhile,
+/// once the frame the rows were rendered in has been painted.
+async fn report(
+    status: Element,
+    matched: usize,
+    started: f64,
+    longest_frame: Rc<Cell<f64>>,
+    place: &str,
+) {
+    let took = now() - started;
+    let mut frames = time::frames();
+    frames.next().await;
+    frames.next().await;
+    let shown = if matched > MAX_SHOWN {
+        format!("{matched} rows, showing the first {MAX_SHOWN}")
+    } else {
+        format!("{matched} rows")
+    };
+    status.set_text_content(Some(&format!(
+        "{shown}; worked out{place} in {took:.0} ms, longest frame {:.0} ms",
+        longest_frame.get()
+    )));
+}
+
+fn now() -> f64 {
+    window()
+        .performance()
+        .map_or(0.0, |performance| performance.now())
+}
+
+fn render(tbody: &Element, rows: &[Row], ellipsis: &MiddleEllipsis) {
+    ellipsis.clear();
+    tbody.set_inner_html("");
+    for row in rows.iter().take(MAX_SHOWN) {
+        let tr = create_element("tr");
+        for field in row {
+            let td = create_element("td");
+            _ = td.set_attribute(
+                "style",
+                "border-bottom: 1px solid #ddd; padding: 2px 8px 2px 0; \
+                 white-space: nowrap; overflow: hidden",
+            );
+            ellipsis.show(&td, field);
+            tr.append_child(&td).unwrap();
+        }
+        let remove = create_element("button");
+        _ = remove.set_attribute("data-remove", &key(row));
+        _ = remove.set_attribute("aria-label", &format!("Remove {}", row[0]));
+        remove.set_text_content(Some("×"));
+        let td = create_element("td");
+        td.append_child(&remove).unwrap();
+        tr.append_child(&td).unwrap();
+        tbody.append_child(&tr).unwrap();
+    }
+}
+
+/// Reads `file` a chunk at a time, decoding and parsing as it goes, so the whole file
+/// is never held as one string.
+async fn read_csv(file: &File) -> Result<Vec<Vec<String>>, String> {
+    let decoder = TextDecoder::new().map_err(|_| "no TextDecoder".to_string())?;
+    let mut streaming = TextDecodeOptions::new();
+    streaming.stream(true);
+    let mut parser = CsvParser::new();
+    let mut records = Vec::new();
+
+    let size = file.size();
+    let mut start = 0.0;
+    while start < size {
+        let end = (start + CHUNK_SIZE).min(size);
+        let chunk = file
+            .slice_with_f64_and_f64(start, end)
+            .map_err(|_| "couldn't slice the file".to_string())?;
+        let buffer = JsFuture::from(chunk.array_buffer())
+            .await
+            .map_err(|_| "couldn't read the file".to_string())?;
+        let mut bytes = js_sys::Uint8Array::new(&buffer).to_vec();
+        // `stream` keeps a multi-byte character that's split across chunks for the next call
+        let text = decoder
+            .decode_with_u8_array_and_options(&mut bytes, &streaming)
+            .map_err(|_| "the file isn't valid text".to_string())?;
+        records.extend(parser.feed(&text));
+        start = end;
+    }
+    let rest = decoder.decode().unwrap_or_default();
+    records.extend(parser.feed(&rest));
+    records.extend(parser.finish());
+    Ok(records)
+}
+
+/// Fills the mapping form with one `<select>` of CSV columns per table column.
+fn show_mapping(form: &Element, header: &[String]) {
+    form.set_inner_html("");
+    let legend = create_element("p");
+    legend.set_text_content(Some("Which CSV column goes into which table column?"));
+    form.append_child(&legend).unwrap();
+
+    for column in COLUMNS {
+        let label = create_element("label");
+        _ = label.set_attribute("style", "margin-right: 1em");
+        label.set_text_content(Some(&format!("{column} ← ")));
+        let select = create_element("select").unchecked_into::<HtmlSelectElement>();
+        let skip = create_element("option");
+        _ = skip.set_attribute("value", "");
+        skip.set_text_content(Some("(leave empty)"));
+        select.append_child(&skip).unwrap();
+        for (i, name) in header.iter().enumerate() {
+            let option = create_element("option");
+            _ = option.set_attribute("value", &i.to_string());
+            option.set_text_content(Some(name));
+            select.append_child(&option).unwrap();
+        }
+        // guess from the header: same name, ignoring case
+        let guess = header
+            .iter()
+            .position(|name| name.trim().eq_ignore_ascii_case(column));
+        select.set_value(&guess.map(|i| i.to_string()).unwrap_or_default());
+        label.append_child(&select).unwrap();
+        form.append_child(&label).unwrap();
+    }
+
+    let import = create_element("button");
+    import.set_text_content(Some("Import"));
+    let cancel = create_element("button");
+    _ = cancel.set_attribute("type", "button");
+    _ = cancel.set_attribute("data-cancel", "");
+    cancel.set_text_content(Some("Cancel"));
+    form.append_child(&import).unwrap();
+    form.append_child(&cancel).unwrap();
+    _ = form.remove_attribute("hidden");
+}
+
+/// For each table column, the CSV column picked for it in the mapping form.
+fn chosen_columns(form: &Element) -> [Option<usize>; 3] {
+    let selects = form.query_selector_all("select").ok();
+    std::array::from_fn(|i| {
+        selects
+            .as_ref()?
+            .item(i as u32)?
+            .unchecked_into::<HtmlSelectElement>()
+            .value()
+            .parse()
+            .ok()
+    })
+}
+
+fn download_csv(rows: &[Row]) {
+    let mut text = csv::write_record(&COLUMNS);
+    for row in rows {
+        text.push_str(&csv::write_record(row));
+    }
+    downloads::download("table.csv", text.as_bytes(), "text/csv");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_and_sorts_by_position() {
+        let rows = [
+            row(["Grace Hopper", "grace@example.com", "New York"]),
+            row(["Ada Lovelace", "ada@example.com", "London"]),
+            row(["Alan Turing", "alan@example.com", "Wilmslow"]),
+        ];
+        let rows = rows.iter().collect::<Vec<_>>();
+        assert_eq!(visible(&rows, "", None), [0, 1, 2]);
+        assert_eq!(visible(&rows, "", Some((0, true))), [1, 2, 0]);
+        assert_eq!(visible(&rows, "a", Some((2, false))), [2, 0, 1]);
+        assert_eq!(visible(&rows, "lo", Some((0, false))), [2, 1]);
+    }
+}
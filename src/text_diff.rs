@@ -0,0 +1,136 @@
+//! The diff example: two texts and a word-by-word comparison of them.
+//!
+//! The diff is computed in Rust ([`diff`]) a moment after typing stops, then drawn as
+//! `<del>` and `<ins>` runs, with a line of stats above it. Texts longer than
+//! [`MAX_PIECES`] aren't compared: two unrelated ones would take the diff seconds and
+//! hundreds of megabytes.
+
+use std::{rc::Rc, time::Duration};
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event, HtmlTextAreaElement};
+
+use crate::{
+    app::App,
+    diff::{self, Change},
+    dom::create_element,
+    time::Debounce,
+};
+
+const DELAY: Duration = Duration::from_millis(150);
+/// The most words and runs of whitespace either text can have.
+const MAX_PIECES: usize = 1_000;
+const OLD: &str = "The quick brown fox jumps over the lazy dog.\nIt was not amused.";
+const NEW: &str = "The quick red fox leaps over the lazy dog.\nThe dog was not amused.";
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let section = create_element("section");
+    let inputs = create_element("div");
+    _ = inputs.set_attribute(
+        "style",
+        "display: grid; grid-template-columns: 1fr 1fr; gap: 1em",
+    );
+    let old = textarea("Before", OLD);
+    let new = textarea("After", NEW);
+    inputs.append_child(&old.0).unwrap();
+    inputs.append_child(&new.0).unwrap();
+    let stats = create_element("p");
+    _ = stats.set_attribute("aria-live", "polite");
+    let output = create_element("pre");
+    _ = output.set_attribute(
+        "style",
+        "white-space: pre-wrap; padding: 1em; background: #f6f6f6; border-radius: 4px",
+    );
+    section.append_child(&inputs).unwrap();
+    section.append_child(&stats).unwrap();
+    section.append_child(&output).unwrap();
+    app.append(parent, &section);
+
+    let render = Rc::new({
+        let (old, new) = (old.1.clone(), new.1.clone());
+        move || render(&output, &stats, &old.value(), &new.value())
+    });
+    render();
+
+    let debounce = Rc::new(Debounce::new(DELAY));
+    for textarea in [&old.1, &new.1] {
+        let render = render.clone();
+        let debounce = debounce.clone();
+        app.listen(textarea, "input", move |_: Event| {
+            let render = render.clone();
+            debounce.call(move || render());
+        });
+    }
+
+    app
+}
+
+/// A labelled textarea: the label (to put in the page) and the textarea inside it.
+fn textarea(label: &str, value: &str) -> (Element, HtmlTextAreaElement) {
+    let wrapper = create_element("label");
+    _ = wrapper.set_attribute("style", "display: flex; flex-direction: column; gap: 4px");
+    let textarea = create_element("textarea").unchecked_into::<HtmlTextAreaElement>();
+    textarea.set_rows(6);
+    textarea.set_value(value);
+    _ = wrapper.append_with_str_1(label);
+    wrapper.append_child(&textarea).unwrap();
+    (wrapper, textarea)
+}
+
+fn render(output: &Element, stats: &Element, old: &str, new: &str) {
+    let (old, new) = (diff::words(old), diff::words(new));
+    output.set_inner_html("");
+    if old.len().max(new.len()) > MAX_PIECES {
+        stats.set_text_content(Some(&format!(
+            "Too long to compare: each text can have up to {MAX_PIECES} words and spaces"
+        )));
+        return;
+    }
+    let changes = diff::diff(&old, &new);
+
+    let (mut removed, mut added) = (0, 0);
+    // neighbouring changes of the same kind go in one element rather than one per word
+    let mut run: Option<(&str, String)> = None;
+    let flush = |run: &mut Option<(&str, String)>| {
+        if let Some((tag, text)) = run.take() {
+            let el = create_element(tag);
+            match tag {
+                "del" => _ = el.set_attribute("style", "background: #fdd"),
+                "ins" => _ = el.set_attribute("style", "background: #dfd; text-decoration: none"),
+                _ => {}
+            }
+            el.set_text_content(Some(&text));
+            output.append_child(&el).unwrap();
+        }
+    };
+    for change in changes {
+        let (tag, word) = match change {
+            Change::Same(word) => ("span", *word),
+            Change::Removed(word) => ("del", *word),
+            Change::Added(word) => ("ins", *word),
+        };
+        if !word.trim().is_empty() {
+            match tag {
+                "del" => removed += 1,
+                "ins" => added += 1,
+                _ => {}
+            }
+        }
+        match &mut run {
+            Some((current, text)) if *current == tag => text.push_str(word),
+            _ => {
+                flush(&mut run);
+                run = Some((tag, word.to_string()));
+            }
+        }
+    }
+    flush(&mut run);
+
+    stats.set_text_content(Some(&if removed + added == 0 {
+        "No differences".to_string()
+    } else {
+        format!("{removed} word(s) removed, {added} added")
+    }));
+}
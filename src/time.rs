@@ -0,0 +1,199 @@
+//! Small timer helpers on top of `setTimeout`, `setInterval` and
+//! `requestAnimationFrame`, and a debouncer.
+
+use std::{
+    cell::RefCell,
+    pin::Pin,
+    rc::{Rc, Weak},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::{
+    channel::mpsc::{self, UnboundedReceiver},
+    Stream, StreamExt,
+};
+use wasm_bindgen::{closure::Closure, JsCast, UnwrapThrowExt};
+use wasm_bindgen_futures::JsFuture;
+
+use crate::dom::window;
+
+/// Resolves after `duration` has passed, without blocking the event loop.
+pub async fn sleep(duration: Duration) {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        window()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                resolve.unchecked_ref(),
+                duration.as_millis() as i32,
+            )
+            .unwrap_throw();
+    });
+    // setTimeout never rejects, so the only way this fails is a broken JS environment
+    JsFuture::from(promise).await.unwrap_throw();
+}
+
+/// A pending `setTimeout`; dropping it before it fires cancels it.
+pub struct Timeout {
+    id: i32,
+    _closure: Closure<dyn FnMut()>,
+}
+
+impl Timeout {
+    pub fn new(duration: Duration, f: impl FnOnce() + 'static) -> Self {
+        let mut f = Some(f);
+        let closure = Closure::wrap(Box::new(move || {
+            if let Some(f) = f.take() {
+                f();
+            }
+        }) as Box<dyn FnMut()>);
+        let id = window()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                closure.as_ref().unchecked_ref(),
+                duration.as_millis() as i32,
+            )
+            .unwrap_throw();
+        Timeout {
+            id,
+            _closure: closure,
+        }
+    }
+}
+
+impl Drop for Timeout {
+    fn drop(&mut self) {
+        window().clear_timeout_with_handle(self.id);
+    }
+}
+
+/// A running `setInterval`; dropping it stops it.
+pub struct Interval {
+    id: i32,
+    _closure: Closure<dyn FnMut()>,
+}
+
+impl Interval {
+    pub fn new(period: Duration, f: impl FnMut() + 'static) -> Self {
+        let closure = Closure::wrap(Box::new(f) as Box<dyn FnMut()>);
+        let id = window()
+            .set_interval_with_callback_and_timeout_and_arguments_0(
+                closure.as_ref().unchecked_ref(),
+                period.as_millis() as i32,
+            )
+            .unwrap_throw();
+        Interval {
+            id,
+            _closure: closure,
+        }
+    }
+}
+
+impl Drop for Interval {
+    fn drop(&mut self) {
+        window().clear_interval_with_handle(self.id);
+    }
+}
+
+/// A stream that yields once every `period`, like Elm's `Time.every`.
+///
+/// The interval runs for as long as the stream is alive, so a task looping over it
+/// stops ticking as soon as it's dropped (for example when its `App` unmounts).
+pub fn every(period: Duration) -> Ticks {
+    let (sender, receiver) = mpsc::unbounded();
+    let interval = Interval::new(period, move || _ = sender.unbounded_send(()));
+    Ticks {
+        receiver,
+        _interval: interval,
+    }
+}
+
+pub struct Ticks {
+    receiver: UnboundedReceiver<()>,
+    _interval: Interval,
+}
+
+impl Stream for Ticks {
+    type Item = ();
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
+
+/// A stream that yields before every repaint, with the frame's timestamp in
+/// milliseconds, from `requestAnimationFrame`. Dropping it stops asking for frames.
+pub fn frames() -> Frames {
+    let (sender, receiver) = mpsc::unbounded();
+    let callback = Rc::new(FrameCallback::default());
+    let id = Rc::new(RefCell::new(0));
+    // each frame asks for the next one, so the closure needs a handle on itself; a weak
+    // one, so that dropping `Frames` frees it
+    let this = Rc::downgrade(&callback);
+    *callback.borrow_mut() = Some(Closure::wrap(Box::new({
+        let id = id.clone();
+        move |time: f64| {
+            _ = sender.unbounded_send(time);
+            *id.borrow_mut() = request_frame(&this);
+        }
+    }) as Box<dyn FnMut(f64)>));
+    *id.borrow_mut() = request_frame(&Rc::downgrade(&callback));
+    Frames {
+        receiver,
+        id,
+        _callback: callback,
+    }
+}
+
+type FrameCallback = RefCell<Option<Closure<dyn FnMut(f64)>>>;
+
+fn request_frame(callback: &Weak<FrameCallback>) -> i32 {
+    let Some(callback) = callback.upgrade() else {
+        return 0;
+    };
+    let callback = callback.borrow();
+    window()
+        .request_animation_frame(callback.as_ref().unwrap_throw().as_ref().unchecked_ref())
+        .unwrap_throw()
+}
+
+pub struct Frames {
+    receiver: UnboundedReceiver<f64>,
+    /// The frame asked for last, to cancel.
+    id: Rc<RefCell<i32>>,
+    _callback: Rc<FrameCallback>,
+}
+
+impl Stream for Frames {
+    type Item = f64;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<f64>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for Frames {
+    fn drop(&mut self) {
+        _ = window().cancel_animation_frame(*self.id.borrow());
+    }
+}
+
+/// Runs only the last of a burst of calls, once `delay` has gone by without another:
+/// for work that should wait until the user stops typing.
+pub struct Debounce {
+    delay: Duration,
+    pending: RefCell<Option<Timeout>>,
+}
+
+impl Debounce {
+    pub fn new(delay: Duration) -> Self {
+        Debounce {
+            delay,
+            pending: RefCell::new(None),
+        }
+    }
+
+    /// Schedules `f`, cancelling whatever was scheduled before it. Dropping the
+    /// `Debounce` cancels the last one too.
+    pub fn call(&self, f: impl FnOnce() + 'static) {
+        *self.pending.borrow_mut() = Some(Timeout::new(self.delay, f));
+    }
+}
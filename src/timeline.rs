@@ -0,0 +1,125 @@
+//! Undo and redo, by keeping a snapshot of the state from before every change.
+//!
+//! Every change clones the whole state, so this is only cheap for states that share
+//! their structure between clones (see the kanban [`Board`](crate::kanban::Board),
+//! whose clone is a handful of `Rc` bumps however many cards it holds).
+
+use std::collections::VecDeque;
+
+pub struct Timeline<T> {
+    /// Oldest first.
+    past: VecDeque<T>,
+    present: T,
+    /// Newest (the next redo) last.
+    future: Vec<T>,
+    limit: usize,
+}
+
+impl<T: Clone> Timeline<T> {
+    /// Starts at `present`, keeping at most `limit` steps to undo.
+    pub fn new(present: T, limit: usize) -> Self {
+        Timeline {
+            past: VecDeque::new(),
+            present,
+            future: Vec::new(),
+            limit,
+        }
+    }
+
+    pub fn present(&self) -> &T {
+        &self.present
+    }
+
+    /// Changes the present with `f`, which returns whether it changed anything; only
+    /// changes become steps to undo, and they drop whatever there was to redo.
+    pub fn update(&mut self, f: impl FnOnce(&mut T) -> bool) {
+        let snapshot = self.present.clone();
+        if !f(&mut self.present) {
+            return;
+        }
+        if self.past.len() == self.limit {
+            self.past.pop_front();
+        }
+        self.past.push_back(snapshot);
+        self.future.clear();
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(previous) = self.past.pop_back() {
+            self.future
+                .push(std::mem::replace(&mut self.present, previous));
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(next) = self.future.pop() {
+            self.past
+                .push_back(std::mem::replace(&mut self.present, next));
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.past.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.future.is_empty()
+    }
+
+    /// How many states there are to move between: the past ones, the present, and the
+    /// ones to redo.
+    pub fn steps(&self) -> usize {
+        self.past.len() + 1 + self.future.len()
+    }
+
+    /// Where the present is among the [`Timeline::steps`], oldest first.
+    pub fn position(&self) -> usize {
+        self.past.len()
+    }
+
+    /// Undoes or redoes as many times as it takes to get to `position`, or as close
+    /// to it as there is.
+    pub fn travel_to(&mut self, position: usize) {
+        while self.position() > position && self.can_undo() {
+            self.undo();
+        }
+        while self.position() < position && self.can_redo() {
+            self.redo();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn travelling_keeps_every_step() {
+        let mut timeline = Timeline::new(0, 10);
+        for n in 1..=4 {
+            timeline.update(|present| {
+                *present = n;
+                true
+            });
+        }
+        timeline.travel_to(1);
+        assert_eq!((*timeline.present(), timeline.position()), (1, 1));
+        assert_eq!(timeline.steps(), 5);
+        timeline.travel_to(99);
+        assert_eq!(*timeline.present(), 4);
+        assert!(!timeline.can_redo());
+    }
+
+    #[test]
+    fn the_oldest_steps_go_past_the_limit() {
+        let mut timeline = Timeline::new(0, 2);
+        for n in 1..=4 {
+            timeline.update(|present| {
+                *present = n;
+                true
+            });
+        }
+        timeline.travel_to(0);
+        assert_eq!(*timeline.present(), 2);
+    }
+}
@@ -0,0 +1,189 @@
+//! `document.title` and the breadcrumb trail, built up from whatever is mounted.
+//!
+//! The router pushes the current example's name and the example can push something
+//! more specific under it; the title reads innermost first ("B3 · Spreadsheet ·
+//! Wasm examples") and the breadcrumb outermost first. Each part belongs to the
+//! `App` that pushed it, so when a view unmounts its part goes and the title it
+//! covered up comes back.
+
+use std::{cell::RefCell, rc::Rc};
+
+use web_sys::Element;
+
+use crate::{
+    app::App,
+    dom::{create_element, document},
+};
+
+const SEPARATOR: &str = " · ";
+
+type Listener = Rc<dyn Fn()>;
+
+struct Part {
+    id: usize,
+    label: String,
+    href: Option<String>,
+}
+
+#[derive(Default)]
+struct Store {
+    /// The page's own `<title>`, read the first time anything is pushed.
+    base: Option<String>,
+    parts: Vec<Part>,
+    next_id: usize,
+    listeners: Vec<(usize, Listener)>,
+}
+
+thread_local! {
+    static STORE: RefCell<Store> = RefCell::new(Store::default());
+}
+
+/// A part of the title pushed by [`push`], which can be changed while it's mounted.
+#[derive(Clone, Copy)]
+pub struct Title {
+    id: usize,
+}
+
+impl Title {
+    pub fn set(&self, label: &str) {
+        self.update(|part| part.label = label.to_string());
+    }
+
+    /// Makes this part's breadcrumb a link (only crumbs above the last one are links).
+    pub fn set_href(&self, href: &str) {
+        self.update(|part| part.href = Some(href.to_string()));
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Part)) {
+        STORE.with(|store| {
+            if let Some(part) = store
+                .borrow_mut()
+                .parts
+                .iter_mut()
+                .find(|part| part.id == self.id)
+            {
+                f(part);
+            }
+        });
+        render();
+    }
+}
+
+/// Adds `label` to the title until `app` unmounts.
+pub fn push(app: &mut App, label: &str) -> Title {
+    let id = STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        store.base.get_or_insert_with(|| document().title());
+        let id = store.next_id;
+        store.next_id += 1;
+        store.parts.push(Part {
+            id,
+            label: label.to_string(),
+            href: None,
+        });
+        id
+    });
+    render();
+    app.on_cleanup(move || {
+        STORE.with(|store| store.borrow_mut().parts.retain(|part| part.id != id));
+        render();
+    });
+    Title { id }
+}
+
+/// The crumbs from the outermost in, each with its link if it has one.
+pub fn trail() -> Vec<(String, Option<String>)> {
+    STORE.with(|store| {
+        store
+            .borrow()
+            .parts
+            .iter()
+            .filter(|part| !part.label.is_empty())
+            .map(|part| (part.label.clone(), part.href.clone()))
+            .collect()
+    })
+}
+
+fn render() {
+    let (title, listeners) = STORE.with(|store| {
+        let store = store.borrow();
+        let title = store
+            .parts
+            .iter()
+            .rev()
+            .map(|part| part.label.as_str())
+            .chain(store.base.as_deref())
+            .filter(|label| !label.is_empty())
+            .collect::<Vec<_>>()
+            .join(SEPARATOR);
+        let listeners = store
+            .listeners
+            .iter()
+            .map(|(_, listener)| listener.clone())
+            .collect::<Vec<_>>();
+        (title, listeners)
+    });
+    document().set_title(&title);
+    for listener in listeners {
+        listener();
+    }
+}
+
+/// A breadcrumb trail that follows the title.
+pub fn mount_breadcrumb(app: &mut App, parent: &Element) {
+    let nav = create_element("nav");
+    _ = nav.set_attribute("aria-label", "Breadcrumb");
+    let list = create_element("ol");
+    _ = list.set_attribute(
+        "style",
+        "display: flex; gap: 0.5em; margin: 0.5em 0; padding: 0; list-style: none",
+    );
+    nav.append_child(&list).unwrap();
+    app.append(parent, &nav);
+
+    let render = move || {
+        list.set_inner_html("");
+        let trail = trail();
+        for (i, (label, href)) in trail.iter().enumerate() {
+            let item = create_element("li");
+            if i > 0 {
+                let separator = create_element("span");
+                _ = separator.set_attribute("aria-hidden", "true");
+                separator.set_text_content(Some("› "));
+                item.append_child(&separator).unwrap();
+            }
+            let last = i + 1 == trail.len();
+            let crumb = match href {
+                Some(href) if !last => {
+                    let link = create_element("a");
+                    _ = link.set_attribute("href", href);
+                    link
+                }
+                _ => create_element("span"),
+            };
+            if last {
+                _ = crumb.set_attribute("aria-current", "page");
+            }
+            crumb.set_text_content(Some(label));
+            item.append_child(&crumb).unwrap();
+            list.append_child(&item).unwrap();
+        }
+    };
+    render();
+
+    let id = STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        let id = store.next_id;
+        store.next_id += 1;
+        store.listeners.push((id, Rc::new(render)));
+        id
+    });
+    app.on_cleanup(move || {
+        STORE.with(|store| {
+            store
+                .borrow_mut()
+                .listeners
+                .retain(|(other, _)| *other != id)
+        })
+    });
+}
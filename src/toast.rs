@@ -0,0 +1,68 @@
+//! Short-lived notifications in the corner of the page.
+
+use std::time::Duration;
+
+use web_sys::Element;
+
+use crate::{
+    analytics,
+    dom::{body, create_element, document, spawn_local},
+    time::sleep,
+};
+
+const CONTAINER_ID: &str = "toasts";
+const VISIBLE_FOR: Duration = Duration::from_secs(3);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ToastKind {
+    Info,
+    Error,
+}
+
+pub fn info(message: &str) {
+    show(message, ToastKind::Info);
+}
+
+pub fn error(message: &str) {
+    analytics::error(message);
+    show(message, ToastKind::Error);
+}
+
+pub fn show(message: &str, kind: ToastKind) {
+    let toast = create_element("div");
+    toast.set_text_content(Some(message));
+    let (role, background) = match kind {
+        ToastKind::Info => ("status", "#333"),
+        ToastKind::Error => ("alert", "#b00020"),
+    };
+    _ = toast.set_attribute("role", role);
+    _ = toast.set_attribute(
+        "style",
+        &format!(
+            "margin-top: 8px; padding: 8px 12px; border-radius: 4px; color: white; background: {background}"
+        ),
+    );
+    container().append_child(&toast).unwrap();
+
+    spawn_local(async move {
+        sleep(VISIBLE_FOR).await;
+        toast.remove();
+    });
+}
+
+/// The element all toasts are stacked in, created on first use.
+fn container() -> Element {
+    document()
+        .get_element_by_id(CONTAINER_ID)
+        .unwrap_or_else(|| {
+            let container = create_element("div");
+            container.set_id(CONTAINER_ID);
+            _ = container.set_attribute("aria-live", "polite");
+            _ = container.set_attribute(
+                "style",
+                "position: fixed; right: 16px; bottom: 16px; z-index: 1000",
+            );
+            body().unwrap().append_child(&container).unwrap();
+            container
+        })
+}
@@ -0,0 +1,364 @@
+//! A todo list: the counter's shape, with a list to render.
+//!
+//! The DOM only dispatches `Msg`s to a [`Store`], `Todos::reduce` is the only thing that
+//! changes the list, and the view renders it all again after every change. The `<ul>`
+//! is a [`KeyedList`] keyed by todo id, so removing or toggling one todo touches one
+//! `<li>`; the others (and a half-typed edit, or focus) stay as they were.
+//!
+//! The list listens once for every click and change rather than once per todo, and is
+//! kept in `localStorage`.
+
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event, HtmlInputElement, KeyboardEvent, MouseEvent};
+
+use crate::{
+    app::App,
+    dom::create_element,
+    html::El,
+    keyed::KeyedList,
+    storage,
+    store::{Reducer, Store},
+};
+
+const STORAGE_KEY: &str = "todos";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Todo {
+    pub id: u32,
+    pub text: String,
+    pub done: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Filter {
+    #[default]
+    All,
+    Active,
+    Completed,
+}
+
+impl Filter {
+    const ALL: [(Filter, &'static str); 3] = [
+        (Filter::All, "All"),
+        (Filter::Active, "Active"),
+        (Filter::Completed, "Completed"),
+    ];
+
+    fn shows(self, todo: &Todo) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Active => !todo.done,
+            Filter::Completed => todo.done,
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Todos {
+    pub items: Vec<Todo>,
+    pub filter: Filter,
+    next_id: u32,
+}
+
+#[derive(Clone, Debug)]
+pub enum Msg {
+    Add(String),
+    Toggle(u32),
+    Remove(u32),
+    /// Marks every todo done, or, if they all are already, none.
+    ToggleAll,
+    ClearCompleted,
+    SetFilter(Filter),
+}
+
+impl Todos {
+    /// The todos the filter lets through, in order.
+    pub fn visible(&self) -> impl Iterator<Item = &Todo> {
+        self.items.iter().filter(|todo| self.filter.shows(todo))
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.items.iter().filter(|todo| !todo.done).count()
+    }
+
+    fn find(&mut self, id: u32) -> Result<&mut Todo, String> {
+        self.items
+            .iter_mut()
+            .find(|todo| todo.id == id)
+            .ok_or_else(|| format!("there's no todo {id}"))
+    }
+}
+
+impl Reducer for Todos {
+    type Msg = Msg;
+
+    fn reduce(&mut self, msg: Msg) -> Result<(), String> {
+        match msg {
+            Msg::Add(text) => {
+                let text = text.trim();
+                if text.is_empty() {
+                    return Err("a todo needs some text".to_string());
+                }
+                self.items.push(Todo {
+                    id: self.next_id,
+                    text: text.to_string(),
+                    done: false,
+                });
+                self.next_id += 1;
+            }
+            Msg::Toggle(id) => {
+                let todo = self.find(id)?;
+                todo.done = !todo.done;
+            }
+            Msg::Remove(id) => {
+                self.find(id)?;
+                self.items.retain(|todo| todo.id != id);
+            }
+            Msg::ToggleAll => {
+                let done = self.remaining() > 0;
+                for todo in &mut self.items {
+                    todo.done = done;
+                }
+            }
+            Msg::ClearCompleted => self.items.retain(|todo| !todo.done),
+            Msg::SetFilter(filter) => self.filter = filter,
+        }
+        Ok(())
+    }
+}
+
+struct View {
+    list: KeyedList<u32>,
+    toggle_all: Element,
+    remaining: Element,
+    filters: Vec<(Filter, Element)>,
+    clear: Element,
+}
+
+impl View {
+    fn render(&mut self, todos: &Todos) {
+        self.list.update(
+            todos.visible().map(|todo| (todo.id, todo)),
+            |id, _| todo_element(*id),
+            |el, todo| {
+                if let Ok(Some(checkbox)) = el.query_selector("input") {
+                    checkbox
+                        .unchecked_into::<HtmlInputElement>()
+                        .set_checked(todo.done);
+                }
+                if let Ok(Some(text)) = el.query_selector("span") {
+                    text.set_text_content(Some(&todo.text));
+                    let style = if todo.done {
+                        "text-decoration: line-through; color: #888"
+                    } else {
+                        ""
+                    };
+                    _ = text.set_attribute("style", style);
+                }
+            },
+        );
+        let remaining = todos.remaining();
+        self.remaining.set_text_content(Some(&match remaining {
+            1 => "1 item left".to_string(),
+            n => format!("{n} items left"),
+        }));
+        _ = self
+            .toggle_all
+            .toggle_attribute_with_force("disabled", todos.items.is_empty());
+        _ = self
+            .clear
+            .toggle_attribute_with_force("disabled", remaining == todos.items.len());
+        for (filter, button) in &self.filters {
+            let pressed = *filter == todos.filter;
+            _ = button.set_attribute("aria-pressed", &pressed.to_string());
+        }
+    }
+}
+
+fn todo_element(id: u32) -> Element {
+    let item = create_element("li");
+    _ = item.set_attribute("data-id", &id.to_string());
+    let checkbox = create_element("input").unchecked_into::<HtmlInputElement>();
+    checkbox.set_type("checkbox");
+    _ = checkbox.set_attribute("data-toggle", "");
+    _ = checkbox.set_attribute("aria-label", "Done");
+    let text = create_element("span");
+    let remove = create_element("button");
+    remove.set_text_content(Some("×"));
+    _ = remove.set_attribute("data-remove", "");
+    _ = remove.set_attribute("aria-label", "Remove");
+    item.append_child(&checkbox).unwrap();
+    item.append_child(&text).unwrap();
+    item.append_child(&remove).unwrap();
+    item
+}
+
+/// The id of the todo `ev` happened in, if it has `attribute`.
+fn todo_id(ev: &Event, attribute: &str) -> Option<u32> {
+    let target = ev.target()?.dyn_into::<Element>().ok()?;
+    if !target.has_attribute(attribute) {
+        return None;
+    }
+    target
+        .closest("[data-id]")
+        .ok()??
+        .get_attribute("data-id")?
+        .parse()
+        .ok()
+}
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+    let state = Rc::new(RefCell::new(
+        storage::load::<Todos>(STORAGE_KEY).unwrap_or_default(),
+    ));
+
+    let section = create_element("section");
+    let input = create_element("input").unchecked_into::<HtmlInputElement>();
+    input.set_placeholder("What needs doing?");
+    _ = input.set_attribute("aria-label", "New todo");
+    let toggle_all = create_element("button");
+    toggle_all.set_text_content(Some("Toggle all"));
+    let ul = create_element("ul");
+    let footer = create_element("p");
+    let remaining = create_element("span");
+    footer.append_child(&remaining).unwrap();
+    let filters: Vec<_> = Filter::ALL
+        .iter()
+        .map(|(filter, label)| {
+            let button = create_element("button");
+            button.set_text_content(Some(label));
+            footer.append_child(&button).unwrap();
+            (*filter, button)
+        })
+        .collect();
+    let clear = create_element("button");
+    clear.set_text_content(Some("Clear completed"));
+    footer.append_child(&clear).unwrap();
+    // a form, so Enter submits it
+    let form = create_element("form");
+    form.append_child(&input).unwrap();
+    section.append_child(&form).unwrap();
+    section.append_child(&toggle_all).unwrap();
+    section.append_child(&ul).unwrap();
+    section.append_child(&footer).unwrap();
+    app.append(parent, &section);
+
+    let mut view = View {
+        list: KeyedList::new(ul.clone()),
+        toggle_all: toggle_all.clone(),
+        remaining,
+        filters: filters.clone(),
+        clear: clear.clone(),
+    };
+    view.render(&state.borrow());
+    let store = Store::new(
+        &mut app,
+        state,
+        Vec::new(),
+        move |todos: &Todos, _: &[Msg]| {
+            storage::persist(STORAGE_KEY, todos);
+            view.render(todos);
+        },
+    );
+
+    El::from(form)
+        .on("submit:prevent", {
+            let store = store.clone();
+            let input = input.clone();
+            move |_: Event| {
+                if !input.value().trim().is_empty() {
+                    store.dispatch(Msg::Add(input.value()));
+                    input.set_value("");
+                }
+            }
+        })
+        .build(&mut app);
+    El::from(Element::from(input.clone()))
+        .on("keydown:escape", move |_: KeyboardEvent| {
+            input.set_value("")
+        })
+        .build(&mut app);
+    for (button, msg) in [(&toggle_all, Msg::ToggleAll), (&clear, Msg::ClearCompleted)] {
+        let store = store.clone();
+        app.listen(button, "click", move |_: MouseEvent| {
+            store.dispatch(msg.clone())
+        });
+    }
+    for (filter, button) in filters {
+        let store = store.clone();
+        app.listen(&button, "click", move |_: MouseEvent| {
+            store.dispatch(Msg::SetFilter(filter))
+        });
+    }
+    app.listen(&ul, "change", {
+        let store = store.clone();
+        move |ev: Event| {
+            if let Some(id) = todo_id(&ev, "data-toggle") {
+                store.dispatch(Msg::Toggle(id));
+            }
+        }
+    });
+    app.listen(&ul, "click", move |ev: MouseEvent| {
+        if let Some(id) = todo_id(&ev, "data-remove") {
+            store.dispatch(Msg::Remove(id));
+        }
+    });
+
+    app
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn todos(texts: &[&str]) -> Todos {
+        let mut todos = Todos::default();
+        for text in texts {
+            todos.reduce(Msg::Add(text.to_string())).unwrap();
+        }
+        todos
+    }
+
+    fn texts(todos: &Todos) -> Vec<&str> {
+        todos.visible().map(|todo| todo.text.as_str()).collect()
+    }
+
+    #[test]
+    fn filters_by_done() {
+        let mut todos = todos(&["milk", "eggs", "bread"]);
+        todos.reduce(Msg::Toggle(1)).unwrap();
+        todos.reduce(Msg::SetFilter(Filter::Active)).unwrap();
+        assert_eq!(texts(&todos), ["milk", "bread"]);
+        todos.reduce(Msg::SetFilter(Filter::Completed)).unwrap();
+        assert_eq!(texts(&todos), ["eggs"]);
+        assert_eq!(todos.remaining(), 2);
+    }
+
+    #[test]
+    fn ids_outlive_removals() {
+        let mut todos = todos(&["milk", "eggs"]);
+        todos.reduce(Msg::Remove(0)).unwrap();
+        todos.reduce(Msg::Add("bread".to_string())).unwrap();
+        let ids: Vec<_> = todos.items.iter().map(|todo| todo.id).collect();
+        assert_eq!(ids, [1, 2]);
+        assert!(todos.reduce(Msg::Toggle(0)).is_err());
+        assert!(todos.reduce(Msg::Add("  ".to_string())).is_err());
+    }
+
+    #[test]
+    fn toggle_all_then_clear() {
+        let mut todos = todos(&["milk", "eggs"]);
+        todos.reduce(Msg::Toggle(0)).unwrap();
+        todos.reduce(Msg::ToggleAll).unwrap();
+        assert_eq!(todos.remaining(), 0);
+        todos.reduce(Msg::ToggleAll).unwrap();
+        assert_eq!(todos.remaining(), 2);
+        todos.reduce(Msg::Toggle(1)).unwrap();
+        todos.reduce(Msg::ClearCompleted).unwrap();
+        assert_eq!(texts(&todos), ["milk"]);
+    }
+}
@@ -0,0 +1,135 @@
+//! Tooltips: a short description that shows when an element is hovered or focused.
+//!
+//! Unlike `title`, these also show on keyboard focus, can be dismissed with Escape,
+//! and are wired to the element with `aria-describedby`, so screen readers read them
+//! as the element's description.
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event, HtmlElement, KeyboardEvent};
+
+use crate::{
+    app::App,
+    dom::{body, create_element, document},
+    geometry::{self, Align, Placement, Side},
+    time::Timeout,
+};
+
+/// How long the pointer (or focus) has to stay before the tooltip shows.
+const DELAY: Duration = Duration::from_millis(500);
+const GAP: f64 = 6.0;
+
+thread_local! {
+    static NEXT_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Describes `el` with `content`, shown above it (or below, if there's no room).
+pub fn tooltip(app: &mut App, el: &Element, content: &str) {
+    let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+    let id = format!("tooltip-{id}");
+    let tip = create_element("div").unchecked_into::<HtmlElement>();
+    tip.set_id(&id);
+    _ = tip.set_attribute("role", "tooltip");
+    _ = tip.set_attribute(
+        "style",
+        "position: fixed; z-index: 1000; max-width: 20em; padding: 4px 8px; \
+         border-radius: 4px; background: #333; color: white; font-size: 0.85em; \
+         pointer-events: none; display: none",
+    );
+    tip.set_text_content(Some(content));
+    // in the document even while hidden, so `aria-describedby` can point at it
+    app.append(&body().unwrap(), &tip);
+
+    let mut described_by = el.get_attribute("aria-describedby").unwrap_or_default();
+    if !described_by.is_empty() {
+        described_by.push(' ');
+    }
+    described_by.push_str(&id);
+    _ = el.set_attribute("aria-describedby", &described_by);
+
+    let tooltip = Rc::new(Tooltip {
+        anchor: el.clone(),
+        tip,
+        pending: RefCell::new(None),
+    });
+
+    for event in ["pointerenter", "focusin"] {
+        let tooltip = tooltip.clone();
+        app.listen(el, event, move |_: Event| tooltip.show_soon());
+    }
+    for event in ["pointerleave", "focusout", "pointerdown"] {
+        let tooltip = tooltip.clone();
+        app.listen(el, event, move |_: Event| tooltip.hide());
+    }
+    app.listen(&document(), "keydown", {
+        let tooltip = tooltip.clone();
+        move |ev: KeyboardEvent| {
+            if ev.key() == "Escape" && tooltip.is_visible() {
+                tooltip.hide();
+            }
+        }
+    });
+
+    let el = el.clone();
+    app.on_cleanup(move || {
+        tooltip.hide();
+        let rest = described_by
+            .split_whitespace()
+            .filter(|other| *other != id)
+            .collect::<Vec<_>>()
+            .join(" ");
+        if rest.is_empty() {
+            _ = el.remove_attribute("aria-describedby");
+        } else {
+            _ = el.set_attribute("aria-describedby", &rest);
+        }
+    });
+}
+
+struct Tooltip {
+    anchor: Element,
+    tip: HtmlElement,
+    /// The delayed show, if the pointer or focus has only just arrived.
+    pending: RefCell<Option<Timeout>>,
+}
+
+impl Tooltip {
+    fn show_soon(self: &Rc<Self>) {
+        // weak, or the tooltip and its own pending timeout would keep each other alive
+        let tooltip = Rc::downgrade(self);
+        let timeout = Timeout::new(DELAY, move || {
+            if let Some(tooltip) = tooltip.upgrade() {
+                tooltip.show();
+            }
+        });
+        *self.pending.borrow_mut() = Some(timeout);
+    }
+
+    /// Leaves `pending` alone: this may be running inside that very timeout.
+    fn show(&self) {
+        _ = self.tip.style().set_property("display", "block");
+        geometry::place_next_to(
+            &self.tip,
+            geometry::bounding_rect(&self.anchor),
+            Placement {
+                side: Side::Top,
+                align: Align::Center,
+                gap: GAP,
+            },
+        );
+    }
+
+    fn hide(&self) {
+        self.pending.borrow_mut().take();
+        _ = self.tip.style().set_property("display", "none");
+    }
+
+    fn is_visible(&self) -> bool {
+        self.tip.style().get_property_value("display").as_deref() == Ok("block")
+    }
+}
@@ -0,0 +1,66 @@
+//! Three counters and their total, which nothing but the counters' own events tell the
+//! parent about: each announces its count with a bubbling [`COUNT_CHANGED`] event, and
+//! the parent listens once, on the element around them all.
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event};
+
+use crate::{
+    app::App,
+    arena::Registry,
+    component::Component,
+    counter::{CountChanged, Counter, COUNT_CHANGED},
+    custom_event,
+    html::el,
+};
+
+const HOSTS: [&str; 3] = ["total-a", "total-b", "total-c"];
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+    let total = el!(p, "aria-live" = "polite")
+        .text("Total: 0")
+        .build(&mut app);
+    let row = el!(div, style = "display: flex; gap: 2em").build(&mut app);
+    el!(section)
+        .child(row.clone())
+        .child(total.clone())
+        .mount(&mut app, parent);
+
+    // by host id, since that's what each count comes from
+    let counts = Rc::new(RefCell::new(BTreeMap::<String, i32>::new()));
+    custom_event::listen(
+        &mut app,
+        &row,
+        COUNT_CHANGED,
+        move |changed: CountChanged, ev: &Event| {
+            let Some(host) = ev.target().and_then(|t| t.dyn_into::<Element>().ok()) else {
+                return;
+            };
+            let mut counts = counts.borrow_mut();
+            counts.insert(host.id(), changed.count);
+            let sum = counts
+                .values()
+                .fold(0i32, |sum, count| sum.saturating_add(*count));
+            total.set_text_content(Some(&format!("Total: {sum}")));
+        },
+    );
+
+    // listening already, so the counts they start with are heard too
+    let counters = Registry::<Counter>::new();
+    let handle = counters.handle();
+    for id in HOSTS {
+        let host = el!(div, id = id).build(&mut app);
+        row.append_child(&host).unwrap();
+        let key = counters.insert(Counter::default());
+        handle.with(key, |counter| counter.mount(&host));
+    }
+    app.on_cleanup(move || {
+        for mut counter in counters.drain() {
+            counter.unmount();
+        }
+    });
+    app
+}
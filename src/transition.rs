@@ -0,0 +1,38 @@
+//! View transitions: `document.startViewTransition`, bound by hand.
+//!
+//! The browser snapshots the page, lets `update` change the DOM, then crossfades from
+//! the snapshot to the new state (restyle it with `::view-transition-*` in CSS).
+//! Where the API is missing, or animations are off for the low-power mode or by their
+//! flag, `update` just runs straight away.
+
+use wasm_bindgen::{closure::Closure, prelude::wasm_bindgen, JsCast, JsValue};
+
+use crate::{battery, dom::document, flags};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = Document)]
+    type ViewTransitionDocument;
+
+    /// Returns a `ViewTransition`, which we don't need.
+    #[wasm_bindgen(method, js_name = startViewTransition)]
+    fn start_view_transition(this: &ViewTransitionDocument, update: &JsValue) -> JsValue;
+}
+
+pub fn is_supported() -> bool {
+    js_sys::Reflect::has(&document(), &"startViewTransition".into()).unwrap_or(false)
+}
+
+/// Runs `update` inside a view transition if we can, or right now if not. Either way
+/// it runs exactly once, but with a transition it runs a frame or so later.
+pub fn run(update: impl FnOnce() + 'static) {
+    if !is_supported() || battery::low_power() || !flags::enabled("animations") {
+        update();
+        return;
+    }
+    // the browser calls it (and so frees it) even when it skips the animation itself
+    let update = Closure::once_into_js(update);
+    document()
+        .unchecked_into::<ViewTransitionDocument>()
+        .start_view_transition(&update);
+}
@@ -0,0 +1,378 @@
+//! A file-explorer style tree whose folders load their children the first time
+//! they're opened.
+//!
+//! All tree state (what's loaded, what's open, what's loading, which item has focus)
+//! lives in one `Tree`, changed only by `Msg`s; the view is rendered recursively from
+//! it. Keyboard support follows the WAI-ARIA tree pattern: Up/Down move, Right opens or
+//! goes to the first child, Left closes or goes to the parent, Home/End jump, Enter
+//! toggles.
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use futures::{channel::mpsc::UnboundedSender, StreamExt};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, KeyboardEvent, MouseEvent};
+
+use crate::{
+    analytics,
+    app::App,
+    dom::{create_element, document, spawn_local},
+    time,
+};
+
+/// How long the pretend file system takes to list a folder.
+const LOAD_DELAY: Duration = Duration::from_millis(600);
+const ROOT: NodeId = 0;
+
+pub type NodeId = u32;
+
+pub struct Node {
+    pub name: String,
+    pub parent: Option<NodeId>,
+    pub is_folder: bool,
+    /// `None` until the folder has been loaded.
+    pub children: Option<Vec<NodeId>>,
+}
+
+/// A child as it comes back from the loader.
+#[derive(Debug)]
+pub struct Entry {
+    pub name: String,
+    pub is_folder: bool,
+}
+
+#[derive(Debug)]
+pub enum Msg {
+    Toggle(NodeId),
+    Expand(NodeId),
+    Collapse(NodeId),
+    Focus(NodeId),
+    Loaded(NodeId, Vec<Entry>),
+}
+
+pub struct Tree {
+    pub nodes: HashMap<NodeId, Node>,
+    pub expanded: HashSet<NodeId>,
+    pub loading: HashSet<NodeId>,
+    pub focused: NodeId,
+    next_id: NodeId,
+}
+
+impl Tree {
+    pub fn new(root_name: &str) -> Self {
+        let mut nodes = HashMap::new();
+        nodes.insert(
+            ROOT,
+            Node {
+                name: root_name.to_string(),
+                parent: None,
+                is_folder: true,
+                children: None,
+            },
+        );
+        Tree {
+            nodes,
+            expanded: HashSet::new(),
+            loading: HashSet::new(),
+            focused: ROOT,
+            next_id: ROOT + 1,
+        }
+    }
+
+    /// Applies `msg`; returns a folder whose children need loading, if any.
+    pub fn update(&mut self, msg: Msg) -> Option<NodeId> {
+        match msg {
+            Msg::Toggle(id) if self.expanded.contains(&id) => self.update(Msg::Collapse(id)),
+            Msg::Toggle(id) => self.update(Msg::Expand(id)),
+            Msg::Expand(id) => {
+                let node = self.nodes.get(&id)?;
+                if !node.is_folder {
+                    return None;
+                }
+                self.expanded.insert(id);
+                (node.children.is_none() && self.loading.insert(id)).then_some(id)
+            }
+            Msg::Collapse(id) => {
+                self.expanded.remove(&id);
+                // focus can't stay on something that's no longer visible
+                if self.is_inside(self.focused, id) {
+                    self.focused = id;
+                }
+                None
+            }
+            Msg::Focus(id) => {
+                if self.nodes.contains_key(&id) {
+                    self.focused = id;
+                }
+                None
+            }
+            Msg::Loaded(id, entries) => {
+                self.loading.remove(&id);
+                let children = entries
+                    .into_iter()
+                    .map(|entry| {
+                        let child = self.next_id;
+                        self.next_id += 1;
+                        self.nodes.insert(
+                            child,
+                            Node {
+                                name: entry.name,
+                                parent: Some(id),
+                                is_folder: entry.is_folder,
+                                children: None,
+                            },
+                        );
+                        child
+                    })
+                    .collect();
+                if let Some(node) = self.nodes.get_mut(&id) {
+                    node.children = Some(children);
+                }
+                None
+            }
+        }
+    }
+
+    /// Whether `id` is a strict descendant of `ancestor`.
+    fn is_inside(&self, id: NodeId, ancestor: NodeId) -> bool {
+        let mut current = self.nodes.get(&id).and_then(|node| node.parent);
+        while let Some(parent) = current {
+            if parent == ancestor {
+                return true;
+            }
+            current = self.nodes.get(&parent).and_then(|node| node.parent);
+        }
+        false
+    }
+
+    /// Every item that's currently on screen, top to bottom.
+    fn visible(&self) -> Vec<NodeId> {
+        let mut visible = Vec::new();
+        let mut stack = vec![ROOT];
+        while let Some(id) = stack.pop() {
+            visible.push(id);
+            if self.expanded.contains(&id) {
+                if let Some(children) = self.nodes.get(&id).and_then(|node| node.children.as_ref())
+                {
+                    stack.extend(children.iter().rev());
+                }
+            }
+        }
+        visible
+    }
+
+    /// What a navigation key does with the focused item.
+    fn key(&self, key: &str) -> Option<Msg> {
+        let focused = self.focused;
+        let node = self.nodes.get(&focused)?;
+        let visible = self.visible();
+        let position = visible.iter().position(|id| *id == focused)?;
+        Some(match key {
+            "ArrowDown" => Msg::Focus(*visible.get(position + 1)?),
+            "ArrowUp" => Msg::Focus(*visible.get(position.checked_sub(1)?)?),
+            "Home" => Msg::Focus(*visible.first()?),
+            "End" => Msg::Focus(*visible.last()?),
+            "ArrowRight" if node.is_folder && !self.expanded.contains(&focused) => {
+                Msg::Expand(focused)
+            }
+            "ArrowRight" => Msg::Focus(*node.children.as_ref()?.first()?),
+            "ArrowLeft" if self.expanded.contains(&focused) => Msg::Collapse(focused),
+            "ArrowLeft" => Msg::Focus(node.parent?),
+            "Enter" | " " => Msg::Toggle(focused),
+            _ => return None,
+        })
+    }
+}
+
+/// Pretends to list a folder on a slow disk: a few subfolders and files, fewer the
+/// deeper you go, so the tree doesn't go on forever.
+async fn load_children(path: String, depth: usize) -> Vec<Entry> {
+    time::sleep(LOAD_DELAY).await;
+    let folders = 3usize.saturating_sub(depth);
+    let files = 2 + path.len() % 3;
+    (1..=folders)
+        .map(|i| Entry {
+            name: format!("folder-{i}"),
+            is_folder: true,
+        })
+        .chain((1..=files).map(|i| Entry {
+            name: format!("file-{i}.txt"),
+            is_folder: false,
+        }))
+        .collect()
+}
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+    let mut tree = Tree::new("project");
+
+    let root = create_element("ul");
+    _ = root.set_attribute("role", "tree");
+    _ = root.set_attribute("aria-label", "Files");
+    _ = root.set_attribute(
+        "style",
+        "list-style: none; padding-left: 0; font-family: monospace",
+    );
+    app.append(parent, &root);
+
+    let (sender, mut receiver) = futures::channel::mpsc::unbounded::<Msg>();
+
+    app.listen(&root, "click", {
+        let sender = sender.clone();
+        move |ev: MouseEvent| {
+            let id = ev
+                .target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+                .and_then(|target| target.closest("[data-id]").ok().flatten())
+                .and_then(|item| item.get_attribute("data-id"))
+                .and_then(|id| id.parse().ok());
+            if let Some(id) = id {
+                _ = sender.unbounded_send(Msg::Focus(id));
+                _ = sender.unbounded_send(Msg::Toggle(id));
+            }
+        }
+    });
+
+    // keys are resolved against the tree inside the loop, where the state lives
+    let (key_sender, mut key_receiver) = futures::channel::mpsc::unbounded::<String>();
+    app.listen(&root, "keydown", move |ev: KeyboardEvent| {
+        let key = ev.key();
+        if matches!(
+            key.as_str(),
+            "ArrowUp" | "ArrowDown" | "ArrowLeft" | "ArrowRight" | "Home" | "End" | "Enter" | " "
+        ) {
+            ev.prevent_default();
+            _ = key_sender.unbounded_send(key);
+        }
+    });
+
+    if let Some(id) = tree.update(Msg::Expand(ROOT)) {
+        load(&tree, id, &sender);
+    }
+    render(&root, &tree);
+
+    app.spawn(async move {
+        loop {
+            let msg = futures::select! {
+                msg = receiver.next() => msg,
+                key = key_receiver.next() => match key {
+                    Some(key) => match tree.key(&key) {
+                        Some(msg) => Some(msg),
+                        None => continue,
+                    },
+                    None => None,
+                },
+            };
+            let Some(msg) = msg else { break };
+            analytics::message(&msg);
+            // re-rendering replaces the focused element, so focus has to be put back
+            let had_focus = document()
+                .active_element()
+                .is_some_and(|active| root.contains(Some(&active)));
+            if let Some(id) = tree.update(msg) {
+                load(&tree, id, &sender);
+            }
+            render(&root, &tree);
+            if had_focus {
+                focus(&root, tree.focused);
+            }
+        }
+    });
+
+    app
+}
+
+/// Starts loading `id`'s children; the result comes back as `Msg::Loaded`.
+fn load(tree: &Tree, id: NodeId, sender: &UnboundedSender<Msg>) {
+    let (path, depth) = path_of(tree, id);
+    let sender = sender.clone();
+    // not owned by the app: if it unmounts first, the send just fails
+    spawn_local(async move {
+        let entries = load_children(path, depth).await;
+        _ = sender.unbounded_send(Msg::Loaded(id, entries));
+    });
+}
+
+fn path_of(tree: &Tree, id: NodeId) -> (String, usize) {
+    let mut names = Vec::new();
+    let mut current = Some(id);
+    while let Some(id) = current {
+        let Some(node) = tree.nodes.get(&id) else {
+            break;
+        };
+        names.push(node.name.as_str());
+        current = node.parent;
+    }
+    let depth = names.len() - 1;
+    names.reverse();
+    (names.join("/"), depth)
+}
+
+fn render(root: &Element, tree: &Tree) {
+    root.set_inner_html("");
+    root.append_child(&render_node(tree, ROOT, 1)).unwrap();
+}
+
+fn render_node(tree: &Tree, id: NodeId, level: usize) -> Element {
+    let node = &tree.nodes[&id];
+    let item = create_element("li");
+    _ = item.set_attribute("role", "treeitem");
+    _ = item.set_attribute("data-id", &id.to_string());
+    _ = item.set_attribute("aria-level", &level.to_string());
+    // roving tabindex: only the focused item is in the tab order
+    _ = item.set_attribute("tabindex", if id == tree.focused { "0" } else { "-1" });
+    _ = item.set_attribute("style", "cursor: pointer; outline-offset: -1px");
+
+    let expanded = tree.expanded.contains(&id);
+    let label = create_element("span");
+    let icon = match (node.is_folder, expanded) {
+        (false, _) => "📄",
+        (true, false) => "▸ 📁",
+        (true, true) => "▾ 📂",
+    };
+    label.set_text_content(Some(&format!("{icon} {}", node.name)));
+    item.append_child(&label).unwrap();
+
+    if !node.is_folder {
+        return item;
+    }
+    _ = item.set_attribute("aria-expanded", &expanded.to_string());
+    if !expanded {
+        return item;
+    }
+
+    let group = create_element("ul");
+    _ = group.set_attribute("role", "group");
+    _ = group.set_attribute("style", "list-style: none; padding-left: 1.5em");
+    match &node.children {
+        Some(children) => {
+            for child in children {
+                group
+                    .append_child(&render_node(tree, *child, level + 1))
+                    .unwrap();
+            }
+            if children.is_empty() {
+                let empty = create_element("li");
+                empty.set_text_content(Some("(empty)"));
+                group.append_child(&empty).unwrap();
+            }
+        }
+        None => {
+            _ = item.set_attribute("aria-busy", "true");
+            let loading = create_element("li");
+            loading.set_text_content(Some("Loading…"));
+            group.append_child(&loading).unwrap();
+        }
+    }
+    item.append_child(&group).unwrap();
+    item
+}
+
+fn focus(root: &Element, id: NodeId) {
+    if let Ok(Some(item)) = root.query_selector(&format!("[data-id=\"{id}\"]")) {
+        _ = item.unchecked_ref::<HtmlElement>().focus();
+    }
+}
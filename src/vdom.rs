@@ -0,0 +1,328 @@
+//! A tiny virtual DOM: describe the whole view as plain data on every change, and let
+//! a diff against the last description work out which DOM mutations that takes.
+//!
+//! Children are matched by position, with no keys (see
+//! [`KeyedList`](crate::keyed::KeyedList) for that). Nodes hold no listeners either,
+//! so a tree is just data that can be compared; views listen once on their container
+//! and find out what was clicked from attributes instead.
+
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::{Element, Node};
+
+use crate::dom::{create_element, document, set_attribute};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum VNode {
+    Element {
+        tag: &'static str,
+        attributes: Vec<(&'static str, String)>,
+        children: Vec<VNode>,
+    },
+    Text(String),
+}
+
+impl VNode {
+    pub fn element(tag: &'static str) -> Self {
+        VNode::Element {
+            tag,
+            attributes: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    pub fn text(text: impl Into<String>) -> Self {
+        VNode::Text(text.into())
+    }
+
+    /// Adds an attribute; does nothing to a text node.
+    pub fn attribute(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        if let VNode::Element { attributes, .. } = &mut self {
+            attributes.push((name, value.into()));
+        }
+        self
+    }
+
+    /// Adds a child; does nothing to a text node.
+    pub fn child(mut self, child: VNode) -> Self {
+        if let VNode::Element { children, .. } = &mut self {
+            children.push(child);
+        }
+        self
+    }
+
+    /// Builds the real DOM for this node and everything under it.
+    pub fn create(&self) -> Node {
+        match self {
+            VNode::Element {
+                tag,
+                attributes,
+                children,
+            } => {
+                let el = create_element(tag);
+                for (name, value) in attributes {
+                    set_attribute(&el, name, value);
+                }
+                for child in children {
+                    el.append_child(&child.create()).unwrap_throw();
+                }
+                el.into()
+            }
+            VNode::Text(text) => document().create_text_node(text).into(),
+        }
+    }
+}
+
+/// One DOM mutation. `path` is the child index at each level down from the root,
+/// so the root itself is the empty path.
+#[derive(Debug, PartialEq)]
+pub enum Patch {
+    Replace {
+        path: Vec<usize>,
+        node: VNode,
+    },
+    SetText {
+        path: Vec<usize>,
+        text: String,
+    },
+    SetAttribute {
+        path: Vec<usize>,
+        name: &'static str,
+        value: String,
+    },
+    RemoveAttribute {
+        path: Vec<usize>,
+        name: &'static str,
+    },
+    Append {
+        path: Vec<usize>,
+        node: VNode,
+    },
+    /// Removes the children from `len` on.
+    Truncate {
+        path: Vec<usize>,
+        len: usize,
+    },
+}
+
+/// The mutations that turn the DOM built from `old` into the DOM `new` describes.
+pub fn diff(old: &VNode, new: &VNode) -> Vec<Patch> {
+    let mut patches = Vec::new();
+    diff_at(&mut Vec::new(), old, new, &mut patches);
+    patches
+}
+
+fn diff_at(path: &mut Vec<usize>, old: &VNode, new: &VNode, patches: &mut Vec<Patch>) {
+    match (old, new) {
+        (VNode::Text(old), VNode::Text(new)) => {
+            if old != new {
+                patches.push(Patch::SetText {
+                    path: path.clone(),
+                    text: new.clone(),
+                });
+            }
+        }
+        (
+            VNode::Element {
+                tag: old_tag,
+                attributes: old_attributes,
+                children: old_children,
+            },
+            VNode::Element {
+                tag,
+                attributes,
+                children,
+            },
+        ) if old_tag == tag => {
+            for (name, value) in attributes {
+                let old_value = old_attributes.iter().find(|(old, _)| old == name);
+                if old_value.map(|(_, old)| old) != Some(value) {
+                    patches.push(Patch::SetAttribute {
+                        path: path.clone(),
+                        name,
+                        value: value.clone(),
+                    });
+                }
+            }
+            for (name, _) in old_attributes {
+                if !attributes.iter().any(|(new, _)| new == name) {
+                    patches.push(Patch::RemoveAttribute {
+                        path: path.clone(),
+                        name,
+                    });
+                }
+            }
+            for (i, (old, new)) in old_children.iter().zip(children).enumerate() {
+                path.push(i);
+                diff_at(path, old, new, patches);
+                path.pop();
+            }
+            if children.len() < old_children.len() {
+                patches.push(Patch::Truncate {
+                    path: path.clone(),
+                    len: children.len(),
+                });
+            }
+            for node in children.iter().skip(old_children.len()) {
+                patches.push(Patch::Append {
+                    path: path.clone(),
+                    node: node.clone(),
+                });
+            }
+        }
+        // a different tag, or an element for a text node: nothing to keep
+        _ => patches.push(Patch::Replace {
+            path: path.clone(),
+            node: new.clone(),
+        }),
+    }
+}
+
+/// A virtual DOM mounted as the only content of `container`.
+pub struct VDom {
+    container: Element,
+    current: Option<VNode>,
+}
+
+impl VDom {
+    pub fn new(container: Element) -> Self {
+        VDom {
+            container,
+            current: None,
+        }
+    }
+
+    /// Brings the DOM in line with `next`, returning how many mutations that took.
+    pub fn render(&mut self, next: VNode) -> usize {
+        let Some(current) = &self.current else {
+            self.container.set_text_content(None);
+            self.container.append_child(&next.create()).unwrap_throw();
+            self.current = Some(next);
+            return 1;
+        };
+        let patches = diff(current, &next);
+        for patch in &patches {
+            self.apply(patch);
+        }
+        self.current = Some(next);
+        patches.len()
+    }
+
+    fn apply(&self, patch: &Patch) {
+        match patch {
+            Patch::Replace { path, node } => {
+                let old = self.node_at(path);
+                if let Some(parent) = old.parent_node() {
+                    _ = parent.replace_child(&node.create(), &old);
+                }
+            }
+            Patch::SetText { path, text } => self.node_at(path).set_text_content(Some(text)),
+            Patch::SetAttribute { path, name, value } => {
+                set_attribute(&self.element_at(path), name, value);
+            }
+            Patch::RemoveAttribute { path, name } => {
+                _ = self.element_at(path).remove_attribute(name);
+            }
+            Patch::Append { path, node } => {
+                _ = self.node_at(path).append_child(&node.create());
+            }
+            Patch::Truncate { path, len } => {
+                let parent = self.node_at(path);
+                while parent.child_nodes().length() as usize > *len {
+                    if let Some(last) = parent.last_child() {
+                        _ = parent.remove_child(&last);
+                    }
+                }
+            }
+        }
+    }
+
+    fn node_at(&self, path: &[usize]) -> Node {
+        let mut node = self
+            .container
+            .first_child()
+            .expect_throw("the root to be mounted");
+        for &i in path {
+            node = node
+                .child_nodes()
+                .item(i as u32)
+                .expect_throw("the DOM to match the last render");
+        }
+        node
+    }
+
+    fn element_at(&self, path: &[usize]) -> Element {
+        self.node_at(path).unchecked_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counter(count: i32) -> VNode {
+        VNode::element("div")
+            .child(VNode::element("button").child(VNode::text("+1")))
+            .child(VNode::element("p").child(VNode::text(count.to_string())))
+    }
+
+    #[test]
+    fn a_changed_text_is_the_only_patch() {
+        assert_eq!(
+            diff(&counter(0), &counter(1)),
+            [Patch::SetText {
+                path: vec![1, 0],
+                text: "1".to_string(),
+            }]
+        );
+        assert!(diff(&counter(1), &counter(1)).is_empty());
+    }
+
+    #[test]
+    fn attributes_and_children_are_patched_in_place() {
+        let old = VNode::element("ul")
+            .attribute("class", "old")
+            .attribute("hidden", "")
+            .child(VNode::element("li"))
+            .child(VNode::element("li"));
+        let new = VNode::element("ul")
+            .attribute("class", "new")
+            .child(VNode::element("li"));
+        assert_eq!(
+            diff(&old, &new),
+            [
+                Patch::SetAttribute {
+                    path: vec![],
+                    name: "class",
+                    value: "new".to_string(),
+                },
+                Patch::RemoveAttribute {
+                    path: vec![],
+                    name: "hidden",
+                },
+                Patch::Truncate {
+                    path: vec![],
+                    len: 1,
+                },
+            ]
+        );
+        assert_eq!(
+            diff(&new, &old)[2..],
+            [Patch::Append {
+                path: vec![],
+                node: VNode::element("li"),
+            }]
+        );
+    }
+
+    #[test]
+    fn a_different_tag_is_replaced() {
+        let new = VNode::element("span");
+        assert_eq!(
+            diff(&VNode::element("p").child(VNode::text("a")), &new),
+            [Patch::Replace {
+                path: vec![],
+                node: new
+            }]
+        );
+    }
+}
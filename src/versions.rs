@@ -0,0 +1,1280 @@
+//! The counter the way this project first built it, in ten versions, side by side.
+//!
+//! Each version is a step along the way: state moved into a single closure, then a
+//! stale copy of it in two, then shared through `Rc<RefCell<_>>`, then messages sent
+//! to a reducer that asks for side effects as commands, then a signal that updates the
+//! text by itself, then a virtual DOM that works out the updates from a description of
+//! the whole view, then form inputs bound both ways, read as they're typed in and
+//! written back when the buttons change the count, then version 3 again, its markup
+//! written with an element builder (SVG icons and a MathML count included), then a
+//! count that lives on a server, loaded on startup and changed by POSTs, and last
+//! updates that only mark the count dirty, written to the page once an animation
+//! frame. The picker unmounts whichever version is showing (its nodes, listener
+//! handles and task all go with its `App`) before mounting the next, so they can all
+//! be tried without recompiling, and without the old version's closures staying
+//! attached to its detached buttons.
+//!
+//! Every version keeps its count in `localStorage` under the same key, so it carries
+//! over from one version to the next, and to the next visit.
+//!
+//! Which version a visitor sees first is their variant of [`EXPERIMENT`], and clicks on
+//! whichever version is showing are counted for it.
+
+use std::{
+    cell::{Cell, RefCell},
+    num::IntErrorKind,
+    rc::Rc,
+    time::Duration,
+};
+
+use futures::{channel::mpsc::Sender, StreamExt};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{
+    Element, Event, HtmlButtonElement, HtmlDivElement, HtmlInputElement, HtmlOptionElement,
+    HtmlParagraphElement, HtmlSelectElement, MouseEvent,
+};
+
+use crate::{
+    api,
+    app::App,
+    counter::{Msg, State},
+    devtools,
+    dom::{add_event_listener, create, document, log, spawn_local, value_of, window},
+    experiment::{self, Experiment},
+    fetch, flags,
+    html::{el, math, svg, El, XLINK},
+    keyboard::{self, Keymap},
+    network,
+    number_spinner::{NumberSpinner, SpinnerOptions},
+    signal::{create_effect, create_signal},
+    sse, storage,
+    subscriptions::{subscribe, window_size, Sub},
+    supervisor::{Backoff, RestartPolicy, Supervisor},
+    sync::Tabs,
+    time::sleep,
+    timeline::Timeline,
+    vdom::{VDom, VNode},
+    ws::{self, Socket},
+};
+
+const STATE_KEY: &str = "versions-state";
+
+/// How many of version 4's changes can be undone.
+const HISTORY_LIMIT: usize = 100;
+
+/// The steps version 7 offers, besides whichever one the count was saved with.
+const STEPS: [i32; 4] = [1, 5, 10, 100];
+
+/// How many increments version 10's burst button makes, all before the next frame.
+const BURST: u32 = 10_000;
+
+/// Plain text: one random number from 1 to 100.
+const RANDOM_ENDPOINT: &str =
+    "https://www.random.org/integers/?num=1&min=1&max=100&col=1&base=10&format=plain&rnd=new";
+
+/// Where version 9's count lives: GET answers `{"count": n}`, and POSTing
+/// `{"by": n}` adds to it and answers the same. `trunk serve --proxy-backend` can
+/// send it on to a server; without one, version 9 says it can't load the count.
+const COUNT_ENDPOINT: &str = "/api/count";
+
+/// Version 4's connection to a server it shares the count with: every change to the
+/// count goes up as a `Msg`, and every `Msg` that comes down is applied, so all the
+/// counters connected to it move together.
+const SOCKET_PATH: &str = "/api/counter";
+
+/// Where the `server-push` flag has version 4 hear the server instead: the same
+/// `Msg`s, as Server-Sent Events, and nothing sent back.
+const EVENTS_PATH: &str = "/api/counter/events";
+
+/// Patient, since without a server version 4 would be trying forever.
+const SOCKET_BACKOFF: Backoff = Backoff {
+    initial: Duration::from_secs(1),
+    max: Duration::from_secs(60),
+    factor: 2,
+};
+
+/// A label for the picker, and how to mount it.
+type Version = (&'static str, fn(&Element) -> App);
+
+const VERSIONS: &[Version] = &[
+    ("1. With a single button", version_1_with_single_button),
+    ("2. With a stale closure", version_2_with_stale_closure),
+    (
+        "3. With interior mutability",
+        version_3_with_interior_mutability,
+    ),
+    (
+        "4. With an async channel and a reducer",
+        version_4_with_async_channel_and_reducer_pattern,
+    ),
+    ("5. With signals", version_5_with_signals),
+    ("6. With a virtual DOM", version_6_with_virtual_dom),
+    ("7. With form inputs", version_7_with_form_inputs),
+    (
+        "8. With an element builder",
+        version_8_with_an_element_builder,
+    ),
+    ("9. With a server", version_9_with_a_server),
+    (
+        "10. With animation frames",
+        version_10_with_animation_frames,
+    ),
+];
+
+/// Which version visitors see first.
+pub const EXPERIMENT: Experiment = Experiment {
+    name: "counter-versions",
+    variants: &[
+        "single-button",
+        "stale-closure",
+        "interior-mutability",
+        "reducer",
+        "signals",
+        "virtual-dom",
+        "form-inputs",
+        "element-builder",
+        "server",
+        "animation-frames",
+    ],
+};
+
+fn restored() -> State {
+    storage::load(STATE_KEY).unwrap_or_default()
+}
+
+fn persist(state: &State) {
+    storage::persist(STATE_KEY, state);
+}
+
+/// For versions that only have a count, not a whole state.
+fn persist_count(count: i32) {
+    persist(&State {
+        count,
+        ..State::default()
+    });
+}
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let picker = create::<HtmlSelectElement>("select");
+    _ = picker.set_attribute("aria-label", "Version");
+    for (i, (label, _)) in VERSIONS.iter().enumerate() {
+        let option = create::<HtmlOptionElement>("option");
+        option.set_value(&i.to_string());
+        option.set_text_content(Some(label));
+        picker.append_child(&option).unwrap();
+    }
+    let stage = create::<HtmlDivElement>("div");
+    app.append(parent, &picker);
+    app.append(parent, &stage);
+
+    let variant = experiment::expose(&EXPERIMENT);
+    picker.set_value(&variant.to_string());
+    let mounted = Rc::new(RefCell::new(Some((VERSIONS[variant].1)(&stage))));
+    app.listen(&stage, "click", {
+        let picker = picker.clone();
+        move |_: MouseEvent| {
+            // only clicks on the version the visitor was assigned say anything about it
+            if picker.value() == variant.to_string() {
+                experiment::record(&EXPERIMENT, variant, "click");
+            }
+        }
+    });
+    app.listen(&picker.clone(), "change", {
+        let mounted = Rc::downgrade(&mounted);
+        move |_: Event| {
+            let Some(mounted) = mounted.upgrade() else {
+                return;
+            };
+            let Some((_, mount)) = picker
+                .value()
+                .parse()
+                .ok()
+                .and_then(|i: usize| VERSIONS.get(i))
+            else {
+                return;
+            };
+            // the old version's gone before the new one's built
+            if let Some(old) = mounted.borrow_mut().take() {
+                old.unmount();
+            }
+            *mounted.borrow_mut() = Some(mount(&stage));
+        }
+    });
+    app.on_cleanup(move || drop(mounted.take()));
+
+    app
+}
+
+fn version_1_with_single_button(parent: &Element) -> App {
+    let mut app = App::new();
+    let mut state = restored();
+
+    let p = create::<HtmlParagraphElement>("p");
+    p.set_text_content(Some("Click the button to update this"));
+
+    let increment = create::<HtmlButtonElement>("button");
+    increment.set_text_content(Some("+1"));
+
+    let decrement = create::<HtmlButtonElement>("button");
+    decrement.set_text_content(Some("-1"));
+
+    app.append(parent, &increment);
+    app.append(parent, &p);
+    app.append(parent, &decrement);
+
+    let on_increment = add_event_listener(&increment, "click", move |_: MouseEvent| {
+        log!("clicked +1");
+        state.count += 1;
+        p.set_text_content(Some(&state.count.to_string()));
+        persist(&state);
+    });
+
+    /* let on_decrement = add_event_listener(&decrement, "click", |_: MouseEvent| {
+        log!("clicked -1");
+        state.clicks -= 1;
+        p.set_text_content(Some(&clicks.to_string()));
+    }); */
+
+    // the handle owns the closure; removing it on unmount leaves nothing attached
+    app.on_cleanup(move || on_increment.remove());
+
+    app
+}
+
+fn version_2_with_stale_closure(parent: &Element) -> App {
+    let mut app = App::new();
+    let mut state = restored();
+
+    let p = create::<HtmlParagraphElement>("p");
+    p.set_text_content(Some("Click the button to update this"));
+
+    let increment = create::<HtmlButtonElement>("button");
+    increment.set_text_content(Some("+1"));
+
+    let decrement = create::<HtmlButtonElement>("button");
+    decrement.set_text_content(Some("-1"));
+
+    app.append(parent, &increment);
+    app.append(parent, &p);
+    app.append(parent, &decrement);
+
+    // each closure captures its own copy of `state.count`
+    let on_increment = add_event_listener(&increment, "click", {
+        let p = p.clone();
+        move |_: MouseEvent| {
+            log!("clicked +1");
+            state.count += 1;
+            p.set_text_content(Some(&state.count.to_string()));
+            // so the stale copy gets saved too, and wins whenever it's the last one clicked
+            persist_count(state.count);
+        }
+    });
+
+    let on_decrement = add_event_listener(&decrement, "click", move |_: MouseEvent| {
+        log!("clicked -1");
+        state.count -= 1;
+        p.set_text_content(Some(&state.count.to_string()));
+        persist_count(state.count);
+    });
+
+    // the handles own the closures; removing them on unmount leaves nothing attached
+    app.on_cleanup(move || {
+        on_increment.remove();
+        on_decrement.remove();
+    });
+
+    app
+}
+
+fn version_3_with_interior_mutability(parent: &Element) -> App {
+    let mut app = App::new();
+    // this kind of wrapping is called "interior mutability" in Rust
+    // in a sense, it moves borrow checking from the compile time to runtime
+    let state = Rc::new(RefCell::new(restored()));
+
+    let p = create::<HtmlParagraphElement>("p");
+    p.set_text_content(Some("Click the button to update this"));
+
+    let increment = create::<HtmlButtonElement>("button");
+    increment.set_text_content(Some("+1"));
+
+    let decrement = create::<HtmlButtonElement>("button");
+    decrement.set_text_content(Some("-1"));
+
+    app.append(parent, &increment);
+    app.append(parent, &p);
+    app.append(parent, &decrement);
+
+    let on_increment = add_event_listener(&increment, "click", {
+        let p = p.clone();
+        let state = state.clone();
+        move |_: MouseEvent| {
+            log!("clicked +1");
+            state.borrow_mut().count += 1;
+            p.set_text_content(Some(&state.borrow().count.to_string()));
+            persist(&state.borrow());
+        }
+    });
+
+    let on_decrement = add_event_listener(&decrement, "click", move |_: MouseEvent| {
+        log!("clicked -1");
+        state.borrow_mut().count -= 1;
+        p.set_text_content(Some(&state.borrow().count.to_string()));
+        persist(&state.borrow());
+    });
+
+    // the handles own the closures; removing them on unmount leaves nothing attached
+    app.on_cleanup(move || {
+        on_increment.remove();
+        on_decrement.remove();
+    });
+
+    app
+}
+
+/// Version 4's state: the count with its history, and what its subscriptions say
+/// about the window.
+struct Model {
+    history: Timeline<State>,
+    size: (f64, f64),
+    online: bool,
+    visible: bool,
+    /// How long the page has been visible for.
+    seconds: u32,
+    /// How the WebSocket is doing, if there is one rather than Server-Sent Events.
+    socket: Option<ws::Status>,
+}
+
+/// Version 4's messages: the counter's own, the ones that start (or finish) work the
+/// reducer can't do itself, and the ones its subscriptions send.
+#[derive(Clone, Debug, Deserialize)]
+enum Action {
+    Counter(Msg),
+    IncrementLater,
+    Roll,
+    Rolled(Result<i32, String>),
+    Resized(f64, f64),
+    Online(bool),
+    Visible(bool),
+    Tick,
+    /// The state another tab ended up with.
+    Synced(State),
+    /// A change someone else made, from the server.
+    Remote(Msg),
+    Socket(ws::Status),
+    Undo,
+    Redo,
+    /// Straight to a step of the history, from the slider.
+    Travel(usize),
+}
+
+/// Side effects, as data: the reducer returns them instead of doing them, and the loop
+/// around it runs them, sending whatever they end with back in as another action.
+enum Cmd {
+    After(Duration, Action),
+    Fetch {
+        url: &'static str,
+        then: fn(Result<String, String>) -> Action,
+    },
+    Log(String),
+    Save(State),
+    /// Tells the other tabs showing version 4.
+    Broadcast(State),
+    /// Tells the server.
+    Send(Msg),
+}
+
+impl Cmd {
+    async fn run(self, mut sender: Sender<Action>, tabs: Tabs<State>, socket: Option<Socket<Msg>>) {
+        let action = match self {
+            Cmd::After(delay, action) => {
+                sleep(delay).await;
+                action
+            }
+            Cmd::Fetch { url, then } => then(fetch::get_text(url).await),
+            Cmd::Log(message) => {
+                log!("{message}");
+                return;
+            }
+            Cmd::Save(state) => {
+                persist(&state);
+                return;
+            }
+            Cmd::Broadcast(state) => {
+                tabs.send(&state);
+                return;
+            }
+            // not kept for later: the count may be somewhere else entirely by then
+            Cmd::Send(msg) => {
+                if !socket.is_some_and(|socket| socket.send(&msg)) {
+                    log!("[version 4] not connected, so {msg:?} stays here");
+                }
+                return;
+            }
+        };
+        // a version unmounted in the meantime has closed the channel; that's fine
+        _ = sender.try_send(action);
+    }
+}
+
+/// Version 4's reducer: the only thing that changes the state, and all it can do
+/// besides is ask for commands.
+fn update(model: &mut Model, action: Action) -> Vec<Cmd> {
+    match action {
+        Action::Counter(msg) => {
+            let mut cmds = record(&mut model.history, |counter| counter.update(msg.clone()));
+            if !cmds.is_empty() {
+                cmds.push(Cmd::Send(msg));
+            }
+            cmds
+        }
+        // applied like a click, but not sent back
+        Action::Remote(msg) => record(&mut model.history, |counter| counter.update(msg)),
+        Action::Socket(status) => {
+            model.socket = Some(status);
+            Vec::new()
+        }
+        Action::IncrementLater => vec![Cmd::After(
+            Duration::from_secs(1),
+            Action::Counter(Msg::Increment),
+        )],
+        Action::Roll => vec![Cmd::Fetch {
+            url: RANDOM_ENDPOINT,
+            then: |text| {
+                Action::Rolled(text.and_then(|text| {
+                    text.trim()
+                        .parse()
+                        .map_err(|error| format!("unexpected response: {error}"))
+                }))
+            },
+        }],
+        Action::Rolled(Ok(count)) => record(&mut model.history, |counter| {
+            counter.update(Msg::Set(count))
+        }),
+        Action::Rolled(Err(error)) => vec![Cmd::Log(format!("couldn't roll: {error}"))],
+        Action::Resized(width, height) => {
+            model.size = (width, height);
+            Vec::new()
+        }
+        Action::Online(online) => {
+            model.online = online;
+            Vec::new()
+        }
+        Action::Visible(visible) => {
+            model.visible = visible;
+            Vec::new()
+        }
+        Action::Tick => {
+            if model.visible {
+                model.seconds += 1;
+            }
+            Vec::new()
+        }
+        // already saved by the tab it came from, and not sent back there
+        Action::Synced(counter) => {
+            record(&mut model.history, |present| *present = counter);
+            Vec::new()
+        }
+        Action::Undo => {
+            model.history.undo();
+            changed(model.history.present())
+        }
+        Action::Redo => {
+            model.history.redo();
+            changed(model.history.present())
+        }
+        Action::Travel(position) => {
+            model.history.travel_to(position);
+            changed(model.history.present())
+        }
+    }
+}
+
+/// Changes the counter with `f` as one step of its history, if it changes at all.
+fn record(history: &mut Timeline<State>, f: impl FnOnce(&mut State)) -> Vec<Cmd> {
+    let mut changed_it = false;
+    history.update(|counter| {
+        let before = counter.clone();
+        f(counter);
+        changed_it = *counter != before;
+        changed_it
+    });
+    if changed_it {
+        changed(history.present())
+    } else {
+        Vec::new()
+    }
+}
+
+fn changed(counter: &State) -> Vec<Cmd> {
+    vec![Cmd::Save(counter.clone()), Cmd::Broadcast(counter.clone())]
+}
+
+fn version_4_with_async_channel_and_reducer_pattern(parent: &Element) -> App {
+    let mut app = App::new();
+
+    let p = create::<HtmlParagraphElement>("p");
+    p.set_text_content(Some("Hello, Ryan!"));
+    app.append(parent, &p);
+
+    let (sender, receiver) = futures::channel::mpsc::channel(4);
+
+    // the spinner only asks for a new value; the reducer decides and renders it back
+    let spinner = NumberSpinner::mount(
+        &mut app,
+        parent,
+        SpinnerOptions {
+            label: "Count".to_string(),
+            ..Default::default()
+        },
+        {
+            let sender = sender.clone();
+            move |value| {
+                _ = sender.clone().try_send(Action::Counter(Msg::Set(value)));
+            }
+        },
+    );
+
+    let later = create::<HtmlButtonElement>("button");
+    later.set_text_content(Some("+1 in a second"));
+    let roll = create::<HtmlButtonElement>("button");
+    roll.set_text_content(Some("Random"));
+    app.append(parent, &later);
+    app.append(parent, &roll);
+    app.listen(&later, "click", {
+        let sender = sender.clone();
+        move |_: MouseEvent| _ = sender.clone().try_send(Action::IncrementLater)
+    });
+    app.listen(&roll, "click", {
+        let sender = sender.clone();
+        move |_: MouseEvent| _ = sender.clone().try_send(Action::Roll)
+    });
+
+    // undo, redo, and a slider over the whole history, Redux DevTools style
+    let undo = create::<HtmlButtonElement>("button");
+    undo.set_text_content(Some("Undo"));
+    let redo = create::<HtmlButtonElement>("button");
+    redo.set_text_content(Some("Redo"));
+    let slider = create::<HtmlInputElement>("input");
+    slider.set_type("range");
+    slider.set_min("0");
+    // nothing to undo yet; the loop keeps these up to date from here on
+    slider.set_max("0");
+    undo.set_disabled(true);
+    redo.set_disabled(true);
+    _ = slider.set_attribute("aria-label", "History");
+    app.append(parent, &undo);
+    app.append(parent, &redo);
+    app.append(parent, &slider);
+    app.listen(&undo, "click", {
+        let sender = sender.clone();
+        move |_: MouseEvent| _ = sender.clone().try_send(Action::Undo)
+    });
+    app.listen(&redo, "click", {
+        let sender = sender.clone();
+        move |_: MouseEvent| _ = sender.clone().try_send(Action::Redo)
+    });
+    app.listen(&slider, "input", {
+        let sender = sender.clone();
+        let slider = slider.clone();
+        move |_: Event| {
+            if let Ok(position) = slider.value().parse() {
+                _ = sender.clone().try_send(Action::Travel(position));
+            }
+        }
+    });
+
+    let status = create::<HtmlParagraphElement>("p");
+    app.append(parent, &status);
+
+    let keys = create::<HtmlParagraphElement>("p");
+    keys.set_text_content(Some(
+        "Keys: ↑ or + adds a step, ↓ or - takes one, 0 resets.",
+    ));
+    app.append(parent, &keys);
+    let keymap: Keymap<Action> = [
+        ("ArrowUp", Msg::Increment),
+        ("+", Msg::Increment),
+        ("ArrowDown", Msg::Decrement),
+        ("-", Msg::Decrement),
+        ("0", Msg::Reset),
+    ]
+    .into_iter()
+    .map(|(key, msg)| (key.to_string(), Action::Counter(msg)))
+    .collect();
+    let keys = keyboard::on_keys(keymap, {
+        let sender = sender.clone();
+        move |action| _ = sender.clone().try_send(action)
+    });
+    // the window outlives this version; without this, the keys would go on sending to
+    // a channel nothing reads, and the next version's counter would get none of them
+    app.on_cleanup(move || keys.remove());
+
+    // the outside world, sent down the same channel as the clicks
+    subscribe(
+        &mut app,
+        &sender,
+        [
+            Sub::resize(Action::Resized),
+            Sub::online(Action::Online),
+            Sub::visibility(Action::Visible),
+            Sub::every(Duration::from_secs(1), || Action::Tick),
+        ],
+    );
+
+    // other tabs send the whole state rather than their messages, so a tab that missed
+    // one (or was showing another version) still ends up with the same count
+    let tabs = Tabs::connect(&mut app, "versions-4", {
+        let sender = sender.clone();
+        move |state| _ = sender.clone().try_send(Action::Synced(state))
+    });
+
+    // the tasks that keep up with the server, restarted when they fail
+    let supervisor = Supervisor::new().with_console_reporter();
+    app.on_cleanup({
+        let supervisor = supervisor.clone();
+        move || supervisor.stop_all()
+    });
+
+    // the server's changes come in over a WebSocket, or down a stream of events
+    let push = flags::enabled("server-push");
+    let socket = (!push).then(|| {
+        Socket::connect(
+            &mut app,
+            &supervisor,
+            "server-socket",
+            ws::url(SOCKET_PATH),
+            SOCKET_BACKOFF,
+            {
+                let sender = sender.clone();
+                move |msg| _ = sender.clone().try_send(Action::Remote(msg))
+            },
+            {
+                let sender = sender.clone();
+                move |status| _ = sender.clone().try_send(Action::Socket(status))
+            },
+        )
+    });
+    if push {
+        let sender = sender.clone();
+        supervisor.spawn(
+            "server-events",
+            RestartPolicy::OnError { max_restarts: None },
+            SOCKET_BACKOFF,
+            move || {
+                let sender = sender.clone();
+                async move {
+                    let mut events = sse::subscribe::<Msg>(EVENTS_PATH)?;
+                    while let Some(msg) = events.next().await {
+                        _ = sender.clone().try_send(Action::Remote(msg));
+                    }
+                    Err("the server stopped sending events".to_string())
+                }
+            },
+        );
+    }
+
+    // subscriptions only say what changes, so they start from how things are
+    let model = Rc::new(RefCell::new(Model {
+        history: Timeline::new(restored(), HISTORY_LIMIT),
+        size: window_size(),
+        online: network::status().online,
+        visible: !document().hidden(),
+        seconds: 0,
+        socket: socket.is_some().then_some(ws::Status::Connecting),
+    }));
+
+    let mut receiver = if flags::enabled("devtools") {
+        let model = model.clone();
+        devtools::tap(
+            &mut app,
+            "version 4",
+            receiver,
+            sender.clone(),
+            &supervisor,
+            move || model.borrow().history.present().clone(),
+        )
+    } else {
+        receiver.boxed_local()
+    };
+
+    app.spawn(async move {
+        while let Some(action) = receiver.next().await {
+            let model = &mut *model.borrow_mut();
+            for cmd in update(model, action) {
+                spawn_local(cmd.run(sender.clone(), tabs.clone(), socket.clone()));
+            }
+            let counter = model.history.present();
+            p.set_text_content(Some(&format!("count is {}", counter.count)));
+            spinner.set_value(counter.count);
+            undo.set_disabled(!model.history.can_undo());
+            redo.set_disabled(!model.history.can_redo());
+            slider.set_max(&(model.history.steps() - 1).to_string());
+            slider.set_value(&model.history.position().to_string());
+            let (width, height) = model.size;
+            status.set_text_content(Some(&format!(
+                "{width}×{height}, {}, on screen for {}s, {}",
+                if model.online { "online" } else { "offline" },
+                model.seconds,
+                match model.socket {
+                    None => "hearing the server's events".to_string(),
+                    Some(ws::Status::Connecting) => "connecting to the server".to_string(),
+                    Some(ws::Status::Open) => "shared with the server".to_string(),
+                    Some(ws::Status::Retrying(delay)) => {
+                        format!("no server, trying again in {}s", delay.as_secs())
+                    }
+                }
+            )));
+        }
+    });
+
+    app
+}
+
+fn version_5_with_signals(parent: &Element) -> App {
+    let mut app = App::new();
+    let (count, set_count) = create_signal(restored().count);
+
+    let p = create::<HtmlParagraphElement>("p");
+
+    let increment = create::<HtmlButtonElement>("button");
+    increment.set_text_content(Some("+1"));
+
+    let decrement = create::<HtmlButtonElement>("button");
+    decrement.set_text_content(Some("-1"));
+
+    let reset = create::<HtmlButtonElement>("button");
+    reset.set_text_content(Some("Reset"));
+
+    app.append(parent, &increment);
+    app.append(parent, &p);
+    app.append(parent, &decrement);
+    app.append(parent, &reset);
+
+    // the only code that touches the `<p>`: it reads `count`, so it reruns with it
+    let effect = create_effect({
+        let count = count.clone();
+        move || p.set_text_content(Some(&count.get().to_string()))
+    });
+    // and saving it is just another effect on the same signal
+    let saving = create_effect(move || persist_count(count.get()));
+
+    // the buttons just change the count; neither knows the `<p>` is there
+    app.listen(&increment, "click", {
+        let set_count = set_count.clone();
+        move |_: MouseEvent| {
+            log!("clicked +1");
+            set_count.update(|count| *count = count.saturating_add(1));
+        }
+    });
+    app.listen(&decrement, "click", {
+        let set_count = set_count.clone();
+        move |_: MouseEvent| {
+            log!("clicked -1");
+            set_count.update(|count| *count = count.saturating_sub(1));
+        }
+    });
+    app.listen(&reset, "click", move |_: MouseEvent| set_count.set(0));
+
+    app.on_cleanup(move || drop((effect, saving)));
+
+    app
+}
+
+/// The whole counter as data, rebuilt from scratch for every state.
+fn render(state: &State) -> VNode {
+    let button = |msg: &str, label: &str| {
+        VNode::element("button")
+            .attribute("data-msg", msg)
+            .child(VNode::text(label))
+    };
+    let mut count = VNode::element("p").child(VNode::text(format!("count is {}", state.count)));
+    if state.count < 0 {
+        count = count.attribute("style", "color: #c00");
+    }
+    VNode::element("div")
+        .child(button("increment", "+1"))
+        .child(count)
+        .child(button("decrement", "-1"))
+        .child(button("reset", "Reset"))
+}
+
+fn version_6_with_virtual_dom(parent: &Element) -> App {
+    let mut app = App::new();
+    let mut state = restored();
+
+    let container = create::<HtmlDivElement>("div");
+    app.append(parent, &container);
+    let mut vdom = VDom::new(container.clone().into());
+    vdom.render(render(&state));
+    let root = Element::from(container.clone());
+
+    // nodes in a virtual DOM carry no listeners: one on the container reads `data-msg`
+    app.listen(&container, "click", move |ev: MouseEvent| {
+        let Some(button) = ev
+            .target()
+            .and_then(|target| target.dyn_into::<Element>().ok())
+            .and_then(|el| el.closest("[data-msg]").ok().flatten())
+        else {
+            return;
+        };
+        let msg = match button.get_attribute("data-msg").as_deref() {
+            Some("increment") => Msg::Increment,
+            Some("decrement") => Msg::Decrement,
+            Some("reset") => Msg::Reset,
+            _ => return,
+        };
+        log!("clicked {msg:?}");
+        state.update(msg);
+        persist(&state);
+        // a whole new tree every time; the diff keeps the DOM work to what changed, and
+        // without it (a fresh `VDom` has nothing to diff against) it's all made again
+        if !flags::enabled("new-renderer") {
+            vdom = VDom::new(root.clone());
+        }
+        let changes = vdom.render(render(&state));
+        log!("{changes} DOM change(s)");
+    });
+
+    app
+}
+
+/// What's typed into version 7's count, as a count, or why it isn't one.
+fn parse_count(text: &str) -> Result<i32, String> {
+    match text.trim().parse() {
+        Ok(count) => Ok(count),
+        Err(error) => Err(match error.kind() {
+            IntErrorKind::Empty => "Enter a count.".to_string(),
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+                format!("The count has to be between {} and {}.", i32::MIN, i32::MAX)
+            }
+            _ => format!("{:?} isn't a whole number.", text.trim()),
+        }),
+    }
+}
+
+fn version_7_with_form_inputs(parent: &Element) -> App {
+    let mut app = App::new();
+    let state = Rc::new(RefCell::new(restored()));
+
+    let count = create::<HtmlInputElement>("input");
+    count.set_type("number");
+    _ = count.set_attribute("aria-label", "Count");
+    let step = create::<HtmlSelectElement>("select");
+    _ = step.set_attribute("aria-label", "Step");
+    let mut steps = STEPS.to_vec();
+    if !steps.contains(&state.borrow().step) {
+        steps.push(state.borrow().step);
+        steps.sort();
+    }
+    for value in steps {
+        let option = create::<HtmlOptionElement>("option");
+        option.set_value(&value.to_string());
+        option.set_text_content(Some(&format!("by {value}")));
+        step.append_child(&option).unwrap();
+    }
+    let error = create::<HtmlParagraphElement>("p");
+    _ = error.set_attribute("role", "alert");
+
+    let increment = create::<HtmlButtonElement>("button");
+    increment.set_text_content(Some("+"));
+    let decrement = create::<HtmlButtonElement>("button");
+    decrement.set_text_content(Some("-"));
+    let reset = create::<HtmlButtonElement>("button");
+    reset.set_text_content(Some("Reset"));
+
+    app.append(parent, &decrement);
+    app.append(parent, &count);
+    app.append(parent, &increment);
+    app.append(parent, &step);
+    app.append(parent, &reset);
+    app.append(parent, &error);
+
+    // state to inputs: for changes that didn't come from the inputs themselves, since
+    // writing back to an input while it's being typed in moves the caret to the end
+    let write_back = {
+        let state = state.clone();
+        let count = count.clone();
+        let step = step.clone();
+        let error = error.clone();
+        move || {
+            let state = state.borrow();
+            count.set_value(&state.count.to_string());
+            step.set_value(&state.step.to_string());
+            _ = count.remove_attribute("aria-invalid");
+            error.set_text_content(None);
+        }
+    };
+    write_back();
+
+    // inputs to state, as they change
+    app.listen(&count, "input", {
+        let state = state.clone();
+        let count = count.clone();
+        let write_back = write_back.clone();
+        move |ev: Event| match parse_count(&value_of(&ev)) {
+            Ok(typed) => {
+                let mut state = state.borrow_mut();
+                state.update(Msg::Set(typed));
+                persist(&state);
+                let clamped = state.count != typed;
+                drop(state);
+                if clamped {
+                    write_back();
+                } else {
+                    _ = count.remove_attribute("aria-invalid");
+                    error.set_text_content(None);
+                }
+            }
+            // the state keeps the last good count until there's another
+            Err(message) => {
+                _ = count.set_attribute("aria-invalid", "true");
+                error.set_text_content(Some(&message));
+            }
+        }
+    });
+    // leaving the input with something that isn't a count puts the count back
+    app.listen(&count, "change", {
+        let write_back = write_back.clone();
+        move |_: Event| write_back()
+    });
+    app.listen(&step, "change", {
+        let state = state.clone();
+        move |ev: Event| {
+            if let Ok(step) = value_of(&ev).parse() {
+                let mut state = state.borrow_mut();
+                state.update(Msg::SetStep(step));
+                persist(&state);
+            }
+        }
+    });
+
+    for (button, msg) in [
+        (&increment, Msg::Increment),
+        (&decrement, Msg::Decrement),
+        (&reset, Msg::Reset),
+    ] {
+        let state = state.clone();
+        let write_back = write_back.clone();
+        app.listen(button, "click", move |_: MouseEvent| {
+            log!("clicked {msg:?}");
+            state.borrow_mut().update(msg.clone());
+            persist(&state.borrow());
+            write_back();
+        });
+    }
+
+    app
+}
+
+/// Version 3 again, with the same state and the same updates, but the markup built as
+/// one expression and the listeners left to the `App`.
+fn version_8_with_an_element_builder(parent: &Element) -> App {
+    let mut app = App::new();
+    let state = Rc::new(RefCell::new(restored()));
+
+    let count = math("mn")
+        .text(&state.borrow().count.to_string())
+        .build(&mut app);
+    let p = el!(p, "aria-live" = "polite").child(
+        math("math")
+            .child(math("mi").text("count"))
+            .child(math("mo").text("="))
+            .child(count.clone()),
+    );
+    let add = Rc::new(move |delta: i32| {
+        let added = state.borrow().count.saturating_add(delta);
+        state.borrow_mut().count = added;
+        count.set_text_content(Some(&state.borrow().count.to_string()));
+        persist(&state.borrow());
+    });
+
+    // the icons, drawn once and used by both buttons
+    let sprite = svg("svg")
+        .attr("hidden", "")
+        .child(icon_symbol("v8-plus", "M2 6h8M6 2v8"))
+        .child(icon_symbol("v8-minus", "M2 6h8"));
+    el!(div)
+        .child(sprite)
+        .child(icon_button("v8-plus", "+1").on("click", {
+            let add = add.clone();
+            move |_: MouseEvent| {
+                log!("clicked +1");
+                add(1);
+            }
+        }))
+        .child(p)
+        .child(
+            icon_button("v8-minus", "-1").on("click", move |_: MouseEvent| {
+                log!("clicked -1");
+                add(-1);
+            }),
+        )
+        .mount(&mut app, parent);
+
+    app
+}
+
+#[derive(Deserialize)]
+struct ServerCount {
+    count: i32,
+}
+
+#[derive(Serialize)]
+struct Increment {
+    by: i32,
+}
+
+fn version_9_with_a_server(parent: &Element) -> App {
+    let mut app = App::new();
+    // only what the server last said, or what's been sent it since
+    let count = Rc::new(RefCell::new(None::<i32>));
+
+    let increment = create::<HtmlButtonElement>("button");
+    increment.set_text_content(Some("+1"));
+    let p = create::<HtmlParagraphElement>("p");
+    _ = p.set_attribute("aria-live", "polite");
+    let decrement = create::<HtmlButtonElement>("button");
+    decrement.set_text_content(Some("-1"));
+    let alert = create::<HtmlParagraphElement>("p");
+    _ = alert.set_attribute("role", "alert");
+    let retry = create::<HtmlButtonElement>("button");
+    retry.set_text_content(Some("Try again"));
+    retry.set_hidden(true);
+    app.append(parent, &increment);
+    app.append(parent, &p);
+    app.append(parent, &decrement);
+    app.append(parent, &alert);
+    app.append(parent, &retry);
+
+    let render = {
+        let count = count.clone();
+        let p = p.clone();
+        let buttons = [increment.clone(), decrement.clone()];
+        move || {
+            let count = *count.borrow();
+            p.set_text_content(Some(
+                &count.map_or("Loading…".to_string(), |n| n.to_string()),
+            ));
+            for button in &buttons {
+                button.set_disabled(count.is_none());
+            }
+        }
+    };
+    let load = Rc::new({
+        let count = count.clone();
+        let alert = alert.clone();
+        let retry = retry.clone();
+        let render = render.clone();
+        move || {
+            *count.borrow_mut() = None;
+            alert.set_text_content(None);
+            retry.set_hidden(true);
+            render();
+            let count = count.clone();
+            let alert = alert.clone();
+            let retry = retry.clone();
+            let render = render.clone();
+            spawn_local(async move {
+                match api::get_json::<ServerCount>(COUNT_ENDPOINT).await {
+                    Ok(loaded) => {
+                        *count.borrow_mut() = Some(loaded.count);
+                        render();
+                    }
+                    Err(error) => {
+                        show_error(&alert, &format!("Couldn't load the count: {error}"));
+                        retry.set_hidden(false);
+                    }
+                }
+            });
+        }
+    });
+    load();
+    app.listen(&retry, "click", {
+        let load = load.clone();
+        move |_: MouseEvent| load()
+    });
+
+    for (button, by) in [(&increment, 1), (&decrement, -1)] {
+        let count = count.clone();
+        let alert = alert.clone();
+        let render = render.clone();
+        app.listen(button, "click", move |_: MouseEvent| {
+            let Some(before) = *count.borrow() else {
+                return;
+            };
+            // shown straight away, and taken back if the server doesn't take it; or
+            // without optimistic updates, only once it has
+            let optimistic = flags::enabled("optimistic-updates");
+            if optimistic {
+                *count.borrow_mut() = Some(before.saturating_add(by));
+            }
+            alert.set_text_content(None);
+            render();
+            let count = count.clone();
+            let alert = alert.clone();
+            let render = render.clone();
+            spawn_local(async move {
+                match api::post_json::<_, ServerCount>(COUNT_ENDPOINT, &Increment { by }).await {
+                    Ok(saved) => *count.borrow_mut() = Some(saved.count),
+                    Err(error) => {
+                        if let Some(n) = count.borrow_mut().as_mut().filter(|_| optimistic) {
+                            *n = n.saturating_sub(by);
+                        }
+                        show_error(&alert, &format!("Couldn't save that: {error}"));
+                    }
+                }
+                render();
+            });
+        });
+    }
+
+    app
+}
+
+/// Version 10's state: the count, and how far the page is behind it.
+#[derive(Default)]
+struct Batched {
+    count: i32,
+    /// Whether the count's changed since it was last written to the page.
+    dirty: bool,
+    /// How many updates there have been since then.
+    updates: u32,
+}
+
+type FrameCallback = Rc<RefCell<Option<Closure<dyn FnMut(f64)>>>>;
+
+fn version_10_with_animation_frames(parent: &Element) -> App {
+    let mut app = App::new();
+    let state = Rc::new(RefCell::new(Batched {
+        count: restored().count,
+        ..Batched::default()
+    }));
+
+    let p = el!(p, "aria-live" = "polite")
+        .text(&state.borrow().count.to_string())
+        .build(&mut app);
+    let stats = el!(p).build(&mut app);
+    // an update only changes the state; the page is written from the frame callback
+    let add = Rc::new({
+        let state = state.clone();
+        move |delta: i32| {
+            let mut state = state.borrow_mut();
+            state.count = state.count.saturating_add(delta);
+            state.dirty = true;
+            state.updates += 1;
+        }
+    });
+    el!(div)
+        .child(el!(button).text("+1").on("click", {
+            let add = add.clone();
+            move |_: MouseEvent| {
+                log!("clicked +1");
+                add(1);
+            }
+        }))
+        .child(p.clone())
+        .child(el!(button).text("-1").on("click", {
+            let add = add.clone();
+            move |_: MouseEvent| {
+                log!("clicked -1");
+                add(-1);
+            }
+        }))
+        .child(
+            el!(button)
+                .text("+1, ten thousand times")
+                .on("click", move |_: MouseEvent| {
+                    for _ in 0..BURST {
+                        add(1);
+                    }
+                }),
+        )
+        .child(stats.clone())
+        .mount(&mut app, parent);
+
+    // Each frame asks for the next, so the closure has to reach itself: it's kept in
+    // `callback`, which it holds on to. That's a cycle of `Rc`s, which would keep the
+    // closure (and the frames) going after unmount; the cleanup below breaks it.
+    let callback = FrameCallback::default();
+    let frame = Rc::new(Cell::new(0));
+    *callback.borrow_mut() = Some(Closure::new({
+        let callback = callback.clone();
+        let frame = frame.clone();
+        move |_: f64| {
+            let mut state = state.borrow_mut();
+            if state.dirty {
+                p.set_text_content(Some(&state.count.to_string()));
+                stats.set_text_content(Some(&format!(
+                    "{} updates since the last frame, written to the page once",
+                    state.updates
+                )));
+                persist_count(state.count);
+                state.dirty = false;
+                state.updates = 0;
+            }
+            if let Some(callback) = &*callback.borrow() {
+                frame.set(request_frame(callback));
+            }
+        }
+    }));
+    frame.set(request_frame(callback.borrow().as_ref().unwrap()));
+    app.on_cleanup(move || {
+        _ = window().cancel_animation_frame(frame.get());
+        callback.take();
+    });
+
+    app
+}
+
+fn request_frame(callback: &Closure<dyn FnMut(f64)>) -> i32 {
+    window()
+        .request_animation_frame(callback.as_ref().unchecked_ref())
+        .unwrap()
+}
+
+fn show_error(alert: &HtmlParagraphElement, message: &str) {
+    log!("{message}");
+    alert.set_text_content(Some(message));
+}
+
+fn icon_symbol(id: &str, path: &str) -> El {
+    svg("symbol")
+        .attr("id", id)
+        .attr("viewBox", "0 0 12 12")
+        .child(
+            svg("path")
+                .attr("d", path)
+                .attr("stroke", "currentColor")
+                .attr("stroke-width", "2"),
+        )
+}
+
+fn icon_button(id: &str, label: &str) -> El {
+    el!(button, "aria-label" = label).child(
+        svg("svg")
+            .attr("width", "1em")
+            .attr("height", "1em")
+            .attr("aria-hidden", "true")
+            .child(svg("use").attr_ns(XLINK, "href", &format!("#{id}"))),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_typed_counts() {
+        assert_eq!(parse_count(" -12 "), Ok(-12));
+        assert_eq!(parse_count(""), Err("Enter a count.".to_string()));
+        assert_eq!(
+            parse_count("1.5"),
+            Err("\"1.5\" isn't a whole number.".to_string())
+        );
+        assert!(parse_count("99999999999").is_err());
+    }
+}
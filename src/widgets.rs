@@ -0,0 +1,170 @@
+//! Widgets: Rust apps mounted wherever a page asks for them.
+//!
+//! A page marks elements with `data-widget="counter"` (or any other registered name)
+//! and each one gets its own app, whether it's there when the page loads or is added
+//! later by other scripts: a `MutationObserver` watches the whole document. When an
+//! element is removed, its app unmounts with it, so every widget has its own
+//! lifecycle and a page can sprinkle as many as it likes.
+//!
+//! [`mount_to`] is such a page, as an example: a couple of widgets in its markup, and
+//! buttons that add and remove plain `data-widget` elements for the observer to find.
+
+use std::{cell::RefCell, collections::HashMap};
+
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{Element, MouseEvent, MutationObserver, MutationObserverInit, MutationRecord};
+
+use crate::{
+    app::App,
+    dom::{create_element, document},
+    examples,
+    html::el,
+};
+
+pub type Factory = fn(&Element) -> App;
+
+#[derive(Default)]
+struct Widgets {
+    factories: HashMap<&'static str, Factory>,
+    /// The elements with widgets in them, and their apps.
+    mounted: Vec<(Element, App)>,
+}
+
+thread_local! {
+    static WIDGETS: RefCell<Widgets> = RefCell::new(Widgets::default());
+}
+
+/// Makes `factory` what mounts every `data-widget="{name}"` element, including any on
+/// the page already.
+pub fn register_widget(name: &'static str, factory: Factory) {
+    WIDGETS.with(|widgets| widgets.borrow_mut().factories.insert(name, factory));
+    mount_within(&document().document_element().unwrap());
+}
+
+/// Mounts widgets added to the page from now on, and unmounts the ones taken off it,
+/// until `app` unmounts.
+pub fn install(app: &mut App) {
+    let on_change = Closure::<dyn FnMut(js_sys::Array)>::new(|records: js_sys::Array| {
+        let mut removed = false;
+        for record in records.iter() {
+            let record = record.unchecked_into::<MutationRecord>();
+            removed |= record.removed_nodes().length() > 0;
+            let added = record.added_nodes();
+            for i in 0..added.length() {
+                if let Some(element) = added.item(i).and_then(|node| node.dyn_into().ok()) {
+                    mount_within(&element);
+                }
+            }
+        }
+        if removed {
+            unmount_removed();
+        }
+    });
+    let Ok(observer) = MutationObserver::new(on_change.as_ref().unchecked_ref()) else {
+        return;
+    };
+    let mut options = MutationObserverInit::new();
+    options.child_list(true).subtree(true);
+    _ = observer.observe_with_options(&document().document_element().unwrap(), &options);
+    app.on_cleanup(move || {
+        observer.disconnect();
+        drop(on_change);
+        // the widgets go with the observer that's been keeping track of them
+        let mounted = WIDGETS.with(|widgets| std::mem::take(&mut widgets.borrow_mut().mounted));
+        drop(mounted);
+    });
+}
+
+/// Mounts the widgets in `root` and its descendants that haven't been yet.
+fn mount_within(root: &Element) {
+    let mut found = Vec::new();
+    if root.has_attribute("data-widget") {
+        found.push(root.clone());
+    }
+    if let Ok(descendants) = root.query_selector_all("[data-widget]") {
+        for i in 0..descendants.length() {
+            if let Some(element) = descendants.item(i).and_then(|node| node.dyn_into().ok()) {
+                found.push(element);
+            }
+        }
+    }
+    for element in found {
+        let name = element.get_attribute("data-widget").unwrap_or_default();
+        let factory = WIDGETS.with(|widgets| {
+            let widgets = widgets.borrow();
+            let mounted = widgets.mounted.iter().any(|(el, _)| *el == element);
+            (!mounted).then(|| widgets.factories.get(name.as_str()).copied())?
+        });
+        // not a widget we know (yet), or one that's already running
+        let Some(factory) = factory else {
+            continue;
+        };
+        // mounting may add elements of its own, which the observer will see, so the
+        // registry mustn't be borrowed while it runs
+        let app = factory(&element);
+        WIDGETS.with(|widgets| widgets.borrow_mut().mounted.push((element, app)));
+    }
+}
+
+/// Unmounts the widgets whose elements are no longer in the document.
+fn unmount_removed() {
+    let removed = WIDGETS.with(|widgets| {
+        let mut widgets = widgets.borrow_mut();
+        let (kept, removed) = std::mem::take(&mut widgets.mounted)
+            .into_iter()
+            .partition(|(element, _)| element.is_connected());
+        widgets.mounted = kept;
+        removed
+    });
+    // dropped outside the borrow, since unmounting touches the DOM too
+    drop::<Vec<(Element, App)>>(removed);
+}
+
+pub fn mount_to(parent: &Element) -> App {
+    let mut app = App::new();
+    let style = "border: 1px solid #ccc; margin: 1em 0; padding: 1em";
+    el!(p)
+        .text("Any element with a data-widget attribute gets that example mounted in it.")
+        .mount(&mut app, parent);
+    el!(
+        div,
+        "data-widget" = "counter",
+        "data-start" = "10",
+        "data-step" = "5",
+        style = style,
+    )
+    .mount(&mut app, parent);
+    el!(aside, "data-widget" = "clock", style = style).mount(&mut app, parent);
+    let more = el!(div).build(&mut app);
+    el!(p)
+        .child(el!(button).text("Add a counter").on("click", {
+            let more = more.clone();
+            move |_: MouseEvent| {
+                // a bare element, as any other script on the page might add
+                let div = create_element("div");
+                _ = div.set_attribute("data-widget", "counter");
+                _ = div.set_attribute("style", style);
+                _ = more.append_child(&div);
+            }
+        }))
+        .child(el!(button).text("Remove the last one").on("click", {
+            let more = more.clone();
+            move |_: MouseEvent| {
+                if let Some(last) = more.last_element_child() {
+                    last.remove();
+                }
+            }
+        }))
+        .mount(&mut app, parent);
+    app.append(parent, &more);
+
+    // installed first, so the widgets registered next are the observer's to unmount
+    install(&mut app);
+    for example in examples::EXAMPLES {
+        // not inside itself
+        if example.name != "widgets" {
+            register_widget(example.name, example.mount);
+        }
+    }
+    app
+}
@@ -0,0 +1,240 @@
+//! Running jobs on a Web Worker that loads this same wasm module.
+//!
+//! There's no separate worker binary: [`Worker::spawn`] starts a module worker that
+//! imports the JS and wasm Trunk built for the page, whose `main` notices it isn't on
+//! a window and calls [`serve`] instead of mounting anything. Jobs are plain functions
+//! listed in [`JOBS`], called by name with a `JsValue` argument. Buffers can be
+//! transferred both ways, so big pixel arrays move rather than get copied.
+//!
+//! Jobs that don't move buffers can be typed instead: [`Worker::request`] sends a
+//! serializable request as JSON and parses the reply, and [`json`] is the worker's
+//! side of that.
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use futures::channel::oneshot;
+use js_sys::Array;
+use serde::{de::DeserializeOwned, Serialize};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{
+    Blob, BlobPropertyBag, DedicatedWorkerGlobalScope, ErrorEvent, MessageEvent, Url,
+    WorkerOptions, WorkerType,
+};
+
+use crate::{dom::document, image_filters, primes, table};
+
+/// What a job sends back: a value, and the buffers in it to move rather than copy.
+pub struct Reply {
+    pub value: JsValue,
+    pub transfer: Array,
+}
+
+pub type Job = fn(JsValue) -> Result<Reply, String>;
+
+/// Everything a worker can be asked to do.
+const JOBS: &[(&str, Job)] = &[
+    ("filter", image_filters::filter_job),
+    ("nth-prime", primes::nth_prime_job),
+    ("table-rows", table::rows_job),
+];
+
+/// Whether this is running in a worker rather than on the page.
+pub fn is_worker() -> bool {
+    js_sys::global().dyn_into::<web_sys::Window>().is_err()
+}
+
+/// Answers jobs posted to this worker, from then on.
+///
+/// Messages are `[id, job, argument]`; replies are `[id, ok, value or error]`.
+pub fn serve() {
+    let scope = js_sys::global().unchecked_into::<DedicatedWorkerGlobalScope>();
+    let onmessage = Closure::<dyn Fn(MessageEvent)>::new({
+        let scope = scope.clone();
+        move |ev: MessageEvent| {
+            let message = Array::from(&ev.data());
+            let (id, name, argument) = (message.get(0), message.get(1), message.get(2));
+            let result = match JOBS
+                .iter()
+                .find(|(job, _)| name.as_string().as_deref() == Some(job))
+            {
+                Some((_, job)) => job(argument),
+                None => Err(format!("no such job: {name:?}")),
+            };
+            let reply = match result {
+                Ok(Reply { value, transfer }) => scope
+                    .post_message_with_transfer(&Array::of3(&id, &true.into(), &value), &transfer),
+                Err(e) => scope.post_message(&Array::of3(&id, &false.into(), &e.into())),
+            };
+            reply.expect("the reply to be cloneable");
+        }
+    });
+    scope.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+    // the worker's job now, for as long as it runs
+    onmessage.forget();
+}
+
+type Pending = Rc<RefCell<HashMap<u32, oneshot::Sender<Result<JsValue, String>>>>>;
+
+/// A worker running this app's wasm, terminated on drop.
+pub struct Worker {
+    worker: web_sys::Worker,
+    pending: Pending,
+    next_id: RefCell<u32>,
+    script: String,
+    _onmessage: Closure<dyn Fn(MessageEvent)>,
+    _onerror: Closure<dyn Fn(ErrorEvent)>,
+}
+
+impl Worker {
+    /// Starts a worker, or says why it couldn't.
+    pub fn spawn() -> Result<Worker, String> {
+        let script = Url::create_object_url_with_blob(&bootstrap()?).map_err(describe)?;
+        let mut options = WorkerOptions::new();
+        options.type_(WorkerType::Module);
+        let worker = web_sys::Worker::new_with_options(&script, &options).map_err(describe)?;
+
+        let pending = Pending::default();
+        let onmessage = Closure::<dyn Fn(MessageEvent)>::new({
+            let pending = pending.clone();
+            move |ev: MessageEvent| {
+                let message = Array::from(&ev.data());
+                let Some(id) = message.get(0).as_f64() else {
+                    return;
+                };
+                let value = message.get(2);
+                let result = if message.get(1).is_truthy() {
+                    Ok(value)
+                } else {
+                    Err(value.as_string().unwrap_or_default())
+                };
+                if let Some(reply) = pending.borrow_mut().remove(&(id as u32)) {
+                    _ = reply.send(result);
+                }
+            }
+        });
+        // a failure to load, or a panic in a job, takes the whole worker down
+        let onerror = Closure::<dyn Fn(ErrorEvent)>::new({
+            let pending = pending.clone();
+            move |ev: ErrorEvent| {
+                for (_, reply) in pending.borrow_mut().drain() {
+                    _ = reply.send(Err(ev.message()));
+                }
+            }
+        });
+        worker.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+        worker.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+
+        Ok(Worker {
+            worker,
+            pending,
+            next_id: RefCell::new(0),
+            script,
+            _onmessage: onmessage,
+            _onerror: onerror,
+        })
+    }
+
+    /// Runs `job` on the worker, handing over the buffers in `transfer`.
+    pub async fn call(
+        &self,
+        job: &str,
+        argument: &JsValue,
+        transfer: &Array,
+    ) -> Result<JsValue, String> {
+        let id = {
+            let mut next_id = self.next_id.borrow_mut();
+            *next_id += 1;
+            *next_id
+        };
+        let (send, receive) = oneshot::channel();
+        self.pending.borrow_mut().insert(id, send);
+        self.worker
+            .post_message_with_transfer(&Array::of3(&id.into(), &job.into(), argument), transfer)
+            .map_err(describe)?;
+        receive
+            .await
+            .unwrap_or_else(|_| Err("the worker stopped".to_string()))
+    }
+
+    /// Runs `job` on the worker with `request`, sent as JSON, and parses what it
+    /// answers the same way.
+    pub async fn request<Req: Serialize, Res: DeserializeOwned>(
+        &self,
+        job: &str,
+        request: &Req,
+    ) -> Result<Res, String> {
+        let request = serde_json::to_string(request).map_err(|error| error.to_string())?;
+        let answer = self.call(job, &request.into(), &Array::new()).await?;
+        serde_json::from_str(&answer.as_string().unwrap_or_default())
+            .map_err(|error| error.to_string())
+    }
+}
+
+/// The worker's side of [`Worker::request`]: parses the request `job` takes from
+/// `argument`, and sends back its answer as JSON.
+pub fn json<Req: DeserializeOwned, Res: Serialize>(
+    argument: JsValue,
+    job: impl FnOnce(Req) -> Result<Res, String>,
+) -> Result<Reply, String> {
+    let request = serde_json::from_str(&argument.as_string().unwrap_or_default())
+        .map_err(|error| error.to_string())?;
+    let answer = serde_json::to_string(&job(request)?).map_err(|error| error.to_string())?;
+    Ok(Reply {
+        value: answer.into(),
+        transfer: Array::new(),
+    })
+}
+
+/// The worker, started if it isn't already.
+pub fn get_or_spawn(worker: &RefCell<Option<Rc<Worker>>>) -> Result<Rc<Worker>, String> {
+    let mut worker = worker.borrow_mut();
+    if worker.is_none() {
+        *worker = Some(Rc::new(Worker::spawn()?));
+    }
+    Ok(worker.clone().unwrap())
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.worker.terminate();
+        _ = Url::revoke_object_url(&self.script);
+    }
+}
+
+/// The worker's script: load the page's wasm, and hold on to any messages that arrive
+/// before it's ready for them.
+fn bootstrap() -> Result<Blob, String> {
+    let js = asset_url("link[rel=modulepreload]")?;
+    let wasm = asset_url("link[rel=preload][type='application/wasm']")?;
+    let source = format!(
+        "const early = [];\n\
+         self.onmessage = (ev) => early.push(ev.data);\n\
+         const {{ default: init }} = await import({js:?});\n\
+         await init({wasm:?});\n\
+         for (const data of early) self.dispatchEvent(new MessageEvent('message', {{ data }}));\n"
+    );
+    let mut options = BlobPropertyBag::new();
+    options.type_("text/javascript");
+    Blob::new_with_str_sequence_and_options(&Array::of1(&source.into()), &options).map_err(describe)
+}
+
+/// The absolute URL of one of the `<link>`s Trunk adds for the app's own files; a
+/// blob worker has no base URL to resolve a relative one against.
+fn asset_url(selector: &str) -> Result<String, String> {
+    let href = document()
+        .query_selector(selector)
+        .ok()
+        .flatten()
+        .and_then(|link| link.get_attribute("href"))
+        .ok_or_else(|| format!("no {selector} to find the app's files by"))?;
+    let base = document().base_uri().ok().flatten().unwrap_or_default();
+    Url::new_with_base(&href, &base)
+        .map(|url| url.href())
+        .map_err(describe)
+}
+
+fn describe(e: JsValue) -> String {
+    e.dyn_ref::<js_sys::Error>()
+        .map(|e| String::from(e.message()))
+        .unwrap_or_else(|| format!("{e:?}"))
+}
@@ -0,0 +1,203 @@
+//! A WebSocket that stays connected: when it closes (or never opens), it's opened again
+//! after a [`Backoff`], for as long as the app that connected it is mounted. The
+//! reading runs as a [`Supervisor`] task, which does the restarting and reports it.
+//!
+//! Messages go both ways as JSON text, one `M` each. Nothing sent while the socket is
+//! down is kept for later, since whatever's on the other end may have moved on by
+//! then; [`Socket::send`] says whether it went.
+
+use std::{
+    cell::{Cell, RefCell},
+    marker::PhantomData,
+    rc::Rc,
+    time::Duration,
+};
+
+use futures::channel::oneshot;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use web_sys::{Event, MessageEvent, WebSocket};
+
+use crate::{
+    app::App,
+    dom::{add_event_listener, log, window, EventListenerHandle},
+    supervisor::{Backoff, RestartPolicy, Supervisor, TaskResult, TaskStatus},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum Status {
+    Connecting,
+    Open,
+    /// Closed, and opening again after this long.
+    Retrying(Duration),
+}
+
+/// The socket that's open (or opening) now, and its listeners.
+struct Current {
+    socket: WebSocket,
+    _listeners: [EventListenerHandle; 3],
+}
+
+pub struct Socket<M> {
+    current: Rc<RefCell<Option<Current>>>,
+    _msg: PhantomData<M>,
+}
+
+// not derived, which would ask for `M: Clone`
+impl<M> Clone for Socket<M> {
+    fn clone(&self) -> Self {
+        Socket {
+            current: self.current.clone(),
+            _msg: PhantomData,
+        }
+    }
+}
+
+impl<M: Serialize + DeserializeOwned + 'static> Socket<M> {
+    /// Keeps a socket to `url` open until `app` unmounts, as `supervisor`'s task
+    /// `name`, calling `on_message` with each message from it and `on_status`
+    /// whenever it opens or closes.
+    pub fn connect(
+        app: &mut App,
+        supervisor: &Supervisor,
+        name: &'static str,
+        url: String,
+        backoff: Backoff,
+        on_message: impl Fn(M) + 'static,
+        on_status: impl Fn(Status) + 'static,
+    ) -> Self {
+        let current = Rc::new(RefCell::new(None::<Current>));
+        let on_message: Rc<dyn Fn(M)> = Rc::new(on_message);
+        let on_status: Rc<dyn Fn(Status)> = Rc::new(on_status);
+        supervisor.on_status({
+            let on_status = on_status.clone();
+            move |task, status| {
+                if let (true, TaskStatus::Restarting { delay, .. }) = (task == name, status) {
+                    on_status(Status::Retrying(*delay));
+                }
+            }
+        });
+        // a socket that opened and then closed is a clean exit, and one that never
+        // opened a failure, so only failures in a row make the wait grow
+        supervisor.spawn(name, RestartPolicy::Always, backoff, {
+            let current = current.clone();
+            move || {
+                read(
+                    url.clone(),
+                    current.clone(),
+                    on_message.clone(),
+                    on_status.clone(),
+                )
+            }
+        });
+        app.on_cleanup({
+            let supervisor = supervisor.clone();
+            let current = current.clone();
+            move || {
+                supervisor.stop(name);
+                if let Some(current) = current.borrow_mut().take() {
+                    _ = current.socket.close();
+                }
+            }
+        });
+        Socket {
+            current,
+            _msg: PhantomData,
+        }
+    }
+
+    /// Sends `msg` if the socket's open, and says whether it did.
+    pub fn send(&self, msg: &M) -> bool {
+        let current = self.current.borrow();
+        let Some(current) = current
+            .as_ref()
+            .filter(|current| current.socket.ready_state() == WebSocket::OPEN)
+        else {
+            return false;
+        };
+        serde_json::to_string(msg).is_ok_and(|json| current.socket.send_with_str(&json).is_ok())
+    }
+}
+
+/// Opens one socket to `url` and waits for it to close: cleanly if it opened first.
+async fn read<M: DeserializeOwned + 'static>(
+    url: String,
+    current: Rc<RefCell<Option<Current>>>,
+    on_message: Rc<dyn Fn(M)>,
+    on_status: Rc<dyn Fn(Status)>,
+) -> TaskResult {
+    on_status(Status::Connecting);
+    // a malformed URL, say, which won't get better; but keep to the backoff
+    let socket = WebSocket::new(&url).map_err(|error| format!("couldn't open {url}: {error:?}"))?;
+    let opened = Rc::new(Cell::new(false));
+    let (closed, on_close) = oneshot::channel();
+    let mut closed = Some(closed);
+    let listeners = [
+        add_event_listener(&socket, "open", {
+            let opened = opened.clone();
+            move |_: Event| {
+                opened.set(true);
+                on_status(Status::Open);
+            }
+        }),
+        add_event_listener(&socket, "message", move |ev: MessageEvent| {
+            match parse(&ev) {
+                Some(msg) => on_message(msg),
+                None => log!("[ws] ignoring a message that doesn't parse"),
+            }
+        }),
+        // an error always comes with a close, so this hears both
+        add_event_listener(&socket, "close", move |_: Event| {
+            if let Some(closed) = closed.take() {
+                _ = closed.send(());
+            }
+        }),
+    ];
+    *current.borrow_mut() = Some(Current {
+        socket,
+        _listeners: listeners,
+    });
+    _ = on_close.await;
+    current.borrow_mut().take();
+    if opened.get() {
+        Ok(())
+    } else {
+        Err(format!("{url} closed before it opened"))
+    }
+}
+
+fn parse<M: DeserializeOwned>(ev: &MessageEvent) -> Option<M> {
+    serde_json::from_str(&ev.data().as_string()?).ok()
+}
+
+/// `path` on this site, as a WebSocket URL.
+pub fn url(path: &str) -> String {
+    let location = window().location();
+    socket_url(
+        &location.protocol().unwrap_or_default(),
+        &location.host().unwrap_or_default(),
+        path,
+    )
+}
+
+/// `wss:` for a page served over `https:`, which mixed content rules would insist on.
+fn socket_url(protocol: &str, host: &str, path: &str) -> String {
+    let scheme = if protocol == "https:" { "wss" } else { "ws" };
+    format!("{scheme}://{host}{path}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn socket_urls_follow_the_page() {
+        assert_eq!(
+            socket_url("https:", "example.com", "/api/counter"),
+            "wss://example.com/api/counter"
+        );
+        assert_eq!(
+            socket_url("http:", "localhost:8080", "/api/counter"),
+            "ws://localhost:8080/api/counter"
+        );
+    }
+}